@@ -1,18 +1,23 @@
 use crate::context::*;
-use std::process;
 use crate::object::*;
+use crate::duration;
+use crate::exception;
+use crate::future;
+use crate::utils::{dart_evalerror, expect_args};
 
 
 pub fn has_function(name: &str) -> bool {
     match name {
         "assert" |
-        "print" => true,
-        _ => false
+        "identical" |
+        "print" |
+        "Duration" => true,
+        _ => exception::has_constructor(name) || future::has_function(name)
     }
 }
 
 
-pub fn call(name: &str, args: &Vec<Object>, ctx: &Ctx) -> Object {
+pub fn call(name: &str, args: &Vec<Object>, ctx: &mut Ctx) -> Object {
     match name {
 
         "assert" => {
@@ -36,34 +41,29 @@ pub fn call(name: &str, args: &Vec<Object>, ctx: &Ctx) -> Object {
                         let filepath = &ctx.filepath;
                         let linenum = 0;
                         let sympos = 0;
-                        println!("'file://{}': Failed assertion: line {} pos {}: argument: {}", filepath, linenum, sympos, msg);
                         // TODO: Dart manages to get the variable name in here.
-                        // println!("'file://{}': Failed assertion: line {} pos {}: 'argname': {}.", filename, linenum, sympos, msg);
-
-                        process::exit(1);
+                        // Thrown, so a failure at the repl prompt doesn't end the session.
+                        return exception::error(
+                            "AssertionError",
+                            format!("'file://{}': Failed assertion: line {} pos {}: argument: {}", filepath, linenum, sympos, msg),
+                            ctx
+                        );
                     }
                 }
                 _ => {
                     // Should be caught generally, by type system. For now, msg like dart.
                     // TODO, get line number, symbol number and object type.
-
-                    let filepath = &ctx.filepath;
-                    let linenum = 0;
-                    let sympos = 0;
                     let objtype = "unknown";
-
-                    println!(
-                        "{}:{}:{}: Error: A value of type '{}' can't be assigned to a variable of type 'bool'.",
-                        filepath,
-                        linenum,
-                        sympos,
-                        objtype
-                    );
-                    process::exit(1);
+                    dart_evalerror(format!("A value of type '{}' can't be assigned to a variable of type 'bool'.", objtype), ctx)
                 }
             }
         }
 
+        "identical" => {
+            expect_args(name, args, 2, ctx);
+            return Object::Bool(args[0].identical(&args[1]));
+        }
+
         "print" => {
             if args.len() < 1 {
                 panic!("Argument expected by print().");
@@ -71,6 +71,18 @@ pub fn call(name: &str, args: &Vec<Object>, ctx: &Ctx) -> Object {
             println!("{}", &args[0]);
         }
 
+        "Duration" => {
+            return duration::construct(args, ctx);
+        }
+
+        x if exception::has_constructor(x) => {
+            return exception::construct(x, args, ctx);
+        }
+
+        x if future::has_function(x) => {
+            return future::call(x, args, ctx);
+        }
+
         _ => panic!("Unknown command: {}", name)
     }
     Object::Null
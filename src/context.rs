@@ -1,7 +1,17 @@
 
 
+use crate::future::EventLoop;
+use crate::map::MapKey;
+
+
 pub struct Ctx {
     pub filepath: String,
-    pub debug: bool
+    pub debug: bool,
+    // Function and file of each running call, innermost last. For stack traces.
+    pub calls: Vec<(String, String)>,
+    // The work async code left for later.
+    pub events: EventLoop,
+    // The collections being turned into strings, innermost last. For those that contain themselves.
+    pub stringifying: Vec<MapKey>
 }
 
@@ -0,0 +1,1162 @@
+// The body of an async, async* or sync* function, run so that it can stop at an 'await' or
+// 'yield' and continue later.
+//
+// eval runs a statement to its end in one go, on the Rust stack. Here, what is left to do of a
+// body is kept as a stack of tasks instead, innermost last, which an 'await' can leave as it is
+// and come back to once the future it waits for completes. Statements without an await in them
+// are still run by eval. So are expressions: what comes before the first await in an expression
+// is evaluated into temporary variables that take its place, and the await itself is replaced by
+// the variable its value will go to. Evaluating the expression again on resume continues where
+// it left off. An async* function stops at each yield too, so that its listener gets the event
+// before it goes on. A sync* function runs only when its iteration asks for the next element, up
+// to the yield that gives it.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::context::*;
+use crate::evaluator::{self, eval, is_abrupt, is_type, loop_label, match_pattern, pattern_variables};
+use crate::exception;
+use crate::future::{self, Future, Listener};
+use crate::iterable::{self, Cursor};
+use crate::node::*;
+use crate::object::*;
+use crate::objsys::ObjSys;
+use crate::stack::{Frame, Stack};
+use crate::stream::{self, Event, Sink, Stream, Subscription};
+use crate::types;
+use crate::utils::{dart_evalerror, dprint};
+
+
+#[derive(Clone)]
+pub struct Coroutine {
+    tasks: Vec<Task>,
+    // What the task on top has evaluated so far of the expression it waits in.
+    pending: Option<Node>,
+    // The variable the value of that await goes to.
+    awaiting: String,
+    temps: usize,
+    // Where it runs: its lex-frames while it waits, its 'this', its file and its call.
+    frames: Vec<Frame>,
+    this: (String, String),
+    filepath: String,
+    call: (String, String),
+    // What it gives: the future an async body completes or the stream an async* body generates.
+    output: Output
+}
+
+
+impl fmt::Debug for Coroutine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Coroutine({} tasks)", self.tasks.len())
+    }
+}
+
+
+#[derive(Clone)]
+enum Output {
+    Future(Future),
+    Stream(Stream),
+    // A sync* body gives its elements one by one, with what it threw once it ends.
+    Iterable(Option<Box<Object>>)
+}
+
+
+#[derive(Clone)]
+enum Task {
+    // The statements of a block, from the next one to run.
+    Block(Node, usize),
+    Stmt(Node),
+    PopLex,
+    // A conditional, at the branch to test next.
+    If(Node, usize),
+    // Loops about to test their condition. A for loop may first run its step.
+    While(Node),
+    DoWhile(Node),
+    For(Node, bool),
+    // A for-in loop, with its iteration once the iterable is evaluated.
+    ForIn(Node, Option<Box<Cursor>>),
+    // An 'await for' loop, with its subscription once the stream is evaluated.
+    AwaitFor(Node, Option<Subscription>),
+    Yield(Node),
+    // After a yield, to end the body if the listener cancelled.
+    Yielded,
+    // A 'yield*', with the subscription to the stream it yields from.
+    YieldFrom(Subscription),
+    // A 'yield*' in a sync* body, with the iteration it yields from.
+    YieldEach(Box<Cursor>),
+    // A statement that is not a loop, with the label a break may end it by.
+    Label(String),
+    Switch(Node),
+    // A switch with its subject, at the case to test next.
+    Cases(Node, Box<Object>, usize),
+    // The guard of a case whose pattern matched, in the scope of the case.
+    Guard(Node, Box<Object>, usize),
+    // The case a switch runs, which a break without label ends.
+    SwitchBody,
+    Try(Node, TryStage)
+}
+
+
+#[derive(Clone)]
+enum TryStage {
+    Body,
+    Catch,
+    // With what ended the body or catch clause abruptly, to go on with after.
+    Finally(Option<Box<Object>>)
+}
+
+
+enum Flow {
+    Next,
+    // Wait for the value of an await.
+    Suspend(Box<Object>),
+    // A return, throw, break or continue, on its way to what handles it.
+    Abrupt(Box<Object>)
+}
+
+
+// Runs an async body until its first await, in the call-frame of its function.
+// Gives the future the body completes.
+pub fn start(
+    body: &Node,
+    valuetype: &str,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let future = future::pending();
+    let mut co = new(Output::Future(future.clone()), objsys, ctx);
+    schedule(&mut co, body, stack);
+    run(co, Flow::Next, looktables, globals, stack, objsys, ctx);
+    Object::Future(future, String::from(valuetype))
+}
+
+
+// The stream of an async* body, which starts running when it is listened to.
+pub fn generate(body: &Node, elemtype: &str, stack: &mut Stack, objsys: &ObjSys, ctx: &Ctx) -> Object {
+    let stream = stream::generator();
+    let mut co = new(Output::Stream(stream.clone()), objsys, ctx);
+    schedule(&mut co, body, stack);
+    co.frames = stack.capture();
+    stream::set_generator(&stream, co);
+    Object::Stream(stream, String::from(elemtype))
+}
+
+
+// The Iterable of a sync* body. Each iteration runs a copy of the coroutine made here.
+pub fn iterate(body: &Node, elemtype: &str, stack: &mut Stack, objsys: &ObjSys, ctx: &Ctx) -> Object {
+    let mut co = new(Output::Iterable(None), objsys, ctx);
+    schedule(&mut co, body, stack);
+    co.frames = stack.capture();
+    Object::Iterable(Rc::new(iterable::Lazy::Generate(co)), String::from(elemtype))
+}
+
+
+// A coroutine about to run a body in the function called right now.
+fn new(output: Output, objsys: &ObjSys, ctx: &Ctx) -> Coroutine {
+    Coroutine {
+        tasks: Vec::new(),
+        pending: None,
+        awaiting: String::new(),
+        temps: 0,
+        frames: Vec::new(),
+        this: (objsys.get_this(), objsys.get_this_class()),
+        filepath: ctx.filepath.clone(),
+        call: ctx.calls.last().cloned().unwrap_or_default(),
+        output
+    }
+}
+
+
+// Runs a sync* body on to its next yield, in the call-frame of its function.
+// Gives what it yielded, or None once it ended.
+pub fn next(
+    co: &mut Coroutine,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Option<Object>, Box<Object>> {
+
+    dprint("Coroutine: next");
+
+    stack.resume_call(std::mem::take(&mut co.frames));
+    let saved = objsys.enter(co.this.0.clone(), co.this.1.clone());
+    let oldfilepath = std::mem::replace(&mut ctx.filepath, co.filepath.clone());
+    ctx.calls.push(co.call.clone());
+
+    let mut flow = Flow::Next;
+    let elem = loop {
+        flow = match flow {
+            Flow::Next => match co.tasks.pop() {
+                Some(task) => step(co, task, looktables, globals, stack, objsys, ctx),
+                None => break None
+            },
+            Flow::Abrupt(outcome) => match unwind(co, outcome, stack, objsys, ctx) {
+                Some(flow) => flow,
+                None => break None
+            },
+            Flow::Suspend(elem) => break Some(*elem)
+        }
+    };
+    co.frames = stack.capture();
+
+    ctx.calls.pop();
+    ctx.filepath = oldfilepath;
+    objsys.leave(saved);
+    stack.pop_call();
+
+    if let Output::Iterable(thrown) = &mut co.output {
+        if let Some(thrown) = thrown.take() {
+            return Err(thrown);
+        }
+    }
+    Ok(elem)
+}
+
+
+// Continues after the future it waited for completed.
+pub fn resume(
+    mut co: Coroutine,
+    outcome: Result<Object, Box<Object>>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) {
+
+    dprint("Coroutine: resume");
+
+    stack.resume_call(std::mem::take(&mut co.frames));
+    let saved = objsys.enter(co.this.0.clone(), co.this.1.clone());
+    let oldfilepath = std::mem::replace(&mut ctx.filepath, co.filepath.clone());
+    let oldcalls = std::mem::replace(&mut ctx.calls, vec![co.call.clone()]);
+
+    let flow = match outcome {
+        Ok(value) => {
+            // Only an await has a variable for what it waited for.
+            if !co.awaiting.is_empty() {
+                stack.add(&co.awaiting, value);
+            }
+            Flow::Next
+        }
+        Err(thrown) => {
+            co.pending = None;
+            Flow::Abrupt(thrown)
+        }
+    };
+    run(co, flow, looktables, globals, stack, objsys, ctx);
+
+    ctx.calls = oldcalls;
+    ctx.filepath = oldfilepath;
+    objsys.leave(saved);
+    stack.pop_call();
+}
+
+
+fn run(
+    mut co: Coroutine,
+    mut flow: Flow,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) {
+
+    loop {
+        flow = match flow {
+            Flow::Next => match co.tasks.pop() {
+                Some(task) => step(&mut co, task, looktables, globals, stack, objsys, ctx),
+                None => {
+                    finish(&mut co, Ok(Object::Null), ctx);
+                    return;
+                }
+            },
+            Flow::Abrupt(outcome) => match unwind(&mut co, outcome, stack, objsys, ctx) {
+                Some(flow) => flow,
+                None => return
+            },
+            Flow::Suspend(awaited) => {
+                // Await a future, or a value as a future that completed with it.
+                let awaited = match *awaited {
+                    Object::Future(future, _) => future,
+                    value => future::completed(Ok(value), ctx)
+                };
+                co.frames = stack.capture();
+                future::listen(&awaited, Listener::Resume(Box::new(co)), ctx);
+                return;
+            }
+        }
+    }
+}
+
+
+fn step(
+    co: &mut Coroutine,
+    task: Task,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Flow {
+
+    match task {
+        Task::Block(node, i) => {
+            if let Some(stmt) = node.children.get(i) {
+                let stmt = stmt.clone();
+                co.tasks.push(Task::Block(node, i + 1));
+                schedule(co, &stmt, stack);
+            }
+            Flow::Next
+        }
+        Task::Stmt(node) => match value(co, &node, looktables, globals, stack, objsys, ctx) {
+            Ok(ret) if is_abrupt(&ret) => Flow::Abrupt(Box::new(ret)),
+            Ok(_) => Flow::Next,
+            Err(flow) => wait(co, Task::Stmt(node), flow)
+        },
+        Task::PopLex => {
+            stack.pop_lex();
+            Flow::Next
+        }
+        Task::If(node, i) => {
+            let branch = match node.children.get(i) {
+                Some(branch) => branch,
+                None => return Flow::Next
+            };
+            if let NodeType::Else = branch.nodetype {
+                body(co, &branch.children[0], stack);
+                return Flow::Next;
+            }
+            match value(co, &branch.children[0], looktables, globals, stack, objsys, ctx) {
+                Ok(Object::Bool(true)) => body(co, &branch.children[1], stack),
+                Ok(Object::Bool(false)) => co.tasks.push(Task::If(node, i + 1)),
+                Ok(_) => not_bool(ctx),
+                Err(flow) => return wait(co, Task::If(node, i), flow)
+            }
+            Flow::Next
+        }
+        Task::While(node) => {
+            match value(co, &node.children[0], looktables, globals, stack, objsys, ctx) {
+                Ok(Object::Bool(true)) => {
+                    let block = node.children[1].clone();
+                    co.tasks.push(Task::While(node));
+                    body(co, &block, stack);
+                }
+                Ok(Object::Bool(false)) => {}
+                Ok(_) => not_bool(ctx),
+                Err(flow) => return wait(co, Task::While(node), flow)
+            }
+            Flow::Next
+        }
+        Task::DoWhile(node) => {
+            match value(co, &node.children[1], looktables, globals, stack, objsys, ctx) {
+                Ok(Object::Bool(true)) => {
+                    let block = node.children[0].clone();
+                    co.tasks.push(Task::DoWhile(node));
+                    body(co, &block, stack);
+                }
+                Ok(Object::Bool(false)) => {}
+                Ok(_) => not_bool(ctx),
+                Err(flow) => return wait(co, Task::DoWhile(node), flow)
+            }
+            Flow::Next
+        }
+        Task::For(node, stepping) => {
+            if stepping {
+                // Not again when resuming the step after an await.
+                if co.pending.is_none() {
+                    stack.renew_lex();
+                }
+                if let Err(flow) = value(co, &node.children[2], looktables, globals, stack, objsys, ctx) {
+                    return wait(co, Task::For(node, true), flow);
+                }
+            }
+            match value(co, &node.children[1], looktables, globals, stack, objsys, ctx) {
+                Ok(Object::Bool(true)) => {
+                    let block = node.children[3].clone();
+                    co.tasks.push(Task::For(node, true));
+                    body(co, &block, stack);
+                }
+                Ok(Object::Bool(false)) => {}
+                Ok(_) => not_bool(ctx),
+                Err(flow) => return wait(co, Task::For(node, false), flow)
+            }
+            Flow::Next
+        }
+        Task::ForIn(node, cursor) => {
+            let mut cursor = match cursor {
+                Some(cursor) => cursor,
+                None => {
+                    let iterable = match value(co, &node.children[1], looktables, globals, stack, objsys, ctx) {
+                        Ok(iterable) => iterable,
+                        Err(flow) => return wait(co, Task::ForIn(node, None), flow)
+                    };
+                    if !iterable::is_iterable(&iterable, objsys) {
+                        // As dart.
+                        dart_evalerror(format!(
+                            "The type '{}' used in the 'for' loop must implement 'Iterable<dynamic>'.",
+                            evaluator::runtime_type(&iterable, objsys)
+                        ), ctx)
+                    }
+                    match iterable::cursor(&iterable, looktables, globals, stack, objsys, ctx) {
+                        Ok(cursor) => Box::new(cursor),
+                        Err(thrown) => return Flow::Abrupt(thrown)
+                    }
+                }
+            };
+            let elem = match iterable::next(&mut cursor, looktables, globals, stack, objsys, ctx) {
+                Ok(Some(elem)) => elem,
+                Ok(None) => return Flow::Next,
+                Err(thrown) => return Flow::Abrupt(thrown)
+            };
+            // Each iteration declares its own loop variable, as in eval.
+            stack.push_lex();
+            let declared = loop_variable(&node, &elem, looktables, globals, stack, objsys, ctx);
+            let block = node.children[2].clone();
+            co.tasks.push(Task::ForIn(node, Some(cursor)));
+            co.tasks.push(Task::PopLex);
+            if let Err(thrown) = declared {
+                return Flow::Abrupt(thrown);
+            }
+            body(co, &block, stack);
+            Flow::Next
+        }
+        Task::AwaitFor(node, None) => {
+            let source = match value(co, &node.children[1], looktables, globals, stack, objsys, ctx) {
+                Ok(Object::Stream(source, _)) => source,
+                // As dart.
+                Ok(x) => dart_evalerror(format!(
+                    "The type '{}' used in the 'for' loop must implement 'Stream<dynamic>'.",
+                    evaluator::runtime_type(&x, objsys)
+                ), ctx),
+                Err(flow) => return wait(co, Task::AwaitFor(node, None), flow)
+            };
+            match stream::listen(&source, Sink::pull(), looktables, globals, stack, objsys, ctx) {
+                Ok(sub) => {
+                    co.tasks.push(Task::AwaitFor(node, Some(sub)));
+                    Flow::Next
+                }
+                Err(thrown) => Flow::Abrupt(thrown)
+            }
+        }
+        Task::AwaitFor(node, Some(sub)) => match stream::pull(&sub) {
+            Ok(Event::Data(elem)) => {
+                stack.push_lex();
+                let declared = loop_variable(&node, &elem, looktables, globals, stack, objsys, ctx);
+                let block = node.children[2].clone();
+                co.tasks.push(Task::AwaitFor(node, Some(sub)));
+                co.tasks.push(Task::PopLex);
+                if let Err(thrown) = declared {
+                    return Flow::Abrupt(thrown);
+                }
+                body(co, &block, stack);
+                Flow::Next
+            }
+            // An error of the stream is thrown by the loop, which cancels it.
+            Ok(Event::Error(thrown)) => {
+                stream::cancel(&sub, ctx);
+                Flow::Abrupt(thrown)
+            }
+            Ok(Event::Done) => Flow::Next,
+            Err(next) => {
+                co.tasks.push(Task::AwaitFor(node, Some(sub)));
+                suspend(co, next)
+            }
+        },
+        Task::Yield(node) => {
+            if let Output::Future(_) = co.output {
+                // As dart.
+                dart_evalerror("Yield statements must be in a generator function (one marked with either 'async*' or 'sync*').", ctx)
+            }
+            let value = match value(co, &node.children[0], looktables, globals, stack, objsys, ctx) {
+                Ok(value) => value,
+                Err(flow) => return wait(co, Task::Yield(node), flow)
+            };
+            let star = matches!(node.nodetype, NodeType::Yield(true));
+            match (co.output.clone(), value) {
+                (Output::Stream(_), Object::Stream(source, _)) if star => {
+                    match stream::listen(&source, Sink::pull(), looktables, globals, stack, objsys, ctx) {
+                        Ok(sub) => {
+                            co.tasks.push(Task::YieldFrom(sub));
+                            Flow::Next
+                        }
+                        Err(thrown) => Flow::Abrupt(thrown)
+                    }
+                }
+                (Output::Iterable(_), elements) if star && iterable::is_iterable(&elements, objsys) => {
+                    match iterable::cursor(&elements, looktables, globals, stack, objsys, ctx) {
+                        Ok(cursor) => {
+                            co.tasks.push(Task::YieldEach(Box::new(cursor)));
+                            Flow::Next
+                        }
+                        Err(thrown) => Flow::Abrupt(thrown)
+                    }
+                }
+                // As dart.
+                (output, x) if star => dart_evalerror(format!(
+                    "A yielded value of type '{}' must be assignable to '{}<dynamic>'.",
+                    evaluator::runtime_type(&x, objsys),
+                    if let Output::Stream(_) = output { "Stream" } else { "Iterable" }
+                ), ctx),
+                (Output::Stream(output), value) => {
+                    stream::emit(&output, Event::Data(Box::new(value)), ctx);
+                    pause(co)
+                }
+                // The iteration gets the element from next.
+                (_, value) => {
+                    co.awaiting.clear();
+                    Flow::Suspend(Box::new(value))
+                }
+            }
+        }
+        Task::Yielded => match &co.output {
+            // A cancelled async* function returns from where it yielded, running its finally clauses.
+            Output::Stream(output) if stream::is_cancelled(output) => Flow::Abrupt(Box::new(Object::Return(Box::new(Object::Null)))),
+            _ => Flow::Next
+        },
+        Task::YieldEach(mut cursor) => match iterable::next(&mut cursor, looktables, globals, stack, objsys, ctx) {
+            Ok(Some(elem)) => {
+                co.tasks.push(Task::YieldEach(cursor));
+                co.awaiting.clear();
+                Flow::Suspend(Box::new(elem))
+            }
+            Ok(None) => Flow::Next,
+            Err(thrown) => Flow::Abrupt(thrown)
+        },
+        Task::YieldFrom(sub) => {
+            let output = match &co.output {
+                Output::Stream(output) => output.clone(),
+                _ => panic!("yield* outside of a generator")
+            };
+            match stream::pull(&sub) {
+                // Errors are yielded too.
+                Ok(event @ (Event::Data(_) | Event::Error(_))) => {
+                    stream::emit(&output, event, ctx);
+                    co.tasks.push(Task::YieldFrom(sub));
+                    pause(co)
+                }
+                Ok(Event::Done) => Flow::Next,
+                Err(next) => {
+                    co.tasks.push(Task::YieldFrom(sub));
+                    suspend(co, next)
+                }
+            }
+        }
+        Task::Label(_) |
+        Task::SwitchBody => Flow::Next,
+        Task::Switch(node) => {
+            let subject = match value(co, &node.children[0], looktables, globals, stack, objsys, ctx) {
+                Ok(subject) => subject,
+                Err(flow) => return wait(co, Task::Switch(node), flow)
+            };
+            co.tasks.push(Task::Cases(node, Box::new(subject), 1));
+            Flow::Next
+        }
+        Task::Cases(node, subject, from) => {
+            for i in from..node.children.len() {
+                let case = &node.children[i];
+                stack.push_lex();
+                // A guard that waits is evaluated as a task of its own.
+                let waits = suspends(&case.children[1]);
+                let matched = if waits {
+                    match_pattern(&case.children[0], &subject, looktables, globals, stack, objsys, ctx)
+                } else {
+                    evaluator::case_matches(case, &subject, looktables, globals, stack, objsys, ctx)
+                };
+                match matched {
+                    Ok(true) => {
+                        co.tasks.push(Task::SwitchBody);
+                        co.tasks.push(Task::PopLex);
+                        if waits {
+                            co.tasks.push(Task::Guard(node, subject, i));
+                        } else {
+                            schedule(co, &node.children[i].children[2], stack);
+                        }
+                        return Flow::Next;
+                    }
+                    Ok(false) => stack.pop_lex(),
+                    Err(thrown) => {
+                        stack.pop_lex();
+                        return Flow::Abrupt(thrown);
+                    }
+                }
+            }
+            Flow::Next
+        }
+        Task::Guard(node, subject, i) => match value(co, &node.children[i].children[1], looktables, globals, stack, objsys, ctx) {
+            Ok(Object::Bool(true)) => {
+                schedule(co, &node.children[i].children[2], stack);
+                Flow::Next
+            }
+            Ok(Object::Bool(false)) => {
+                // Out of the scope of the case, on to the next one.
+                co.tasks.truncate(co.tasks.len() - 2);
+                stack.pop_lex();
+                co.tasks.push(Task::Cases(node, subject, i + 1));
+                Flow::Next
+            }
+            Ok(_) => not_bool(ctx),
+            Err(flow) => wait(co, Task::Guard(node, subject, i), flow)
+        },
+        Task::Try(node, stage) => match stage {
+            TryStage::Body |
+            TryStage::Catch => finally(co, node, None, stack),
+            TryStage::Finally(Some(outcome)) => Flow::Abrupt(outcome),
+            TryStage::Finally(None) => Flow::Next
+        }
+    }
+}
+
+
+// Passes a return, throw, break or continue out through the tasks, to the loop, label, switch or
+// try that handles it. Gives None when it got out of the body, completing its future.
+fn unwind(
+    co: &mut Coroutine,
+    outcome: Box<Object>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Option<Flow> {
+
+    loop {
+        let task = match co.tasks.pop() {
+            Some(task) => task,
+            None => {
+                let outcome = match *outcome {
+                    Object::Return(value) => Ok(*value),
+                    thrown @ Object::Throw(_, _) => Err(Box::new(thrown)),
+                    // As dart.
+                    _ => dart_evalerror("A break or continue statement can't be used outside of a loop or switch statement.", ctx)
+                };
+                finish(co, outcome, ctx);
+                return None;
+            }
+        };
+        match task {
+            Task::PopLex => stack.pop_lex(),
+            Task::While(ref node) |
+            Task::DoWhile(ref node) |
+            Task::For(ref node, _) |
+            Task::ForIn(ref node, _) |
+            Task::AwaitFor(ref node, _) => {
+                let index = match node.nodetype {
+                    NodeType::For => 4,
+                    NodeType::ForIn(_) | NodeType::AwaitFor(_) => 3,
+                    _ => 2
+                };
+                let label = loop_label(node, index);
+                let (breaks, continues) = match &*outcome {
+                    Object::Break(l) => (l.is_none() || l.as_ref() == label, false),
+                    Object::Continue(l) => (false, l.is_none() || l.as_ref() == label),
+                    _ => (false, false)
+                };
+                if continues {
+                    // A for loop goes on with its step.
+                    let task = match task {
+                        Task::For(node, _) => Task::For(node, true),
+                        task => task
+                    };
+                    co.tasks.push(task);
+                    return Some(Flow::Next);
+                }
+                // Leaving an 'await for' cancels its subscription.
+                if let Task::AwaitFor(_, Some(sub)) = &task {
+                    stream::cancel(sub, ctx);
+                }
+                if breaks {
+                    return Some(Flow::Next);
+                }
+            }
+            Task::YieldFrom(sub) => stream::cancel(&sub, ctx),
+            Task::Label(label) => {
+                if let Object::Break(Some(l)) = &*outcome {
+                    if *l == label {
+                        return Some(Flow::Next);
+                    }
+                }
+            }
+            Task::SwitchBody => {
+                if let Object::Break(None) = *outcome {
+                    return Some(Flow::Next);
+                }
+            }
+            Task::Try(node, TryStage::Body) => {
+                if let Object::Throw(thrown, trace) = &*outcome {
+                    let clause = node.children[1..].iter().find(|c| match &c.nodetype {
+                        NodeType::Catch(Some(typename), _, _) => is_type(thrown, typename, objsys),
+                        NodeType::Catch(None, _, _) => true,
+                        _ => false
+                    });
+                    if let Some(clause) = clause {
+                        // Catch clauses keep what they caught under the reserved word, as in eval.
+                        let clause = clause.clone();
+                        co.tasks.push(Task::Try(node, TryStage::Catch));
+                        stack.push_lex();
+                        co.tasks.push(Task::PopLex);
+                        stack.add("rethrow", (*outcome).clone());
+                        if let NodeType::Catch(_, e, st) = &clause.nodetype {
+                            if let Some(e) = e {
+                                stack.add(e, (**thrown).clone());
+                            }
+                            if let Some(st) = st {
+                                stack.add(st, Object::String(trace.clone()));
+                            }
+                        }
+                        schedule(co, &clause.children[0], stack);
+                        return Some(Flow::Next);
+                    }
+                }
+                if has_finally(&node) {
+                    return Some(finally(co, node, Some(outcome), stack));
+                }
+            }
+            Task::Try(node, TryStage::Catch) if has_finally(&node) => {
+                return Some(finally(co, node, Some(outcome), stack));
+            }
+            // A finally that returns or throws wins over what it was run for.
+            _ => {}
+        }
+    }
+}
+
+
+// Completes the future with what the body returned or threw, or ends the stream or iteration, with
+// the error it threw.
+fn finish(co: &mut Coroutine, outcome: Result<Object, Box<Object>>, ctx: &mut Ctx) {
+    match &mut co.output {
+        Output::Future(future) => future::complete(future, outcome, ctx),
+        Output::Stream(stream) => {
+            if let Err(thrown) = outcome {
+                stream::emit(stream, Event::Error(thrown), ctx);
+            }
+            stream::emit(stream, Event::Done, ctx);
+        }
+        Output::Iterable(thrown) => *thrown = outcome.err()
+    }
+}
+
+
+// Runs the finally clause of a try, if it has one, then goes on with the outcome.
+fn finally(co: &mut Coroutine, node: Node, outcome: Option<Box<Object>>, stack: &mut Stack) -> Flow {
+    match node.children.iter().find(|c| matches!(c.nodetype, NodeType::Finally)).cloned() {
+        Some(fin) => {
+            co.tasks.push(Task::Try(node, TryStage::Finally(outcome)));
+            body(co, &fin.children[0], stack);
+            Flow::Next
+        }
+        None => match outcome {
+            Some(outcome) => Flow::Abrupt(outcome),
+            None => Flow::Next
+        }
+    }
+}
+
+
+fn has_finally(node: &Node) -> bool {
+    node.children.iter().any(|c| matches!(c.nodetype, NodeType::Finally))
+}
+
+
+// Pushes the tasks that run a statement. Those that don't suspend run as a whole.
+fn schedule(co: &mut Coroutine, stmt: &Node, stack: &mut Stack) {
+    if !suspends(stmt) {
+        co.tasks.push(Task::Stmt(stmt.clone()));
+        return;
+    }
+    match &stmt.nodetype {
+        NodeType::Block => co.tasks.push(Task::Block(stmt.clone(), 0)),
+        NodeType::Scope => body(co, &stmt.children[0], stack),
+        NodeType::Conditional => co.tasks.push(Task::If(stmt.clone(), 0)),
+        NodeType::While => co.tasks.push(Task::While(stmt.clone())),
+        NodeType::DoWhile => {
+            co.tasks.push(Task::DoWhile(stmt.clone()));
+            body(co, &stmt.children[0], stack);
+        }
+        NodeType::For => {
+            // The loop variable is only in scope in the loop.
+            stack.push_lex();
+            co.tasks.push(Task::PopLex);
+            co.tasks.push(Task::For(stmt.clone(), false));
+            co.tasks.push(Task::Stmt(stmt.children[0].clone()));
+        }
+        NodeType::ForIn(_) => co.tasks.push(Task::ForIn(stmt.clone(), None)),
+        NodeType::AwaitFor(_) => co.tasks.push(Task::AwaitFor(stmt.clone(), None)),
+        NodeType::Yield(_) => co.tasks.push(Task::Yield(stmt.clone())),
+        NodeType::Label(label) => {
+            co.tasks.push(Task::Label(label.clone()));
+            schedule(co, &stmt.children[0], stack);
+        }
+        NodeType::Switch => co.tasks.push(Task::Switch(stmt.clone())),
+        NodeType::Try => {
+            co.tasks.push(Task::Try(stmt.clone(), TryStage::Body));
+            body(co, &stmt.children[0], stack);
+        }
+        _ => co.tasks.push(Task::Stmt(stmt.clone()))
+    }
+}
+
+
+// Schedules a statement in a lexical scope of its own.
+fn body(co: &mut Coroutine, stmt: &Node, stack: &mut Stack) {
+    stack.push_lex();
+    co.tasks.push(Task::PopLex);
+    schedule(co, stmt, stack);
+}
+
+
+// Waits for the listener to get what was yielded.
+fn pause(co: &mut Coroutine) -> Flow {
+    co.tasks.push(Task::Yielded);
+    co.awaiting.clear();
+    Flow::Suspend(Box::new(Object::Null))
+}
+
+
+// Waits for the next event of a stream, which completes the future.
+fn suspend(co: &mut Coroutine, next: Future) -> Flow {
+    co.awaiting.clear();
+    Flow::Suspend(Box::new(Object::Future(next, String::from("void"))))
+}
+
+
+// Declares the variable of a for-in or 'await for' loop, with the element of this iteration.
+fn loop_variable(
+    node: &Node,
+    elem: &Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<(), Box<Object>> {
+
+    let variable = &node.children[0];
+    if !match_pattern(variable, elem, looktables, globals, stack, objsys, ctx)? {
+        // As dart.
+        return Err(Box::new(exception::error(
+            "TypeError",
+            format!("type '{}' is not a subtype of type '{}' in type cast", evaluator::runtime_type(elem, objsys), types::of_pattern(variable)),
+            ctx
+        )));
+    }
+    if matches!(node.nodetype, NodeType::ForIn(true) | NodeType::AwaitFor(true)) {
+        for name in pattern_variables(variable) {
+            stack.make_final(&name, "final", true);
+        }
+    }
+    Ok(())
+}
+
+
+// Puts a task back, to run again when the value it waits for is there.
+fn wait(co: &mut Coroutine, task: Task, flow: Flow) -> Flow {
+    if let Flow::Suspend(_) = flow {
+        co.tasks.push(task);
+    }
+    flow
+}
+
+
+// The value of an expression of the task on top, or the flow to take instead: Suspend at an
+// await, or Abrupt if it threw. Starts from where the expression was left, if it waited before.
+fn value(
+    co: &mut Coroutine,
+    expr: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Object, Flow> {
+
+    let mut expr = co.pending.take().unwrap_or_else(|| expr.clone());
+    match settle(co, &mut expr, looktables, globals, stack, objsys, ctx) {
+        Ok(Some(awaited)) => {
+            co.pending = Some(expr);
+            Err(Flow::Suspend(Box::new(awaited)))
+        }
+        Ok(None) => match eval(&expr, looktables, globals, stack, objsys, ctx) {
+            thrown @ Object::Throw(_, _) => Err(Flow::Abrupt(Box::new(thrown))),
+            value => Ok(value)
+        },
+        Err(thrown) => Err(Flow::Abrupt(thrown))
+    }
+}
+
+
+// Evaluates what comes before the first await in expr, in the order eval would, and gives the
+// value the await waits for. What was evaluated is replaced by temporary variables that hold
+// its values, and the await by the one that will hold its value. Gives None if there is no
+// await left in expr.
+fn settle(
+    co: &mut Coroutine,
+    expr: &mut Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Option<Object>, Box<Object>> {
+
+    if !suspends(expr) {
+        return Ok(None);
+    }
+    match &expr.nodetype {
+        NodeType::Await => {
+            if let Some(awaited) = settle(co, &mut expr.children[0], looktables, globals, stack, objsys, ctx)? {
+                return Ok(Some(awaited));
+            }
+            let awaited = thrown(eval(&expr.children[0], looktables, globals, stack, objsys, ctx))?;
+            co.awaiting = temp(co);
+            *expr = Node::new(NodeType::Name(co.awaiting.clone()));
+            Ok(Some(awaited))
+        }
+        // The right operand is only evaluated if the left one does not decide.
+        NodeType::LogAnd |
+        NodeType::LogOr if !suspends(&expr.children[0]) => {
+            let left = thrown(eval(&expr.children[0], looktables, globals, stack, objsys, ctx))?;
+            *expr = match (&expr.nodetype, left) {
+                (NodeType::LogAnd, Object::Bool(false)) => Node::new(NodeType::Bool(false)),
+                (NodeType::LogOr, Object::Bool(true)) => Node::new(NodeType::Bool(true)),
+                _ => expr.children[1].clone()
+            };
+            settle(co, expr, looktables, globals, stack, objsys, ctx)
+        }
+        // The same, for 'a ?? b' and 'a ??= b'.
+        NodeType::IfNull |
+        NodeType::IfNullAssign if !suspends(&expr.children[0]) => {
+            *expr = match thrown(eval(&expr.children[0], looktables, globals, stack, objsys, ctx))? {
+                Object::Null => expr.children[1].clone(),
+                value => hold(co, value, stack)
+            };
+            settle(co, expr, looktables, globals, stack, objsys, ctx)
+        }
+        // The target is evaluated first, and assigned to as it was evaluated.
+        NodeType::IfNullAssign => {
+            let parts: Vec<&mut Node> = match expr.children[0].nodetype {
+                NodeType::Name(_) | NodeType::Index => expr.children[0].children.iter_mut().collect(),
+                _ => Vec::new()
+            };
+            let awaited = in_order(co, parts, looktables, globals, stack, objsys, ctx)?;
+            expr.children[1].children[0] = expr.children[0].clone();
+            Ok(awaited)
+        }
+        // Only the parts of the target that say where to assign are evaluated before the value.
+        NodeType::Assign => {
+            let (target, value) = expr.children.split_at_mut(1);
+            let mut parts: Vec<&mut Node> = match target[0].nodetype {
+                NodeType::Name(_) | NodeType::Index => target[0].children.iter_mut().collect(),
+                _ => Vec::new()
+            };
+            parts.push(&mut value[0]);
+            in_order(co, parts, looktables, globals, stack, objsys, ctx)
+        }
+        NodeType::MethodCall(_, _) => {
+            let Node { nodetype, children, .. } = expr;
+            let mut parts: Vec<&mut Node> = Vec::new();
+            if let NodeType::MethodCall(_, owner) = nodetype {
+                parts.push(owner);
+            }
+            parts.extend(children[0].children.iter_mut());
+            in_order(co, parts, looktables, globals, stack, objsys, ctx)
+        }
+        // Only the branch the condition takes is evaluated.
+        NodeType::IfElement => {
+            if let Some(awaited) = settle(co, &mut expr.children[0], looktables, globals, stack, objsys, ctx)? {
+                return Ok(Some(awaited));
+            }
+            match thrown(eval(&expr.children[0], looktables, globals, stack, objsys, ctx))? {
+                Object::Bool(true) => *expr = expr.children.swap_remove(1),
+                Object::Bool(false) if expr.children.len() > 2 => *expr = expr.children.swap_remove(2),
+                // An element that adds nothing.
+                Object::Bool(false) => expr.children = vec![Node::new(NodeType::Bool(false))],
+                _ => not_bool(ctx)
+            }
+            settle(co, expr, looktables, globals, stack, objsys, ctx)
+        }
+        // Of a for-in element, the iterable is evaluated once, before the loop.
+        NodeType::ForIn(_) if !suspends(&expr.children[2]) => settle(co, &mut expr.children[1], looktables, globals, stack, objsys, ctx),
+        NodeType::For |
+        NodeType::ForIn(_) => dart_evalerror("An 'await' in a collection 'for' element is only supported in the iterable of a for-in element.", ctx),
+        NodeType::Case => dart_evalerror("An 'await' in a case of a switch expression is not supported.", ctx),
+        NodeType::Cascade(_) if expr.children[1..].iter().any(suspends) => cascade(co, expr, looktables, globals, stack, objsys, ctx),
+        _ => in_order(co, expr.children.iter_mut().collect(), looktables, globals, stack, objsys, ctx)
+    }
+}
+
+
+// Settles the first of the parts, evaluated in this order, that has an await, after the ones before it.
+fn in_order(
+    co: &mut Coroutine,
+    mut parts: Vec<&mut Node>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Option<Object>, Box<Object>> {
+
+    let first = match parts.iter().position(|part| suspends(part)) {
+        Some(first) => first,
+        // Only the target of an assignment may have an await that none of its parts has.
+        None => dart_evalerror("An 'await' in the target of an assignment is only supported in its receiver or index.", ctx)
+    };
+    for part in parts.iter_mut().take(first) {
+        freeze(co, part, looktables, globals, stack, objsys, ctx)?;
+    }
+    settle(co, parts[first], looktables, globals, stack, objsys, ctx)
+}
+
+
+// Settles a cascade with an await in a section. The receiver is held in a temporary variable that
+// the sections use in place of '..', and the sections before the first await run as it settles.
+fn cascade(
+    co: &mut Coroutine,
+    expr: &mut Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Option<Object>, Box<Object>> {
+
+    if let Some(awaited) = settle(co, &mut expr.children[0], looktables, globals, stack, objsys, ctx)? {
+        return Ok(Some(awaited));
+    }
+    let value = thrown(eval(&expr.children[0], looktables, globals, stack, objsys, ctx))?;
+    if matches!((&expr.nodetype, &value), (NodeType::Cascade(true), Object::Null)) {
+        *expr = Node::new(NodeType::Null);
+        return Ok(None);
+    }
+    let name = match &expr.children[0].nodetype {
+        NodeType::Name(name) if name.starts_with("await#") => name.clone(),
+        _ => {
+            let name = temp(co);
+            stack.add(&name, value);
+            name
+        }
+    };
+    for section in &mut expr.children[1..] {
+        rename_receiver(section, &name);
+    }
+    let receiver = Node::new(NodeType::Name(name));
+    expr.children[0] = receiver.clone();
+    while expr.children.len() > 1 {
+        if let Some(awaited) = settle(co, &mut expr.children[1], looktables, globals, stack, objsys, ctx)? {
+            return Ok(Some(awaited));
+        }
+        thrown(eval(&expr.children[1], looktables, globals, stack, objsys, ctx))?;
+        expr.children.remove(1);
+    }
+    *expr = receiver;
+    Ok(None)
+}
+
+
+// Puts the variable that holds the receiver of a cascade in place of '..' in a section of it. The
+// sections of a cascade in it have a receiver of their own.
+fn rename_receiver(node: &mut Node, receiver: &str) {
+    match &mut node.nodetype {
+        NodeType::Name(name) if name == ".." => *name = receiver.to_string(),
+        NodeType::Cascade(_) => return rename_receiver(&mut node.children[0], receiver),
+        NodeType::MethodCall(_, owner) => rename_receiver(owner, receiver),
+        _ => {}
+    }
+    for child in node.children.iter_mut() {
+        rename_receiver(child, receiver);
+    }
+}
+
+
+// Evaluates part of an expression into a temporary variable that takes its place, so it is not
+// evaluated again. What can't change while waiting is left as it is.
+fn freeze(
+    co: &mut Coroutine,
+    part: &mut Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<(), Box<Object>> {
+
+    match &part.nodetype {
+        NodeType::Int(_) |
+        NodeType::Double(_) |
+        NodeType::Bool(_) |
+        NodeType::Null |
+        NodeType::This |
+        NodeType::Super |
+        NodeType::FunLit(_) => Ok(()),
+        NodeType::Str(_) if part.children.is_empty() => Ok(()),
+        // Temporaries, and names of classes and functions.
+        NodeType::Name(name) if part.children.is_empty() && (name.starts_with("await#") || !stack.has(name)) => Ok(()),
+        // Parts of what they are in, and not values of their own.
+        NodeType::ArgList |
+        NodeType::NamedArg(_) |
+        NodeType::MapEntry |
+        NodeType::Spread(_) |
+        NodeType::NullAware => {
+            for child in part.children.iter_mut() {
+                freeze(co, child, looktables, globals, stack, objsys, ctx)?;
+            }
+            Ok(())
+        }
+        // Elements that give any number of values, which a spread of them takes the place of.
+        NodeType::IfElement |
+        NodeType::For |
+        NodeType::ForIn(_) => {
+            let values = evaluator::collection(std::slice::from_ref(part), looktables, globals, stack, objsys, ctx)?;
+            let mut spread = Node::new(NodeType::Spread(false));
+            spread.children.push(hold(co, Object::new_list(values, "dynamic"), stack));
+            *part = spread;
+            Ok(())
+        }
+        _ => {
+            let value = thrown(eval(part, looktables, globals, stack, objsys, ctx))?;
+            *part = hold(co, value, stack);
+            Ok(())
+        }
+    }
+}
+
+
+// Whether a statement or expression may suspend: at an await, a yield or an 'await for'.
+// Functions defined in it suspend on their own.
+fn suspends(node: &Node) -> bool {
+    match &node.nodetype {
+        NodeType::Await |
+        NodeType::AwaitFor(_) |
+        NodeType::Yield(_) => true,
+        NodeType::FunDef(..) |
+        NodeType::FunLit(_) => false,
+        NodeType::MethodCall(_, owner) => suspends(owner) || node.children.iter().any(suspends),
+        _ => node.children.iter().any(suspends)
+    }
+}
+
+
+// A new temporary variable. The '#' keeps it apart from those of the program.
+fn temp(co: &mut Coroutine) -> String {
+    co.temps += 1;
+    format!("await#{}", co.temps)
+}
+
+
+fn hold(co: &mut Coroutine, value: Object, stack: &mut Stack) -> Node {
+    let name = temp(co);
+    stack.add(&name, value);
+    Node::new(NodeType::Name(name))
+}
+
+
+fn thrown(obj: Object) -> Result<Object, Box<Object>> {
+    match obj {
+        Object::Throw(e, st) => Err(Box::new(Object::Throw(e, st))),
+        obj => Ok(obj)
+    }
+}
+
+
+fn not_bool(ctx: &Ctx) -> ! {
+    // As dart.
+    dart_evalerror("Conditions must have a static type of 'bool'.", ctx)
+}
+
@@ -0,0 +1,70 @@
+// Duration of dart:core, kept in microseconds. Futures and timers are delayed by one.
+
+use crate::context::*;
+use crate::object::*;
+use crate::utils::dart_evalerror;
+
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_MINUTE: i64 = 60 * MICROS_PER_SECOND;
+const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+const MICROS_PER_DAY: i64 = 24 * MICROS_PER_HOUR;
+
+
+// Duration({days, hours, minutes, seconds, milliseconds, microseconds}), all of them named.
+pub fn construct(args: &[Object], ctx: &Ctx) -> Object {
+    let mut micros = 0;
+    for arg in args {
+        let (name, value) = match arg {
+            Object::NamedArg(name, value) => (name, value),
+            // As dart.
+            _ => dart_evalerror(format!("Too many positional arguments: 0 allowed, but {} found.", args.len()), ctx)
+        };
+        let unit = match name.as_str() {
+            "days" => MICROS_PER_DAY,
+            "hours" => MICROS_PER_HOUR,
+            "minutes" => MICROS_PER_MINUTE,
+            "seconds" => MICROS_PER_SECOND,
+            "milliseconds" => 1000,
+            "microseconds" => 1,
+            // As dart.
+            _ => dart_evalerror(format!("No named parameter with the name '{}'.", name), ctx)
+        };
+        match **value {
+            Object::Int(n) => micros += n * unit,
+            // As dart.
+            ref x => dart_evalerror(format!("The argument type '{}' can't be assigned to the parameter type 'int'.", x), ctx)
+        }
+    }
+    Object::Duration(micros)
+}
+
+
+pub fn get_property(micros: i64, name: &str, ctx: &Ctx) -> Object {
+    match name {
+        "inDays" => Object::Int(micros / MICROS_PER_DAY),
+        "inHours" => Object::Int(micros / MICROS_PER_HOUR),
+        "inMinutes" => Object::Int(micros / MICROS_PER_MINUTE),
+        "inSeconds" => Object::Int(micros / MICROS_PER_SECOND),
+        "inMilliseconds" => Object::Int(micros / 1000),
+        "inMicroseconds" => Object::Int(micros),
+        "isNegative" => Object::Bool(micros < 0),
+        // As dart.
+        _ => dart_evalerror(format!("The getter '{}' isn't defined for the class 'Duration'.", name), ctx)
+    }
+}
+
+
+// As dart: '1:02:03.000004', hours not wrapping into days.
+pub fn to_string(micros: i64) -> String {
+    let sign = if micros < 0 { "-" } else { "" };
+    let micros = micros.abs();
+    format!(
+        "{}{}:{:02}:{:02}.{:06}",
+        sign,
+        micros / MICROS_PER_HOUR,
+        micros % MICROS_PER_HOUR / MICROS_PER_MINUTE,
+        micros % MICROS_PER_MINUTE / MICROS_PER_SECOND,
+        micros % MICROS_PER_SECOND
+    )
+}
@@ -1,14 +1,50 @@
 use crate::builtin;
 use crate::context::*;
+use crate::coroutine;
+use crate::duration;
+use crate::exception;
+use crate::future;
+use crate::iterable;
+use crate::list;
+use crate::map;
+use crate::map::{DartMap, MapKey};
+use crate::set;
 use crate::node::*;
 use crate::object::Object;
-use crate::object::ParamObj;
+use crate::object::{ParamKind, ParamObj};
 use crate::objsys::ObjSys;
-use crate::stack::Stack;
-use crate::utils::dart_evalerror;
+use crate::stack::{Env, Stack};
+use crate::stream;
+use crate::string;
+use crate::types;
+use crate::utils::{dart_evalerror, dart_unhandled, expect_args};
 use crate::utils::dprint;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ops::{BitAnd, BitOr, BitXor};
+use std::rc::Rc;
+
+
+// Exceptions propagate like returns, all the way up to a try or main.
+// Gives the value of an evaluation, or returns early if it threw.
+macro_rules! propagate {
+    ($obj:expr) => {
+        match $obj {
+            Object::Throw(e, st) => return Object::Throw(e, st),
+            obj => obj
+        }
+    };
+}
+
+// The same, for the result of argnodes_to_argobjs.
+macro_rules! propagate_all {
+    ($objs:expr) => {
+        match $objs {
+            Ok(objs) => objs,
+            Err(thrown) => return *thrown
+        }
+    };
+}
 
 
 pub fn eval(
@@ -23,1299 +59,4153 @@ pub fn eval(
 
     match t {
 
-        NodeType::Assign => {
-            dprint("Eval: NodeType::Assign");
-            match &node.children[0].nodetype {
-                NodeType::Name(name) => {
-
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::Assign => eval_assign(node, looktables, globals, stack, objsys, ctx),
 
-                    if stack.has(name) {
-                        stack.add(name, right_obj);
-                        return Object::Null;
-                    }
-
-                    if !objsys.has_this() {
-                        // As dart.
-                        dart_evalerror(format!("Setter not found: '{}'", name), ctx)
-                    }
-                    let this = objsys.get_this_instance_mut();
+        NodeType::TypedVar(typ, name) => {
+            dprint("Eval: NodeType::TypedVar");
 
-                    if !this.has_field(name.to_string()) {
-                        // As dart.
-                        dart_evalerror(format!("The setter '{}' isn't defined for the class '{}'", name, this.classname), ctx)
-                    }
-                    this.set_field(name.to_string(), right_obj);
+            // Declared without initializer, so null until assigned.
+            declare(name, Object::Null, stack, objsys, ctx);
+            if typ == "dynamic" {
+                stack.make_dynamic(name);
+            }
+            Object::Null
+        }
 
-                    return Object::Null;
-                }
-                NodeType::TypedVar(_, name) => {
+        NodeType::Final(..) => eval_final(node, looktables, globals, stack, objsys, ctx),
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::Const => eval_const(node, looktables, globals, stack, objsys, ctx),
 
-                    if stack.has_in_lexscope(name) {
-                        // As dart.
-                        dart_evalerror(format!("'{}' is already declared in this scope.", name), ctx);
-                    }
-                    else {
-                        if objsys.has_this() {
-                            let this = objsys.get_this_instance_mut();
-                            if this.has_field(name.to_string()) {
-                                panic!("Variable with name {} already exists.", name);
-                            }
-                        }
-                        stack.add(name, right_obj);
-                    }
+        NodeType::Not => eval_not(node, looktables, globals, stack, objsys, ctx),
 
-                    return Object::Null;
-                }
-                _ => panic!("Illegal name for assignment: {}", &node.children[0].nodetype)
-            }
-        }
+        NodeType::LogOr => eval_log_or(node, looktables, globals, stack, objsys, ctx),
 
-        NodeType::Not => {
-            dprint("Eval: NodeType::Not");
+        NodeType::LogAnd => eval_log_and(node, looktables, globals, stack, objsys, ctx),
 
-            let obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+        NodeType::LessThan => eval_less_than(node, looktables, globals, stack, objsys, ctx),
 
-            return match obj {
-                Object::Bool(b) => {
-                    Object::Bool(!b)
-                }
-                _ => panic!("Illegal operand for '!'")
-            }
-        }
+        NodeType::GreaterThan => eval_greater_than(node, looktables, globals, stack, objsys, ctx),
 
-        NodeType::LogOr => {
-            dprint("Eval: NodeType::LogOr");
+        NodeType::LessOrEq => eval_less_or_eq(node, looktables, globals, stack, objsys, ctx),
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+        NodeType::GreaterOrEq => eval_greater_or_eq(node, looktables, globals, stack, objsys, ctx),
 
-            match left_obj {
+        NodeType::Equal => {
+            dprint("Eval: NodeType::Equal");
 
-                Object::Bool(b1) => {
+            let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            return equal_objects(left_obj, right_obj, looktables, globals, stack, objsys, ctx);
+        }
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::BitAnd => eval_bit_and(node, looktables, globals, stack, objsys, ctx),
 
-                    match right_obj {
+        NodeType::BitOr => eval_bit_or(node, looktables, globals, stack, objsys, ctx),
 
-                        Object::Bool(b2) => {
-                            return Object::Bool(b1 || b2)
-                        }
-                        _ => panic!("Illegal right operand for ||")
-                    }
-                }
-                _ => panic!("Illegal left operand for ||")
-            }
-        }
+        NodeType::BitXor => eval_bit_xor(node, looktables, globals, stack, objsys, ctx),
 
-        NodeType::LogAnd => {
-            dprint("Eval: NodeType::LogAnd");
+        NodeType::Add => eval_add(node, looktables, globals, stack, objsys, ctx),
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+        NodeType::Sub => eval_sub(node, looktables, globals, stack, objsys, ctx),
 
-            match left_obj {
+        NodeType::Mul => eval_mul(node, looktables, globals, stack, objsys, ctx),
 
-                Object::Bool(b1) => {
+        NodeType::Div => eval_div(node, looktables, globals, stack, objsys, ctx),
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::IntDiv => eval_int_div(node, looktables, globals, stack, objsys, ctx),
 
-                    match right_obj {
+        NodeType::PreIncrement |
+        NodeType::PreDecrement |
+        NodeType::PostIncrement |
+        NodeType::PostDecrement => eval_increment(node, looktables, globals, stack, objsys, ctx),
 
-                        Object::Bool(b2) => {
-                            return Object::Bool(b1 && b2)
-                        }
-                        _ => panic!("Illegal right operand for &&")
-                    }
-                }
-                _ => panic!("Illegal left operand for &&")
-            }
-        }
+        NodeType::Int(val) => {
+            dprint("Eval: NodeType::Int");
+            Object::Int(*val)
+        },
 
-        NodeType::LessThan => {
-            dprint("Eval: NodeType::LessThan");
+        NodeType::Double(val) => {
+            dprint("Eval: NodeType::Double");
+            Object::Double(*val)
+        },
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+        NodeType::Bool(v) => {
+            dprint("Eval: NodeType::Bool");
+            Object::Bool(*v)
+        },
 
-            match left_obj {
+        NodeType::Str(..) => eval_str(node, looktables, globals, stack, objsys, ctx),
 
-                Object::Int(n1) => {
+        NodeType::Name(..) => eval_name(node, looktables, globals, stack, objsys, ctx, &mut false),
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::Async(valuetype) => {
+            dprint("Eval: NodeType::Async");
 
-                    match right_obj {
+            // The body runs until its first await, and the function returns the future it completes.
+            let future = coroutine::start(&node.children[0], valuetype, looktables, globals, stack, objsys, ctx);
+            Object::Return(Box::new(future))
+        }
 
-                        Object::Int(n2) => {
-                            return Object::Bool(n1 < n2)
-                        }
-                        Object::Double(x2) => {
-                            return Object::Bool((n1 as f64) < x2)
-                        }
-                        _ => panic!("Illegal right operand for <")
-                    }
-                }
+        NodeType::AsyncStar(elemtype) => {
+            dprint("Eval: NodeType::AsyncStar");
 
-                Object::Double(x1) => {
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+            // The body runs when the stream is listened to, and pauses at each yield.
+            Object::Return(Box::new(coroutine::generate(&node.children[0], elemtype, stack, objsys, ctx)))
+        }
 
-                    match right_obj {
+        NodeType::SyncStar(elemtype) => {
+            dprint("Eval: NodeType::SyncStar");
 
-                        Object::Int(n2) => {
-                            return Object::Bool(x1 < (n2 as f64))
-                        }
-                        Object::Double(x2) => {
-                            return Object::Bool(x1 < x2)
-                        }
-                        _ => panic!("Illegal right operand for <")
-                    }
-                }
-                _ => panic!("Illegal left operand for <")
-            }
+            // The body runs as the Iterable it gives is iterated over, up to each yield.
+            Object::Return(Box::new(coroutine::iterate(&node.children[0], elemtype, stack, objsys, ctx)))
         }
 
-        NodeType::GreaterThan => {
-            dprint("Eval: NodeType::GreaterThan");
+        NodeType::AwaitFor(_) => {
+            dprint("Eval: NodeType::AwaitFor");
+            // As dart.
+            dart_evalerror("The asynchronous for-in can only be used in functions marked with 'async' or 'async*'.", ctx)
+        }
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+        NodeType::Yield(_) => {
+            dprint("Eval: NodeType::Yield");
+            // As dart.
+            dart_evalerror("Yield statements must be in a generator function (one marked with either 'async*' or 'sync*').", ctx)
+        }
 
-            match left_obj {
+        NodeType::Await => {
+            dprint("Eval: NodeType::Await");
 
-                Object::Int(n1) => {
+            // Awaits in async bodies are run by coroutine, which takes them out before eval gets here.
+            // As dart.
+            dart_evalerror("'await' can only be used in 'async' or 'async*' methods.", ctx)
+        }
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::Return => {
+            dprint(format!("Eval: NodeType::Return"));
+            let retval = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+            return Object::Return(Box::new(retval));
+        }
 
-                    match right_obj {
+        NodeType::MethodCall(..) => eval_method_call(node, looktables, globals, stack, objsys, ctx, &mut false),
 
-                        Object::Int(n2) => {
-                            return Object::Bool(n1 > n2)
-                        }
-                        Object::Double(x2) => {
-                            return Object::Bool((n1 as f64) > x2)
-                        }
-                        _ => panic!("Illegal right operand for >")
-                    }
-                }
+        NodeType::FunCall(..) => eval_funcall(node, looktables, globals, stack, objsys, ctx),
 
-                Object::Double(x1) => {
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::Record => eval_record(node, looktables, globals, stack, objsys, ctx),
 
-                    match right_obj {
+        NodeType::EnumValue(..) => eval_enum_value(node, looktables, globals, stack, objsys, ctx),
 
-                        Object::Int(n2) => {
-                            return Object::Bool(x1 > (n2 as f64))
-                        }
-                        Object::Double(x2) => {
-                            return Object::Bool(x1 > x2)
-                        }
-                        _ => panic!("Illegal right operand for >")
-                    }
-                }
-                _ => panic!("Illegal left operand for >")
-            }
+        NodeType::FunDef(s, _, _, _) => {
+            dprint("Eval: NodeType::FunDef");
+            // Capture the enclosing scopes, which includes the frame the function itself goes into.
+            let funcobj = create_closure(node, stack, objsys);
+            stack.add(s, funcobj);
+            return Object::Null;
         }
 
-        NodeType::LessOrEq => {
-            dprint("Eval: NodeType::LessOrEq");
+        NodeType::FunLit(_) => {
+            dprint("Eval: NodeType::FunLit");
+            create_closure(node, stack, objsys)
+        }
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+        NodeType::Conditional => eval_conditional(node, looktables, globals, stack, objsys, ctx),
 
-            match left_obj {
+        NodeType::While => eval_while(node, looktables, globals, stack, objsys, ctx),
 
-                Object::Int(n1) => {
+        NodeType::DoWhile => eval_do_while(node, looktables, globals, stack, objsys, ctx),
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::For => eval_for(node, looktables, globals, stack, objsys, ctx),
 
-                    match right_obj {
+        NodeType::ForIn(..) => eval_for_in(node, looktables, globals, stack, objsys, ctx),
 
-                        Object::Int(n2) => {
-                            return Object::Bool(n1 <= n2)
-                        }
-                        Object::Double(x2) => {
-                            return Object::Bool((n1 as f64) <= x2)
-                        }
-                        _ => panic!("Illegal right operand for <=")
-                    }
-                }
+        NodeType::Break(label) => {
+            dprint("Eval: NodeType::Break");
+            Object::Break(label.clone())
+        }
 
-                Object::Double(x1) => {
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::Continue(label) => {
+            dprint("Eval: NodeType::Continue");
+            Object::Continue(label.clone())
+        }
 
-                    match right_obj {
+        NodeType::Label(label) => {
+            dprint("Eval: NodeType::Label");
 
-                        Object::Int(n2) => {
-                            return Object::Bool(x1 <= (n2 as f64))
-                        }
-                        Object::Double(x2) => {
-                            return Object::Bool(x1 <= x2)
-                        }
-                        _ => panic!("Illegal right operand for <=")
-                    }
-                }
-                _ => panic!("Illegal left operand for <=")
+            // Only labeled statements that are not loops get here.
+            match eval(&node.children[0], looktables, globals, stack, objsys, ctx) {
+                Object::Break(Some(l)) if l == *label => Object::Null,
+                ret => ret
             }
         }
 
-        NodeType::GreaterOrEq => {
-            dprint("Eval: NodeType::GreaterOrEq");
+        NodeType::Switch => eval_switch(node, looktables, globals, stack, objsys, ctx),
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+        NodeType::SwitchExpr => eval_switch_expr(node, looktables, globals, stack, objsys, ctx),
 
-            match left_obj {
+        NodeType::Scope => {
+            dprint("Eval: NodeType::Scope");
 
-                Object::Int(n1) => {
+            stack.push_lex();
+            let ret = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+            stack.pop_lex();
+            ret
+        }
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::Block => eval_block(node, looktables, globals, stack, objsys, ctx),
 
-                    match right_obj {
+        NodeType::List(..) => eval_list(node, looktables, globals, stack, objsys, ctx),
 
-                        Object::Int(n2) => {
-                            return Object::Bool(n1 >= n2)
-                        }
-                        Object::Double(x2) => {
-                            return Object::Bool((n1 as f64) >= x2)
-                        }
-                        _ => panic!("Illegal right operand for >=")
-                    }
-                }
+        NodeType::Map(typeargs) => {
+            dprint("Eval: NodeType::Map");
 
-                Object::Double(x1) => {
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+            let entries = propagate_all!(collection(&node.children, looktables, globals, stack, objsys, ctx));
+            new_map(entries, typeargs, looktables, globals, stack, objsys, ctx)
+        }
 
-                    match right_obj {
+        NodeType::Set(..) => eval_set(node, looktables, globals, stack, objsys, ctx),
 
-                        Object::Int(n2) => {
-                            return Object::Bool(x1 >= (n2 as f64))
-                        }
-                        Object::Double(x2) => {
-                            return Object::Bool(x1 >= x2)
-                        }
-                        _ => panic!("Illegal right operand for >=")
-                    }
-                }
-                _ => panic!("Illegal left operand for >=")
-            }
-        }
+        NodeType::Index => eval_index(node, looktables, globals, stack, objsys, ctx, &mut false),
 
-        NodeType::Equal => {
-            dprint("Eval: NodeType::Equal");
+        NodeType::NullAware => {
+            dprint("Eval: NodeType::NullAware");
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
-            let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+            // The owner of a '?.' access. The access checks for null, see eval_owner.
+            eval(&node.children[0], looktables, globals, stack, objsys, ctx)
+        }
 
-            match left_obj {
+        NodeType::Cascade(..) => eval_cascade(node, looktables, globals, stack, objsys, ctx),
 
-                Object::Int(n1) => {
-                    return match right_obj {
-                        Object::Int(n2) => {
-                            Object::Bool(n1 == n2)
-                        }
-                        Object::Double(x2) => {
-                            Object::Bool((n1 as f64) == x2)
-                        }
-                        _ => Object::Bool(false)
-                    }
-                }
-                Object::Double(x1) => {
-                    return match right_obj {
-                        Object::Int(n2) => {
-                            Object::Bool(x1 == (n2 as f64))
-                        }
-                        Object::Double(x2) => {
-                            Object::Bool(x1 == x2)
-                        }
-                        _ => Object::Bool(false)
-                    }
-                }
-                Object::Bool(b1) => {
-                    return match right_obj {
-                        Object::Bool(b2) => {
-                            Object::Bool(b1 == b2)
-                        }
-                        _ => Object::Bool(false)
-                    }
+        NodeType::NullCheck => {
+            dprint("Eval: NodeType::NullCheck");
 
-                }
-                Object::String(s1) => {
-                    return match right_obj {
-                        Object::String(s2) => {
-                            Object::Bool(s1 == s2)
-                        }
-                        _ => Object::Bool(false)
-                    }
-                }
-                x => panic!("Equality not implemented for object: {:?}", x)
+            match eval_owner(&node.children[0], looktables, globals, stack, objsys, ctx) {
+                Some(obj) => null_check(obj, ctx),
+                None => Object::Null
             }
         }
 
-        NodeType::BitAnd => {
-            dprint("Eval: NodeType::BitAnd");
-
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+        NodeType::IfNull => {
+            dprint("Eval: NodeType::IfNull");
 
-            match &left_obj {
+            match propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx)) {
+                Object::Null => eval(&node.children[1], looktables, globals, stack, objsys, ctx),
+                obj => obj
+            }
+        }
 
-                Object::Int(s1) => {
+        NodeType::IfNullAssign => eval_if_null_assign(node, looktables, globals, stack, objsys, ctx),
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::Throw => eval_throw(node, looktables, globals, stack, objsys, ctx),
 
-                    match &right_obj {
+        NodeType::Rethrow => {
+            dprint("Eval: NodeType::Rethrow");
 
-                        Object::Int(s2) => {
-                            Object::Int(s1.bitand(s2))
-                        }
-                        _ => panic!("Illegal right operand for bitwise and: {:?}", &right_obj)
-                    }
-                }
-                _ => panic!("Illegal left operand for bitwise and: {:?}", &left_obj)
+            // Catch clauses keep what they caught under the reserved word, see Try.
+            if !stack.has("rethrow") {
+                // As dart.
+                dart_evalerror("'rethrow' can only be used in catch clauses.", ctx)
             }
+            stack.get("rethrow")
         }
 
-        NodeType::BitOr => {
-            dprint("Eval: NodeType::BitOr");
+        NodeType::Try => eval_try(node, looktables, globals, stack, objsys, ctx),
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+        NodeType::Is(typename) => {
+            dprint("Eval: NodeType::Is");
 
-            match &left_obj {
-
-                Object::Int(s1) => {
+            let obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+            Object::Bool(is_type(&obj, typename, objsys))
+        }
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+        NodeType::This |
+        NodeType::Super => {
+            dprint("Eval: NodeType::This");
 
-                    match &right_obj {
+            // In an extension, see Env::extension.
+            if matches!(node.nodetype, NodeType::This) && stack.has("this") {
+                return stack.get("this");
+            }
 
-                        Object::Int(s2) => {
-                            Object::Int(s1.bitor(s2))
-                        }
-                        _ => panic!("Illegal right operand for bitwise or: {:?}", &right_obj)
-                    }
-                }
-                _ => panic!("Illegal left operand for bitwise or: {:?}", &left_obj)
+            if !objsys.has_this() {
+                // As dart.
+                dart_evalerror(format!("Invalid context for '{}' expression.", t), ctx)
             }
+            Object::Reference(objsys.get_this())
         }
 
-        NodeType::BitXor => {
-            dprint("Eval: NodeType::BitXor");
+        NodeType::SuperCall => eval_super_call(node, looktables, globals, stack, objsys, ctx),
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+        NodeType::Null => {
+            dprint("Eval:: NodeType::Null");
+            return Object::Null;
+        }
 
-            match &left_obj {
+        _ => panic!("Unknown node type: {}", t)
+    }
+}
 
-                Object::Int(s1) => {
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+// An assignment to a name, a typed declaration, an index or a record pattern.
+#[inline(never)]
+fn eval_assign(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
-                    match &right_obj {
+    dprint("Eval: NodeType::Assign");
+    match &node.children[0].nodetype {
+        NodeType::Name(_) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            return assign(&node.children[0], right_obj, looktables, globals, stack, objsys, ctx);
+        }
+        NodeType::TypedVar(typ, name) => {
 
-                        Object::Int(s2) => {
-                            Object::Int(s1.bitxor(s2))
-                        }
-                        _ => panic!("Illegal right operand for bitwise xor: {:?}", &right_obj)
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            let right_obj = with_context_type(right_obj, &node.children[1], typ, objsys);
+            declare(name, right_obj, stack, objsys, ctx);
+            if typ == "dynamic" {
+                stack.make_dynamic(name);
+            }
+            return Object::Null;
+        }
+        NodeType::Index => {
+            let target = &node.children[0];
+            let owner = propagate!(eval(&target.children[0], looktables, globals, stack, objsys, ctx));
+            let index = propagate!(eval(&target.children[1], looktables, globals, stack, objsys, ctx));
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+
+            if let Some(thrown) = unmodifiable(&owner, "[]=", objsys, ctx) {
+                return thrown;
+            }
+            match &owner {
+                Object::List(items, elemtype) => {
+                    if let Some(thrown) = mistyped(&right_obj, elemtype, "value", objsys, ctx) {
+                        return thrown;
                     }
+                    return list::set_index(items, &index, right_obj, ctx);
                 }
-                _ => panic!("Illegal left operand for bitwise xor: {:?}", &left_obj)
+                Object::Map(entries, keytype, valuetype) => {
+                    if let Some(thrown) = mistyped(&index, keytype, "key", objsys, ctx).or_else(|| mistyped(&right_obj, valuetype, "value", objsys, ctx)) {
+                        return thrown;
+                    }
+                    propagate_all!(put_entry(entries, index, right_obj, looktables, globals, stack, objsys, ctx));
+                }
+                Object::Reference(_) => {
+                    propagate!(call_operator("[]=", vec![owner, index, right_obj], looktables, globals, stack, objsys, ctx));
+                }
+                // As dart.
+                Object::Null => return exception::error(
+                    "NoSuchMethodError",
+                    format!("The method '[]=' was called on null.\nReceiver: null\nTried calling: []=({}, {})", index, right_obj),
+                    ctx
+                ),
+                // As dart.
+                x => dart_evalerror(format!("The operator '[]=' isn't defined for '{}'.", x), ctx)
+            }
+            return Object::Null;
+        }
+        NodeType::RecordPattern => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            match match_pattern(&node.children[0], &right_obj, looktables, globals, stack, objsys, ctx) {
+                Ok(true) => Object::Null,
+                // As dart.
+                Ok(false) => exception::error(
+                    "TypeError",
+                    format!("type '{}' is not a subtype of type '{}' in type cast",
+                        runtime_type(&right_obj, objsys), types::of_pattern(&node.children[0])),
+                    ctx
+                ),
+                Err(thrown) => *thrown
             }
         }
+        _ => panic!("Illegal name for assignment: {}", &node.children[0].nodetype)
+    }
+}
 
-        NodeType::Add => {
-            dprint("Eval: NodeType::Add");
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+// A final or const declaration.
+#[inline(never)]
+fn eval_final(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
-            match &left_obj {
-                Object::Int(s1) => {
+    let NodeType::Final(keyword) = &node.nodetype else {
+        unreachable!()
+    };
+    dprint("Eval: NodeType::Final");
+
+    let decl = &node.children[0];
+    let (names, assigned) = match (&decl.nodetype, decl.children.first().map(|c| &c.nodetype)) {
+        (NodeType::TypedVar(_, name), _) => (vec![name.clone()], false),
+        (_, Some(NodeType::TypedVar(_, name))) => (vec![name.clone()], true),
+        (_, Some(NodeType::RecordPattern)) => (pattern_variables(&decl.children[0]), true),
+        _ => panic!("Invalid declaration: {}", decl.nodetype)
+    };
+    propagate!(eval(decl, looktables, globals, stack, objsys, ctx));
+    for name in &names {
+        stack.make_final(name, keyword, assigned);
+    }
+    Object::Null
+}
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
 
-                    match &right_obj {
-                        Object::Int(s2) => {
-                            Object::Int(s1 + s2)
-                        }
-                        Object::Double(s2) => {
-                            Object::Double(*s1 as f64 + s2)
-                        }
-                        _ => panic!("Illegal right operand for addition: {:?}", &right_obj)
-                    }
-                },
-                Object::Double(s1) => {
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+// A const expression, the same object each time it is evaluated.
+#[inline(never)]
+fn eval_const(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
-                    match &right_obj {
-                        Object::Int(s2) => {
-                            Object::Double(s1 + *s2 as f64)
-                        }
-                        Object::Double(s2) => {
-                            Object::Double(s1 + s2)
-                        }
-                        _ => panic!("Illegal right operand for addition: {:?}", &right_obj)
-                    }
-                }
-                Object::String(s1) => {
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+    dprint("Eval: NodeType::Const");
 
-                    match &right_obj {
-                        Object::String(s2) => {
-                            let mut ret = s1.clone();
-                            ret.push_str(s2);
-                            return Object::String(ret);
-                        }
-                        _ => panic!("Illegal right operand for addition: {:?}", &right_obj)
-                    }
-                }
-                _ => panic!("Illegal left operand for addition: {:?}", &left_obj)
+    let expr = &node.children[0];
+    if !matches!(expr.nodetype, NodeType::FunCall(_) | NodeType::List(_) | NodeType::Map(_) | NodeType::Set(_) | NodeType::Record) {
+        return propagate_all!(const_operand(expr, looktables, globals, stack, objsys, ctx));
+    }
+    // Every evaluation of equal const expressions gives the same object.
+    let key = propagate_all!(const_key(expr, looktables, globals, stack, objsys, ctx));
+    if let Some(value) = objsys.get_const(&key) {
+        return value;
+    }
+    let value = propagate!(eval(expr, looktables, globals, stack, objsys, ctx));
+    objsys.add_const(&key, value.clone());
+    value
+}
+
+// What a const object, list, map, set or record is canonicalized by: its form, with the values of its operands.
+// What a const object, list, map or set is canonicalized by: its form, with the values of its operands.
+fn const_key(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<String, Box<Object>> {
+
+    match node.nodetype {
+        NodeType::FunCall(_) |
+        NodeType::List(_) |
+        NodeType::Map(_) |
+        NodeType::Set(_) |
+        NodeType::MapEntry |
+        NodeType::ArgList |
+        NodeType::NamedArg(_) |
+        NodeType::Spread(_) |
+        NodeType::IfElement |
+        NodeType::Record => {
+            let children = node.children.iter().map(|c| const_key(c, looktables, globals, stack, objsys, ctx)).collect::<Result<Vec<String>, _>>()?;
+            Ok(format!("{:?}({})", node.nodetype, children.join(", ")))
+        }
+        // Nested const objects are canonical already, so they go by identity.
+        _ => Ok(match const_operand(node, looktables, globals, stack, objsys, ctx)? {
+            Object::List(items, _) => format!("@{:x}", Rc::as_ptr(&items) as usize),
+            Object::Map(entries, _, _) |
+            Object::Set(entries, _) => format!("@{:x}", Rc::as_ptr(&entries) as usize),
+            Object::Reference(refid) => format!("@{}", refid),
+            value => format!("{:?}", value)
+        })
+    }
+}
+
+
+// The variables in an operand of a const expression must be const. Nested const expressions check their own.
+fn check_const_names(node: &Node, stack: &Stack, ctx: &Ctx) {
+    match &node.nodetype {
+        NodeType::Name(name) if node.children.is_empty() => {
+            if stack.has(name) && stack.final_keyword(name).as_deref() != Some("const") {
+                // As dart.
+                dart_evalerror("Not a constant expression.", ctx);
             }
         }
+        NodeType::Const => {}
+        _ => node.children.iter().for_each(|c| check_const_names(c, stack, ctx))
+    }
+}
 
-        NodeType::Sub => {
-            dprint("Eval: NodeType::Sub");
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+// The value of an operand of a const expression.
+fn const_operand(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Object, Box<Object>> {
 
-            if node.children.len() == 1 {
-                return match &left_obj {
-                    Object::Int(n) => {
-                        Object::Int(-*n)
-                    }
-                    Object::Double(x) => {
-                        Object::Double(-*x)
-                    }
-                    _ => panic!("Illegal operand unary minus: {:?}", &left_obj)
-                }
-            }
+    check_const_names(node, stack, ctx);
+    match eval(node, looktables, globals, stack, objsys, ctx) {
+        thrown @ Object::Throw(_, _) => Err(Box::new(thrown)),
+        value => Ok(value)
+    }
+}
 
-            match &left_obj {
-                Object::Int(s1) => {
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+// '!' of a bool.
+#[inline(never)]
+fn eval_not(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
-                    match &right_obj {
-                        Object::Int(s2) => {
-                            Object::Int(s1 - s2)
-                        }
-                        Object::Double(s2) => {
-                            Object::Double(*s1 as f64 - s2)
-                        }
-                        _ => panic!("Illegal right operand for subtraction: {:?}", &right_obj)
-                    }
-                },
-                Object::Double(s1) => {
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+    dprint("Eval: NodeType::Not");
 
-                    match &right_obj {
-                        Object::Int(s2) => {
-                            Object::Double(s1 - *s2 as f64)
-                        }
-                        Object::Double(s2) => {
-                            Object::Double(s1 - s2)
-                        }
-                        _ => panic!("Illegal right operand for subtraction: {:?}", &right_obj)
-                    }
-                }
-                _ => panic!("Illegal left operand for subtraction: {:?}", &left_obj)
-            }
+    let obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+
+    return match obj {
+        Object::Bool(b) => {
+            Object::Bool(!b)
         }
+        _ => not_a("bool", &obj, objsys, ctx)
+    }
+}
+
+
+// '||', evaluating the right side only if needed.
+#[inline(never)]
+fn eval_log_or(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
+    dprint("Eval: NodeType::LogOr");
 
-        NodeType::Mul => {
-            dprint("Eval: NodeType::Mul");
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+    match left_obj {
 
-            match &left_obj {
-                Object::Int(s1) => {
+        Object::Bool(true) => left_obj,
+        Object::Bool(_) => {
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-                    match &right_obj {
-                        Object::Int(s2) => {
-                            Object::Int(s1 * s2)
-                        }
-                        Object::Double(s2) => {
-                            Object::Double(*s1 as f64 * s2)
-                        }
-                        _ => panic!("Illegal right operand for multiplication: {:?}", &right_obj)
-                    }
-                },
-                Object::Double(s1) => {
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+            match right_obj {
 
-                    match &right_obj {
-                        Object::Int(s2) => {
-                            Object::Double(s1 * *s2 as f64)
-                        }
-                        Object::Double(s2) => {
-                            Object::Double(s1 * s2)
-                        }
-                        _ => panic!("Illegal right operand for multiplication: {:?}", &right_obj)
-                    }
+                Object::Bool(_) => {
+                    return right_obj
                 }
-                _ => panic!("Illegal left operand for multiplication: {:?}", &left_obj)
+                _ => not_a("bool", &right_obj, objsys, ctx)
             }
         }
+        _ => not_a("bool", &left_obj, objsys, ctx)
+    }
+}
 
-        NodeType::Div => {
-            dprint("Eval: NodeType::Div");
 
-            let left_obj = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+// '&&', evaluating the right side only if needed.
+#[inline(never)]
+fn eval_log_and(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
-            match &left_obj {
-                Object::Int(s1) => {
+    dprint("Eval: NodeType::LogAnd");
 
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
 
-                    match &right_obj {
-                        Object::Int(s2) => {
-                            Object::Double(*s1 as f64 / *s2 as f64)
-                        }
-                        Object::Double(s2) => {
-                            Object::Double(*s1 as f64 / *s2)
-                        }
-                        _ => panic!("Illegal right operand for division: {:?}", &right_obj)
-                    }
-                },
-                Object::Double(s1) => {
-
-                    let right_obj = eval(&node.children[1], looktables, globals, stack, objsys, ctx);
-
-                    match &right_obj {
-                        Object::Int(s2) => {
-                            Object::Double(*s1 as f64 / *s2 as f64)
-                        }
-                        Object::Double(s2) => {
-                            Object::Double(*s1 as f64 / *s2)
-                        }
-                        _ => panic!("Illegal right operand for division: {:?}", &right_obj)
-                    }
-                },
-                _ => panic!("Illegal left operand for division: {:?}", &left_obj)
-            }
-        }
+    match left_obj {
 
-        NodeType::PreIncrement => {
-            dprint("Eval: NodeType::PreIncrement");
+        Object::Bool(false) => left_obj,
+        Object::Bool(_) => {
 
-            let valnode = &node.children[0];
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-            match valnode.nodetype {
-                NodeType::Name(ref s) => {
+            match right_obj {
 
-                    if stack.has(s) {
-                        let oldval = stack.get(s).clone();
-
-                        match oldval {
-                            Object::Int(n) => {
-                                let newval = Object::Int(n + 1);
-                                stack.add(s.as_str(), newval.clone());
-                                return newval;
-                            }
-                            _ => panic!("Illegal operand for preincrement.")
-                        }
-                    }
-                    else {
-                        let this = objsys.get_this_instance_mut();
-                        let oldval = this.get_field(s.clone()).clone();
-
-                        match oldval {
-                            Object::Int(n) => {
-                                let newval = Object::Int(n + 1);
-                                this.set_field(s.clone(), newval.clone());
-                                return newval;
-                            }
-                            _ => panic!("Illegal operand for preincrement.")
-                        }
-                    }
+                Object::Bool(_) => {
+                    return right_obj
                 }
-                _ => panic!("Illegal operand for preincrement: {}", valnode)
+                _ => not_a("bool", &right_obj, objsys, ctx)
             }
         }
+        _ => not_a("bool", &left_obj, objsys, ctx)
+    }
+}
 
-        NodeType::PreDecrement => {
-            dprint("Eval: NodeType::PreDecrement");
-
-            let valnode = &node.children[0];
-
-            match valnode.nodetype {
-                NodeType::Name(ref s) => {
 
-                    if stack.has(s) {
-                        let oldval = stack.get(s).clone();
+// '<' of numbers, or a user defined operator.
+#[inline(never)]
+fn eval_less_than(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
-                        match oldval {
-                            Object::Int(n) => {
-                                let newval = Object::Int(n - 1);
-                                stack.add(s.as_str(), newval.clone());
-                                return newval;
-                            }
-                            _ => panic!("Illegal operand for preincrement.")
-                        }
-                    }
-                    else {
-                        let this = objsys.get_this_instance_mut();
-                        let oldval = this.get_field(s.clone()).clone();
-
-                        match oldval {
-                            Object::Int(n) => {
-                                let newval = Object::Int(n - 1);
-                                this.set_field(s.clone(), newval.clone());
-                                return newval;
-                            }
-                            _ => panic!("Illegal operand for predecrement.")
-                        }
-                    }
-                }
-                _ => panic!("Illegal operand for predecrement: {}", valnode)
-            }
-        }
+    dprint("Eval: NodeType::LessThan");
 
-        NodeType::PostIncrement => {
-            dprint("Eval: NodeType::PostIncrement");
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
 
-            let valnode = &node.children[0];
+    match left_obj {
 
-            match valnode.nodetype {
-                NodeType::Name(ref s) => {
+        Object::Int(n1) => {
 
-                    if stack.has(s) {
-                        let oldval = stack.get(s).clone();
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-                        match oldval {
-                            Object::Int(n) => {
-                                let newval = Object::Int(n + 1);
-                                stack.add(s.as_str(), newval);
-                                return oldval;
-                            }
-                            _ => panic!("Illegal operand for increment.")
-                        }
-                    }
-                    else {
-                        let this = objsys.get_this_instance_mut();
-                        let oldval = this.get_field(s.clone()).clone();
-
-                        match oldval {
-                            Object::Int(n) => {
-                                let newval = Object::Int(n + 1);
-                                this.set_field(s.clone(), newval);
-                                return oldval;
-                            }
-                            _ => panic!("Illegal operand for increment.")
-                        }
+            match right_obj {
 
-                    }
+                Object::Int(n2) => {
+                    return Object::Bool(n1 < n2)
                 }
-                _ => panic!("Illegal operand for increment: {}", valnode)
+                Object::Double(x2) => {
+                    return Object::Bool((n1 as f64) < x2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
             }
         }
 
-        NodeType::PostDecrement => {
-            dprint("Eval: NodeType::PostDecrement");
-
-            let valnode = &node.children[0];
+        Object::Double(x1) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-            match valnode.nodetype {
-                NodeType::Name(ref s) => {
+            match right_obj {
 
-                    if stack.has(s) {
-                        let oldval = stack.get(s).clone();
-
-                        match oldval {
-                            Object::Int(n) => {
-                                let newval = Object::Int(n - 1);
-                                stack.add(s.as_str(), newval);
-                                return oldval;
-                            }
-                            _ => panic!("Illegal operand for decrement.")
-                        }
-                    }
-                    else {
-                        let this = objsys.get_this_instance_mut();
-                        let oldval = this.get_field(s.clone()).clone();
-
-                        match oldval {
-                            Object::Int(n) => {
-                                let newval = Object::Int(n - 1);
-                                this.set_field(s.clone(), newval);
-                                return oldval;
-                            }
-                            _ => panic!("Illegal operand for decrement.")
-                        }
-                    }
+                Object::Int(n2) => {
+                    return Object::Bool(x1 < (n2 as f64))
+                }
+                Object::Double(x2) => {
+                    return Object::Bool(x1 < x2)
                 }
-                _ => panic!("Illegal operand for decrement: {}", valnode)
+                _ => not_a("num", &right_obj, objsys, ctx)
             }
         }
+        Object::Reference(_) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            call_operator("<", vec![left_obj, right_obj], looktables, globals, stack, objsys, ctx)
+        }
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
+    }
+}
 
-        NodeType::Int(val) => {
-            dprint("Eval: NodeType::Int");
-            Object::Int(*val)
-        },
-
-        NodeType::Double(val) => {
-            dprint("Eval: NodeType::Double");
-            Object::Double(*val)
-        },
-
-        NodeType::Bool(v) => {
-            dprint("Eval: NodeType::Bool");
-            Object::Bool(*v)
-        },
-
-        NodeType::Str(s) => {
-            dprint("Eval: NodeType::Str");
-            if node.children.is_empty() {
-                return Object::String(s.clone())
-            }
 
-            let mut evaled_itps = Vec::new();
-            for itp in &node.children {
-                evaled_itps.push(eval(itp, looktables, globals, stack, objsys, ctx));
-            }
+// '>' of numbers, or a user defined operator.
+#[inline(never)]
+fn eval_greater_than(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
-            let parts : Vec<&str> = s.as_str().split("$").collect();
+    dprint("Eval: NodeType::GreaterThan");
 
-            let mut built : String = String::new();
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
 
-            for i in 0 .. evaled_itps.len() {
-                built = String::from(format!("{}{}{}", parts[0], built.clone(), evaled_itps[i].clone()).as_str());
-            }
-            built.push_str(parts.last().unwrap());
+    match left_obj {
 
-            return Object::String(built)
-        },
+        Object::Int(n1) => {
 
-        NodeType::Name(s) => {
-            dprint(format!("Eval: NodeType::Name({})", s));
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-            // For Name, having a child means having an owner.
-            if node.children.len() > 0 {
-                let owner = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+            match right_obj {
 
-                if let Object::Reference(refid) = owner {
-                    let instance = objsys.get_instance(&refid);
-                    return instance.get_field(s.to_string()).clone();
+                Object::Int(n2) => {
+                    return Object::Bool(n1 > n2)
                 }
-
-                panic!("Unexpected owner for {}: {}", s, owner)
+                Object::Double(x2) => {
+                    return Object::Bool((n1 as f64) > x2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
             }
+        }
 
+        Object::Double(x1) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-            if stack.has(s) {
-                dprint(format!("got value for {}", s));
-                return stack.get(s).clone();
-            }
-            else if objsys.has_this() {
-                let this = objsys.get_this_instance_mut();
-                return this.get_field(s.clone()).clone();
-            }
-            else {
-                stack.printstack();
-                // As dart.
-                dart_evalerror(format!("Undefined name: '{}'.", s), ctx);
+            match right_obj {
+
+                Object::Int(n2) => {
+                    return Object::Bool(x1 > (n2 as f64))
+                }
+                Object::Double(x2) => {
+                    return Object::Bool(x1 > x2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
             }
         }
-
-        NodeType::Return => {
-            dprint(format!("Eval: NodeType::Return"));
-            let retval = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
-            return Object::Return(Box::new(retval));
+        Object::Reference(_) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            call_operator(">", vec![left_obj, right_obj], looktables, globals, stack, objsys, ctx)
         }
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
+    }
+}
 
-        NodeType::MethodCall(name, owner, filename) => {
-            dprint(format!("Eval: NodeType::MethodCall({})", name));
-
-
-            let reference: Object = eval(owner, looktables, globals, stack, objsys, ctx);
-
-
-            if let Object::Reference(refid) = reference {
-
-                let instance = objsys.get_instance(&refid);
-                let c = objsys.get_class(&instance.classname);
-
-                let meth = c.get_method(name);
-                if let Object::Function(_, _, node, params) = meth {
-
-                    let argslist = &node.children[0];
 
-                    stack.push_call();
+// '<=' of numbers, or a user defined operator.
+#[inline(never)]
+fn eval_less_or_eq(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
-                    let oldfilename = ctx.filepath.clone();
-                    ctx.filepath = filename.clone();
+    dprint("Eval: NodeType::LessOrEq");
 
-                    let oldthis = objsys.get_this();
-                    objsys.set_this(instance.id.clone());
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
 
-                    for i in 0 .. params.len() {
-                        let argtree = &argslist.children[i];
-                        let argobj = eval(argtree, looktables, globals, stack, objsys, ctx);
-                        stack.add(params[i].name.as_str(), argobj);
-                    }
+    match left_obj {
 
-                    let result = eval(&node, looktables, globals, stack, objsys, ctx);
+        Object::Int(n1) => {
 
-                    objsys.set_this(oldthis);
-                    ctx.filepath = oldfilename;
-                    stack.pop_call();
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-                    return match result {
-                        Object::Return(v) => {
-                            *v.clone()
-                        }
+            match right_obj {
 
-                        _ => {
-                            result.clone()
-                        }
-                    }
+                Object::Int(n2) => {
+                    return Object::Bool(n1 <= n2)
+                }
+                Object::Double(x2) => {
+                    return Object::Bool((n1 as f64) <= x2)
                 }
+                _ => not_a("num", &right_obj, objsys, ctx)
             }
-            panic!("Can't access {} of {}", name, owner);
         }
 
-        NodeType::FunCall(s) => {
-            dprint(format!("Eval: NodeType::FunCall({})", s));
+        Object::Double(x1) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-            if stack.has(s) {
-                let funcobj = stack.get(s).clone();
+            match right_obj {
 
-                return match funcobj {
-                    Object::Function(_, _, _, _) => {
-                        call_function(funcobj, &node.children[0], looktables, globals, stack, objsys, ctx)
-                    }
-                    Object::Constructor(_, _, _, _) => {
-                        call_constructor(&funcobj, &node.children[0], looktables, globals, stack, objsys, ctx)
-                    }
-                    _ => panic!("Called non-callable.")
+                Object::Int(n2) => {
+                    return Object::Bool(x1 <= (n2 as f64))
                 }
-            }
-            else if builtin::has_function(s) {
-
-                let args = argnodes_to_argobjs(
-                    &node.children[0].children,
-                    looktables,
-                    globals,
-                    stack,
-                    objsys,
-                    ctx
-                );
-
-                return builtin::call(s, &args, ctx);
-            }
-            else {
-                println!("FuncCall, table: {}", &ctx.filepath);
-                let ltable = &looktables[&ctx.filepath];
-                if ltable.contains_key(s) {
-
-                    let funcindex = ltable.get(s).unwrap().clone();
-                    let funcnode = &globals[funcindex];
-
-                    return match funcnode.nodetype {
-                        NodeType::FunDef(_, _) => {
-                            call_function(
-                                create_function(&funcnode),
-                                &node.children[0],
-                                looktables,
-                                globals,
-                                stack,
-                                objsys,
-                                ctx)
-                        }
-                        NodeType::Constructor(_, _) => {
-                            call_constructor(
-                                &create_constructor(&funcnode),
-                                &node.children[0],
-                                looktables,
-                                globals,
-                                stack,
-                                objsys,
-                                ctx)
-                        }
-                        _ => panic!("Expected function definition or constructor.")
-                    }
+                Object::Double(x2) => {
+                    return Object::Bool(x1 <= x2)
                 }
-
-                panic!("Unknown function: {}", s)
+                _ => not_a("num", &right_obj, objsys, ctx)
             }
         }
-
-        NodeType::FunDef(s, _) => {
-            dprint("Eval: NodeType::FunDef");
-            let funcobj = create_function(node);
-            stack.add(s, funcobj);
-            return Object::Null;
+        Object::Reference(_) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            call_operator("<=", vec![left_obj, right_obj], looktables, globals, stack, objsys, ctx)
         }
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
+    }
+}
 
-        NodeType::Conditional => {
-            dprint("Eval: NodeType::Conditional");
 
-            for condnode in &node.children {
+// '>=' of numbers, or a user defined operator.
+#[inline(never)]
+fn eval_greater_or_eq(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::GreaterOrEq");
 
-                match condnode.nodetype {
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
 
-                    NodeType::If |
-                    NodeType::ElseIf => {
-                        let boolnode= &condnode.children[0];
+    match left_obj {
 
-                        let cond = eval(&boolnode, looktables, globals, stack, objsys, ctx);
-                        match cond {
+        Object::Int(n1) => {
 
-                            Object::Bool(v) => {
-                                if v {
-                                    let bodynode= &condnode.children[1];
-                                    stack.push_lex();
-                                    let ret = eval(&bodynode, looktables, globals, stack, objsys, ctx);
-                                    stack.pop_lex();
-                                    return ret;
-                                }
-                            }
-                            _ => panic!("Expected bool in conditional")
-                        }
-                    }
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-                    NodeType::Else => {
-                        let bodynode= &condnode.children[0];
-                        stack.push_lex();
-                        let ret = eval(&bodynode, looktables, globals, stack, objsys, ctx);
-                        stack.pop_lex();
-                        return ret;
-                    }
-                    _ => panic!("Invalid node in conditional!")
+            match right_obj {
 
+                Object::Int(n2) => {
+                    return Object::Bool(n1 >= n2)
                 }
+                Object::Double(x2) => {
+                    return Object::Bool((n1 as f64) >= x2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
             }
-
-            return Object::Null;
         }
 
-        NodeType::While => {
-            dprint("Eval: NodeType::While");
-
-            let boolnode = &node.children[0];
-            let block = &node.children[1];
-
-            let mut cond = eval(boolnode, looktables, globals, stack, objsys, ctx);
-
-            match &cond {
-
-                Object::Bool(mut v) => {
+        Object::Double(x1) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-                    while v {
-                        eval(block, looktables, globals, stack, objsys, ctx);
-                        cond = eval(boolnode, looktables, globals, stack, objsys, ctx);
+            match right_obj {
 
-                        match &cond {
-                            Object::Bool(newcond) => {
-                                v = *newcond;
-                            }
-                            _ => {
-                                panic!("Conditional no longer bool: {}", cond)
-                            }
-                        }
-                    }
+                Object::Int(n2) => {
+                    return Object::Bool(x1 >= (n2 as f64))
                 }
-                _ => panic!("Expected bool in conditional")
+                Object::Double(x2) => {
+                    return Object::Bool(x1 >= x2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
             }
-            return Object::Null;
         }
+        Object::Reference(_) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            call_operator(">=", vec![left_obj, right_obj], looktables, globals, stack, objsys, ctx)
+        }
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
+    }
+}
+
 
-        NodeType::DoWhile => {
-            dprint("Eval: NodeType::DoWhile");
+// '&' of ints.
+#[inline(never)]
+fn eval_bit_and(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
-            let block = &node.children[0];
-            let boolnode = &node.children[1];
+    dprint("Eval: NodeType::BitAnd");
 
-            eval(block, looktables, globals, stack, objsys, ctx);
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
 
-            let mut cond = eval(boolnode, looktables, globals, stack, objsys, ctx);
+    match &left_obj {
 
-            if let Object::Bool(mut b) = cond {
+        Object::Int(s1) => {
 
-                while b {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-                    eval(block, looktables, globals, stack, objsys, ctx);
-                    cond = eval(boolnode, looktables, globals, stack, objsys, ctx);
+            match &right_obj {
 
-                    match &cond {
-                        Object::Bool(new_b) => {
-                            b = *new_b;
-                        }
-                        _ => panic!("Conditional no longer bool: {}", cond)
-                    }
+                Object::Int(s2) => {
+                    Object::Int(s1.bitand(s2))
                 }
+                _ => not_a("int", &right_obj, objsys, ctx)
             }
-            else {
-                panic!("Expected bool in conditional")
-            }
-
-            return Object::Null;
         }
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
+    }
+}
 
-        NodeType::For => {
-            dprint("Eval: NodeType::For");
 
-            let assign = &node.children[0];
-            let condexpr = &node.children[1];
-            let mutexpr = &node.children[2];
-            let body = &node.children[3];
+// '|' of ints.
+#[inline(never)]
+fn eval_bit_or(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
-            eval(assign, looktables, globals, stack, objsys, ctx);
+    dprint("Eval: NodeType::BitOr");
 
-            loop {
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
 
-                let condobj = eval(condexpr, looktables, globals, stack, objsys, ctx);
+    match &left_obj {
 
-                match condobj {
-                    Object::Bool(b) => {
+        Object::Int(s1) => {
 
-                        if !b {
-                            break;
-                        }
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-                        eval(body, looktables, globals, stack, objsys, ctx);
-                        eval(mutexpr, looktables, globals, stack, objsys, ctx);
-                    }
-                    x => dart_evalerror(format!("Expected bool. Got: {}", x), ctx)
+            match &right_obj {
 
+                Object::Int(s2) => {
+                    Object::Int(s1.bitor(s2))
                 }
+                _ => not_a("int", &right_obj, objsys, ctx)
             }
-            return Object::Null;
         }
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
+    }
+}
 
-        NodeType::Block => {
-            dprint("Eval: NodeType::Block");
-
-            for c in &node.children {
-
-                let retval = eval(c, looktables, globals, stack, objsys, ctx);
 
-                match &retval {
-                    Object::Return(_) => {
-                        return retval;
-                    }
-                    _ => {}
-                }
-            }
-            return Object::Null;
-        }
+// '^' of ints.
+#[inline(never)]
+fn eval_bit_xor(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
 
-        NodeType::Null => {
-            dprint("Eval:: NodeType::Null");
-            return Object::Null;
-        }
+    dprint("Eval: NodeType::BitXor");
 
-        _ => panic!("Unknown node type: {}", t)
-    }
-}
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
 
+    match &left_obj {
 
-fn create_function(funcnode: &Node) -> Object {
+        Object::Int(s1) => {
 
-    match &funcnode.nodetype {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-        NodeType::FunDef(fname, filename) => {
-            let paramnodes = &funcnode.children[0];
-            let bodynode = &funcnode.children[1];
-            let mut paramobjs: Vec<ParamObj> = Vec::new();
+            match &right_obj {
 
-            for i in 0..paramnodes.children.len() {
-                let p = &paramnodes.children[i];
-                match &p.nodetype {
-                    NodeType::Name(s) => {
-                        paramobjs.push(ParamObj { typ: String::from("var"), name: s.clone(), fieldinit: false });
-                    }
-                    x => panic!("Invalid parameter: {}", x)
+                Object::Int(s2) => {
+                    Object::Int(s1.bitxor(s2))
                 }
+                _ => not_a("int", &right_obj, objsys, ctx)
             }
-            return Object::Function(fname.clone(), filename.clone(), bodynode.clone(), paramobjs);
         }
-        _ => panic!("Invalid node type.")
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
     }
 }
 
 
-fn call_function(
-    funcobj: Object,
-    args: &Node,
+
+
+// '+' of numbers or strings, or a user defined operator.
+#[inline(never)]
+fn eval_add(
+    node: &Node,
     looktables: &HashMap<String, HashMap<String, usize>>,
     globals: &Vec<Node>,
-    store: &mut Stack,
+    stack: &mut Stack,
     objsys: &mut ObjSys,
     ctx: &mut Ctx) -> Object {
 
-    match funcobj {
+    dprint("Eval: NodeType::Add");
 
-        Object::Function(_, filename, body, params) => {
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
 
-            let mut argobjs = argnodes_to_argobjs(
-                &args.children,
-                looktables,
-                globals,
-                store,
-                objsys,
-                ctx
-            );
+    match &left_obj {
+        Object::Int(s1) => {
 
-            // Argtrees must be evaluated in callers context, but stored in new context.
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-            store.push_call();
-            for i in 0..params.len() {
-                store.add(params[i].name.as_str(), argobjs.remove(0));
+            match &right_obj {
+                Object::Int(s2) => {
+                    Object::Int(s1 + s2)
+                }
+                Object::Double(s2) => {
+                    Object::Double(*s1 as f64 + s2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
             }
+        },
+        Object::Double(s1) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
 
-            let oldfilepath = ctx.filepath.clone();
-
-            ctx.filepath = filename;
-            println!("Setting filepath: {}", &ctx.filepath);
-
-            let result = eval(&body, looktables, globals, store, objsys, ctx);
-
-            ctx.filepath = oldfilepath;
-            println!("Restoring filepath: {}", &ctx.filepath);
-
-            store.pop_call();
-
-            return match result {
-                Object::Return(v) => {
-                    *v
+            match &right_obj {
+                Object::Int(s2) => {
+                    Object::Double(s1 + *s2 as f64)
                 }
-                _ => {
-                    result
+                Object::Double(s2) => {
+                    Object::Double(s1 + s2)
                 }
+                _ => not_a("num", &right_obj, objsys, ctx)
             }
         }
-        _ => panic!("Called a non-function object.")
-    }
-}
-
-
-fn create_constructor(funcnode: &Node) -> Object {
-
-    match &funcnode.nodetype {
-
-        NodeType::Constructor(cname, filename) => {
-
-            let paramnodes = &funcnode.children[0];
-            let bodynode = &funcnode.children[1];
-
-            let mut paramobjs : Vec<ParamObj> = Vec::new();
-
-            for i in 0..paramnodes.children.len() {
-                let p = &paramnodes.children[i];
-                match &p.nodetype {
-                    NodeType::Name(s) => {
-                        paramobjs.push(ParamObj{ typ: String::from(""), name: s.clone(), fieldinit: false });
-                    }
-                    NodeType::ThisFieldInit(s) => {
-                        paramobjs.push(ParamObj{ typ: String::from(""), name: s.clone(), fieldinit: true });
-                    }
-                    x => panic!("Invalid parameter: {}", x)
+        Object::String(s1) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+
+            match &right_obj {
+                Object::String(s2) => {
+                    let mut ret = s1.clone();
+                    ret.push_str(s2);
+                    return Object::String(ret);
                 }
+                _ => not_a("String", &right_obj, objsys, ctx)
             }
-
-            return Object::Constructor(cname.to_string(), filename.clone(), bodynode.clone(), paramobjs);
         }
-        _ => panic!("Invalid node type.")
+        Object::Reference(_) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            call_operator("+", vec![left_obj.clone(), right_obj], looktables, globals, stack, objsys, ctx)
+        }
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
     }
 }
 
 
-fn call_constructor(
-    funcobj: &Object,
-    args: &Node,
+// '-', binary or unary, of numbers or by a user defined operator.
+#[inline(never)]
+fn eval_sub(
+    node: &Node,
     looktables: &HashMap<String, HashMap<String, usize>>,
     globals: &Vec<Node>,
-    store: &mut Stack,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::Sub");
+
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+
+    if node.children.len() == 1 {
+        return match &left_obj {
+            Object::Int(n) => {
+                Object::Int(-*n)
+            }
+            Object::Double(x) => {
+                Object::Double(-*x)
+            }
+            Object::Reference(_) => call_operator("unary-", vec![left_obj.clone()], looktables, globals, stack, objsys, ctx),
+            // As dart.
+            Object::Null => exception::error(
+                "NoSuchMethodError",
+                "The method 'unary-' was called on null.\nReceiver: null\nTried calling: unary-()",
+                ctx
+            ),
+            x => exception::error(
+                "NoSuchMethodError",
+                format!("Class '{}' has no instance method 'unary-'.\nReceiver: {}\nTried calling: unary-()", runtime_type(x, objsys), x),
+                ctx
+            )
+        }
+    }
+
+    match &left_obj {
+        Object::Int(s1) => {
+
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+
+            match &right_obj {
+                Object::Int(s2) => {
+                    Object::Int(s1 - s2)
+                }
+                Object::Double(s2) => {
+                    Object::Double(*s1 as f64 - s2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
+            }
+        },
+        Object::Double(s1) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+
+            match &right_obj {
+                Object::Int(s2) => {
+                    Object::Double(s1 - *s2 as f64)
+                }
+                Object::Double(s2) => {
+                    Object::Double(s1 - s2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
+            }
+        }
+        Object::Reference(_) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            call_operator("-", vec![left_obj.clone(), right_obj], looktables, globals, stack, objsys, ctx)
+        }
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
+    }
+}
+
+
+// '*' of numbers, or a user defined operator.
+#[inline(never)]
+fn eval_mul(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::Mul");
+
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+
+    match &left_obj {
+        Object::Int(s1) => {
+
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+
+            match &right_obj {
+                Object::Int(s2) => {
+                    Object::Int(s1 * s2)
+                }
+                Object::Double(s2) => {
+                    Object::Double(*s1 as f64 * s2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
+            }
+        },
+        Object::Double(s1) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+
+            match &right_obj {
+                Object::Int(s2) => {
+                    Object::Double(s1 * *s2 as f64)
+                }
+                Object::Double(s2) => {
+                    Object::Double(s1 * s2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
+            }
+        }
+        Object::Reference(_) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            call_operator("*", vec![left_obj.clone(), right_obj], looktables, globals, stack, objsys, ctx)
+        }
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
+    }
+}
+
+
+// '/', which always gives a double.
+#[inline(never)]
+fn eval_div(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::Div");
+
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+
+    match &left_obj {
+        Object::Int(s1) => {
+
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+
+            match &right_obj {
+                Object::Int(s2) => {
+                    Object::Double(*s1 as f64 / *s2 as f64)
+                }
+                Object::Double(s2) => {
+                    Object::Double(*s1 as f64 / *s2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
+            }
+        },
+        Object::Double(s1) => {
+
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+
+            match &right_obj {
+                Object::Int(s2) => {
+                    Object::Double(*s1 as f64 / *s2 as f64)
+                }
+                Object::Double(s2) => {
+                    Object::Double(*s1 as f64 / *s2)
+                }
+                _ => not_a("num", &right_obj, objsys, ctx)
+            }
+        },
+        Object::Reference(_) => {
+            let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            call_operator("/", vec![left_obj.clone(), right_obj], looktables, globals, stack, objsys, ctx)
+        }
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
+    }
+}
+
+
+// '~/', which always gives an int.
+#[inline(never)]
+fn eval_int_div(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::IntDiv");
+
+    let left_obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+    let right_obj = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+
+    match (&left_obj, &right_obj) {
+        (Object::Int(_), Object::Int(0)) => exception::error("IntegerDivisionByZeroException", "", ctx),
+        (Object::Int(n1), Object::Int(n2)) => Object::Int(n1 / n2),
+        (Object::Int(_), Object::Double(_)) |
+        (Object::Double(_), Object::Int(_)) |
+        (Object::Double(_), Object::Double(_)) => {
+            let x = as_double(&left_obj) / as_double(&right_obj);
+            if !x.is_finite() {
+                // As dart.
+                return exception::error("UnsupportedError", format!("Result of truncating division is {}: {} ~/ {}", x, left_obj, right_obj), ctx);
+            }
+            Object::Int(x.trunc() as i64)
+        }
+        (Object::Reference(_), _) => call_operator("~/", vec![left_obj.clone(), right_obj.clone()], looktables, globals, stack, objsys, ctx),
+        (Object::Int(_) | Object::Double(_), _) => not_a("num", &right_obj, objsys, ctx),
+        _ => no_operator(&left_obj, node, looktables, globals, stack, objsys, ctx)
+    }
+}
+
+
+// The binary operator of node on a left operand that doesn't have it, as a dynamic one may be.
+fn no_operator(
+    left: &Object,
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let op = node.nodetype.to_string();
+    let right = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+    let message = match left {
+        // As dart.
+        Object::Null => format!("The method '{}' was called on null.\nReceiver: null\nTried calling: {}({})", op, op, right),
+        x => format!("Class '{}' has no instance method '{}'.\nReceiver: {}\nTried calling: {}({})", runtime_type(x, objsys), op, x, op, right)
+    };
+    exception::error("NoSuchMethodError", message, ctx)
+}
+
+
+// An operand of the wrong type, as a dynamic one may be.
+fn not_a(expected: &str, value: &Object, objsys: &ObjSys, ctx: &Ctx) -> Object {
+    // As dart.
+    exception::error("TypeError", format!("type '{}' is not a subtype of type '{}'", runtime_type(value, objsys), expected), ctx)
+}
+
+
+// The TypeError storing value as the parameter param of type t of a list, map or set throws, if it is not a t.
+// The checker rejects that, unless the collection is dynamic.
+fn mistyped(value: &Object, t: &str, param: &str, objsys: &ObjSys, ctx: &Ctx) -> Option<Object> {
+    if is_type(value, t, objsys) {
+        return None;
+    }
+    // As dart.
+    Some(exception::error("TypeError", format!("type '{}' is not a subtype of type '{}' of '{}'", runtime_type(value, objsys), t, param), ctx))
+}
+
+
+// '++' or '--', before or after what it changes.
+#[inline(never)]
+fn eval_increment(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let t: &NodeType = &node.nodetype;
+    dprint(format!("Eval: NodeType::{:?}", t));
+
+    let target = &node.children[0];
+    let oldval = propagate!(eval(target, looktables, globals, stack, objsys, ctx));
+    let delta = if matches!(t, NodeType::PreIncrement | NodeType::PostIncrement) { 1 } else { -1 };
+    let newval = match oldval {
+        Object::Int(n) => Object::Int(n + delta),
+        Object::Double(x) => Object::Double(x + delta as f64),
+        // As dart.
+        ref x => dart_evalerror(format!("The operator '{}' isn't defined for the class '{}'.", if delta > 0 { "+" } else { "-" }, runtime_type(x, objsys)), ctx)
+    };
+    propagate!(assign(target, newval.clone(), looktables, globals, stack, objsys, ctx));
+
+    match t {
+        NodeType::PreIncrement | NodeType::PreDecrement => newval,
+        _ => oldval
+    }
+}
+
+
+// A string literal, with its interpolations.
+#[inline(never)]
+fn eval_str(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let NodeType::Str(s) = &node.nodetype else {
+        unreachable!()
+    };
+    dprint("Eval: NodeType::Str");
+    if node.children.is_empty() {
+        return Object::String(s.clone())
+    }
+
+    let mut evaled_itps = Vec::new();
+    for itp in &node.children {
+        let value = propagate!(eval(itp, looktables, globals, stack, objsys, ctx));
+        evaled_itps.push(propagate!(stringify(&value, looktables, globals, stack, objsys, ctx)));
+    }
+
+    let parts : Vec<&str> = s.as_str().split("$").collect();
+
+    let mut built : String = String::from(parts[0]);
+
+    for (i, itp) in evaled_itps.iter().enumerate() {
+        built.push_str(&itp.to_string());
+        built.push_str(parts.get(i + 1).unwrap_or(&""));
+    }
+
+    return Object::String(built)
+}
+
+
+// The owner of a member access or an index. Gives None when a '?.' found null before it:
+// that skips the rest of the selectors, so 'a?.b.c' is null when a is.
+fn eval_owner(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Option<Object> {
+
+    let mut shorted = false;
+    let value = match &node.nodetype {
+        NodeType::NullAware => match eval(&node.children[0], looktables, globals, stack, objsys, ctx) {
+            Object::Null => return None,
+            value => value
+        },
+        NodeType::Name(_) if !node.children.is_empty() => eval_name(node, looktables, globals, stack, objsys, ctx, &mut shorted),
+        NodeType::MethodCall(..) => eval_method_call(node, looktables, globals, stack, objsys, ctx, &mut shorted),
+        NodeType::Index => eval_index(node, looktables, globals, stack, objsys, ctx, &mut shorted),
+        NodeType::NullCheck => null_check(eval_owner(&node.children[0], looktables, globals, stack, objsys, ctx)?, ctx),
+        _ => eval(node, looktables, globals, stack, objsys, ctx)
+    };
+    if shorted { None } else { Some(value) }
+}
+
+
+fn null_check(value: Object, ctx: &Ctx) -> Object {
+    match value {
+        // As dart.
+        Object::Null => exception::error("TypeError", "Null check operator used on a null value", ctx),
+        value => value
+    }
+}
+
+
+// A name, or a property of the owner that is its child.
+#[inline(never)]
+fn eval_name(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx,
+    shorted: &mut bool) -> Object {
+
+    let NodeType::Name(s) = &node.nodetype else {
+        unreachable!()
+    };
+    dprint(format!("Eval: NodeType::Name({})", s));
+
+    // For Name, having a child means having an owner.
+    if node.children.len() > 0 {
+        if let Some(classname) = static_owner(&node.children[0], stack, objsys) {
+            return match get_static(&classname, s, looktables, globals, stack, objsys, ctx) {
+                Some(value) => value,
+                // As dart.
+                None => dart_evalerror(format!("Member not found: '{}'.", s), ctx)
+            };
+        }
+        let owner = match eval_owner(&node.children[0], looktables, globals, stack, objsys, ctx) {
+            Some(owner) => propagate!(owner),
+            None => {
+                *shorted = true;
+                return Object::Null;
+            }
+        };
+
+        if s == "runtimeType" {
+            // Map and set literals are of the private classes _Map and _Set in dart, futures of _Future.
+            let t = runtime_type(&owner, objsys);
+            return Object::Type(if t.starts_with("Map<") || t.starts_with("Set<") || t.starts_with("Future<") { format!("_{}", t) } else { t });
+        }
+
+        // Members of the type of the owner win over those of extensions.
+        if !has_member(&owner, s, objsys) && !declared_dynamic(&node.children[0], stack) {
+            if let Some(getter) = find_extension(&owner, s, true, looktables, globals, objsys, ctx) {
+                return call_extension(getter, Vec::new(), looktables, globals, stack, objsys, ctx);
+            }
+        }
+
+        if let Object::Reference(refid) = &owner {
+            let instance = objsys.get_instance(refid);
+            if instance.has_field(s.to_string()) {
+                return instance.get_field(s.to_string()).clone();
+            }
+            let classname = instance.classname.clone();
+            if objsys.find_late(&classname, s).is_some() {
+                return late_field(refid.clone(), s, looktables, globals, stack, objsys, ctx);
+            }
+            if !objsys.is_getter(&classname, s) && objsys.is_subtype(&classname, "Iterable") && iterable::has_property(s) {
+                return iterable::get_property(&owner, s, looktables, globals, stack, objsys, ctx);
+            }
+            if !objsys.is_getter(&classname, s) {
+                // As dart.
+                return exception::no_getter(&runtime_type(&owner, objsys), &owner, s, ctx);
+            }
+            return call_member(s, vec![owner], looktables, globals, stack, objsys, ctx).unwrap();
+        }
+
+        match &owner {
+            Object::Record(positional, named) => {
+                // Positional fields are named '$1', '$2' and so on.
+                let field = match s.strip_prefix('$').and_then(|i| i.parse::<usize>().ok()) {
+                    Some(i) if i >= 1 => positional.get(i - 1),
+                    _ => named.iter().find(|(name, _)| name == s).map(|(_, v)| v)
+                };
+                return match field {
+                    Some(v) => v.clone(),
+                    // As dart.
+                    None => dart_evalerror(format!("The getter '{}' isn't defined for the type '{}'.", s, runtime_type(&owner, objsys)), ctx)
+                };
+            }
+            Object::List(items, elemtype) => return list::get_property(items, elemtype, s, ctx),
+            Object::Map(entries, keytype, valuetype) => return map::get_property(entries, keytype, valuetype, s, ctx),
+            Object::Set(set, elemtype) => return set::get_property(set, elemtype, s, ctx),
+            Object::Iterable(_, _) => return iterable::get_property(&owner, s, looktables, globals, stack, objsys, ctx),
+            Object::Iterator(_, _) => return propagate_all!(iterable::iterator_member(&owner, s, looktables, globals, stack, objsys, ctx)),
+            Object::MapEntry(k, v) => return map::get_entry_property(k, v, s, ctx),
+            Object::Exception(e) => return exception::get_property(e, s, ctx),
+            Object::String(string) => return string::get_property(string, s, ctx),
+            Object::Duration(micros) => return duration::get_property(*micros, s, ctx),
+            Object::Stream(_, _) | Object::StreamController(_, _) | Object::StreamSubscription(_) => return stream::get_property(&owner, s, ctx),
+            // As dart.
+            Object::Null => return exception::error(
+                "NoSuchMethodError",
+                format!("The getter '{}' was called on null.\nReceiver: null\nTried calling: {}", s, s),
+                ctx
+            ),
+            _ => {}
+        }
+
+        // As dart, for a getter it doesn't have.
+        return exception::no_getter(&runtime_type(&owner, objsys), &owner, s, ctx);
+    }
+
+
+    if stack.has(s) {
+        dprint(format!("got value for {}", s));
+        return stack.get(s);
+    }
+    else if is_this_member(s, looktables, stack, objsys, ctx) {
+        let mut member = Node::new(NodeType::Name(s.clone()));
+        member.children.push(Node::new(NodeType::This));
+        member.pos = node.pos;
+        eval(&member, looktables, globals, stack, objsys, ctx)
+    }
+    else if objsys.has_this() && objsys.get_this_instance_mut().has_field(s.clone()) {
+        let this = objsys.get_this_instance_mut();
+        return this.get_field(s.clone()).clone();
+    }
+    else if objsys.has_this() && objsys.find_late(&objsys.get_instance(&objsys.get_this()).classname, s).is_some() {
+        late_field(objsys.get_this(), s, looktables, globals, stack, objsys, ctx)
+    }
+    else if objsys.has_this() && objsys.is_getter(&objsys.get_instance(&objsys.get_this()).classname, s) {
+        let this = Object::Reference(objsys.get_this());
+        call_member(s, vec![this], looktables, globals, stack, objsys, ctx).unwrap()
+    }
+    else if let Some(value) = get_static(&objsys.get_this_class(), s, looktables, globals, stack, objsys, ctx) {
+        value
+    }
+    else if let Some(funcindex) = looktables[&ctx.filepath].get(s) {
+        // A top level function used as a value.
+        let funcnode = &globals[*funcindex];
+        if let NodeType::FunDef(..) = funcnode.nodetype {
+            return create_function(funcnode);
+        }
+        dart_evalerror(format!("Undefined name: '{}'.", s), ctx);
+    }
+    else if let Some(t) = type_literal(s, objsys) {
+        t
+    }
+    else {
+        stack.printstack();
+        // As dart.
+        dart_evalerror(format!("Undefined name: '{}'.", s), ctx);
+    }
+}
+
+
+// A method call on the value of the owner.
+#[inline(never)]
+fn eval_method_call(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx,
+    shorted: &mut bool) -> Object {
+
+    let NodeType::MethodCall(name, owner) = &node.nodetype else {
+        unreachable!()
+    };
+    dprint(format!("Eval: NodeType::MethodCall({})", name));
+
+    if let Some(classname) = static_owner(owner, stack, objsys) {
+        let meth = match objsys.find_static_method(&classname, name) {
+            Some(meth) => meth,
+            // As dart.
+            None => dart_evalerror(format!("Member not found: '{}'.", name), ctx)
+        };
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        // Static methods run without 'this'.
+        let saved = objsys.enter(String::new(), classname);
+        let result = call_function(meth, args, looktables, globals, stack, objsys, ctx);
+        objsys.leave(saved);
+        return result;
+    }
+
+    // Future.value(1), Stream<int>.fromIterable(xs) and so on.
+    if let NodeType::Name(ownername) = &owner.nodetype {
+        let base = types::split(ownername).0;
+        if owner.children.is_empty() && !stack.has(ownername) && ["Future", "Stream", "StreamController"].contains(&base.as_str()) {
+            let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+            if base == "Future" {
+                return future::call_static(name, args, objsys, ctx);
+            }
+            return stream::call_static(ownername, name, args, objsys, ctx);
+        }
+    }
+
+    let reference = match eval_owner(owner, looktables, globals, stack, objsys, ctx) {
+        Some(reference) => propagate!(reference),
+        None => {
+            *shorted = true;
+            return Object::Null;
+        }
+    };
+
+    if let Object::Null = reference {
+        // As dart.
+        return exception::error(
+            "NoSuchMethodError",
+            format!("The method '{}' was called on null.\nReceiver: null\nTried calling: {}()", name, name),
+            ctx
+        );
+    }
+
+    // What every object has, unless its class defines its own.
+    let own_to_string = match &reference {
+        Object::Reference(refid) => objsys.find_method(&objsys.get_instance(refid).classname, name).is_some(),
+        _ => false
+    };
+    if name == "toString" && !own_to_string {
+        return stringify(&reference, looktables, globals, stack, objsys, ctx);
+    }
+
+    // Members of the type of the receiver win over those of extensions.
+    if !has_member(&reference, name, objsys) && !declared_dynamic(owner, stack) {
+        if let Some(meth) = find_extension(&reference, name, false, looktables, globals, objsys, ctx) {
+            let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+            return call_extension(meth, args, looktables, globals, stack, objsys, ctx);
+        }
+    }
+
+    // As dart, for a method a list, set, map or string doesn't have.
+    if matches!(reference, Object::List(_, _) | Object::Set(_, _) | Object::Map(_, _, _) | Object::String(_)) && !has_member(&reference, name, objsys) {
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        return exception::no_method(&runtime_type(&reference, objsys), &reference, name, &args, ctx);
+    }
+
+    // The methods lists, sets and strings have as Iterables.
+    let shared = iterable::METHODS.contains(&name.as_str()) && matches!(reference, Object::List(_, _) | Object::Set(_, _) | Object::String(_));
+    if shared || matches!(reference, Object::Iterable(_, _)) {
+        let mut operands = vec![reference];
+        operands.extend(propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx)));
+        return iterable::call_method(name, operands, looktables, globals, stack, objsys, ctx);
+    }
+
+    if let Object::List(items, elemtype) = &reference {
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        // Joining calls the toString of each element.
+        if name == "join" {
+            let elements = items.borrow().clone();
+            let parts = propagate_all!(stringify_all(&elements, looktables, globals, stack, objsys, ctx));
+            if let Object::List(parts, _) = Object::new_list(parts, "String") {
+                return list::call_method(&parts, "String", name, args, ctx);
+            }
+        }
+        if matches!(name.as_str(), "indexOf" | "contains") && args.len() == 1 && (user_equality(&args[0], objsys) || items.borrow().iter().any(|elem| user_equality(elem, objsys))) {
+            // Compares with each element in turn, by its '=='.
+            let mut i = 0;
+            while let Some(elem) = items.borrow().get(i).cloned() {
+                match equal_objects(elem, args[0].clone(), looktables, globals, stack, objsys, ctx) {
+                    Object::Bool(true) => return if name == "contains" { Object::Bool(true) } else { Object::Int(i as i64) },
+                    thrown @ Object::Throw(_, _) => return thrown,
+                    _ => i += 1
+                }
+            }
+            return if name == "contains" { Object::Bool(false) } else { Object::Int(-1) };
+        }
+        if let Some(thrown) = unmodifiable(&reference, name, objsys, ctx).or_else(|| mistyped_arg(&reference, name, &args, objsys, ctx)) {
+            return thrown;
+        }
+        // Sets and lazy iterables give their elements first.
+        let mut args = args;
+        if let ("addAll", [other @ (Object::Set(_, _) | Object::Iterable(_, _) | Object::Reference(_))]) = (name.as_str(), args.as_slice()) {
+            let extra = propagate_all!(iterable::elements(other, looktables, globals, stack, objsys, ctx));
+            if let Some(thrown) = extra.iter().find_map(|elem| mistyped(elem, elemtype, "value", objsys, ctx)) {
+                return thrown;
+            }
+            args = vec![Object::new_list(extra, elemtype)];
+        }
+        return list::call_method(items, elemtype, name, args, ctx);
+    }
+
+    if let Object::Set(_, _) = &reference {
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        if let Some(thrown) = unmodifiable(&reference, name, objsys, ctx).or_else(|| mistyped_arg(&reference, name, &args, objsys, ctx)) {
+            return thrown;
+        }
+        return call_keyed(&reference, name, args, looktables, globals, stack, objsys, ctx);
+    }
+
+    if let Object::String(string) = &reference {
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        return string::call_method(string, name, args, ctx);
+    }
+
+    if let Object::Iterator(_, _) = &reference {
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        expect_args(name, &args, 0, ctx);
+        return propagate_all!(iterable::iterator_member(&reference, name, looktables, globals, stack, objsys, ctx));
+    }
+
+    if let Object::Future(future, _) = &reference {
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        return future::call_method(future, name, args, ctx);
+    }
+
+    if matches!(reference, Object::Stream(_, _) | Object::StreamController(_, _) | Object::StreamSubscription(_)) {
+        let mut operands = vec![reference];
+        operands.extend(propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx)));
+        return stream::call_method(name, operands, looktables, globals, stack, objsys, ctx);
+    }
+
+    if let Object::Map(entries, _, _) = &reference {
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        if let Some(thrown) = unmodifiable(&reference, name, objsys, ctx) {
+            return thrown;
+        }
+
+        if name == "forEach" {
+            // Iterate over a copy, so the callback can't invalidate the iteration.
+            let pairs = entries.borrow().entries().clone();
+            for (k, v) in pairs {
+                call_function(args[0].clone(), vec![k, v], looktables, globals, stack, objsys, ctx);
+            }
+            return Object::Null;
+        }
+        return call_keyed(&reference, name, args, looktables, globals, stack, objsys, ctx);
+    }
+
+    if let (Object::Function(..), "call") = (&reference, name.as_str()) {
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        return call_function(reference, args, looktables, globals, stack, objsys, ctx);
+    }
+
+    if let Object::Reference(refid) = reference {
+
+        let classname = objsys.get_instance(&refid).classname.clone();
+
+        // 'super.m()' continues the lookup after the class of the running method.
+        let found = match owner.nodetype {
+            NodeType::Super => objsys.find_super_method(&classname, &objsys.get_this_class(), name),
+            _ => objsys.find_method(&classname, name)
+        };
+        let (declaring, meth) = match found {
+            Some(found) => found,
+            // What a user defined Iterable inherits.
+            None if objsys.is_subtype(&classname, "Iterable") && iterable::METHODS.contains(&name.as_str()) => {
+                let mut operands = vec![Object::Reference(refid)];
+                operands.extend(propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx)));
+                return iterable::call_method(name, operands, looktables, globals, stack, objsys, ctx);
+            }
+            // A field holding a function.
+            None if objsys.get_instance(&refid).has_field(name.clone()) => {
+                let mut operands = vec![objsys.get_instance(&refid).get_field(name.clone()).clone()];
+                operands.extend(propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx)));
+                return call_value(name, operands, looktables, globals, stack, objsys, ctx);
+            }
+            // As dart.
+            None => {
+                let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+                let receiver = Object::Reference(refid);
+                return exception::no_method(&runtime_type(&receiver, objsys), &receiver, name, &args, ctx);
+            }
+        };
+
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+
+        if objsys.get_class(&declaring).getters.contains(name) {
+            // Calling a getter calls what it gives.
+            let saved = objsys.enter(refid, declaring);
+            let value = call_function(meth, Vec::new(), looktables, globals, stack, objsys, ctx);
+            objsys.leave(saved);
+            let mut operands = vec![propagate!(value)];
+            operands.extend(args);
+            return call_value(name, operands, looktables, globals, stack, objsys, ctx);
+        }
+
+        let saved = objsys.enter(refid, declaring);
+        let result = call_function(meth, args, looktables, globals, stack, objsys, ctx);
+        objsys.leave(saved);
+        return result;
+    }
+    // As dart.
+    let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+    exception::no_method(&runtime_type(&reference, objsys), &reference, name, &args, ctx)
+}
+
+
+// Calls what a field or getter gives, as in 'onTap()'. The first operand is the value called,
+// the rest are the arguments.
+fn call_value(
+    name: &str,
+    mut operands: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    match &operands[0] {
+        Object::Function(..) => {
+            let func = operands.remove(0);
+            call_function(func, operands, looktables, globals, stack, objsys, ctx)
+        }
+        // An instance of a class with a 'call' method.
+        Object::Reference(refid) if objsys.find_method(&objsys.get_instance(refid).classname, "call").is_some() => {
+            call_member("call", operands, looktables, globals, stack, objsys, ctx).unwrap()
+        }
+        // As dart.
+        Object::Null => exception::error(
+            "NoSuchMethodError",
+            format!("The method '{}' was called on null.\nReceiver: null\nTried calling: {}()", name, name),
+            ctx
+        ),
+        // As dart.
+        x => exception::no_method(&runtime_type(x, objsys), x, "call", &operands[1..], ctx)
+    }
+}
+
+
+// A call of a local or top level function, a builtin or a constructor.
+#[inline(never)]
+fn eval_funcall(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let NodeType::FunCall(s) = &node.nodetype else {
+        unreachable!()
+    };
+    dprint(format!("Eval: NodeType::FunCall({})", s));
+
+    // Type arguments, as in Box<int>(1), are reified on the instance constructed.
+    let (name, typeargs) = types::split(s);
+    let s = &name;
+
+    if stack.has(s) {
+        let funcobj = stack.get(s);
+
+        return match funcobj {
+            Object::Function(_, _, _, _, _) => {
+                let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+                call_function(funcobj, args, looktables, globals, stack, objsys, ctx)
+            }
+            Object::Constructor(_, _, _, _) => {
+                let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+                let instref = call_constructor(&funcobj, args, looktables, globals, stack, objsys, ctx);
+                give_typeargs(instref, &typeargs, objsys)
+            }
+            // A variable holding something else, as a dynamic one may.
+            value => {
+                let mut operands = vec![value];
+                operands.extend(propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx)));
+                call_value("call", operands, looktables, globals, stack, objsys, ctx)
+            }
+        }
+    }
+    else if is_this_member(s, looktables, stack, objsys, ctx) {
+        let mut call = Node::new(NodeType::MethodCall(s.clone(), Box::new(Node::new(NodeType::This))));
+        call.children = node.children.clone();
+        call.pos = node.pos;
+        eval(&call, looktables, globals, stack, objsys, ctx)
+    }
+    else if let Some((declaring, meth)) = this_method(s, objsys) {
+        // Implicit 'this', dispatched on the class of the instance.
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        let saved = objsys.enter(objsys.get_this(), declaring);
+        let result = call_function(meth, args, looktables, globals, stack, objsys, ctx);
+        objsys.leave(saved);
+        return result;
+    }
+    else if objsys.has_this() && objsys.get_instance(&objsys.get_this()).has_field(s.clone()) {
+        // A field of 'this' holding a function.
+        let mut operands = vec![objsys.get_instance(&objsys.get_this()).get_field(s.clone()).clone()];
+        operands.extend(propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx)));
+        call_value(s, operands, looktables, globals, stack, objsys, ctx)
+    }
+    else if let Some(meth) = objsys.find_static_method(&objsys.get_this_class(), s) {
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        let saved = objsys.enter(String::new(), objsys.get_this_class());
+        let result = call_function(meth, args, looktables, globals, stack, objsys, ctx);
+        objsys.leave(saved);
+        return result;
+    }
+    else if stream::has_constructor(s) {
+        let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+        stream::construct(&typeargs, args, ctx)
+    }
+    else if builtin::has_function(s) {
+
+        let args = propagate_all!(argnodes_to_argobjs(
+            &node.children[0].children,
+            looktables,
+            globals,
+            stack,
+            objsys,
+            ctx
+        ));
+
+        // Printing an object calls its toString.
+        if let (true, Some(obj)) = (s == "print", args.first()) {
+            let printed = propagate!(stringify(obj, looktables, globals, stack, objsys, ctx));
+            return builtin::call(s, &vec![printed], ctx);
+        }
+        return builtin::call(s, &args, ctx);
+    }
+    else {
+        dprint(format!("FuncCall, table: {}", &ctx.filepath));
+        let ltable = &looktables[&ctx.filepath];
+        if ltable.contains_key(s) {
+
+            let funcindex = ltable.get(s).unwrap().clone();
+            let funcnode = &globals[funcindex];
+
+            return match funcnode.nodetype {
+                NodeType::FunDef(..) => {
+                    let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+                    call_function(
+                        create_function(funcnode),
+                        args,
+                        looktables,
+                        globals,
+                        stack,
+                        objsys,
+                        ctx)
+                }
+                NodeType::Constructor(_, _) => {
+                    let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+                    let instref = call_constructor(
+                        &create_constructor(&funcnode),
+                        args,
+                        looktables,
+                        globals,
+                        stack,
+                        objsys,
+                        ctx);
+                    give_typeargs(instref, &typeargs, objsys)
+                }
+                NodeType::Extension(_) => dart_evalerror(format!("Explicitly applying the extension '{}' is not supported.", s), ctx),
+                _ => panic!("Expected function definition or constructor.")
+            }
+        }
+
+        panic!("Unknown function: {}", s)
+    }
+}
+
+
+// A record literal, with its positional and named fields.
+#[inline(never)]
+fn eval_record(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::Record");
+
+    let mut positional = Vec::new();
+    let mut named = Vec::new();
+    for field in propagate_all!(argnodes_to_argobjs(&node.children, looktables, globals, stack, objsys, ctx)) {
+        match field {
+            Object::NamedArg(name, value) => named.push((name, *value)),
+            value => positional.push(value)
+        }
+    }
+    named.sort_by(|(n1, _), (n2, _)| n1.cmp(n2));
+    Object::Record(Rc::new(positional), Rc::new(named))
+}
+
+
+// A value of an enum, which knows its index and name.
+#[inline(never)]
+fn eval_enum_value(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let NodeType::EnumValue(index, name) = &node.nodetype else {
+        unreachable!()
+    };
+    dprint(format!("Eval: NodeType::EnumValue({})", name));
+
+    let value = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+    if let Object::Reference(refid) = &value {
+        let inst = objsys.get_instance_mut(refid);
+        inst.set_field(String::from("index"), Object::Int(*index as i64));
+        inst.set_field(String::from("name"), Object::String(name.clone()));
+    }
+    value
+}
+
+
+// An if statement, with its else if and else branches.
+#[inline(never)]
+fn eval_conditional(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::Conditional");
+
+    for condnode in &node.children {
+
+        match condnode.nodetype {
+
+            NodeType::If |
+            NodeType::ElseIf => {
+                let boolnode= &condnode.children[0];
+
+                let cond = propagate!(eval(&boolnode, looktables, globals, stack, objsys, ctx));
+                match cond {
+
+                    Object::Bool(v) => {
+                        if v {
+                            let bodynode= &condnode.children[1];
+                            stack.push_lex();
+                            let ret = eval(&bodynode, looktables, globals, stack, objsys, ctx);
+                            stack.pop_lex();
+                            return ret;
+                        }
+                    }
+                    _ => return not_a("bool", &cond, objsys, ctx)
+                }
+            }
+
+            NodeType::Else => {
+                let bodynode= &condnode.children[0];
+                stack.push_lex();
+                let ret = eval(&bodynode, looktables, globals, stack, objsys, ctx);
+                stack.pop_lex();
+                return ret;
+            }
+            _ => panic!("Invalid node in conditional!")
+
+        }
+    }
+
+    return Object::Null;
+}
+
+
+// A while loop.
+#[inline(never)]
+fn eval_while(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::While");
+
+    let boolnode = &node.children[0];
+    let block = &node.children[1];
+    let label = loop_label(node, 2);
+
+    let mut cond = propagate!(eval(boolnode, looktables, globals, stack, objsys, ctx));
+
+    match &cond {
+
+        Object::Bool(mut v) => {
+
+            while v {
+                match loop_step(eval_body(block, looktables, globals, stack, objsys, ctx), label) {
+                    LoopStep::Next => {}
+                    LoopStep::Exit => break,
+                    LoopStep::Leave(ret) => return *ret
+                }
+                cond = propagate!(eval(boolnode, looktables, globals, stack, objsys, ctx));
+
+                match &cond {
+                    Object::Bool(newcond) => {
+                        v = *newcond;
+                    }
+                    _ => return not_a("bool", &cond, objsys, ctx)
+                }
+            }
+        }
+        _ => return not_a("bool", &cond, objsys, ctx)
+    }
+    return Object::Null;
+}
+
+
+// A do-while loop.
+#[inline(never)]
+fn eval_do_while(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::DoWhile");
+
+    let block = &node.children[0];
+    let boolnode = &node.children[1];
+    let label = loop_label(node, 2);
+
+    match loop_step(eval_body(block, looktables, globals, stack, objsys, ctx), label) {
+        LoopStep::Next => {}
+        LoopStep::Exit => return Object::Null,
+        LoopStep::Leave(ret) => return *ret
+    }
+
+    let mut cond = propagate!(eval(boolnode, looktables, globals, stack, objsys, ctx));
+
+    if let Object::Bool(mut b) = cond {
+
+        while b {
+
+            match loop_step(eval_body(block, looktables, globals, stack, objsys, ctx), label) {
+                LoopStep::Next => {}
+                LoopStep::Exit => break,
+                LoopStep::Leave(ret) => return *ret
+            }
+            cond = propagate!(eval(boolnode, looktables, globals, stack, objsys, ctx));
+
+            match &cond {
+                Object::Bool(new_b) => {
+                    b = *new_b;
+                }
+                _ => return not_a("bool", &cond, objsys, ctx)
+            }
+        }
+    }
+    else {
+        return not_a("bool", &cond, objsys, ctx);
+    }
+
+    return Object::Null;
+}
+
+
+// A for loop, each iteration with its own loop variables.
+#[inline(never)]
+fn eval_for(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::For");
+
+    let assign = &node.children[0];
+    let condexpr = &node.children[1];
+    let mutexpr = &node.children[2];
+    let body = &node.children[3];
+    let label = loop_label(node, 4);
+
+    // The loop variable is only in scope in the loop.
+    stack.push_lex();
+
+    let mut result = eval(assign, looktables, globals, stack, objsys, ctx);
+
+    while !is_abrupt(&result) {
+
+        let condobj = eval(condexpr, looktables, globals, stack, objsys, ctx);
+
+        match condobj {
+            Object::Bool(b) => {
+
+                if !b {
+                    result = Object::Null;
+                    break;
+                }
+
+                match loop_step(eval_body(body, looktables, globals, stack, objsys, ctx), label) {
+                    LoopStep::Next => {}
+                    LoopStep::Exit => {
+                        result = Object::Null;
+                        break;
+                    }
+                    LoopStep::Leave(ret) => {
+                        result = *ret;
+                        break;
+                    }
+                }
+                stack.renew_lex();
+                result = eval(mutexpr, looktables, globals, stack, objsys, ctx);
+            }
+            Object::Throw(_, _) => result = condobj,
+            x => dart_evalerror(format!("Expected bool. Got: {}", x), ctx)
+
+        }
+    }
+
+    stack.pop_lex();
+    result
+}
+
+
+// A for-in loop over an Iterable.
+#[inline(never)]
+fn eval_for_in(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let NodeType::ForIn(is_final) = &node.nodetype else {
+        unreachable!()
+    };
+    dprint("Eval: NodeType::ForIn");
+
+    let variable = &node.children[0];
+    let body = &node.children[2];
+    let label = loop_label(node, 3);
+
+    let iterable = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+    if !iterable::is_iterable(&iterable, objsys) {
+        // As dart.
+        dart_evalerror(format!("The type '{}' used in the 'for' loop must implement 'Iterable<dynamic>'.", runtime_type(&iterable, objsys)), ctx)
+    }
+    let mut cursor = match iterable::cursor(&iterable, looktables, globals, stack, objsys, ctx) {
+        Ok(cursor) => cursor,
+        Err(thrown) => return *thrown
+    };
+
+    loop {
+        let elem = match iterable::next(&mut cursor, looktables, globals, stack, objsys, ctx) {
+            Ok(Some(elem)) => elem,
+            Ok(None) => return Object::Null,
+            Err(thrown) => return *thrown
+        };
+
+        // Each iteration declares its own loop variable, for closures to capture.
+        stack.push_lex();
+        let matched = match_pattern(variable, &elem, looktables, globals, stack, objsys, ctx);
+        if *is_final {
+            for name in pattern_variables(variable) {
+                stack.make_final(&name, "final", true);
+            }
+        }
+        let ret = match matched {
+            Ok(true) => eval_body(body, looktables, globals, stack, objsys, ctx),
+            // As dart.
+            Ok(false) => exception::error(
+                "TypeError",
+                format!("type '{}' is not a subtype of type '{}' in type cast", runtime_type(&elem, objsys), types::of_pattern(variable)),
+                ctx
+            ),
+            Err(thrown) => *thrown
+        };
+        stack.pop_lex();
+
+        match loop_step(ret, label) {
+            LoopStep::Next => {}
+            LoopStep::Exit => return Object::Null,
+            LoopStep::Leave(ret) => return *ret
+        }
+    }
+}
+
+
+// A switch statement.
+#[inline(never)]
+fn eval_switch(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::Switch");
+
+    let subject = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+    match switch_case(node, &subject, looktables, globals, stack, objsys, ctx) {
+        Ok(Some(Object::Break(None))) => Object::Null,
+        Ok(Some(ret)) => ret,
+        Ok(None) => Object::Null,
+        Err(thrown) => *thrown
+    }
+}
+
+
+// A switch expression, giving the value of the first case that matches.
+#[inline(never)]
+fn eval_switch_expr(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::SwitchExpr");
+
+    let subject = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+    match switch_case(node, &subject, looktables, globals, stack, objsys, ctx) {
+        Ok(Some(ret)) => ret,
+        Ok(None) => exception::error("StateError", format!("No switch case matched the value {}.", subject), ctx),
+        Err(thrown) => *thrown
+    }
+}
+
+
+// A block, in a lexical scope of its own.
+#[inline(never)]
+fn eval_block(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::Block");
+
+    for c in &node.children {
+
+        let retval = eval(c, looktables, globals, stack, objsys, ctx);
+
+        if is_abrupt(&retval) {
+            return retval;
+        }
+    }
+    return Object::Null;
+}
+
+
+// A list literal, with its element type.
+#[inline(never)]
+fn eval_list(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let NodeType::List(typeargs) = &node.nodetype else {
+        unreachable!()
+    };
+    dprint("Eval: NodeType::List");
+
+    let items = propagate_all!(collection(&node.children, looktables, globals, stack, objsys, ctx));
+    // Without type arguments, the element type is inferred from the elements.
+    let elemtype = match typeargs.first() {
+        Some(t) => resolve_type(t, objsys),
+        None => types::lub(&items.iter().map(|obj| runtime_type(obj, objsys)).collect::<Vec<String>>(), objsys)
+    };
+    return Object::new_list(items, &elemtype);
+}
+
+
+// A set literal, or a map literal made of spreads of maps.
+#[inline(never)]
+fn eval_set(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let NodeType::Set(typeargs) = &node.nodetype else {
+        unreachable!()
+    };
+    dprint("Eval: NodeType::Set");
+
+    let items = propagate_all!(collection(&node.children, looktables, globals, stack, objsys, ctx));
+    // Spreads of maps make a map.
+    if node.children.iter().all(|e| matches!(e.nodetype, NodeType::Spread(_))) && matches!(items.first(), Some(Object::MapEntry(_, _))) {
+        return new_map(items, typeargs, looktables, globals, stack, objsys, ctx);
+    }
+    let elemtype = match typeargs.first() {
+        Some(t) => resolve_type(t, objsys),
+        None => types::lub(&items.iter().map(|obj| runtime_type(obj, objsys)).collect::<Vec<String>>(), objsys)
+    };
+    let set = Rc::new(RefCell::new(DartMap::new()));
+    for item in items {
+        propagate_all!(put_entry(&set, item, Object::Null, looktables, globals, stack, objsys, ctx));
+    }
+    Object::new_set(set.take(), &elemtype)
+}
+
+
+// Indexing a list, a map or an instance defining operator [].
+#[inline(never)]
+fn eval_index(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx,
+    shorted: &mut bool) -> Object {
+
+    dprint("Eval: NodeType::Index");
+
+    let owner = match eval_owner(&node.children[0], looktables, globals, stack, objsys, ctx) {
+        Some(owner) => propagate!(owner),
+        None => {
+            *shorted = true;
+            return Object::Null;
+        }
+    };
+    let index = propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+
+    return match &owner {
+        Object::List(items, _) => list::get_index(items, &index, ctx),
+        Object::Map(entries, _, _) => match propagate_all!(find_entry(entries, &index, looktables, globals, stack, objsys, ctx)) {
+            (_, Some(i)) => entries.borrow().entries()[i].1.clone(),
+            // A missing key gives null.
+            (_, None) => Object::Null
+        },
+        Object::Reference(_) => call_operator("[]", vec![owner, index], looktables, globals, stack, objsys, ctx),
+        // As dart.
+        Object::Null => exception::error(
+            "NoSuchMethodError",
+            format!("The method '[]' was called on null.\nReceiver: null\nTried calling: []({})", index),
+            ctx
+        ),
+        // As dart.
+        x => dart_evalerror(format!("The operator '[]' isn't defined for '{}'.", x), ctx)
+    }
+}
+
+
+// A cascade, giving its receiver.
+#[inline(never)]
+fn eval_cascade(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let NodeType::Cascade(nullaware) = &node.nodetype else {
+        unreachable!()
+    };
+    dprint("Eval: NodeType::Cascade");
+
+    let receiver = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+    if *nullaware && matches!(receiver, Object::Null) {
+        return Object::Null;
+    }
+
+    // The sections reach the receiver through the variable '..', in a scope of their own.
+    stack.push_lex();
+    stack.add("..", receiver.clone());
+    for section in &node.children[1..] {
+        let retval = eval(section, looktables, globals, stack, objsys, ctx);
+        if is_abrupt(&retval) {
+            stack.pop_lex();
+            return retval;
+        }
+    }
+    stack.pop_lex();
+    receiver
+}
+
+
+// '??=', assigning only if the target is null.
+#[inline(never)]
+fn eval_if_null_assign(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::IfNullAssign");
+
+    // The second child is the assignment to do, if the first is null.
+    match propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx)) {
+        Object::Null => {
+            propagate!(eval(&node.children[1], looktables, globals, stack, objsys, ctx));
+            eval(&node.children[0], looktables, globals, stack, objsys, ctx)
+        }
+        obj => obj
+    }
+}
+
+
+// A throw expression, which can't throw null.
+#[inline(never)]
+fn eval_throw(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::Throw");
+
+    let obj = propagate!(eval(&node.children[0], looktables, globals, stack, objsys, ctx));
+    if let Object::Null = obj {
+        // As dart.
+        return exception::error("TypeError", "Throw of null.", ctx);
+    }
+    exception::throw(obj, ctx)
+}
+
+
+
+
+// A try statement, with its catch clauses and finally.
+#[inline(never)]
+fn eval_try(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::Try");
+
+    stack.push_lex();
+    let mut result = eval(&node.children[0], looktables, globals, stack, objsys, ctx);
+    stack.pop_lex();
+
+    if let Object::Throw(thrown, trace) = &result {
+        let clause = node.children[1..].iter().find(|c| match &c.nodetype {
+            NodeType::Catch(Some(typename), _, _) => is_type(thrown, typename, objsys),
+            NodeType::Catch(None, _, _) => true,
+            _ => false
+        });
+
+        if let Some(NodeType::Catch(_, e, st)) = clause.map(|c| &c.nodetype) {
+            stack.push_lex();
+            stack.add("rethrow", result.clone());
+            if let Some(e) = e {
+                stack.add(e, (**thrown).clone());
+            }
+            if let Some(st) = st {
+                stack.add(st, Object::String(trace.clone()));
+            }
+            result = eval(&clause.unwrap().children[0], looktables, globals, stack, objsys, ctx);
+            stack.pop_lex();
+        }
+    }
+
+    // A finally that returns or throws wins over the rest.
+    if let Some(fin) = node.children.iter().find(|c| matches!(c.nodetype, NodeType::Finally)) {
+        stack.push_lex();
+        let finresult = eval(&fin.children[0], looktables, globals, stack, objsys, ctx);
+        stack.pop_lex();
+        if is_abrupt(&finresult) {
+            return finresult;
+        }
+    }
+    result
+}
+
+
+// A call of the superclass constructor from an initializer list.
+#[inline(never)]
+fn eval_super_call(
+    node: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    dprint("Eval: NodeType::SuperCall");
+
+    // Runs the superclass constructor on the instance under construction.
+    let parent = superclass_of(&objsys.get_this_class(), objsys, ctx);
+    let ctornode = match looktables[&ctx.filepath].get(&parent) {
+        Some(index) => &globals[*index],
+        // The classes of dart:core, like Iterable, have nothing to construct.
+        None if objsys.get_class(&parent).filepath.is_empty() => return Object::Null,
+        // As dart.
+        None => dart_evalerror(format!("Type '{}' not found.", parent), ctx)
+    };
+
+    let args = propagate_all!(argnodes_to_argobjs(&node.children[0].children, looktables, globals, stack, objsys, ctx));
+
+    let saved = objsys.enter(objsys.get_this(), parent);
+    let result = run_constructor(&create_constructor(ctornode), args, looktables, globals, stack, objsys, ctx);
+    objsys.leave(saved);
+    result
+}
+
+
+// A function object for a nested function or function literal, capturing where it is evaluated.
+fn create_closure(funcnode: &Node, stack: &Stack, objsys: &ObjSys) -> Object {
+    match create_function(funcnode) {
+        Object::Function(name, filename, body, params, _) => {
+            let env = Env { frames: stack.capture(), this: objsys.get_this() };
+            Object::Function(name, filename, body, params, env)
+        }
+        x => x
+    }
+}
+
+
+fn create_function(funcnode: &Node) -> Object {
+
+    // Function literals have no name.
+    let (fname, filename) = match &funcnode.nodetype {
+        NodeType::FunDef(fname, filename, _, _) => (fname.clone(), filename.clone()),
+        NodeType::FunLit(filename) => (String::new(), filename.clone()),
+        _ => panic!("Invalid node type.")
+    };
+
+    let paramnodes = &funcnode.children[0];
+    let bodynode = &funcnode.children[1];
+    let paramobjs: Vec<ParamObj> = paramnodes.children.iter().map(ParamObj::from_node).collect();
+
+    Object::Function(fname, filename, bodynode.clone(), paramobjs, Env::default())
+}
+
+
+// How deep dart calls may nest before a StackOverflowError, about as deep as in the dart vm.
+const MAX_CALL_DEPTH: usize = 10000;
+
+
+// The args must already be evaluated, in the callers context.
+pub fn call_function(
+    funcobj: Object,
+    argobjs: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    store: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    match funcobj {
+
+        Object::Function(fname, filename, body, params, env) => {
+
+            // Well before the native stack would overflow.
+            if ctx.calls.len() >= MAX_CALL_DEPTH {
+                return exception::error("StackOverflowError", "", ctx);
+            }
+
+            let values = propagate_all!(param_values(&params, argobjs, looktables, globals, store, objsys, ctx));
+            store.push_closure_call(&env);
+            for (param, value) in params.iter().zip(values) {
+                store.add(param.name.as_str(), value);
+                if param.typ == "dynamic" {
+                    store.make_dynamic(&param.name);
+                }
+            }
+
+            // Closures see the 'this' of where they were defined.
+            let oldthis = objsys.get_this();
+            if !env.this.is_empty() {
+                objsys.set_this(env.this.clone());
+            }
+
+            let oldfilepath = ctx.filepath.clone();
+
+            ctx.filepath = filename.clone();
+            dprint(format!("Setting filepath: {}", &ctx.filepath));
+
+            // As dart names function literals in stack traces.
+            let tracename = if fname.is_empty() { String::from("<anonymous closure>") } else { fname };
+            ctx.calls.push((tracename, filename));
+
+            let result = eval(&body, looktables, globals, store, objsys, ctx);
+
+            ctx.calls.pop();
+            ctx.filepath = oldfilepath;
+            dprint(format!("Restoring filepath: {}", &ctx.filepath));
+
+            objsys.set_this(oldthis);
+
+            store.pop_call();
+
+            return match result {
+                Object::Return(v) => {
+                    *v
+                }
+                Object::Break(_) |
+                Object::Continue(_) => {
+                    // As dart.
+                    dart_evalerror("A break or continue statement can't be used outside of a loop or switch statement.", ctx)
+                }
+                _ => {
+                    result
+                }
+            }
+        }
+        _ => panic!("Called a non-function object.")
+    }
+}
+
+
+fn create_constructor(funcnode: &Node) -> Object {
+
+    match &funcnode.nodetype {
+
+        NodeType::Constructor(cname, filename) => {
+
+            let paramnodes = &funcnode.children[0];
+            let bodynode = &funcnode.children[1];
+
+            let paramobjs: Vec<ParamObj> = paramnodes.children.iter().map(ParamObj::from_node).collect();
+
+            return Object::Constructor(cname.to_string(), filename.clone(), bodynode.clone(), paramobjs);
+        }
+        _ => panic!("Invalid node type.")
+    }
+}
+
+
+fn call_constructor(
+    funcobj: &Object,
+    args: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    store: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    match funcobj {
+
+        Object::Constructor(cname, _, _, _) => {
+
+            let class = objsys.get_class(cname.as_str());
+            if class.is_abstract {
+                // As dart.
+                dart_evalerror(format!("The class '{}' is abstract and can't be instantiated.", cname), ctx)
+            }
+
+            // Make an instance. Fields are set as the constructors along the chain run.
+            let mut inst = class.instantiate();
+            inst.typeargs = infer_typeargs(funcobj, &args, objsys, ctx);
+            let instref = objsys.register_instance(inst);
+
+            match &instref {
+                Object::Reference(refid) => {
+                    let saved = objsys.enter(refid.clone(), cname.clone());
+                    let result = run_constructor(funcobj, args, looktables, globals, store, objsys, ctx);
+                    objsys.leave(saved);
+                    propagate!(result);
+                    instref.clone()
+                }
+                _ => panic!("Couldn't find intance that was just created.")
+            }
+        }
+
+        _ => {
+            panic!("Called a non-constructor object.")
+        }
+    }
+}
+
+
+// The type arguments of an instance constructed without them, bound from the types of the
+// arguments given for parameters typed with the type parameters.
+fn infer_typeargs(funcobj: &Object, args: &[Object], objsys: &ObjSys, ctx: &Ctx) -> Vec<String> {
+    let (cname, params) = match funcobj {
+        Object::Constructor(cname, _, _, params) => (cname, params),
+        _ => panic!("Called a non-constructor object.")
+    };
+    let class = objsys.get_class(cname);
+    let mut bound = vec![Vec::new(); class.typeparams.len()];
+
+    for (param, arg) in params.iter().zip(bind_args(params, args.to_vec(), ctx)) {
+        // Parameters left to their default value bind nothing.
+        let arg = match arg {
+            Some(arg) => arg,
+            None => continue
+        };
+        // Field initializing parameters have the type of their field.
+        let paramtype = match param.fieldinit {
+            true => class.fields.iter().find(|(_, fname, _)| *fname == param.name).map(|(ftype, _, _)| ftype.clone()),
+            false => Some(param.typ.clone())
+        };
+        if let Some(paramtype) = paramtype {
+            types::bind(&paramtype, &runtime_type(&arg, objsys), &class.typeparams, &mut bound);
+        }
+    }
+    bound.iter().map(|b| if b.is_empty() { String::from("dynamic") } else { types::lub(b, objsys) }).collect()
+}
+
+
+// Sets type arguments given explicitly, as in Box<int>(1), on a newly constructed instance.
+fn give_typeargs(instref: Object, typeargs: &[String], objsys: &mut ObjSys) -> Object {
+    if let Object::Reference(refid) = &instref {
+        if !typeargs.is_empty() {
+            let typeargs = typeargs.iter().map(|t| resolve_type(t, objsys)).collect();
+            objsys.get_instance_mut(refid).typeargs = typeargs;
+        }
+    }
+    instref
+}
+
+
+// Run a constructor of the class of funcobj on the instance that is 'this'.
+// The args must already be evaluated, in the callers context. Gives null, or what it threw.
+fn run_constructor(
+    funcobj: &Object,
+    args: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    store: &mut Stack,
     objsys: &mut ObjSys,
     ctx: &mut Ctx) -> Object {
 
     match funcobj {
 
-        Object::Constructor(cname, filename, body, params) => {
+        Object::Constructor(cname, filename, body, params) => {
+
+            let args = propagate_all!(param_values(params, args, looktables, globals, store, objsys, ctx));
+
+            // Evaluate the initial field values declared by this class and its mixins.
+            let field_nodes = objsys.field_inits(cname);
+            let mut initvals = Vec::new();
+            for (ftype, fname, initexpr) in &field_nodes {
+                // Late fields are set when first used.
+                if objsys.find_late(cname, fname).is_some() {
+                    continue;
+                }
+                let initval = match initexpr {
+                    Some(initexpr) => {
+                        let initval = propagate!(eval(initexpr, looktables, globals, store, objsys, ctx));
+                        with_context_type(initval, initexpr, ftype, objsys)
+                    }
+                    None => Object::Null
+                };
+                initvals.push((fname.clone(), initval));
+            }
+
+            // Argtrees must be evaluated in callers context, but stored in new context.
+
+            store.push_call();
+            for i in 0..params.len() {
+                // Field initializers does not need to be in symbol table.
+                // They are set directly on the instance. See below.
+                if !params[i].fieldinit {
+                    store.add(params[i].name.as_str(), args[i].clone());
+                }
+            }
+
+            let oldfilename = ctx.filepath.clone();
+            ctx.filepath = filename.clone();
+            ctx.calls.push((format!("new {}", cname), filename.clone()));
+
+            dprint(format!("This: {}, classname: {}, filename: {}", objsys.get_this(), cname, filename));
+
+            // Set fields from params that uses "this" to auto-init.
+            // Ie Bike(this.gears)
+            let inst = objsys.get_this_instance_mut();
+            for (fname, val) in initvals {
+                inst.set_field(fname, val);
+            }
+            for i in 0..params.len() {
+                if params[i].fieldinit {
+                    inst.set_field(params[i].name.clone(), args[i].clone());
+                }
+            }
+
+            // Run body, which starts with the superclass constructor call if there is one.
+            let result = eval(body, looktables, globals, store, objsys, ctx);
+
+            ctx.calls.pop();
+            ctx.filepath = oldfilename;
+            dprint(format!("Resetting filepath to {}", ctx.filepath));
+
+            store.pop_call();
+
+            match result {
+                Object::Throw(_, _) => result,
+                _ => Object::Null
+            }
+        }
+
+        _ => {
+            panic!("Called a non-constructor object.")
+        }
+    }
+}
+
+
+fn as_double(obj: &Object) -> f64 {
+    match obj {
+        Object::Int(n) => *n as f64,
+        Object::Double(x) => *x,
+        x => panic!("Expected a number, got: {}", x)
+    }
+}
+
+
+// Report an exception nothing caught, like the dart vm does.
+pub fn unhandled(thrown: &Object, objsys: &ObjSys, ctx: &Ctx) -> ! {
+    match thrown {
+        Object::Throw(obj, trace) => {
+            let text = match &**obj {
+                Object::Reference(refid) => format!("Instance of '{}'", objsys.get_instance(refid).classname),
+                x => x.to_string()
+            };
+            dart_unhandled(text, trace, ctx)
+        }
+        _ => panic!("Not a thrown object: {:?}", thrown)
+    }
+}
+
+
+// Whether evaluation of a statement must stop what encloses it.
+pub fn is_abrupt(obj: &Object) -> bool {
+    matches!(obj, Object::Return(_) | Object::Throw(_, _) | Object::Break(_) | Object::Continue(_))
+}
+
+
+enum LoopStep {
+    Next,
+    Exit,
+    // Leave the loop and pass the result on.
+    Leave(Box<Object>)
+}
+
+
+// What a loop does after running its body once.
+fn loop_step(ret: Object, label: Option<&String>) -> LoopStep {
+    match ret {
+        Object::Break(None) => LoopStep::Exit,
+        Object::Continue(None) => LoopStep::Next,
+        Object::Break(Some(ref l)) if Some(l) == label => LoopStep::Exit,
+        Object::Continue(Some(ref l)) if Some(l) == label => LoopStep::Next,
+        ret if is_abrupt(&ret) => LoopStep::Leave(Box::new(ret)),
+        _ => LoopStep::Next
+    }
+}
+
+
+// The label of a loop is kept after its other children, see parser::labeled.
+pub fn loop_label(node: &Node, index: usize) -> Option<&String> {
+    match node.children.get(index).map(|c| &c.nodetype) {
+        Some(NodeType::Label(label)) => Some(label),
+        _ => None
+    }
+}
+
+
+// Each run of a loop body has a scope of its own.
+fn eval_body(
+    body: &Node,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    stack.push_lex();
+    let ret = eval(body, looktables, globals, stack, objsys, ctx);
+    stack.pop_lex();
+    ret
+}
+
+
+// A new variable in the innermost lexical scope.
+fn declare(name: &str, value: Object, stack: &mut Stack, objsys: &mut ObjSys, ctx: &Ctx) {
+    if stack.has_in_lexscope(name) {
+        // As dart.
+        dart_evalerror(format!("'{}' is already declared in this scope.", name), ctx);
+    }
+    if objsys.has_this() {
+        let this = objsys.get_this_instance_mut();
+        if this.has_field(name.to_string()) {
+            panic!("Variable with name {} already exists.", name);
+        }
+    }
+    stack.add(name, value);
+}
+
+
+// Runs the body of the first case of a switch that matches the subject.
+// Each case gets its own lexical scope for the variables its pattern binds.
+fn switch_case(
+    node: &Node,
+    subject: &Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Option<Object>, Box<Object>> {
+
+    for case in &node.children[1..] {
+        stack.push_lex();
+        let ret = match case_matches(case, subject, looktables, globals, stack, objsys, ctx) {
+            Ok(true) => Ok(Some(eval(&case.children[2], looktables, globals, stack, objsys, ctx))),
+            Ok(false) => Ok(None),
+            Err(thrown) => Err(thrown)
+        };
+        stack.pop_lex();
+        if !matches!(ret, Ok(None)) {
+            return ret;
+        }
+    }
+    Ok(None)
+}
+
+
+// Whether the subject matches the pattern of a case and its guard holds.
+pub fn case_matches(
+    case: &Node,
+    subject: &Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<bool, Box<Object>> {
+
+    match match_pattern(&case.children[0], subject, looktables, globals, stack, objsys, ctx) {
+        Ok(true) if !matches!(case.children[1].nodetype, NodeType::Null) => {
+            match eval(&case.children[1], looktables, globals, stack, objsys, ctx) {
+                Object::Bool(b) => Ok(b),
+                Object::Throw(e, st) => Err(Box::new(Object::Throw(e, st))),
+                // As dart.
+                _ => dart_evalerror("Conditions must have a static type of 'bool'.", ctx)
+            }
+        }
+        m => m
+    }
+}
+
+
+// Tests a value against a pattern, binding the variables of the pattern on the stack.
+pub fn match_pattern(
+    pattern: &Node,
+    value: &Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<bool, Box<Object>> {
+
+    match &pattern.nodetype {
+        NodeType::ConstPattern => {
+            match eval(&pattern.children[0], looktables, globals, stack, objsys, ctx) {
+                Object::Throw(e, st) => Err(Box::new(Object::Throw(e, st))),
+                constant => Ok(value.equals(&constant))
+            }
+        }
+        NodeType::VarPattern(typ, name) => {
+            if typ != "var" && !is_type(value, typ, objsys) {
+                return Ok(false);
+            }
+            if name != "_" {
+                stack.add(name, value.clone());
+            }
+            Ok(true)
+        }
+        NodeType::RelPattern(op) => {
+            let right = match eval(&pattern.children[0], looktables, globals, stack, objsys, ctx) {
+                Object::Throw(e, st) => return Err(Box::new(Object::Throw(e, st))),
+                right => right
+            };
+            match op.as_str() {
+                "==" => Ok(value.equals(&right)),
+                "!=" => Ok(!value.equals(&right)),
+                _ => Ok(relation(op, value, &right, ctx))
+            }
+        }
+        NodeType::OrPattern => {
+            Ok(match_pattern(&pattern.children[0], value, looktables, globals, stack, objsys, ctx)? ||
+               match_pattern(&pattern.children[1], value, looktables, globals, stack, objsys, ctx)?)
+        }
+        NodeType::AndPattern => {
+            Ok(match_pattern(&pattern.children[0], value, looktables, globals, stack, objsys, ctx)? &&
+               match_pattern(&pattern.children[1], value, looktables, globals, stack, objsys, ctx)?)
+        }
+        // A record of the same shape, with fields that match.
+        NodeType::RecordPattern => {
+            let (positional, named) = match value {
+                Object::Record(positional, named) => (positional, named),
+                _ => return Ok(false)
+            };
+            let npositional = pattern.children.iter().filter(|f| !matches!(f.nodetype, NodeType::NamedArg(_))).count();
+            if npositional != positional.len() || pattern.children.len() - npositional != named.len() {
+                return Ok(false);
+            }
+            let mut positional = positional.iter();
+            for field in &pattern.children {
+                let matched = match &field.nodetype {
+                    NodeType::NamedArg(name) => match named.iter().find(|(n, _)| n == name) {
+                        Some((_, v)) => match_pattern(&field.children[0], v, looktables, globals, stack, objsys, ctx)?,
+                        None => false
+                    },
+                    _ => match_pattern(field, positional.next().unwrap(), looktables, globals, stack, objsys, ctx)?
+                };
+                if !matched {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        // The variables of a pattern assignment: '(a, b) = (b, a)'.
+        NodeType::Name(_) => {
+            match assign(pattern, value.clone(), looktables, globals, stack, objsys, ctx) {
+                Object::Throw(e, st) => Err(Box::new(Object::Throw(e, st))),
+                _ => Ok(true)
+            }
+        }
+        x => panic!("Invalid pattern: {}", x)
+    }
+}
+
+
+// The variables a pattern declares.
+pub fn pattern_variables(pattern: &Node) -> Vec<String> {
+    match &pattern.nodetype {
+        NodeType::VarPattern(_, name) if name != "_" => vec![name.clone()],
+        _ => pattern.children.iter().flat_map(pattern_variables).collect()
+    }
+}
+
+
+// The relational operators of patterns, on numbers.
+fn relation(op: &str, left: &Object, right: &Object, ctx: &Ctx) -> bool {
+    let ordering = match (left, right) {
+        (Object::Int(n1), Object::Int(n2)) => n1.partial_cmp(n2),
+        (Object::Int(n1), Object::Double(x2)) => (*n1 as f64).partial_cmp(x2),
+        (Object::Double(x1), Object::Int(n2)) => x1.partial_cmp(&(*n2 as f64)),
+        (Object::Double(x1), Object::Double(x2)) => x1.partial_cmp(x2),
+        (Object::Int(_), _) |
+        (Object::Double(_), _) => None,
+        // As dart.
+        _ => dart_evalerror(format!("The operator '{}' isn't defined for '{}'.", op, left), ctx)
+    };
+    match (op, ordering) {
+        ("<", Some(o)) => o.is_lt(),
+        ("<=", Some(o)) => o.is_le(),
+        (">", Some(o)) => o.is_gt(),
+        (">=", Some(o)) => o.is_ge(),
+        _ => false
+    }
+}
+
+
+// The runtime type test of 'is'.
+pub fn is_type(obj: &Object, typename: &str, objsys: &ObjSys) -> bool {
+    types::is_subtype(&runtime_type(obj, objsys), &resolve_type(typename, objsys), objsys)
+}
+
+
+// The type of a value, with its reified type arguments.
+pub fn runtime_type(obj: &Object, objsys: &ObjSys) -> String {
+    match obj {
+        Object::Int(_) => String::from("int"),
+        Object::Double(_) => String::from("double"),
+        Object::Bool(_) => String::from("bool"),
+        Object::String(_) => String::from("String"),
+        Object::Null => String::from("Null"),
+        Object::List(_, elemtype) => format!("List<{}>", elemtype),
+        Object::Map(_, keytype, valuetype) => format!("Map<{}, {}>", keytype, valuetype),
+        Object::Set(_, elemtype) => format!("Set<{}>", elemtype),
+        Object::Iterable(_, elemtype) => format!("Iterable<{}>", elemtype),
+        Object::Iterator(_, elemtype) => format!("Iterator<{}>", elemtype),
+        Object::MapEntry(k, v) => format!("MapEntry<{}, {}>", runtime_type(k, objsys), runtime_type(v, objsys)),
+        Object::Record(positional, named) => {
+            let positional: Vec<String> = positional.iter().map(|v| runtime_type(v, objsys)).collect();
+            let named: Vec<(String, String)> = named.iter().map(|(n, v)| (n.clone(), runtime_type(v, objsys))).collect();
+            types::record(&positional, &named)
+        }
+        Object::Exception(e) => e.kind.clone(),
+        Object::Future(_, valuetype) => format!("Future<{}>", valuetype),
+        Object::Stream(_, elemtype) => format!("Stream<{}>", elemtype),
+        Object::StreamController(_, elemtype) => format!("StreamController<{}>", elemtype),
+        Object::StreamSubscription(_) => String::from("StreamSubscription<dynamic>"),
+        Object::Duration(_) => String::from("Duration"),
+        Object::Type(_) => String::from("Type"),
+        Object::Reference(refid) => objsys.get_instance(refid).runtime_type(),
+        _ => String::from("Function")
+    }
+}
+
+
+// Replaces the type parameters of the class of the running code with the type arguments of 'this'.
+fn resolve_type(typename: &str, objsys: &ObjSys) -> String {
+    let classname = objsys.get_this_class();
+    if !objsys.has_this() || !objsys.has_class(&classname) || objsys.get_class(&classname).typeparams.is_empty() {
+        return String::from(typename);
+    }
+    let this = objsys.get_instance(&objsys.get_this());
+    let typeargs = objsys.supertype_args(&this.classname, &this.typeargs, &classname);
+    types::substitute(typename, &objsys.get_class(&classname).typeparams, &typeargs)
+}
+
+
+// A name that is a type, used as a value.
+fn type_literal(name: &str, objsys: &ObjSys) -> Option<Object> {
+    let resolved = resolve_type(name, objsys);
+    let base = types::split(&resolved).0;
+    if resolved != name || types::BUILTINS.contains(&base.as_str()) || objsys.has_class(&base) || exception::has_constructor(&base) {
+        return Some(Object::Type(resolved));
+    }
+    None
+}
+
+
+// An untyped list, map or set literal gets its type arguments from where it is stored,
+// as in 'List<int> xs = [];'.
+fn with_context_type(value: Object, valnode: &Node, declared: &str, objsys: &ObjSys) -> Object {
+    let (base, args) = types::split(&resolve_type(declared, objsys));
+    match (&valnode.nodetype, value) {
+        (NodeType::List(written), Object::List(items, _)) if written.is_empty() && base == "List" && args.len() == 1 => {
+            Object::List(items, args[0].clone())
+        }
+        (NodeType::Map(written) | NodeType::Set(written), Object::Map(entries, _, _)) if written.is_empty() && base == "Map" && args.len() == 2 => {
+            Object::Map(entries, args[0].clone(), args[1].clone())
+        }
+        // '{}' is an empty set where a set is expected.
+        (NodeType::Map(written), Object::Map(_, _, _)) if written.is_empty() && valnode.children.is_empty() && base == "Set" && args.len() == 1 => {
+            Object::new_set(DartMap::new(), &args[0])
+        }
+        (NodeType::Set(written), Object::Set(set, _)) if written.is_empty() && base == "Set" && args.len() == 1 => {
+            Object::Set(set, args[0].clone())
+        }
+        (_, Object::Int(n)) if base.trim_end_matches('?') == "double" && types::is_int_literal(valnode) => Object::Double(n as f64),
+        (_, value) => value
+    }
+}
+
+
+// Assigns value to a variable or field, as named by target: 'x', 'this.x', 'p.x' or 'C.x'.
+fn assign(
+    target: &Node,
+    value: Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let name = match &target.nodetype {
+        NodeType::Name(name) => name,
+        x => panic!("Illegal name for assignment: {}", x)
+    };
+
+    // A field of some instance, ie 'this.x = 1' or 'p.x = 1'.
+    if !target.children.is_empty() {
+        if let Some(classname) = static_owner(&target.children[0], stack, objsys) {
+            return set_static(&classname, name, value, objsys, ctx);
+        }
+        let owner = match eval_owner(&target.children[0], looktables, globals, stack, objsys, ctx) {
+            Some(owner) => propagate!(owner),
+            None => return Object::Null
+        };
+        return match owner {
+            // As dart.
+            Object::Null => exception::error(
+                "NoSuchMethodError",
+                format!("The setter '{}=' was called on null.\nReceiver: null\nTried calling: {}={}", name, name, value),
+                ctx
+            ),
+            Object::Reference(refid) => {
+                let inst = objsys.get_instance(&refid);
+                let classname = inst.classname.clone();
+                if inst.has_field(name.to_string()) || objsys.find_late(&classname, name).is_some() {
+                    return set_field(&refid, name, value, objsys, ctx);
+                }
+                let setter = format!("{}=", name);
+                match call_member(&setter, vec![Object::Reference(refid), value], looktables, globals, stack, objsys, ctx) {
+                    Some(Object::Throw(e, st)) => Object::Throw(e, st),
+                    Some(_) => Object::Null,
+                    // As dart.
+                    None => dart_evalerror(format!("The setter '{}' isn't defined for the class '{}'", name, classname), ctx)
+                }
+            }
+            x => dart_evalerror(format!("The setter '{}' isn't defined for '{}'", name, x), ctx)
+        };
+    }
+
+    if stack.has(name) {
+        if let Some(keyword) = stack.final_keyword(name) {
+            // As dart.
+            dart_evalerror(format!("Can't assign to the {} variable '{}'.", keyword, name), ctx)
+        }
+        stack.set(name, value);
+        return Object::Null;
+    }
+
+    if objsys.has_this() {
+        let this = objsys.get_this();
+        let classname = objsys.get_instance(&this).classname.clone();
+        if objsys.get_instance(&this).has_field(name.to_string()) || objsys.find_late(&classname, name).is_some() {
+            return set_field(&this, name, value, objsys, ctx);
+        }
+    }
+
+    let this_class = objsys.get_this_class();
+    if objsys.has_class(&this_class) && objsys.get_class(&this_class).static_fields.iter().any(|(_, f, _)| f == name) {
+        return set_static(&this_class, name, value, objsys, ctx);
+    }
+
+    if !objsys.has_this() {
+        // As dart.
+        dart_evalerror(format!("Setter not found: '{}'", name), ctx)
+    }
+    let this = objsys.get_this_instance_mut();
+    let classname = this.classname.clone();
+    let this = Object::Reference(objsys.get_this());
+    match call_member(&format!("{}=", name), vec![this, value], looktables, globals, stack, objsys, ctx) {
+        Some(Object::Throw(e, st)) => Object::Throw(e, st),
+        Some(_) => Object::Null,
+        // As dart.
+        None => dart_evalerror(format!("The setter '{}' isn't defined for the class '{}'", name, classname), ctx)
+    }
+}
+
+
+// Sets a field of an instance. A final one can't be set, unless it is late and not set yet.
+fn set_field(refid: &str, name: &str, value: Object, objsys: &mut ObjSys, ctx: &Ctx) -> Object {
+    let inst = objsys.get_instance(refid);
+    let classname = inst.classname.clone();
+    let assigned = inst.has_field(name.to_string());
+
+    if objsys.is_final(&classname, name) {
+        if objsys.find_late(&classname, name).is_none() {
+            // As dart.
+            dart_evalerror(format!("The setter '{}' isn't defined for the class '{}'.", name, classname), ctx)
+        }
+        if assigned {
+            // As dart.
+            return exception::error("LateInitializationError", format!("Field '{}' has already been initialized.", name), ctx);
+        }
+    }
+    objsys.get_instance_mut(refid).set_field(name.to_string(), value);
+    Object::Null
+}
+
+
+// Reads a late field that is not set: runs its initializer, or throws if it has none.
+fn late_field(
+    refid: String,
+    name: &str,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let classname = objsys.get_instance(&refid).classname.clone();
+    match objsys.find_late(&classname, name) {
+        Some((declaring, Some(initexpr))) => {
+            let saved = objsys.enter(refid.clone(), declaring);
+            let value = eval(&initexpr, looktables, globals, stack, objsys, ctx);
+            objsys.leave(saved);
+            let value = propagate!(value);
+            objsys.get_instance_mut(&refid).set_field(name.to_string(), value.clone());
+            value
+        }
+        // As dart.
+        _ => exception::error("LateInitializationError", format!("Field '{}' has not been initialized.", name), ctx)
+    }
+}
+
+
+// The class named by the owner of 'C.x', when C is not a variable.
+fn static_owner(owner: &Node, stack: &Stack, objsys: &ObjSys) -> Option<String> {
+    match &owner.nodetype {
+        NodeType::Name(name) if owner.children.is_empty() && !stack.has(name) && objsys.has_class(name) => Some(name.clone()),
+        _ => None
+    }
+}
+
+
+// A static field or method of classname as a value. A static field is initialized when first used.
+fn get_static(
+    classname: &str,
+    name: &str,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Option<Object> {
+
+    if !objsys.has_class(classname) {
+        return None;
+    }
+    let class = objsys.get_class(classname);
+    if let Some(value) = class.statics.get(name) {
+        return Some(value.clone());
+    }
+    if let Some(meth) = class.static_methods.get(name) {
+        return Some(meth.clone());
+    }
+    let (ftype, _, initexpr) = class.static_fields.iter().find(|(_, f, _)| f == name)?.clone();
+
+    let value = match initexpr {
+        Some(initexpr) => {
+            let oldfilepath = std::mem::replace(&mut ctx.filepath, class.filepath.clone());
+            let saved = objsys.enter(String::new(), String::from(classname));
+            let value = eval(&initexpr, looktables, globals, stack, objsys, ctx);
+            objsys.leave(saved);
+            ctx.filepath = oldfilepath;
+            if let Object::Throw(_, _) = value {
+                return Some(value);
+            }
+            with_context_type(value, &initexpr, &ftype, objsys)
+        }
+        None => Object::Null
+    };
+    objsys.set_static(classname, name, value.clone());
+    Some(value)
+}
+
+
+fn set_static(classname: &str, name: &str, value: Object, objsys: &mut ObjSys, ctx: &Ctx) -> Object {
+    let class = objsys.get_class(classname);
+    if !class.static_fields.iter().any(|(_, f, _)| f == name) || class.finals.iter().any(|f| f == name) {
+        // As dart.
+        dart_evalerror(format!("Setter not found: '{}'.", name), ctx)
+    }
+    objsys.set_static(classname, name, value);
+    Object::Null
+}
 
-            let args = argnodes_to_argobjs(
-                &args.children,
-                looktables,
-                globals,
-                store,
-                objsys,
-                ctx
-            );
 
-            // Argtrees must be evaluated in callers context, but stored in new context.
+// A method of 'this' called without a receiver.
+fn this_method(name: &str, objsys: &ObjSys) -> Option<(String, Object)> {
+    if !objsys.has_this() {
+        return None;
+    }
+    let classname = &objsys.get_instance(&objsys.get_this()).classname;
+    objsys.find_method(classname, name)
+}
 
-            store.push_call();
-            for i in 0..params.len() {
-                // Field initializers does not need to be in symbol table.
-                // They are set directly on the instance. See below.
-                if !params[i].fieldinit {
-                    store.add(params[i].name.as_str(), args[i].clone());
+
+// Whether the type of receiver has a member by that name. Extensions only add to a type.
+fn has_member(receiver: &Object, name: &str, objsys: &ObjSys) -> bool {
+    if matches!(name, "toString" | "hashCode" | "runtimeType") {
+        return true;
+    }
+    let name = &name;
+    match receiver {
+        Object::Reference(refid) => {
+            let inst = objsys.get_instance(refid);
+            let iterable = objsys.is_subtype(&inst.classname, "Iterable") && (iterable::METHODS.contains(name) || iterable::has_property(name));
+            inst.has_field(name.to_string()) || objsys.find_method(&inst.classname, name).is_some() || iterable
+        }
+        Object::List(_, _) => list::MEMBERS.contains(name) || iterable::METHODS.contains(name),
+        Object::Set(_, _) => set::MEMBERS.contains(name) || iterable::METHODS.contains(name),
+        Object::String(_) => string::MEMBERS.contains(name) || iterable::METHODS.contains(name),
+        Object::Map(_, _, _) => map::MEMBERS.contains(name),
+        Object::Iterable(_, _) => iterable::METHODS.contains(name) || iterable::has_property(name),
+        Object::Iterator(_, _) => matches!(*name, "moveNext" | "current"),
+        Object::Record(positional, named) => {
+            let field = name.strip_prefix('$').and_then(|i| i.parse::<usize>().ok());
+            field.is_some_and(|i| i >= 1 && i <= positional.len()) || named.iter().any(|(n, _)| n == *name)
+        }
+        _ => false
+    }
+}
+
+
+// What toString gives for obj. That of an instance is its own, if its class defines one, and that
+// of a collection has the toString of each element.
+pub fn stringify(
+    obj: &Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    // As dart, a collection within itself shows as '[...]', '{...}' or '(...)'.
+    let brackets = match obj {
+        Object::List(_, _) => Some("[...]"),
+        Object::Set(_, _) | Object::Map(_, _, _) => Some("{...}"),
+        Object::Iterable(_, _) => Some("(...)"),
+        Object::Reference(_) if is_type(obj, "Iterable", objsys) => Some("(...)"),
+        _ => None
+    };
+    let Some(brackets) = brackets else {
+        return stringify_value(obj, looktables, globals, stack, objsys, ctx);
+    };
+    let key = MapKey::from(obj, ctx);
+    if ctx.stringifying.contains(&key) {
+        return Object::String(String::from(brackets));
+    }
+    ctx.stringifying.push(key);
+    let text = stringify_value(obj, looktables, globals, stack, objsys, ctx);
+    ctx.stringifying.pop();
+    text
+}
+
+
+fn stringify_value(
+    obj: &Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let all = |items: Vec<String>| items.join(", ");
+    let text = match obj {
+        Object::Reference(refid) => {
+            let classname = objsys.get_instance(refid).classname.clone();
+            if let Some((declaring, meth)) = objsys.find_method(&classname, "toString") {
+                let saved = objsys.enter(refid.clone(), declaring);
+                let result = call_function(meth, Vec::new(), looktables, globals, stack, objsys, ctx);
+                objsys.leave(saved);
+                return result;
+            }
+            // As dart: 'Color.red', and an Iterable of its own as its elements.
+            if objsys.get_class(&classname).is_enum {
+                format!("{}.{}", classname, objsys.get_instance(refid).get_field(String::from("name")))
+            }
+            else if is_type(obj, "Iterable", objsys) {
+                let items = propagate_all!(iterable::elements(obj, looktables, globals, stack, objsys, ctx));
+                format!("({})", all(texts(propagate_all!(stringify_all(&items, looktables, globals, stack, objsys, ctx)))))
+            }
+            else {
+                format!("Instance of '{}'", runtime_type(obj, objsys))
+            }
+        }
+        Object::List(items, _) => {
+            let items = items.borrow().clone();
+            format!("[{}]", all(texts(propagate_all!(stringify_all(&items, looktables, globals, stack, objsys, ctx)))))
+        }
+        Object::Set(set, _) => {
+            let items: Vec<Object> = set.borrow().entries().iter().map(|(k, _)| k.clone()).collect();
+            format!("{{{}}}", all(texts(propagate_all!(stringify_all(&items, looktables, globals, stack, objsys, ctx)))))
+        }
+        Object::Map(map, _, _) => {
+            let entries: Vec<Object> = map.borrow().entries().iter().flat_map(|(k, v)| [k.clone(), v.clone()]).collect();
+            let parts = texts(propagate_all!(stringify_all(&entries, looktables, globals, stack, objsys, ctx)));
+            format!("{{{}}}", all(parts.chunks(2).map(|kv| format!("{}: {}", kv[0], kv[1])).collect()))
+        }
+        Object::MapEntry(k, v) => {
+            let parts = texts(propagate_all!(stringify_all(&[(**k).clone(), (**v).clone()], looktables, globals, stack, objsys, ctx)));
+            format!("MapEntry({}: {})", parts[0], parts[1])
+        }
+        Object::Record(positional, named) => {
+            let values: Vec<Object> = positional.iter().chain(named.iter().map(|(_, v)| v)).cloned().collect();
+            let mut parts = texts(propagate_all!(stringify_all(&values, looktables, globals, stack, objsys, ctx)));
+            for (i, (name, _)) in named.iter().enumerate() {
+                let part = &mut parts[positional.len() + i];
+                *part = format!("{}: {}", name, part);
+            }
+            // As dart, a record of one positional field: '(1,)'.
+            match (positional.len(), named.len()) {
+                (1, 0) => format!("({},)", parts[0]),
+                _ => format!("({})", all(parts))
+            }
+        }
+        // Its elements are only known by evaluating it.
+        Object::Iterable(_, _) => {
+            let items = propagate_all!(iterable::elements(obj, looktables, globals, stack, objsys, ctx));
+            format!("({})", all(texts(propagate_all!(stringify_all(&items, looktables, globals, stack, objsys, ctx)))))
+        }
+        x => x.to_string()
+    };
+    Object::String(text)
+}
+
+
+// The toString of each of items, stopping at the first that throws.
+fn stringify_all(
+    items: &[Object],
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Vec<Object>, Box<Object>> {
+
+    items.iter().map(|item| match stringify(item, looktables, globals, stack, objsys, ctx) {
+        thrown @ Object::Throw(_, _) => Err(Box::new(thrown)),
+        text => Ok(text)
+    }).collect()
+}
+
+
+fn texts(objs: Vec<Object>) -> Vec<String> {
+    objs.iter().map(|obj| obj.to_string()).collect()
+}
+
+
+// What adding an element of the wrong type to a list or set by the method name throws.
+fn mistyped_arg(receiver: &Object, name: &str, args: &[Object], objsys: &ObjSys, ctx: &Ctx) -> Option<Object> {
+    let (Object::List(_, elemtype) | Object::Set(_, elemtype)) = receiver else {
+        return None;
+    };
+    let (arg, t, param) = match (receiver, name) {
+        (_, "add") => (args.first()?, elemtype.clone(), "value"),
+        (Object::List(_, _), "insert") => (args.get(1)?, elemtype.clone(), "element"),
+        // What map and expand make has no element type, so its elements are checked as they are added.
+        (_, "addAll") if matches!(args.first()?, Object::Iterable(_, t) if t == "dynamic") => return None,
+        (Object::List(_, _), "addAll") => (args.first()?, format!("Iterable<{}>", elemtype), "iterable"),
+        (_, "addAll") => (args.first()?, format!("Iterable<{}>", elemtype), "elements"),
+        _ => return None
+    };
+    mistyped(arg, &t, param, objsys, ctx)
+}
+
+
+// What modifying a const list, set or map by the method or operator name throws, if that modifies it.
+fn unmodifiable(receiver: &Object, name: &str, objsys: &ObjSys, ctx: &Ctx) -> Option<Object> {
+    if !objsys.is_unmodifiable(receiver) {
+        return None;
+    }
+    // As dart.
+    let message = match (receiver, name) {
+        (Object::List(_, _), "add" | "addAll" | "insert") => "Cannot add to an unmodifiable list",
+        (Object::List(_, _), "removeAt") => "Cannot remove from an unmodifiable list",
+        (Object::List(_, _), "[]=") => "Cannot modify an unmodifiable list",
+        (Object::Set(_, _), "add" | "addAll" | "remove") => "Cannot change an unmodifiable set",
+        (Object::Map(_, _, _), "[]=" | "remove") => "Cannot modify unmodifiable map",
+        _ => return None
+    };
+    Some(exception::error("UnsupportedError", message, ctx))
+}
+
+
+// 'left == right', by the operator of left if it defines one. That is not called with null.
+// Records compare their fields so.
+fn equal_objects(
+    left: Object,
+    right: Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    if let (Object::Reference(refid), false) = (&left, matches!(right, Object::Null)) {
+        let classname = objsys.get_instance(refid).classname.clone();
+        if objsys.find_method(&classname, "==").is_some() {
+            return call_operator("==", vec![left, right], looktables, globals, stack, objsys, ctx);
+        }
+    }
+    if let (Object::Record(p1, n1), Object::Record(p2, n2)) = (&left, &right) {
+        if p1.len() != p2.len() || n1.iter().map(|(k, _)| k).ne(n2.iter().map(|(k, _)| k)) {
+            return Object::Bool(false);
+        }
+        let fields = p1.iter().zip(p2.iter()).chain(n1.iter().map(|(_, v)| v).zip(n2.iter().map(|(_, v)| v)));
+        for (v1, v2) in fields {
+            match equal_objects(v1.clone(), v2.clone(), looktables, globals, stack, objsys, ctx) {
+                Object::Bool(true) => {}
+                result => return result
+            }
+        }
+        return Object::Bool(true);
+    }
+    Object::Bool(left.equals(&right))
+}
+
+
+// Whether obj is an instance of a class with a user defined '=='.
+fn user_equality(obj: &Object, objsys: &ObjSys) -> bool {
+    match obj {
+        Object::Reference(refid) => objsys.find_method(&objsys.get_instance(refid).classname, "==").is_some(),
+        _ => false
+    }
+}
+
+
+// The hash key of a map key or set element. An instance with a user defined '=='
+// goes by its hashCode getter, if its class has one, also as the field of a record.
+fn hash_key(
+    key: &Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<MapKey, Box<Object>> {
+
+    if let Object::Record(positional, named) = key {
+        let positional = positional.iter().map(|v| hash_key(v, looktables, globals, stack, objsys, ctx)).collect::<Result<_, _>>()?;
+        let named = named.iter().map(|(name, v)| hash_key(v, looktables, globals, stack, objsys, ctx).map(|k| (name.clone(), k))).collect::<Result<_, _>>()?;
+        return Ok(MapKey::Record(positional, named));
+    }
+    let Object::Reference(refid) = key else {
+        return Ok(MapKey::from(key, ctx));
+    };
+    let classname = objsys.get_instance(refid).classname.clone();
+    let Some((declaring, _)) = objsys.find_method(&classname, "==") else {
+        return Ok(MapKey::from(key, ctx));
+    };
+    if !objsys.is_getter(&classname, "hashCode") {
+        return Ok(MapKey::Equality(declaring));
+    }
+    match call_member("hashCode", vec![key.clone()], looktables, globals, stack, objsys, ctx) {
+        Some(Object::Int(h)) => Ok(MapKey::Hash(h)),
+        Some(thrown @ Object::Throw(_, _)) => Err(Box::new(thrown)),
+        Some(x) => Err(Box::new(not_a("int", &x, objsys, ctx))),
+        None => unreachable!()
+    }
+}
+
+
+// The hash key of key and the position of the entry of map with a key '==' to it, if there is one.
+// Only the entries of the same hash key are compared.
+fn find_entry(
+    map: &Rc<RefCell<DartMap>>,
+    key: &Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<(MapKey, Option<usize>), Box<Object>> {
+
+    let hash = hash_key(key, looktables, globals, stack, objsys, ctx)?;
+    if hash.is_exact() {
+        let found = map.borrow().bucket(&hash).first().copied();
+        return Ok((hash, found));
+    }
+    // The '==' may change the map, so take one position at a time.
+    let mut j = 0;
+    loop {
+        let Some((i, elem)) = map.borrow().bucket(&hash).get(j).map(|i| (*i, map.borrow().entries()[*i].0.clone())) else {
+            return Ok((hash, None));
+        };
+        match equal_objects(elem, key.clone(), looktables, globals, stack, objsys, ctx) {
+            Object::Bool(true) => return Ok((hash, Some(i))),
+            thrown @ Object::Throw(_, _) => return Err(Box::new(thrown)),
+            _ => j += 1
+        }
+    }
+}
+
+
+// Sets the value of key in map, adding an entry if it has none. Gives whether it added one.
+#[allow(clippy::too_many_arguments)]
+fn put_entry(
+    map: &Rc<RefCell<DartMap>>,
+    key: Object,
+    value: Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<bool, Box<Object>> {
+
+    match find_entry(map, &key, looktables, globals, stack, objsys, ctx)? {
+        (_, Some(i)) => {
+            map.borrow_mut().set_value(i, value);
+            Ok(false)
+        }
+        (hash, None) => {
+            map.borrow_mut().push(hash, key, value);
+            Ok(true)
+        }
+    }
+}
+
+
+// The methods of sets and maps that look up an element or key.
+#[allow(clippy::too_many_arguments)]
+fn call_keyed(
+    receiver: &Object,
+    name: &str,
+    args: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let (map, class) = match receiver {
+        Object::Set(set, _) => (set, "Set"),
+        Object::Map(map, _, _) => (map, "Map"),
+        x => panic!("Not a set or map: {:?}", x)
+    };
+    match (class, name) {
+        // Gives whether the element was not in the set already.
+        ("Set", "add") => {
+            expect_args(name, &args, 1, ctx);
+            Object::Bool(propagate_all!(put_entry(map, args[0].clone(), Object::Null, looktables, globals, stack, objsys, ctx)))
+        }
+        ("Set", "addAll") => {
+            expect_args(name, &args, 1, ctx);
+            // Copy first, the argument may be the set itself.
+            let extra: Vec<Object> = match &args[0] {
+                Object::String(_) => dart_evalerror("The argument type 'String' can't be assigned to the parameter type 'Iterable'.", ctx),
+                other if iterable::is_iterable(other, objsys) => propagate_all!(iterable::elements(other, looktables, globals, stack, objsys, ctx)),
+                x => dart_evalerror(format!("The argument type '{}' can't be assigned to the parameter type 'Iterable'.", x), ctx)
+            };
+            if let (Object::Iterable(_, _), Object::Set(_, elemtype)) = (&args[0], receiver) {
+                if let Some(thrown) = extra.iter().find_map(|elem| mistyped(elem, elemtype, "value", objsys, ctx)) {
+                    return thrown;
+                }
+            }
+            for elem in extra {
+                propagate_all!(put_entry(map, elem, Object::Null, looktables, globals, stack, objsys, ctx));
+            }
+            Object::Null
+        }
+        ("Set", "contains") | ("Map", "containsKey") => {
+            expect_args(name, &args, 1, ctx);
+            Object::Bool(propagate_all!(find_entry(map, &args[0], looktables, globals, stack, objsys, ctx)).1.is_some())
+        }
+        ("Set", "remove") | ("Map", "remove") => {
+            expect_args(name, &args, 1, ctx);
+            let removed = match propagate_all!(find_entry(map, &args[0], looktables, globals, stack, objsys, ctx)) {
+                (hash, Some(i)) => Some(map.borrow_mut().remove_at(&hash, i)),
+                (_, None) => None
+            };
+            match (class, removed) {
+                ("Set", removed) => Object::Bool(removed.is_some()),
+                (_, removed) => removed.unwrap_or(Object::Null)
+            }
+        }
+        ("Map", "containsValue") => {
+            expect_args(name, &args, 1, ctx);
+            let mut i = 0;
+            while let Some(value) = map.borrow().entries().get(i).map(|(_, v)| v.clone()) {
+                match equal_objects(value, args[0].clone(), looktables, globals, stack, objsys, ctx) {
+                    Object::Bool(true) => return Object::Bool(true),
+                    thrown @ Object::Throw(_, _) => return thrown,
+                    _ => i += 1
                 }
             }
+            Object::Bool(false)
+        }
+        // As dart.
+        _ => exception::no_method(&runtime_type(receiver, objsys), receiver, name, &args, ctx)
+    }
+}
 
-            // Make an instance.
 
-            let class = objsys.get_class(cname.as_str());
-            let mut inst = class.instantiate();
+// Whether node is a variable declared dynamic. As dart, extension members don't apply to it.
+fn declared_dynamic(node: &Node, stack: &Stack) -> bool {
+    matches!(&node.nodetype, NodeType::Name(name) if node.children.is_empty() && stack.is_dynamic(name))
+}
 
-            // Evaluate the initial field values.
 
-            let field_nodes = class.fields.clone();
-            for (_, fname, initexpr) in &field_nodes {
-                inst.set_field(fname.clone(), eval(initexpr, looktables, globals, store, objsys, ctx));
+// The method or getter of an extension visible in the running file that applies to receiver,
+// bound to it. Of those that apply, the one on the most specific type wins; without one, they are ambiguous.
+fn find_extension(
+    receiver: &Object,
+    name: &str,
+    getter: bool,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &[Node],
+    objsys: &ObjSys,
+    ctx: &Ctx) -> Option<(String, Object)> {
+
+    let receivertype = runtime_type(receiver, objsys);
+    let mut found: Vec<(&String, String)> = looktables[&ctx.filepath].values()
+        .filter_map(|i| match &globals[*i].nodetype {
+            NodeType::Extension(extension) => Some(extension),
+            _ => None
+        })
+        .filter_map(|extension| {
+            let class = objsys.get_class(extension);
+            // Type parameters of the extension match any type.
+            let dynamics = vec![String::from("dynamic"); class.typeparams.len()];
+            let on = types::substitute(class.on.as_deref().unwrap_or("dynamic"), &class.typeparams, &dynamics);
+            let applies = class.methods.contains_key(name) && class.getters.iter().any(|g| g == name) == getter && types::is_subtype(&receivertype, &on, objsys);
+            applies.then_some((extension, on))
+        })
+        .collect();
+    found.sort();
+    let specific = found.iter().find(|(_, on)| found.iter().all(|(_, other)| types::is_subtype(on, other, objsys)));
+    match (specific, found.as_slice()) {
+        (_, []) => None,
+        (Some((extension, _)), _) => match objsys.get_class(extension).get_method(name) {
+            Some(Object::Function(fname, filepath, body, params, _)) => {
+                Some((extension.to_string(), Object::Function(fname, filepath, body, params, Env::extension(receiver.clone()))))
             }
+            _ => None
+        },
+        // As dart.
+        (None, [(first, _), (second, _), ..]) => dart_evalerror(format!(
+            "A member named '{}' is defined in '{}' and '{}', and neither is more specific.",
+            name, first, second
+        ), ctx),
+        (None, [_]) => unreachable!()
+    }
+}
 
-            let instref = objsys.register_instance(inst);
 
-            // Run the constructor body.
+// Calls what find_extension found.
+fn call_extension(
+    (extension, func): (String, Object),
+    args: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let saved = objsys.enter(String::new(), extension);
+    let result = call_function(func, args, looktables, globals, stack, objsys, ctx);
+    objsys.leave(saved);
+    result
+}
+
 
-            match &instref {
-                Object::Reference(refid) => {
+// Inside an extension member, a name that is not declared in scope is a member of 'this', as
+// in a class. The members of the extension come before top level declarations.
+fn is_this_member(name: &str, looktables: &HashMap<String, HashMap<String, usize>>, stack: &Stack, objsys: &ObjSys, ctx: &Ctx) -> bool {
+    if !stack.has("this") {
+        return false;
+    }
+    let extension = objsys.get_this_class();
+    if objsys.has_class(&extension) {
+        let class = objsys.get_class(&extension);
+        if class.methods.contains_key(name) {
+            return true;
+        }
+        if class.static_methods.contains_key(name) || class.static_fields.iter().any(|(_, f, _)| f == name) {
+            return false;
+        }
+    }
+    !(looktables[&ctx.filepath].contains_key(name) ||
+        builtin::has_function(name) ||
+        stream::has_constructor(name) ||
+        exception::has_constructor(name) ||
+        type_literal(name, objsys).is_some())
+}
 
-                    let oldfilename = ctx.filepath.clone();
-                    ctx.filepath = filename.clone();
 
+fn superclass_of(classname: &str, objsys: &ObjSys, ctx: &Ctx) -> String {
+    match objsys.has_class(classname) {
+        true => match &objsys.get_class(classname).parent {
+            Some(parent) => parent.clone(),
+            // As dart.
+            None => dart_evalerror(format!("The class '{}' doesn't have a superclass.", classname), ctx)
+        },
+        false => dart_evalerror("Invalid context for 'super' expression.", ctx)
+    }
+}
 
-                    let oldthis = objsys.get_this();
-                    objsys.set_this(refid.clone());
-                    println!("Set this: {}, classname: {}, filename: {}", refid, cname, filename);
 
-                    // Set fields from params that uses "this" to auto-init.
-                    // Ie Bike(this.gears)
-                    let inst = objsys.get_this_instance_mut();
-                    for i in 0..params.len() {
-                        if params[i].fieldinit {
-                            inst.set_field(params[i].name.clone(),args[i].clone());
-                        }
-                    }
+// A user defined operator, getter or setter: the member of the class of operands[0],
+// called with the rest of the operands. Gives None if the class has no such member.
+pub fn call_member(
+    member: &str,
+    mut operands: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Option<Object> {
+
+    let refid = match operands.remove(0) {
+        Object::Reference(refid) => refid,
+        x => panic!("Called member {} of non-instance {}", member, x)
+    };
+    let classname = objsys.get_instance(&refid).classname.clone();
+    let (declaring, meth) = objsys.find_method(&classname, member)?;
+
+    let saved = objsys.enter(refid, declaring);
+    let result = call_function(meth, operands, looktables, globals, stack, objsys, ctx);
+    objsys.leave(saved);
+    Some(result)
+}
 
-                    // Run body
-                    eval(&body, looktables, globals, store, objsys, ctx);
 
-                    objsys.set_this(oldthis);
-                    ctx.filepath = oldfilename;
-                    println!("Resetting filepath to {}", ctx.filepath);
+// Applies the operator op, defined by the class of the left operand, operands[0].
+fn call_operator(
+    op: &str,
+    operands: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let classname = runtime_type(&operands[0], objsys);
+    match call_member(op, operands, looktables, globals, stack, objsys, ctx) {
+        Some(result) => result,
+        // As dart.
+        None => dart_evalerror(format!("The operator '{}' isn't defined for the class '{}'.", op.trim_start_matches("unary"), classname), ctx)
+    }
+}
 
-                    store.pop_call();
 
-                    return instref.clone();
+// Matches the arguments of a call to the parameters. Parameters given no argument get None.
+fn bind_args(params: &[ParamObj], args: Vec<Object>, ctx: &Ctx) -> Vec<Option<Object>> {
+    let mut positional = Vec::new();
+    let mut named: Vec<(String, Object)> = Vec::new();
+    for arg in args {
+        match arg {
+            Object::NamedArg(name, value) => {
+                if named.iter().any(|(n, _)| *n == name) {
+                    // As dart.
+                    dart_evalerror(format!("Duplicated named argument '{}'.", name), ctx)
                 }
-                _ => panic!("Couldn't find intance that was just created.")
+                named.push((name, *value));
             }
+            arg => positional.push(arg)
+        }
+    }
+
+    let required = params.iter().filter(|p| p.kind == ParamKind::Positional).count();
+    let allowed = params.iter().filter(|p| !matches!(p.kind, ParamKind::Named(_))).count();
+    // As dart.
+    if positional.len() < required {
+        dart_evalerror(format!("Too few positional arguments: {} required, {} given.", required, positional.len()), ctx)
+    }
+    if positional.len() > allowed {
+        dart_evalerror(format!("Too many positional arguments: {} allowed, but {} found.", allowed, positional.len()), ctx)
+    }
+    for (name, _) in &named {
+        if !params.iter().any(|p| matches!(p.kind, ParamKind::Named(_)) && p.name == *name) {
+            dart_evalerror(format!("No named parameter with the name '{}'.", name), ctx)
         }
+    }
 
-        _ => {
-            panic!("Called a non-constructor object.")
+    let mut positional = positional.into_iter();
+    params.iter().map(|p| match p.kind {
+        ParamKind::Named(required) => match named.iter().position(|(n, _)| *n == p.name) {
+            Some(i) => Some(named.swap_remove(i).1),
+            None if required => {
+                // As dart.
+                dart_evalerror(format!("Required named parameter '{}' must be provided.", p.name), ctx)
+            }
+            None => None
+        },
+        _ => positional.next()
+    }).collect()
+}
+
+
+// The values of the parameters of a call: the arguments, or the defaults of the parameters not given one.
+fn param_values(
+    params: &[ParamObj],
+    args: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    store: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Vec<Object>, Box<Object>> {
+
+    params.iter().zip(bind_args(params, args, ctx)).map(|(param, arg)| {
+        match (arg, &param.default) {
+            (Some(arg), _) => Ok(arg),
+            (None, Some(default)) => match eval(default, looktables, globals, store, objsys, ctx) {
+                Object::Throw(e, st) => Err(Box::new(Object::Throw(e, st))),
+                obj => Ok(with_context_type(obj, default, &param.typ, objsys))
+            },
+            (None, None) => Ok(Object::Null)
+        }
+    }).collect()
+}
+
+
+// A map of the MapEntry objects of its literal. Without type arguments, they are inferred from the entries.
+fn new_map(
+    entries: Vec<Object>,
+    typeargs: &[String],
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let map = Rc::new(RefCell::new(DartMap::new()));
+    let mut keytypes = Vec::new();
+    let mut valuetypes = Vec::new();
+    for entry in entries {
+        match entry {
+            Object::MapEntry(k, v) => {
+                keytypes.push(runtime_type(&k, objsys));
+                valuetypes.push(runtime_type(&v, objsys));
+                propagate_all!(put_entry(&map, *k, *v, looktables, globals, stack, objsys, ctx));
+            }
+            // As dart.
+            x => dart_evalerror(format!("A value of type '{}' can't be assigned to a map entry.", runtime_type(&x, objsys)), ctx)
+        }
+    }
+    let (keytype, valuetype) = match typeargs {
+        [k, v] => (resolve_type(k, objsys), resolve_type(v, objsys)),
+        _ => (types::lub(&keytypes, objsys), types::lub(&valuetypes, objsys))
+    };
+    Object::new_map(map.take(), &keytype, &valuetype)
+}
+
+
+// The values of the elements of a list, set or map literal, with spreads, if elements and for elements
+// expanded. Map entries give MapEntry objects. Stops at the first element that throws, giving the throw.
+pub fn collection(
+    elements: &[Node],
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Vec<Object>, Box<Object>> {
+
+    let mut values = Vec::new();
+    for element in elements {
+        add_element(element, &mut values, looktables, globals, stack, objsys, ctx)?;
+    }
+    Ok(values)
+}
+
+
+fn add_element(
+    element: &Node,
+    values: &mut Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<(), Box<Object>> {
+
+    let value = |node: &Node, stack: &mut Stack, objsys: &mut ObjSys, ctx: &mut Ctx| match eval(node, looktables, globals, stack, objsys, ctx) {
+        Object::Throw(e, st) => Err(Box::new(Object::Throw(e, st))),
+        obj => Ok(obj)
+    };
+
+    match &element.nodetype {
+        NodeType::MapEntry => {
+            let k = value(&element.children[0], stack, objsys, ctx)?;
+            let v = value(&element.children[1], stack, objsys, ctx)?;
+            values.push(Object::MapEntry(Box::new(k), Box::new(v)));
+        }
+        NodeType::Spread(nullaware) => {
+            match value(&element.children[0], stack, objsys, ctx)? {
+                Object::Null if *nullaware => {}
+                Object::Map(map, _, _) => {
+                    let entries = map.borrow().entries().clone();
+                    values.extend(entries.into_iter().map(|(k, v)| Object::MapEntry(Box::new(k), Box::new(v))));
+                }
+                spread if iterable::is_iterable(&spread, objsys) => {
+                    values.extend(iterable::elements(&spread, looktables, globals, stack, objsys, ctx)?);
+                }
+                // As dart.
+                spread => return Err(Box::new(exception::error(
+                    "TypeError",
+                    format!("type '{}' is not a subtype of type 'Iterable<dynamic>'", runtime_type(&spread, objsys)),
+                    ctx
+                )))
+            }
+        }
+        NodeType::IfElement => {
+            let taken = match value(&element.children[0], stack, objsys, ctx)? {
+                Object::Bool(true) => element.children.get(1),
+                Object::Bool(false) => element.children.get(2),
+                // As dart.
+                _ => dart_evalerror("Conditions must have a static type of 'bool'.", ctx)
+            };
+            if let Some(taken) = taken {
+                add_element(taken, values, looktables, globals, stack, objsys, ctx)?;
+            }
+        }
+        NodeType::For => {
+            // Like the for statement, with the loop variable in scope of the loop only.
+            stack.push_lex();
+            let result = for_elements(element, values, looktables, globals, stack, objsys, ctx);
+            stack.pop_lex();
+            result?;
+        }
+        NodeType::ForIn(_) => {
+            let iterable = value(&element.children[1], stack, objsys, ctx)?;
+            if !iterable::is_iterable(&iterable, objsys) {
+                // As dart.
+                dart_evalerror(format!("The type '{}' used in the 'for' loop must implement 'Iterable<dynamic>'.", runtime_type(&iterable, objsys)), ctx)
+            }
+            let mut cursor = iterable::cursor(&iterable, looktables, globals, stack, objsys, ctx)?;
+            while let Some(elem) = iterable::next(&mut cursor, looktables, globals, stack, objsys, ctx)? {
+                stack.push_lex();
+                let result = match match_pattern(&element.children[0], &elem, looktables, globals, stack, objsys, ctx) {
+                    Ok(true) => add_element(&element.children[2], values, looktables, globals, stack, objsys, ctx),
+                    // As dart.
+                    Ok(false) => Err(Box::new(exception::error(
+                        "TypeError",
+                        format!("type '{}' is not a subtype of type '{}' in type cast", runtime_type(&elem, objsys), types::of_pattern(&element.children[0])),
+                        ctx
+                    ))),
+                    Err(thrown) => Err(thrown)
+                };
+                stack.pop_lex();
+                result?;
+            }
+        }
+        _ => values.push(value(element, stack, objsys, ctx)?)
+    }
+    Ok(())
+}
+
+
+// The elements of 'for (init; cond; step) element', in the scope of the loop.
+fn for_elements(
+    element: &Node,
+    values: &mut Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<(), Box<Object>> {
+
+    let thrown = |obj: Object| match obj {
+        Object::Throw(e, st) => Err(Box::new(Object::Throw(e, st))),
+        obj => Ok(obj)
+    };
+    thrown(eval(&element.children[0], looktables, globals, stack, objsys, ctx))?;
+    loop {
+        match thrown(eval(&element.children[1], looktables, globals, stack, objsys, ctx))? {
+            Object::Bool(true) => {}
+            Object::Bool(false) => return Ok(()),
+            x => dart_evalerror(format!("Expected bool. Got: {}", x), ctx)
         }
+        stack.push_lex();
+        let result = add_element(&element.children[3], values, looktables, globals, stack, objsys, ctx);
+        stack.pop_lex();
+        result?;
+        stack.renew_lex();
+        thrown(eval(&element.children[2], looktables, globals, stack, objsys, ctx))?;
     }
 }
 
 
+// Stops at the first argument that throws, giving the throw.
 fn argnodes_to_argobjs(
     argnodes: &Vec<Node>,
     looktables: &HashMap<String, HashMap<String, usize>>,
     globals: &Vec<Node>,
     store: &mut Stack,
     objsys: &mut ObjSys,
-    ctx: &mut Ctx) -> Vec<Object> {
-
-    argnodes.iter().map(
-        |argtree| eval(&argtree, looktables, globals, store, objsys, ctx)
-    ).collect()
+    ctx: &mut Ctx) -> Result<Vec<Object>, Box<Object>> {
+
+    argnodes.iter().map(|argtree| {
+        // Named arguments are bound by name, see bind_args.
+        let (name, argtree) = match &argtree.nodetype {
+            NodeType::NamedArg(name) => (Some(name), &argtree.children[0]),
+            _ => (None, argtree)
+        };
+        match (eval(argtree, looktables, globals, store, objsys, ctx), name) {
+            (Object::Throw(e, st), _) => Err(Box::new(Object::Throw(e, st))),
+            (obj, Some(name)) => Ok(Object::NamedArg(name.clone(), Box::new(obj))),
+            (obj, None) => Ok(obj)
+        }
+    }).collect()
 }
@@ -0,0 +1,144 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::context::*;
+use crate::object::*;
+use crate::utils::{dart_evalerror, expect_args};
+
+
+// An exception or error of dart:core, thrown by the runtime or created by user code.
+#[derive(Debug)]
+pub struct DartException {
+    // The class, like 'RangeError'.
+    pub kind: String,
+    pub message: String,
+}
+
+
+impl fmt::Display for DartException {
+
+    // As the toString of each class in dart.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind.as_str() {
+            "StateError" => write!(f, "Bad state: {}", self.message),
+            "ArgumentError" => write!(f, "Invalid argument(s): {}", self.message),
+            "UnsupportedError" => write!(f, "Unsupported operation: {}", self.message),
+            "RangeError" => write!(f, "RangeError {}", self.message),
+            "StackOverflowError" => write!(f, "Stack Overflow"),
+            "TypeError" |
+            "AssertionError" => write!(f, "{}", self.message),
+            _ if self.message.is_empty() => write!(f, "{}", self.kind),
+            _ => write!(f, "{}: {}", self.kind, self.message)
+        }
+    }
+}
+
+
+pub fn new(kind: &str, message: String) -> Object {
+    Object::Exception(Rc::new(DartException { kind: String::from(kind), message }))
+}
+
+
+// Throw value from where ctx is now.
+pub fn throw(value: Object, ctx: &Ctx) -> Object {
+    Object::Throw(Box::new(value), stack_trace(ctx))
+}
+
+
+// Throw one of the exceptions of dart:core.
+pub fn error<S: Into<String>>(kind: &str, message: S, ctx: &Ctx) -> Object {
+    throw(new(kind, message.into()), ctx)
+}
+
+
+// What calling a method the receiver, of class, doesn't have throws. As dart.
+pub fn no_method(class: &str, receiver: &Object, name: &str, args: &[Object], ctx: &Ctx) -> Object {
+    let args: Vec<String> = args.iter().map(|arg| match arg {
+        Object::NamedArg(n, value) => format!("{}: {}", n, safe_string(value, None)),
+        arg => safe_string(arg, None)
+    }).collect();
+    error("NoSuchMethodError", format!(
+        "Class '{}' has no instance method '{}'.\nReceiver: {}\nTried calling: {}({})",
+        class, name, safe_string(receiver, Some(class)), name, args.join(", ")
+    ), ctx)
+}
+
+
+// What reading a getter the receiver, of class, doesn't have throws. As dart.
+pub fn no_getter(class: &str, receiver: &Object, name: &str, ctx: &Ctx) -> Object {
+    error("NoSuchMethodError", format!(
+        "Class '{}' has no instance getter '{}'.\nReceiver: {}\nTried calling: {}",
+        class, name, safe_string(receiver, Some(class)), name
+    ), ctx)
+}
+
+
+// As dart shows a value in an error: numbers, booleans and null as such, strings quoted,
+// and the rest by its class alone, as their toString may well fail too.
+fn safe_string(value: &Object, class: Option<&str>) -> String {
+    match (value, class) {
+        (Object::Int(_) | Object::Double(_) | Object::Bool(_) | Object::Null, _) => value.to_string(),
+        (Object::String(s), _) => format!("\"{}\"", s),
+        (_, Some(class)) => format!("Instance of '{}'", class),
+        (_, None) => String::from("Instance")
+    }
+}
+
+
+// Like the dart vm prints them, innermost call first.
+pub fn stack_trace(ctx: &Ctx) -> String {
+    let frames: Vec<String> = ctx.calls.iter().rev().enumerate().map(|(i, (name, file))| {
+        format!("{:<8}{} ({})", format!("#{}", i), name, file)
+    }).collect();
+    frames.join("\n")
+}
+
+
+// Superclasses and interfaces, as in dart:core.
+fn supertypes(kind: &str) -> &'static [&'static str] {
+    match kind {
+        "RangeError" => &["ArgumentError", "Error"],
+        "IntegerDivisionByZeroException" => &["UnsupportedError", "Error", "Exception"],
+        "ArgumentError" |
+        "StateError" |
+        "UnsupportedError" |
+        "NoSuchMethodError" |
+        "LateInitializationError" |
+        "AssertionError" |
+        "StackOverflowError" |
+        "TypeError" => &["Error"],
+        "FormatException" => &["Exception"],
+        _ => &[]
+    }
+}
+
+
+pub fn is_subtype(kind: &str, typename: &str) -> bool {
+    kind == typename || supertypes(kind).contains(&typename)
+}
+
+
+pub fn has_constructor(name: &str) -> bool {
+    matches!(name, "Exception" | "FormatException" | "StateError" | "ArgumentError" | "UnsupportedError")
+}
+
+
+pub fn construct(name: &str, args: &[Object], ctx: &Ctx) -> Object {
+    let message = match args.len() {
+        0 => String::new(),
+        _ => {
+            expect_args(name, args, 1, ctx);
+            args[0].to_string()
+        }
+    };
+    new(name, message)
+}
+
+
+pub fn get_property(e: &DartException, name: &str, ctx: &Ctx) -> Object {
+    match name {
+        "message" => Object::String(e.message.clone()),
+        // As dart.
+        _ => dart_evalerror(format!("The getter '{}' isn't defined for the class '{}'.", name, e.kind), ctx)
+    }
+}
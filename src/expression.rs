@@ -9,7 +9,85 @@ use queues::*;
 
 pub fn expression(reader: &mut Reader, ctx: &Ctx) -> Node {
     dprint(format!("Parse: expression: {:?}", reader.sym()));
-    disjunction(reader, ctx)
+
+    let pos = reader.sym().map(|t| t.find_token_position());
+    let mut node = cascade(reader, ctx);
+    if let Some(pos) = pos {
+        node.pos = pos;
+    }
+    node
+}
+
+// 'a..b()..c = v' runs its sections on a in turn and gives a. After '?..', they run only if a is not null.
+fn cascade(reader: &mut Reader, ctx: &Ctx) -> Node {
+    dprint(format!("Parse: cascade: {:?}", reader.sym()));
+
+    let target = if_null(reader, ctx);
+
+    let nullaware = match reader.sym() {
+        Some(Token::Cascade(_, _)) => false,
+        Some(Token::NullCascade(_, _)) => true,
+        _ => return target,
+    };
+    let mut node = Node::new(NodeType::Cascade(nullaware));
+    node.children.push(target);
+    loop {
+        node.children.push(cascade_section(reader, ctx));
+        if !matches!(reader.sym(), Some(Token::Cascade(_, _))) {
+            return node;
+        }
+    }
+}
+
+// A section, at its '..', as accesses on the receiver, which is the hidden variable '..'.
+// The value it assigns can't have cascades of its own.
+fn cascade_section(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let receiver = Node::new(NodeType::Name(String::from("..")));
+    let section = match reader.next() {
+        Some(Token::Name(_, _, _)) => selector(reader, receiver, ctx),
+        Some(Token::Brack1(_, _)) => access_help(reader, receiver, ctx),
+        // As dart.
+        _ => dart_parseerror("Expected an identifier.", ctx, reader.tokens(), reader.pos())
+    };
+
+    match reader.sym() {
+        Some(Token::Assign(_, _)) => {
+            reader.next();
+            let mut assign = Node::new(NodeType::Assign);
+            assign.children.push(section);
+            assign.children.push(if_null(reader, ctx));
+            assign
+        }
+        Some(Token::IfNullAssign(_, _)) => {
+            reader.next();
+            let mut assign = Node::new(NodeType::Assign);
+            assign.children.push(section.clone());
+            assign.children.push(if_null(reader, ctx));
+            let mut node = Node::new(NodeType::IfNullAssign);
+            node.children.push(section);
+            node.children.push(assign);
+            node
+        }
+        _ => section
+    }
+}
+
+fn if_null(reader: &mut Reader, ctx: &Ctx) -> Node {
+    dprint(format!("Parse: if_null: {:?}", reader.sym()));
+
+    let left = disjunction(reader, ctx);
+
+    match reader.sym() {
+        Some(Token::IfNull(_, _)) => {
+            reader.next();
+            let right = if_null(reader, ctx);
+            let mut node = Node::new(NodeType::IfNull);
+            node.children.push(left);
+            node.children.push(right);
+            node
+        }
+        _ => left,
+    }
 }
 
 fn disjunction(reader: &mut Reader, ctx: &Ctx) -> Node {
@@ -74,6 +152,17 @@ fn equality(reader: &mut Reader, ctx: &Ctx) -> Node {
             eqnode.children.push(right);
             eqnode
         }
+        Some(Token::NotEqual(_, _)) => {
+            // 'a != b' is '!(a == b)'.
+            reader.next();
+            let right = comparison(reader, ctx);
+            let mut eqnode = Node::new(NodeType::Equal);
+            eqnode.children.push(left);
+            eqnode.children.push(right);
+            let mut notnode = Node::new(NodeType::Not);
+            notnode.children.push(eqnode);
+            notnode
+        }
         _ => left,
     }
 }
@@ -120,10 +209,44 @@ fn comparison(reader: &mut Reader, ctx: &Ctx) -> Node {
             connode.children.push(right);
             connode
         }
+        Some(Token::Is(_, _)) => {
+            // 'x is! T' is '!(x is T)'.
+            let negated = matches!(reader.next(), Some(Token::Not(_, _)));
+            if negated {
+                reader.next();
+            }
+            let typename = match reader.sym() {
+                Some(Token::Name(t, _, _)) => t,
+                Some(paren @ Token::Paren1(_, _)) => match scan_record_type(reader.tokens(), reader.pos()) {
+                    Some((t, end)) => {
+                        let (line, col) = paren.find_token_position();
+                        reader.replace(end, Token::Name(t.clone(), line, col));
+                        t
+                    }
+                    None => dart_parseerror("Expected a type after 'is'.", ctx, reader.tokens(), reader.pos())
+                },
+                _ => dart_parseerror("Expected a type after 'is'.", ctx, reader.tokens(), reader.pos())
+            };
+            reader.next();
+            let mut isnode = Node::new(NodeType::Is(typename));
+            isnode.children.push(left);
+            if negated {
+                let mut notnode = Node::new(NodeType::Not);
+                notnode.children.push(isnode);
+                return notnode;
+            }
+            isnode
+        }
         _ => left,
     }
 }
 
+/// An expression without equality, relational or logical operators, as used
+/// for the operand of a relational pattern.
+pub fn operand(reader: &mut Reader, ctx: &Ctx) -> Node {
+    bit_or(reader, ctx)
+}
+
 fn bit_or(reader: &mut Reader, ctx: &Ctx) -> Node {
     dprint(format!("Parse: bit_or: {:?}", reader.sym()));
 
@@ -258,61 +381,146 @@ fn product_help(reader: &mut Reader, righties: &mut Queue<Node>, ops: &mut Queue
             node.children.push(righties.remove().unwrap());
             node
         }
+        Some(Token::IntDiv(_, _)) => {
+            ops.add(Node::new(NodeType::IntDiv)).ok();
+            reader.next();
+            let deeper = product_help(reader, righties, ops, ctx);
+            let mut node = ops.remove().unwrap();
+            node.children.push(deeper);
+            node.children.push(righties.remove().unwrap());
+            node
+        }
         _ => righties.remove().unwrap(),
     }
 }
 
+// Postfix member access and indexing bind tighter than prefix operators.
 fn access(reader: &mut Reader, ctx: &Ctx) -> Node {
     let n = term(reader, ctx);
 
     match reader.sym() {
-        Some(Token::Access(_, _)) => access_help(reader, n, ctx),
+        Some(Token::Access(_, _)) |
+        Some(Token::NullAccess(_, _)) |
+        Some(Token::Not(_, _)) |
+        Some(Token::Paren1(_, _)) |
+        Some(Token::Brack1(_, _)) => access_help(reader, n, ctx),
         _ => n,
     }
 }
 
 fn access_help(reader: &mut Reader, owner: Node, ctx: &Ctx) -> Node {
     match reader.sym() {
-        Some(Token::Access(_, _)) => match reader.next() {
-            Some(Token::Name(name, _, _)) => match reader.next() {
-                Some(Token::Paren1(_, _)) => {
-                    let args_node = arglist(reader, ctx);
-                    let mut funcall_node = Node::new(NodeType::MethodCall(name.to_string(), Box::new(owner), ctx.filepath.clone()));
-                    funcall_node.children.push(args_node);
-                    access_help(reader, funcall_node, ctx)
-                }
-                Some(Token::Decrement(_, _)) => {
-                    let mut decnode = Node::new(NodeType::PostDecrement);
-                    let node = Node::new(NodeType::Name(name.clone()));
-                    decnode.children.push(node);
-                    decnode
-                }
-                Some(Token::Increment(_, _)) => {
-                    let mut incnode = Node::new(NodeType::PostIncrement);
-                    let node = Node::new(NodeType::Name(name.clone()));
-                    incnode.children.push(node);
-                    incnode
-                }
-                _ => {
-                    let mut node = Node::new(NodeType::Name(name.clone()));
-                    node.children.push(owner);
-                    access_help(reader, node, ctx)
-                }
-            },
-            Some(x) => {
-                showln!(red_bold, "error", white_bold, "Expected name after accessor, got: ", yellow_bold,  x);
-                owner
+        Some(Token::Access(_, _)) => {
+            reader.next();
+            selector(reader, owner, ctx)
+        }
+        Some(Token::NullAccess(_, _)) => {
+            // 'a?.b' is 'a.b' on an owner that may be null.
+            let mut nullaware = Node::new(NodeType::NullAware);
+            nullaware.children.push(owner);
+            match reader.next() {
+                Some(Token::Name(name, _, _)) => match reader.next() {
+                    Some(Token::Paren1(_, _)) => {
+                        let args_node = arglist(reader, ctx);
+                        let mut funcall_node = Node::new(NodeType::MethodCall(name.to_string(), Box::new(nullaware)));
+                        funcall_node.children.push(args_node);
+                        access_help(reader, funcall_node, ctx)
+                    }
+                    _ => {
+                        let mut node = Node::new(NodeType::Name(name.clone()));
+                        node.children.push(nullaware);
+                        access_help(reader, node, ctx)
+                    }
+                },
+                _ => dart_parseerror("Expected an identifier after '?.'.", ctx, reader.tokens(), reader.pos())
             }
-            None => {
-                showln!(red_bold, "error", white_bold, "Unexpected end of tokens.");
-                owner
+        }
+        Some(Token::Paren1(_, _)) => {
+            // Calling what an expression gives, as in 'adder(2)(5)', is calling its 'call' method.
+            let args_node = arglist(reader, ctx);
+            let mut funcall_node = Node::new(NodeType::MethodCall(String::from("call"), Box::new(owner)));
+            funcall_node.children.push(args_node);
+            access_help(reader, funcall_node, ctx)
+        }
+        Some(Token::Not(_, _)) => {
+            // After an operand, '!' is the postfix null check.
+            reader.next();
+            let mut node = Node::new(NodeType::NullCheck);
+            node.children.push(owner);
+            access_help(reader, node, ctx)
+        }
+        Some(Token::Brack1(_, _)) => {
+            reader.next();
+            let index = expression(reader, ctx);
+            if let Err(e) = reader.skip("]", ctx) {
+                showln!(red_bold, "error", white_bold, "Error while skipping ']': ", yellow_bold, e);
+                return owner;
             }
-        },
+            let mut node = Node::new(NodeType::Index);
+            node.children.push(owner);
+            node.children.push(index);
+            access_help(reader, node, ctx)
+        }
         _ => owner,
     }
 }
 
+// Operands remember where they start, for errors found after parsing.
+
+// The member after a '.', at its name, and the accesses that follow it.
+fn selector(reader: &mut Reader, owner: Node, ctx: &Ctx) -> Node {
+    match reader.sym() {
+        Some(Token::Name(name, line, col)) => match reader.next() {
+            Some(Token::Paren1(_, _)) => {
+                let args_node = arglist(reader, ctx);
+                let mut funcall_node = Node::new(NodeType::MethodCall(name.to_string(), Box::new(owner)));
+                funcall_node.children.push(args_node);
+                funcall_node.pos = (line, col);
+                access_help(reader, funcall_node, ctx)
+            }
+            Some(Token::Decrement(_, _)) => {
+                reader.next();
+                let mut decnode = Node::new(NodeType::PostDecrement);
+                let mut node = Node::new(NodeType::Name(name.clone()));
+                node.children.push(owner);
+                decnode.children.push(node);
+                decnode
+            }
+            Some(Token::Increment(_, _)) => {
+                reader.next();
+                let mut incnode = Node::new(NodeType::PostIncrement);
+                let mut node = Node::new(NodeType::Name(name.clone()));
+                node.children.push(owner);
+                incnode.children.push(node);
+                incnode
+            }
+            _ => {
+                let mut node = Node::new(NodeType::Name(name.clone()));
+                node.children.push(owner);
+                access_help(reader, node, ctx)
+            }
+        },
+        Some(x) => {
+            showln!(red_bold, "error", white_bold, "Expected name after accessor, got: ", yellow_bold,  x);
+            owner
+        }
+        None => {
+            showln!(red_bold, "error", white_bold, "Unexpected end of tokens.");
+            owner
+        }
+    }
+}
+
 fn term(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let pos = reader.sym().map(|t| t.find_token_position());
+    let mut node = term_real(reader, ctx);
+    if let Some(pos) = pos {
+        node.pos = pos;
+    }
+    node
+}
+
+fn term_real(reader: &mut Reader, ctx: &Ctx) -> Node {
     dprint(format!("Parse: term: {:?}", reader.sym()));
 
     match reader.sym() {
@@ -336,14 +544,14 @@ fn term(reader: &mut Reader, ctx: &Ctx) -> Node {
         Some(Token::Sub(_, _)) => {
             reader.next();
             let mut unary = Node::new(NodeType::Sub);
-            let next = term(reader, ctx);
+            let next = access(reader, ctx);
             unary.children.push(next);
             unary
         }
         Some(Token::Not(_, _)) => {
             reader.next();
             let mut notnode = Node::new(NodeType::Not);
-            let next = term(reader, ctx);
+            let next = access(reader, ctx);
             notnode.children.push(next);
             notnode
         }
@@ -368,6 +576,42 @@ fn term(reader: &mut Reader, ctx: &Ctx) -> Node {
             reader.next();
             Node::new(NodeType::Bool(v))
         }
+        Some(Token::Null(_, _)) => {
+            reader.next();
+            Node::new(NodeType::Null)
+        }
+        Some(Token::Switch(_, _)) => {
+            switchexpression(reader, ctx)
+        }
+        Some(Token::Const(_, _)) => {
+            reader.next();
+            let node = term(reader, ctx);
+            constant(node, reader, ctx)
+        }
+        Some(Token::Throw(_, _)) => {
+            reader.next();
+            let mut node = Node::new(NodeType::Throw);
+            node.children.push(expression(reader, ctx));
+            node
+        }
+        Some(Token::Rethrow(_, _)) => {
+            reader.next();
+            Node::new(NodeType::Rethrow)
+        }
+        Some(Token::This(_, _)) => {
+            reader.next();
+            Node::new(NodeType::This)
+        }
+        Some(Token::Super(_, _)) => {
+            reader.next();
+            Node::new(NodeType::Super)
+        }
+        Some(Token::Name(ref s, _, _)) if s == "await" => {
+            reader.next();
+            let mut node = Node::new(NodeType::Await);
+            node.children.push(access(reader, ctx));
+            node
+        }
         Some(Token::Name(ref s, _, _)) => {
             if reader.len() > reader.pos() + 1 {
                 reader.next();
@@ -431,17 +675,45 @@ fn term(reader: &mut Reader, ctx: &Ctx) -> Node {
                 }
             }
         }
+        Some(Token::Paren1(_, _)) if is_funclit(reader) => {
+            let mut node = Node::new(NodeType::FunLit(ctx.filepath.clone()));
+            let params = paramlist(reader, ctx);
+            node.children.push(params);
+            let body = funbody(reader, "dynamic", ctx);
+            node.children.push(body);
+            node
+        }
         Some(Token::Paren1(_, _)) => {
+            record(reader, ctx)
+        }
+        Some(Token::LessThan(_, _)) => {
+            // Type arguments of a list or map literal, as in <int>[] or <String, int>{}.
             reader.next();
-            let wnode = expression(reader, ctx);
-            if let Err(e) = reader.skip(")", ctx) {
-                showln!(red_bold, "error", white_bold, "Error while skipping ')': ", yellow_bold, e);
+            let mut typeargs = Vec::new();
+            while let Some(Token::Name(t, _, _)) = reader.sym() {
+                typeargs.push(t);
+                reader.next();
+                if let Some(Token::Comma(_, _)) = reader.sym() {
+                    reader.next();
+                }
+            }
+            if let Err(e) = reader.skip(">", ctx) {
+                showln!(red_bold, "error", white_bold, "Error while skipping '>': ", yellow_bold, e);
             }
-            wnode
+            let mut literal = term(reader, ctx);
+            match &mut literal.nodetype {
+                // With one type argument, '{}' is an empty set.
+                NodeType::Map(_) if typeargs.len() == 1 => literal.nodetype = NodeType::Set(typeargs),
+                // With two, a literal of only spreads is a map.
+                NodeType::Set(_) if typeargs.len() == 2 => literal.nodetype = NodeType::Map(typeargs),
+                NodeType::List(args) | NodeType::Map(args) | NodeType::Set(args) => *args = typeargs,
+                _ => dart_parseerror("Expected a list, map or set literal after type arguments.", ctx, reader.tokens(), reader.pos())
+            }
+            literal
         }
         Some(Token::Brack1(_, _)) => {
             reader.next();
-            let mut list_node = Node::new(NodeType::List);
+            let mut list_node = Node::new(NodeType::List(Vec::new()));
             let mut expect_sep = false;
 
             match reader.sym() {
@@ -476,13 +748,42 @@ fn term(reader: &mut Reader, ctx: &Ctx) -> Node {
                             }
                         }
                         expect_sep = true;
-                        let entry = expression(reader, ctx);
+                        let entry = element(reader, ctx);
                         list_node.children.push(entry);
                     }
                     list_node
                 }
             }
         }
+        Some(Token::Block1(_, _)) => {
+            // In expression position, '{' starts a map or a set literal. The elements tell which,
+            // and '{}' is a map.
+            reader.next();
+            let mut map_node = Node::new(NodeType::Map(Vec::new()));
+
+            while reader.more() {
+                if let Some(Token::Block2(_, _)) = reader.sym() {
+                    reader.next();
+                    break;
+                }
+                map_node.children.push(element(reader, ctx));
+
+                match reader.sym() {
+                    Some(Token::Comma(_, _)) => {
+                        reader.next();
+                    }
+                    Some(Token::Block2(_, _)) => {}
+                    _ => {
+                        dart_parseerror("Expected ',' or '}' in map or set literal.", ctx, reader.tokens(), reader.pos());
+                    }
+                }
+            }
+            // Only spreads can't tell, then the spread values do. See evaluator::collection.
+            if !map_node.children.is_empty() && !map_node.children.iter().any(|e| is_map_element(e) == Some(true)) {
+                map_node.nodetype = NodeType::Set(Vec::new());
+            }
+            map_node
+        }
         Some(x) => {
             showln!(red_bold, "error", white_bold, "Unexpected token: ", yellow_bold, format!("{:?}", x));
             Node::new(NodeType::Null)
@@ -493,3 +794,235 @@ fn term(reader: &mut Reader, ctx: &Ctx) -> Node {
         }
     }
 }
+
+
+// An element of a collection literal: an expression, a map entry 'k: v', a spread '...xs'
+// or '...?xs', 'if (cond) e else e' or 'for (var x in xs) e'.
+fn element(reader: &mut Reader, ctx: &Ctx) -> Node {
+    match reader.sym() {
+        Some(Token::Spread(_, _)) |
+        Some(Token::NullSpread(_, _)) => {
+            let nullaware = matches!(reader.sym(), Some(Token::NullSpread(_, _)));
+            reader.next();
+            let mut node = Node::new(NodeType::Spread(nullaware));
+            node.children.push(expression(reader, ctx));
+            node
+        }
+        Some(Token::If(_, _)) => {
+            reader.next();
+            if let Err(e) = reader.skip("(", ctx) {
+                showln!(red_bold, "error", white_bold, "Error while skipping '(': ", yellow_bold, e);
+                return Node::new(NodeType::Null);
+            }
+            let cond = expression(reader, ctx);
+            if let Err(e) = reader.skip(")", ctx) {
+                showln!(red_bold, "error", white_bold, "Error while skipping ')': ", yellow_bold, e);
+                return Node::new(NodeType::Null);
+            }
+            let mut node = Node::new(NodeType::IfElement);
+            node.children.push(cond);
+            node.children.push(element(reader, ctx));
+            if let Some(Token::Else(_, _)) = reader.sym() {
+                reader.next();
+                node.children.push(element(reader, ctx));
+            }
+            node
+        }
+        Some(Token::For(_, _)) => {
+            let mut node = for_header(reader, ctx);
+            node.children.push(element(reader, ctx));
+            node
+        }
+        _ => {
+            let key = expression(reader, ctx);
+            if let Some(Token::Colon(_, _)) = reader.sym() {
+                reader.next();
+                let mut entry = Node::new(NodeType::MapEntry);
+                entry.children.push(key);
+                entry.children.push(expression(reader, ctx));
+                return entry;
+            }
+            key
+        }
+    }
+}
+
+// Whether an element of a '{' literal makes it a map, if it tells.
+fn is_map_element(node: &Node) -> Option<bool> {
+    match node.nodetype {
+        NodeType::MapEntry => Some(true),
+        NodeType::Spread(_) => None,
+        NodeType::IfElement => node.children[1..].iter().find_map(is_map_element),
+        NodeType::For | NodeType::ForIn(_) => node.children.last().and_then(is_map_element),
+        _ => Some(false)
+    }
+}
+
+
+// A const expression. It is folded here, and everything in it is const too. Const objects, lists,
+// maps and sets are canonicalized when evaluated, so equal ones are identical. The names in it
+// are checked to be const variables when evaluated too, so only literals are left as they are.
+pub fn constant(node: Node, reader: &Reader, ctx: &Ctx) -> Node {
+    match const_operands(node, reader, ctx) {
+        node @ Node { nodetype: NodeType::Const | NodeType::Int(_) | NodeType::Double(_) | NodeType::Str(_) | NodeType::Bool(_) | NodeType::Null, .. } => node,
+        node => {
+            let mut wrapped = Node::new(NodeType::Const);
+            wrapped.pos = node.pos;
+            wrapped.children.push(node);
+            wrapped
+        }
+    }
+}
+
+// A const expression with its objects, lists, maps and sets marked for canonicalization.
+fn const_operands(node: Node, reader: &Reader, ctx: &Ctx) -> Node {
+    let mut node = fold(node);
+    match node.nodetype {
+        // As dart.
+        NodeType::MethodCall(_, _) => dart_parseerror("Method invocation is not a constant expression.", ctx, reader.tokens(), reader.pos()),
+        NodeType::FunLit(_) => dart_parseerror("Not a constant expression.", ctx, reader.tokens(), reader.pos()),
+        _ => {}
+    }
+    node.children = node.children.into_iter().map(|c| const_operands(c, reader, ctx)).collect();
+
+    match node.nodetype {
+        NodeType::FunCall(_) |
+        NodeType::List(_) |
+        NodeType::Map(_) |
+        NodeType::Set(_) => {
+            let mut canonical = Node::new(NodeType::Const);
+            canonical.pos = node.pos;
+            canonical.children.push(node);
+            canonical
+        }
+        _ => node
+    }
+}
+
+// Evaluates the operators of an expression whose operands are literals.
+fn fold(mut node: Node) -> Node {
+    node.children = node.children.into_iter().map(fold).collect();
+    let literal = |n: &Node| n.children.is_empty();
+
+    let folded = match node.children.as_slice() {
+        [operand] if literal(operand) => match (&node.nodetype, &operand.nodetype) {
+            (NodeType::Sub, NodeType::Int(n)) => Some(NodeType::Int(-n)),
+            (NodeType::Sub, NodeType::Double(x)) => Some(NodeType::Double(-x)),
+            (NodeType::Not, NodeType::Bool(b)) => Some(NodeType::Bool(!b)),
+            _ => None
+        },
+        [left, right] if literal(left) && literal(right) => fold_binary(&node.nodetype, &left.nodetype, &right.nodetype),
+        _ => None
+    };
+    match folded {
+        Some(nodetype) => {
+            let mut literal = Node::new(nodetype);
+            literal.pos = node.pos;
+            literal
+        }
+        None => node
+    }
+}
+
+fn fold_binary(op: &NodeType, left: &NodeType, right: &NodeType) -> Option<NodeType> {
+    let number = |t: &NodeType| match t {
+        NodeType::Int(n) => Some(*n as f64),
+        NodeType::Double(x) => Some(*x),
+        _ => None
+    };
+
+    match (op, left, right) {
+        (NodeType::Add, NodeType::Int(a), NodeType::Int(b)) => Some(NodeType::Int(a + b)),
+        (NodeType::Sub, NodeType::Int(a), NodeType::Int(b)) => Some(NodeType::Int(a - b)),
+        (NodeType::Mul, NodeType::Int(a), NodeType::Int(b)) => Some(NodeType::Int(a * b)),
+        (NodeType::IntDiv, NodeType::Int(a), NodeType::Int(b)) if *b != 0 => Some(NodeType::Int(a / b)),
+        (NodeType::BitAnd, NodeType::Int(a), NodeType::Int(b)) => Some(NodeType::Int(a & b)),
+        (NodeType::BitOr, NodeType::Int(a), NodeType::Int(b)) => Some(NodeType::Int(a | b)),
+        (NodeType::BitXor, NodeType::Int(a), NodeType::Int(b)) => Some(NodeType::Int(a ^ b)),
+        (NodeType::Add, NodeType::Str(a), NodeType::Str(b)) => Some(NodeType::Str(format!("{}{}", a, b))),
+        (NodeType::LogAnd, NodeType::Bool(a), NodeType::Bool(b)) => Some(NodeType::Bool(*a && *b)),
+        (NodeType::LogOr, NodeType::Bool(a), NodeType::Bool(b)) => Some(NodeType::Bool(*a || *b)),
+        (NodeType::Equal, NodeType::Str(a), NodeType::Str(b)) => Some(NodeType::Bool(a == b)),
+        (NodeType::Equal, NodeType::Bool(a), NodeType::Bool(b)) => Some(NodeType::Bool(a == b)),
+        _ => {
+            let (a, b) = (number(left)?, number(right)?);
+            match op {
+                NodeType::Add => Some(NodeType::Double(a + b)),
+                NodeType::Sub => Some(NodeType::Double(a - b)),
+                NodeType::Mul => Some(NodeType::Double(a * b)),
+                NodeType::Div => Some(NodeType::Double(a / b)),
+                NodeType::Equal => Some(NodeType::Bool(a == b)),
+                NodeType::LessThan => Some(NodeType::Bool(a < b)),
+                NodeType::GreaterThan => Some(NodeType::Bool(a > b)),
+                NodeType::LessOrEq => Some(NodeType::Bool(a <= b)),
+                NodeType::GreaterOrEq => Some(NodeType::Bool(a >= b)),
+                _ => None
+            }
+        }
+    }
+}
+
+
+// A record, '(1, x: 2)', or an expression in parentheses. A single positional field
+// makes a record only with a trailing comma: '(1,)'.
+fn record(reader: &mut Reader, ctx: &Ctx) -> Node {
+    reader.next();
+    let mut node = Node::new(NodeType::Record);
+    let mut is_record = false;
+
+    while !matches!(reader.sym(), Some(Token::Paren2(_, _))) {
+        let field = match (reader.sym(), reader.peek()) {
+            (Some(Token::Name(name, _, _)), Some(Token::Colon(_, _))) => {
+                reader.next();
+                reader.next();
+                let mut named = Node::new(NodeType::NamedArg(name));
+                named.children.push(expression(reader, ctx));
+                is_record = true;
+                named
+            }
+            _ => expression(reader, ctx)
+        };
+        node.children.push(field);
+        match reader.sym() {
+            Some(Token::Comma(_, _)) => {
+                reader.next();
+                is_record = true;
+            }
+            Some(Token::Paren2(_, _)) => {}
+            // As dart.
+            _ => dart_parseerror("Expected ')' before this.", ctx, reader.tokens(), reader.pos())
+        }
+    }
+    reader.next();
+
+    if !is_record && node.children.len() == 1 {
+        return node.children.remove(0);
+    }
+    node
+}
+
+// A parenthesis starts a function literal if the matching one is followed by a body.
+fn is_funclit(reader: &Reader) -> bool {
+    let tokens = reader.tokens();
+    let mut depth = 0;
+
+    for i in reader.pos()..tokens.len() {
+        match tokens[i] {
+            Token::Paren1(_, _) => depth += 1,
+            Token::Paren2(_, _) => {
+                depth -= 1;
+                if depth == 0 {
+                    // The body may be marked async.
+                    let body = match tokens.get(i + 1) {
+                        Some(Token::Name(s, _, _)) if s == "async" => tokens.get(i + 2),
+                        t => t
+                    };
+                    return matches!(body, Some(Token::Block1(_, _)) | Some(Token::Arrow(_, _)));
+                }
+            }
+            Token::End => return false,
+            _ => {}
+        }
+    }
+    false
+}
@@ -0,0 +1,365 @@
+// Futures, and the event loop that completes them. Everything runs on the one thread: a future
+// tells its listeners that it completed from a microtask, and timers, like that of Future.delayed,
+// run on a virtual clock once no microtasks are left. The loop runs after main returns, until
+// no work is left.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::rc::Rc;
+
+use crate::context::*;
+use crate::coroutine::{self, Coroutine};
+use crate::evaluator::{call_function, runtime_type, unhandled};
+use crate::exception;
+use crate::node::Node;
+use crate::object::*;
+use crate::objsys::ObjSys;
+use crate::stack::Stack;
+use crate::stream::{self, Event, Subscription};
+use crate::utils::{dart_evalerror, expect_args};
+
+
+pub type Future = Rc<RefCell<FutureState>>;
+
+
+#[derive(Default)]
+pub struct FutureState {
+    // The value, or the Throw it completed with. None while it is pending.
+    outcome: Option<Result<Object, Box<Object>>>,
+    listeners: Vec<Listener>,
+    // Whether anything ever listened, so that an error it completed with was handled.
+    handled: bool
+}
+
+
+impl fmt::Debug for FutureState {
+    // The listeners may well hold the future itself.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Future({})", if self.outcome.is_some() { "completed" } else { "pending" })
+    }
+}
+
+
+pub enum Listener {
+    // An async function, waiting in an 'await'.
+    Resume(Box<Coroutine>),
+    // then(onValue, onError: onError), with the future it gave.
+    Then(Box<Object>, Option<Box<Object>>, Future),
+    // catchError(onError), with the future it gave.
+    CatchError(Box<Object>, Future),
+    // A future completed with this one, which completes the same way.
+    Follow(Future)
+}
+
+
+pub enum Job {
+    // Call a function, completing the future if there is one with what it gives.
+    Call(Box<Object>, Option<Future>),
+    // Tell the listeners of a completed future.
+    Propagate(Future),
+    // Complete a future with null, as when a delay is over.
+    Complete(Future),
+    // Give an event of a stream to a subscription.
+    Deliver(Subscription, Event),
+    // Start an async* function, once its stream is listened to.
+    Start(Box<Coroutine>)
+}
+
+
+#[derive(Default)]
+pub struct EventLoop {
+    microtasks: VecDeque<Job>,
+    // With when they are due on the clock, and the order they were made in for those due at once.
+    timers: Vec<(i64, usize, Job)>,
+    // The virtual clock, in microseconds since main started.
+    now: i64,
+    made: usize
+}
+
+
+impl EventLoop {
+
+    pub fn microtask(&mut self, job: Job) {
+        self.microtasks.push_back(job);
+    }
+
+
+    pub fn timer(&mut self, delay: i64, job: Job) {
+        self.made += 1;
+        self.timers.push((self.now + delay.max(0), self.made, job));
+    }
+
+
+    // All microtasks go before the next timer, which moves the clock to when it is due.
+    fn next(&mut self) -> Option<Job> {
+        if let Some(job) = self.microtasks.pop_front() {
+            return Some(job);
+        }
+        let first = (0..self.timers.len()).min_by_key(|&i| (self.timers[i].0, self.timers[i].1))?;
+        let (due, _, job) = self.timers.remove(first);
+        self.now = due;
+        Some(job)
+    }
+}
+
+
+pub fn pending() -> Future {
+    Rc::new(RefCell::new(FutureState::default()))
+}
+
+
+// A future that completed, with a value or with a Throw.
+pub fn completed(outcome: Result<Object, Box<Object>>, ctx: &mut Ctx) -> Future {
+    let future = pending();
+    complete(&future, outcome, ctx);
+    future
+}
+
+
+// Completing with a future completes when that one does, the same way.
+pub fn complete(future: &Future, outcome: Result<Object, Box<Object>>, ctx: &mut Ctx) {
+    if let Ok(Object::Future(other, _)) = &outcome {
+        listen(other, Listener::Follow(future.clone()), ctx);
+        return;
+    }
+    future.borrow_mut().outcome = Some(outcome);
+    ctx.events.microtask(Job::Propagate(future.clone()));
+}
+
+
+// Completes a future and tells its listeners right away rather than in a microtask of their own,
+// as dart resumes an 'await for' in the microtask that gave it the event it waited on.
+pub fn complete_now(
+    future: &Future,
+    outcome: Result<Object, Box<Object>>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) {
+
+    future.borrow_mut().outcome = Some(outcome);
+    run(Job::Propagate(future.clone()), looktables, globals, stack, objsys, ctx);
+}
+
+
+pub fn listen(future: &Future, listener: Listener, ctx: &mut Ctx) {
+    let mut state = future.borrow_mut();
+    state.listeners.push(listener);
+    state.handled = true;
+    if state.outcome.is_some() {
+        ctx.events.microtask(Job::Propagate(future.clone()));
+    }
+}
+
+
+// Runs what is left to do after main, until nothing is.
+pub fn run_event_loop(
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) {
+
+    while let Some(job) = ctx.events.next() {
+        run(job, looktables, globals, stack, objsys, ctx);
+    }
+}
+
+
+fn run(
+    job: Job,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) {
+
+    match job {
+        Job::Call(func, future) => {
+            let outcome = callback(*func, Vec::new(), looktables, globals, stack, objsys, ctx);
+            match (future, outcome) {
+                (Some(future), outcome) => complete(&future, outcome, ctx),
+                // Nothing can catch what a microtask throws.
+                (None, Err(thrown)) => unhandled(&thrown, objsys, ctx),
+                (None, Ok(_)) => {}
+            }
+        }
+        Job::Propagate(future) => {
+            let (outcome, listeners, handled) = {
+                let mut state = future.borrow_mut();
+                (state.outcome.clone().unwrap(), std::mem::take(&mut state.listeners), state.handled)
+            };
+            if let (Err(thrown), false) = (&outcome, handled) {
+                unhandled(thrown, objsys, ctx);
+            }
+            for listener in listeners {
+                notify(listener, outcome.clone(), looktables, globals, stack, objsys, ctx);
+            }
+        }
+        Job::Complete(future) => complete(&future, Ok(Object::Null), ctx),
+        Job::Deliver(sub, event) => stream::deliver(&sub, event, looktables, globals, stack, objsys, ctx),
+        Job::Start(co) => coroutine::resume(*co, Ok(Object::Null), looktables, globals, stack, objsys, ctx)
+    }
+}
+
+
+fn notify(
+    listener: Listener,
+    outcome: Result<Object, Box<Object>>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) {
+
+    match (listener, outcome) {
+        (Listener::Resume(co), outcome) => coroutine::resume(*co, outcome, looktables, globals, stack, objsys, ctx),
+        (Listener::Then(on_value, _, result), Ok(value)) => {
+            let outcome = callback(*on_value, vec![value], looktables, globals, stack, objsys, ctx);
+            complete(&result, outcome, ctx);
+        }
+        (Listener::Then(_, Some(on_error), result) | Listener::CatchError(on_error, result), Err(thrown)) => {
+            let args = error_args(&on_error, *thrown);
+            let outcome = callback(*on_error, args, looktables, globals, stack, objsys, ctx);
+            complete(&result, outcome, ctx);
+        }
+        (Listener::Then(_, None, result) | Listener::CatchError(_, result) | Listener::Follow(result), outcome) => {
+            complete(&result, outcome, ctx);
+        }
+    }
+}
+
+
+// Calls a function given to a future or stream, giving what it returns or throws.
+pub fn callback(
+    func: Object,
+    args: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Object, Box<Object>> {
+
+    match call_function(func, args, looktables, globals, stack, objsys, ctx) {
+        thrown @ Object::Throw(_, _) => Err(Box::new(thrown)),
+        value => Ok(value)
+    }
+}
+
+
+// An error handler taking two parameters gets the stack trace too.
+pub fn error_args(handler: &Object, thrown: Object) -> Vec<Object> {
+    let (error, trace) = match thrown {
+        Object::Throw(error, trace) => (*error, trace),
+        x => panic!("Not a thrown object: {:?}", x)
+    };
+    match handler {
+        Object::Function(_, _, _, params, _) if params.len() >= 2 => vec![error, Object::String(trace)],
+        _ => vec![error]
+    }
+}
+
+
+pub fn has_function(name: &str) -> bool {
+    matches!(name, "Future" | "scheduleMicrotask")
+}
+
+
+// Future(computation), which runs it in a timer, and scheduleMicrotask(callback).
+pub fn call(name: &str, args: &[Object], ctx: &mut Ctx) -> Object {
+    expect_args(name, args, 1, ctx);
+    let func = Box::new(expect_function(name, &args[0], ctx));
+    match name {
+        "Future" => {
+            let future = pending();
+            ctx.events.timer(0, Job::Call(func, Some(future.clone())));
+            Object::Future(future, String::from("dynamic"))
+        }
+        _ => {
+            ctx.events.microtask(Job::Call(func, None));
+            Object::Null
+        }
+    }
+}
+
+
+// Future.value, Future.error, Future.delayed and Future.microtask.
+pub fn call_static(name: &str, args: Vec<Object>, objsys: &ObjSys, ctx: &mut Ctx) -> Object {
+    match name {
+        "value" => {
+            let value = args.into_iter().next().unwrap_or(Object::Null);
+            let valuetype = match &value {
+                Object::Future(_, valuetype) => valuetype.clone(),
+                value => runtime_type(value, objsys)
+            };
+            Object::Future(completed(Ok(value), ctx), valuetype)
+        }
+        "error" => {
+            if args.is_empty() {
+                // As dart.
+                dart_evalerror("Too few positional arguments: 1 required, 0 given.", ctx)
+            }
+            let thrown = exception::throw(args[0].clone(), ctx);
+            Object::Future(completed(Err(Box::new(thrown)), ctx), String::from("dynamic"))
+        }
+        "delayed" => {
+            let delay = match args.first() {
+                Some(Object::Duration(micros)) => *micros,
+                // As dart.
+                _ => dart_evalerror("The argument type of 'Future.delayed' must be 'Duration'.", ctx)
+            };
+            let future = pending();
+            let job = match args.get(1) {
+                Some(func) => Job::Call(Box::new(expect_function(name, func, ctx)), Some(future.clone())),
+                None => Job::Complete(future.clone())
+            };
+            ctx.events.timer(delay, job);
+            Object::Future(future, String::from("dynamic"))
+        }
+        "microtask" => {
+            expect_args(name, &args, 1, ctx);
+            let future = pending();
+            ctx.events.microtask(Job::Call(Box::new(expect_function(name, &args[0], ctx)), Some(future.clone())));
+            Object::Future(future, String::from("dynamic"))
+        }
+        // As dart.
+        _ => dart_evalerror(format!("Member not found: '{}'.", name), ctx)
+    }
+}
+
+
+// then(onValue, {onError}) and catchError(onError).
+pub fn call_method(future: &Future, name: &str, args: Vec<Object>, ctx: &mut Ctx) -> Object {
+    let result = pending();
+    let mut positional = Vec::new();
+    let mut on_error = None;
+    for arg in args {
+        match arg {
+            Object::NamedArg(n, value) if n == "onError" && name == "then" => on_error = Some(Box::new(expect_function(name, &value, ctx))),
+            // As dart.
+            Object::NamedArg(n, _) => dart_evalerror(format!("No named parameter with the name '{}'.", n), ctx),
+            arg => positional.push(arg)
+        }
+    }
+    expect_args(name, &positional, 1, ctx);
+    let func = Box::new(expect_function(name, &positional[0], ctx));
+    let listener = match name {
+        "then" => Listener::Then(func, on_error, result.clone()),
+        "catchError" => Listener::CatchError(func, result.clone()),
+        // As dart.
+        _ => dart_evalerror(format!("The method '{}' isn't defined for the class 'Future'.", name), ctx)
+    };
+    listen(future, listener, ctx);
+    Object::Future(result, String::from("dynamic"))
+}
+
+
+pub fn expect_function(name: &str, arg: &Object, ctx: &Ctx) -> Object {
+    match arg {
+        Object::Function(..) => arg.clone(),
+        // As dart.
+        x => dart_evalerror(format!("The argument type '{}' can't be assigned to the parameter type 'Function' of '{}'.", x, name), ctx)
+    }
+}
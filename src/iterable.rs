@@ -0,0 +1,534 @@
+// Iteration over lists, sets, strings and instances of user classes extending Iterable, and
+// the Iterable methods they share. The methods that give an Iterable are lazy: they only
+// record what to do, and the callbacks run as a for-in loop or a method like toList asks
+// for the elements. So does the body of a sync* function, up to its next yield.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::context::*;
+use crate::coroutine::{self, Coroutine};
+use crate::evaluator::{call_function, call_member, runtime_type, stringify};
+use crate::exception;
+use crate::node::Node;
+use crate::object::*;
+use crate::objsys::ObjSys;
+use crate::stack::Stack;
+use crate::types;
+use crate::utils::{dart_evalerror, expect_args};
+
+
+pub const METHODS: [&str; 10] = ["map", "where", "take", "skip", "expand", "fold", "reduce", "any", "every", "toList"];
+
+
+// An Iterable made by one of the lazy methods, from the iterable it was called on.
+#[derive(Debug)]
+pub enum Lazy {
+    Map(Object, Object),
+    Where(Object, Object),
+    Expand(Object, Object),
+    Take(Object, usize),
+    Skip(Object, usize),
+    // The keys, values or entries of a map, as it is when iterated.
+    Keys(Object),
+    Values(Object),
+    Entries(Object),
+    // The elements of a list backwards, as it is when iterated.
+    Reversed(Object),
+    // The body of a sync* function, which each iteration runs anew.
+    Generate(Coroutine)
+}
+
+
+// Where an iteration is. Lists, sets and strings are iterated over a copy of their elements.
+#[derive(Clone)]
+pub enum Cursor {
+    Elements(std::vec::IntoIter<Object>),
+    // The Iterator of a user defined Iterable.
+    Iterator(Object),
+    Map(Box<Cursor>, Object),
+    Where(Box<Cursor>, Object),
+    // With the iteration of what the function gave for the current element.
+    Expand(Box<Cursor>, Object, Option<Box<Cursor>>),
+    Take(Box<Cursor>, usize),
+    Skip(Box<Cursor>, usize),
+    Generate(Box<Coroutine>)
+}
+
+
+// What the iterator getter of a list, set or lazy Iterable gives: its iteration, and the element
+// moveNext last moved to.
+pub struct Iteration {
+    cursor: Cursor,
+    current: Option<Object>
+}
+
+
+impl fmt::Debug for Iteration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Iterator({:?})", self.current)
+    }
+}
+
+
+pub fn new_iterator(elements: Vec<Object>, elemtype: &str) -> Object {
+    let iteration = Iteration { cursor: Cursor::Elements(elements.into_iter()), current: None };
+    Object::Iterator(Rc::new(RefCell::new(iteration)), String::from(elemtype))
+}
+
+
+pub fn is_iterable(obj: &Object, objsys: &ObjSys) -> bool {
+    match obj {
+        Object::List(_, _) |
+        Object::Set(_, _) |
+        Object::String(_) |
+        Object::Iterable(_, _) => true,
+        Object::Reference(refid) => objsys.is_subtype(&objsys.get_instance(refid).classname, "Iterable"),
+        _ => false
+    }
+}
+
+
+// The type of the elements of an iterable.
+pub fn element_type(obj: &Object, objsys: &ObjSys) -> String {
+    match obj {
+        Object::List(_, elemtype) |
+        Object::Set(_, elemtype) |
+        Object::Iterable(_, elemtype) => elemtype.clone(),
+        Object::String(_) => String::from("String"),
+        Object::Reference(refid) => {
+            let inst = objsys.get_instance(refid);
+            objsys.supertype_args(&inst.classname, &inst.typeargs, "Iterable").remove(0)
+        }
+        _ => String::from("dynamic")
+    }
+}
+
+
+pub fn cursor(
+    iterable: &Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Cursor, Box<Object>> {
+
+    let cursor = match iterable {
+        Object::List(items, _) => Cursor::Elements(items.borrow().clone().into_iter()),
+        Object::Set(set, _) => Cursor::Elements(set.borrow().entries().iter().map(|(k, _)| k.clone()).collect::<Vec<Object>>().into_iter()),
+        // The characters of a string.
+        Object::String(s) => Cursor::Elements(s.chars().map(|c| Object::String(c.to_string())).collect::<Vec<Object>>().into_iter()),
+        Object::Iterable(lazy, _) => match lazy.as_ref() {
+            Lazy::Map(source, f) => Cursor::Map(Box::new(cursor(source, looktables, globals, stack, objsys, ctx)?), f.clone()),
+            Lazy::Where(source, f) => Cursor::Where(Box::new(cursor(source, looktables, globals, stack, objsys, ctx)?), f.clone()),
+            Lazy::Expand(source, f) => Cursor::Expand(Box::new(cursor(source, looktables, globals, stack, objsys, ctx)?), f.clone(), None),
+            Lazy::Take(source, n) => Cursor::Take(Box::new(cursor(source, looktables, globals, stack, objsys, ctx)?), *n),
+            Lazy::Skip(source, n) => Cursor::Skip(Box::new(cursor(source, looktables, globals, stack, objsys, ctx)?), *n),
+            Lazy::Keys(Object::Map(map, _, _)) => Cursor::Elements(map.borrow().entries().iter().map(|(k, _)| k.clone()).collect::<Vec<Object>>().into_iter()),
+            Lazy::Values(Object::Map(map, _, _)) => Cursor::Elements(map.borrow().entries().iter().map(|(_, v)| v.clone()).collect::<Vec<Object>>().into_iter()),
+            Lazy::Entries(Object::Map(map, _, _)) => Cursor::Elements(
+                map.borrow().entries().iter().map(|(k, v)| Object::MapEntry(Box::new(k.clone()), Box::new(v.clone()))).collect::<Vec<Object>>().into_iter()
+            ),
+            Lazy::Keys(x) | Lazy::Values(x) | Lazy::Entries(x) => panic!("Not a map: {}", x),
+            Lazy::Reversed(Object::List(items, _)) => Cursor::Elements(items.borrow().iter().rev().cloned().collect::<Vec<Object>>().into_iter()),
+            Lazy::Reversed(x) => panic!("Not a list: {}", x),
+            Lazy::Generate(co) => Cursor::Generate(Box::new(co.clone()))
+        },
+        Object::Reference(_) => Cursor::Iterator(member(iterable, "iterator", looktables, globals, stack, objsys, ctx)?),
+        // As dart.
+        x => dart_evalerror(format!("The type '{}' used in the 'for' loop must implement 'Iterable<dynamic>'.", runtime_type(x, objsys)), ctx)
+    };
+    Ok(cursor)
+}
+
+
+// The next element of an iteration, or None at its end.
+pub fn next(
+    cursor: &mut Cursor,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Option<Object>, Box<Object>> {
+
+    match cursor {
+        Cursor::Elements(elements) => Ok(elements.next()),
+        Cursor::Iterator(iterator) => {
+            let iterator = iterator.clone();
+            match member(&iterator, "moveNext", looktables, globals, stack, objsys, ctx)? {
+                Object::Bool(true) => Ok(Some(member(&iterator, "current", looktables, globals, stack, objsys, ctx)?)),
+                Object::Bool(false) => Ok(None),
+                x => dart_evalerror(format!("Expected bool. Got: {}", x), ctx)
+            }
+        }
+        Cursor::Map(inner, f) => match next(inner, looktables, globals, stack, objsys, ctx)? {
+            Some(elem) => Ok(Some(call(f, vec![elem], looktables, globals, stack, objsys, ctx)?)),
+            None => Ok(None)
+        },
+        Cursor::Where(inner, f) => {
+            while let Some(elem) = next(inner, looktables, globals, stack, objsys, ctx)? {
+                if test(f, elem.clone(), looktables, globals, stack, objsys, ctx)? {
+                    return Ok(Some(elem));
+                }
+            }
+            Ok(None)
+        }
+        Cursor::Expand(inner, f, expanded) => loop {
+            if let Some(current) = expanded {
+                if let Some(elem) = next(current, looktables, globals, stack, objsys, ctx)? {
+                    return Ok(Some(elem));
+                }
+            }
+            let elem = match next(inner, looktables, globals, stack, objsys, ctx)? {
+                Some(elem) => elem,
+                None => return Ok(None)
+            };
+            let elements = call(f, vec![elem], looktables, globals, stack, objsys, ctx)?;
+            if !is_iterable(&elements, objsys) {
+                // As dart.
+                dart_evalerror(format!("type '{}' is not a subtype of type 'Iterable<dynamic>'", runtime_type(&elements, objsys)), ctx)
+            }
+            *expanded = Some(Box::new(self::cursor(&elements, looktables, globals, stack, objsys, ctx)?));
+        },
+        Cursor::Take(inner, left) => {
+            if *left == 0 {
+                return Ok(None);
+            }
+            *left -= 1;
+            next(inner, looktables, globals, stack, objsys, ctx)
+        }
+        Cursor::Skip(inner, left) => {
+            while *left > 0 {
+                *left -= 1;
+                if next(inner, looktables, globals, stack, objsys, ctx)?.is_none() {
+                    return Ok(None);
+                }
+            }
+            next(inner, looktables, globals, stack, objsys, ctx)
+        }
+        Cursor::Generate(co) => coroutine::next(co, looktables, globals, stack, objsys, ctx)
+    }
+}
+
+
+// The Iterable method name of operands[0], called with the rest of the operands.
+// Gives the error thrown, if one is.
+pub fn call_method(
+    name: &str,
+    mut operands: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let iterable = operands.remove(0);
+    let args = operands;
+    let elemtype = element_type(&iterable, objsys);
+    let lazy = |lazy: Lazy, elemtype: &str| Object::Iterable(Rc::new(lazy), String::from(elemtype));
+
+    let result = match name {
+        "map" => {
+            expect_args(name, &args, 1, ctx);
+            Ok(lazy(Lazy::Map(iterable, args[0].clone()), "dynamic"))
+        }
+        "where" => {
+            expect_args(name, &args, 1, ctx);
+            Ok(lazy(Lazy::Where(iterable, args[0].clone()), &elemtype))
+        }
+        "expand" => {
+            expect_args(name, &args, 1, ctx);
+            Ok(lazy(Lazy::Expand(iterable, args[0].clone()), "dynamic"))
+        }
+        "take" | "skip" => {
+            expect_args(name, &args, 1, ctx);
+            let count = match &args[0] {
+                Object::Int(n) if *n >= 0 => *n as usize,
+                // As dart.
+                Object::Int(n) => return exception::error(
+                    "RangeError",
+                    format!("(count): Invalid value: Not greater than or equal to 0: {}", n),
+                    ctx
+                ),
+                x => dart_evalerror(format!("The argument type '{}' can't be assigned to the parameter type 'int'.", runtime_type(x, objsys)), ctx)
+            };
+            match name {
+                "take" => Ok(lazy(Lazy::Take(iterable, count), &elemtype)),
+                _ => Ok(lazy(Lazy::Skip(iterable, count), &elemtype))
+            }
+        }
+        "fold" | "reduce" => {
+            // Without an initial value, reduce starts from the first element.
+            let (mut acc, f) = match name {
+                "fold" => {
+                    expect_args(name, &args, 2, ctx);
+                    (Some(args[0].clone()), &args[1])
+                }
+                _ => {
+                    expect_args(name, &args, 1, ctx);
+                    (None, &args[0])
+                }
+            };
+            let mut cursor = match cursor(&iterable, looktables, globals, stack, objsys, ctx) {
+                Ok(cursor) => cursor,
+                Err(thrown) => return *thrown
+            };
+            loop {
+                let elem = match next(&mut cursor, looktables, globals, stack, objsys, ctx) {
+                    Ok(Some(elem)) => elem,
+                    Ok(None) => break,
+                    Err(thrown) => return *thrown
+                };
+                acc = match acc {
+                    Some(acc) => match call(f, vec![acc, elem], looktables, globals, stack, objsys, ctx) {
+                        Ok(value) => Some(value),
+                        Err(thrown) => return *thrown
+                    },
+                    None => Some(elem)
+                };
+            }
+            match acc {
+                Some(value) => Ok(value),
+                // As dart.
+                None => Ok(exception::error("StateError", "No element", ctx))
+            }
+        }
+        "any" | "every" => {
+            expect_args(name, &args, 1, ctx);
+            // Stops at the first element that decides it.
+            let wanted = name == "any";
+            let mut cursor = match cursor(&iterable, looktables, globals, stack, objsys, ctx) {
+                Ok(cursor) => cursor,
+                Err(thrown) => return *thrown
+            };
+            loop {
+                let elem = match next(&mut cursor, looktables, globals, stack, objsys, ctx) {
+                    Ok(Some(elem)) => elem,
+                    Ok(None) => break Ok(Object::Bool(!wanted)),
+                    Err(thrown) => break Err(thrown)
+                };
+                match test(&args[0], elem, looktables, globals, stack, objsys, ctx) {
+                    Ok(b) if b == wanted => break Ok(Object::Bool(wanted)),
+                    Ok(_) => {}
+                    Err(thrown) => break Err(thrown)
+                }
+            }
+        }
+        "join" => {
+            let sep = match args.first() {
+                Some(Object::String(s)) => s.clone(),
+                None => String::new(),
+                // As dart.
+                Some(x) => dart_evalerror(format!("The argument type '{}' can't be assigned to the parameter type 'String'.", runtime_type(x, objsys)), ctx)
+            };
+            // Joining calls the toString of each element.
+            let mut parts = Vec::new();
+            let mut cursor = match cursor(&iterable, looktables, globals, stack, objsys, ctx) {
+                Ok(cursor) => cursor,
+                Err(thrown) => return *thrown
+            };
+            loop {
+                match next(&mut cursor, looktables, globals, stack, objsys, ctx) {
+                    Ok(Some(elem)) => match stringify(&elem, looktables, globals, stack, objsys, ctx) {
+                        thrown @ Object::Throw(_, _) => return thrown,
+                        text => parts.push(text.to_string())
+                    },
+                    Ok(None) => break Ok(Object::String(parts.join(&sep))),
+                    Err(thrown) => break Err(thrown)
+                }
+            }
+        }
+        "toList" => {
+            expect_args(name, &args, 0, ctx);
+            elements(&iterable, looktables, globals, stack, objsys, ctx).map(|items| {
+                // The element type of what map and expand give is the one of their elements.
+                let elemtype = match elemtype.as_str() {
+                    "dynamic" if !items.is_empty() => {
+                        types::lub(&items.iter().map(|obj| runtime_type(obj, objsys)).collect::<Vec<String>>(), objsys)
+                    }
+                    _ => elemtype
+                };
+                Object::new_list(items, &elemtype)
+            })
+        }
+        // As dart.
+        _ => dart_evalerror(format!("The method '{}' isn't defined for the class '{}'.", name, runtime_type(&iterable, objsys)), ctx)
+    };
+
+    match result {
+        Ok(obj) => obj,
+        Err(thrown) => *thrown
+    }
+}
+
+
+// The properties of Iterables that are not lists or sets, which have their own.
+pub fn get_property(
+    iterable: &Object,
+    name: &str,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    if name == "iterator" {
+        return match cursor(iterable, looktables, globals, stack, objsys, ctx) {
+            Ok(cursor) => {
+                let iteration = Iteration { cursor, current: None };
+                Object::Iterator(Rc::new(RefCell::new(iteration)), element_type(iterable, objsys))
+            }
+            Err(thrown) => *thrown
+        };
+    }
+
+    // These only need the first element, and so work on an infinite sync* generator too.
+    if matches!(name, "first" | "isEmpty" | "isNotEmpty") {
+        let first = match cursor(iterable, looktables, globals, stack, objsys, ctx) {
+            Ok(mut cursor) => next(&mut cursor, looktables, globals, stack, objsys, ctx),
+            Err(thrown) => Err(thrown)
+        };
+        return match (name, first) {
+            (_, Err(thrown)) => *thrown,
+            ("isEmpty", Ok(first)) => Object::Bool(first.is_none()),
+            ("isNotEmpty", Ok(first)) => Object::Bool(first.is_some()),
+            (_, Ok(Some(obj))) => obj,
+            // As dart.
+            (_, Ok(None)) => exception::error("StateError", "No element", ctx)
+        };
+    }
+
+    let items = match elements(iterable, looktables, globals, stack, objsys, ctx) {
+        Ok(items) => items,
+        Err(thrown) => return *thrown
+    };
+    match name {
+        "length" => Object::Int(items.len() as i64),
+        "last" => match items.last() {
+            Some(obj) => obj.clone(),
+            None => exception::error("StateError", "No element", ctx)
+        },
+        // As dart.
+        _ => dart_evalerror(format!("The getter '{}' isn't defined for the class '{}'.", name, runtime_type(iterable, objsys)), ctx)
+    }
+}
+
+
+pub fn has_property(name: &str) -> bool {
+    matches!(name, "length" | "isEmpty" | "isNotEmpty" | "first" | "last" | "iterator")
+}
+
+
+// moveNext or current of an Iterator that is not a user defined one.
+pub fn iterator_member(
+    iterator: &Object,
+    name: &str,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Object, Box<Object>> {
+
+    let iteration = match iterator {
+        Object::Iterator(iteration, _) => iteration,
+        x => panic!("Not an Iterator: {}", x)
+    };
+    match name {
+        "moveNext" => {
+            // Moved out, as the next element may need the evaluator to iterate this same iterator.
+            let mut cursor = iteration.borrow().cursor.clone();
+            let current = next(&mut cursor, looktables, globals, stack, objsys, ctx)?;
+            let moved = current.is_some();
+            *iteration.borrow_mut() = Iteration { cursor, current };
+            Ok(Object::Bool(moved))
+        }
+        // Before moveNext, and at the end, dart leaves it undefined.
+        "current" => Ok(iteration.borrow().current.clone().unwrap_or(Object::Null)),
+        // As dart.
+        _ => dart_evalerror(format!("The getter '{}' isn't defined for the class '{}'.", name, runtime_type(iterator, objsys)), ctx)
+    }
+}
+
+
+// All the elements of an iterable.
+pub fn elements(
+    iterable: &Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Vec<Object>, Box<Object>> {
+
+    let mut cursor = cursor(iterable, looktables, globals, stack, objsys, ctx)?;
+    let mut items = Vec::new();
+    while let Some(elem) = next(&mut cursor, looktables, globals, stack, objsys, ctx)? {
+        items.push(elem);
+    }
+    Ok(items)
+}
+
+
+// A field or getter of an instance, or what a method without arguments gives.
+fn member(
+    instance: &Object,
+    name: &str,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Object, Box<Object>> {
+
+    if let Object::Iterator(_, _) = instance {
+        return iterator_member(instance, name, looktables, globals, stack, objsys, ctx);
+    }
+    let (refid, classname) = match instance {
+        Object::Reference(refid) => (refid, objsys.get_instance(refid).classname.clone()),
+        // As dart.
+        x => dart_evalerror(format!("The getter '{}' isn't defined for the class '{}'.", name, runtime_type(x, objsys)), ctx)
+    };
+    let inst = objsys.get_instance(refid);
+    if inst.has_field(String::from(name)) {
+        return Ok(inst.get_field(String::from(name)).clone());
+    }
+    match call_member(name, vec![instance.clone()], looktables, globals, stack, objsys, ctx) {
+        Some(Object::Throw(e, st)) => Err(Box::new(Object::Throw(e, st))),
+        Some(value) => Ok(value),
+        // As dart.
+        None => dart_evalerror(format!("The getter '{}' isn't defined for the class '{}'.", name, classname), ctx)
+    }
+}
+
+
+fn call(
+    f: &Object,
+    args: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Object, Box<Object>> {
+
+    match call_function(f.clone(), args, looktables, globals, stack, objsys, ctx) {
+        Object::Throw(e, st) => Err(Box::new(Object::Throw(e, st))),
+        value => Ok(value)
+    }
+}
+
+
+// What a predicate, as given to where, any and every, says of an element.
+fn test(
+    f: &Object,
+    elem: Object,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<bool, Box<Object>> {
+
+    match call(f, vec![elem], looktables, globals, stack, objsys, ctx)? {
+        Object::Bool(b) => Ok(b),
+        x => dart_evalerror(format!("Expected bool. Got: {}", x), ctx)
+    }
+}
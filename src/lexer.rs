@@ -6,7 +6,7 @@ use crate::utils::dprint;
 
 /// Not applicable for first char in name, where only letters are allowed
 fn is_legal_namechar(c: char) -> bool {
-    c.is_alphabetic() || c.is_digit(10) || (c == '_')
+    c.is_alphabetic() || c.is_digit(10) || (c == '_') || (c == '$')
 }
 
 
@@ -33,6 +33,9 @@ fn read_word(tokens: &mut Vec<Token>, chars: &[char], start: usize, linenum: usi
     else if &sym == "false" {
         tokens.push(Token::Bool(false, linenum, symnum));
     }
+    else if &sym == "null" {
+        tokens.push(Token::Null(linenum, symnum));
+    }
     else if &sym == "if" {
         tokens.push(Token::If(linenum, symnum));
     }
@@ -57,13 +60,125 @@ fn read_word(tokens: &mut Vec<Token>, chars: &[char], start: usize, linenum: usi
     else if &sym == "this" {
         tokens.push(Token::This(linenum, symnum));
     }
+    else if &sym == "super" {
+        tokens.push(Token::Super(linenum, symnum));
+    }
+    else if &sym == "extends" {
+        tokens.push(Token::Extends(linenum, symnum));
+    }
+    else if &sym == "abstract" {
+        tokens.push(Token::Abstract(linenum, symnum));
+    }
+    else if &sym == "implements" {
+        tokens.push(Token::Implements(linenum, symnum));
+    }
+    else if &sym == "with" {
+        tokens.push(Token::With(linenum, symnum));
+    }
+    else if &sym == "mixin" {
+        tokens.push(Token::Mixin(linenum, symnum));
+    }
+    else if &sym == "is" {
+        tokens.push(Token::Is(linenum, symnum));
+    }
+    else if &sym == "throw" {
+        tokens.push(Token::Throw(linenum, symnum));
+    }
+    else if &sym == "rethrow" {
+        tokens.push(Token::Rethrow(linenum, symnum));
+    }
+    else if &sym == "try" {
+        tokens.push(Token::Try(linenum, symnum));
+    }
+    else if &sym == "catch" {
+        tokens.push(Token::Catch(linenum, symnum));
+    }
+    else if &sym == "finally" {
+        tokens.push(Token::Finally(linenum, symnum));
+    }
+    else if &sym == "break" {
+        tokens.push(Token::Break(linenum, symnum));
+    }
+    else if &sym == "continue" {
+        tokens.push(Token::Continue(linenum, symnum));
+    }
+    else if &sym == "switch" {
+        tokens.push(Token::Switch(linenum, symnum));
+    }
+    else if &sym == "case" {
+        tokens.push(Token::Case(linenum, symnum));
+    }
+    else if &sym == "default" {
+        tokens.push(Token::Default(linenum, symnum));
+    }
+    else if &sym == "static" {
+        tokens.push(Token::Static(linenum, symnum));
+    }
+    else if &sym == "final" {
+        tokens.push(Token::Final(linenum, symnum));
+    }
+    else if &sym == "const" {
+        tokens.push(Token::Const(linenum, symnum));
+    }
+    else if &sym == "late" {
+        tokens.push(Token::Late(linenum, symnum));
+    }
+    else if &sym == "enum" {
+        tokens.push(Token::Enum(linenum, symnum));
+    }
     else {
+        // A type argument list written right after a name, like List<int>, is part of the name.
+        let (args, args_len) = read_typeargs(chars, start + len);
+        sym.push_str(&args);
+        len += args_len;
         tokens.push(Token::Name(sym, linenum, symnum));
     }
     return len;
 }
 
 
+// Reads a balanced '<...>' holding only type names, returning it normalized as '<A, B<C>>'.
+// Anything else, like the comparison in 'i<n', is left alone.
+fn read_typeargs(chars: &[char], start: usize) -> (String, usize) {
+    if chars.get(start) != Some(&'<') || !chars.get(start + 1).is_some_and(|c| c.is_alphabetic()) {
+        return (String::new(), 0);
+    }
+
+    let mut args = String::new();
+    let mut depth = 0;
+    let mut len = 0;
+
+    while let Some(&c) = chars.get(start + len) {
+        len += 1;
+        match c {
+            // Spaces only matter between words, as in 'T extends num'.
+            ' ' => {
+                if args.ends_with(is_legal_namechar) {
+                    args.push(' ');
+                }
+                continue;
+            }
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' | '?' => {}
+            _ if is_legal_namechar(c) => {}
+            _ => return (String::new(), 0)
+        }
+        if !is_legal_namechar(c) && args.ends_with(' ') {
+            args.pop();
+        }
+        args.push(c);
+        if c == ',' {
+            args.push(' ');
+        }
+        if depth == 0 {
+            return (args, len);
+        }
+    }
+    (String::new(), 0)
+}
+
+
 pub fn lex(input: &str) -> Reader {
     let (tokens, pos) = lex_real(input, 0, 0, 1, 1);
     let reader = Reader::new(tokens);
@@ -230,9 +345,61 @@ fn lex_real(input: &str, startpos: usize, interpol: usize, mut linenum: usize, m
             }
 
             '.' => {
+                if chars.get(i+1) == Some(&'.') && chars.get(i+2) == Some(&'.') {
+                    if chars.get(i+3) == Some(&'?') {
+                        tokens.push(Token::NullSpread(linenum, symnum));
+                        i += 4;
+                        symnum += 4;
+                        continue;
+                    }
+                    tokens.push(Token::Spread(linenum, symnum));
+                    i += 3;
+                    symnum += 3;
+                    continue;
+                }
+                if chars.get(i+1) == Some(&'.') {
+                    tokens.push(Token::Cascade(linenum, symnum));
+                    i += 2;
+                    symnum += 2;
+                    continue;
+                }
                 tokens.push(Token::Access(linenum, symnum));
             }
 
+            '?' => {
+                if chars.get(i+1) == Some(&'?') {
+                    if chars.get(i+2) == Some(&'=') {
+                        tokens.push(Token::IfNullAssign(linenum, symnum));
+                        i += 3;
+                        symnum += 3;
+                        continue;
+                    }
+                    tokens.push(Token::IfNull(linenum, symnum));
+                    i += 2;
+                    symnum += 2;
+                    continue;
+                }
+                if chars.get(i+1) == Some(&'.') && chars.get(i+2) == Some(&'.') {
+                    tokens.push(Token::NullCascade(linenum, symnum));
+                    i += 3;
+                    symnum += 3;
+                    continue;
+                }
+                if chars.get(i+1) == Some(&'.') {
+                    tokens.push(Token::NullAccess(linenum, symnum));
+                    i += 2;
+                    symnum += 2;
+                    continue;
+                }
+                // A nullable type, 'int?' or 'List<int>?', is a single name.
+                match tokens.last_mut() {
+                    Some(Token::Name(name, _, _)) if i > 0 && (is_legal_namechar(chars[i-1]) || chars[i-1] == '>' && name.ends_with('>')) => {
+                        name.push('?');
+                    }
+                    _ => panic!("Unexpected symbol: \"?\"")
+                }
+            }
+
             ',' => {
                 tokens.push(Token::Comma(linenum, symnum));
             }
@@ -241,6 +408,10 @@ fn lex_real(input: &str, startpos: usize, interpol: usize, mut linenum: usize, m
                 tokens.push(Token::EndSt(linenum, symnum));
             }
 
+            ':' => {
+                tokens.push(Token::Colon(linenum, symnum));
+            }
+
             '=' => {
                 if chars[i+1] == '=' {
                     tokens.push(Token::Equal(linenum, symnum));
@@ -248,6 +419,12 @@ fn lex_real(input: &str, startpos: usize, interpol: usize, mut linenum: usize, m
                     symnum += 2;
                     continue;
                 }
+                if chars[i+1] == '>' {
+                    tokens.push(Token::Arrow(linenum, symnum));
+                    i += 2;
+                    symnum += 2;
+                    continue;
+                }
                 tokens.push(Token::Assign(linenum, symnum));
             }
 
@@ -295,6 +472,16 @@ fn lex_real(input: &str, startpos: usize, interpol: usize, mut linenum: usize, m
                 tokens.push(Token::GreaterThan(linenum, symnum));
             }
 
+            '~' => {
+                if chars.get(i+1) == Some(&'/') {
+                    tokens.push(Token::IntDiv(linenum, symnum));
+                    i += 2;
+                    symnum += 2;
+                    continue;
+                }
+                panic!("Unexpected symbol: \"~\"");
+            }
+
             '|' => {
                 if chars[i+1] == '|' {
                     tokens.push(Token::LogOr(linenum, symnum));
@@ -320,6 +507,12 @@ fn lex_real(input: &str, startpos: usize, interpol: usize, mut linenum: usize, m
             }
 
             '!' => {
+                if chars.get(i+1) == Some(&'=') {
+                    tokens.push(Token::NotEqual(linenum, symnum));
+                    i += 2;
+                    symnum += 2;
+                    continue;
+                }
                 tokens.push(Token::Not(linenum, symnum));
             }
 
@@ -335,10 +528,8 @@ fn lex_real(input: &str, startpos: usize, interpol: usize, mut linenum: usize, m
                         symnum += 1;
                         continue;
                     }
-                    else if nc == '.' {
-                        if input.get(i+nl-1 .. i+nl) == Some(".") {
-                            panic!("Unexpected symbol: \".\"");
-                        }
+                    // A '.' not followed by a digit starts a member access, as in '21.isEven'.
+                    else if nc == '.' && is_int && input.get(i+nl+1 .. i+nl+2).is_some_and(|d| d.chars().all(|d| d.is_ascii_digit())) {
                         is_int = false;
                         nl += 1;
                         symnum += 1;
@@ -360,7 +551,7 @@ fn lex_real(input: &str, startpos: usize, interpol: usize, mut linenum: usize, m
                 continue;
             }
 
-            x if x.is_alphabetic() => {
+            x if x.is_alphabetic() || x == '_' || x == '$' => {
                 let word_len: usize = read_word(&mut tokens, &chars, i, linenum, symnum);
                 if word_len > 0 {
                     i += word_len;
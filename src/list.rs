@@ -0,0 +1,181 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::context::*;
+use crate::exception;
+use crate::iterable::{self, Lazy};
+use crate::object::*;
+use crate::utils::{dart_evalerror, expect_args};
+
+
+// The members lists have besides the Iterable methods, which extensions on List can't replace.
+pub const MEMBERS: [&str; 15] = [
+    "length", "isEmpty", "isNotEmpty", "first", "last", "reversed", "iterator",
+    "add", "addAll", "removeAt", "insert", "indexOf", "contains", "sublist", "join"
+];
+
+
+// The members of List<E> as declared in dart, for the checker.
+pub const SIGNATURES: [&str; 17] = [
+    "int get length", "bool get isEmpty", "bool get isNotEmpty", "E get first", "E get last",
+    "Iterable<E> get reversed", "Iterator<E> get iterator",
+    "E operator [](int index)", "void operator []=(int index, E value)",
+    "void add(E value)", "void addAll(Iterable<E> iterable)", "E removeAt(int index)", "void insert(int index, E element)",
+    "int indexOf(E element)", "bool contains(Object? element)", "List<E> sublist(int start, [int? end])", "String join([String separator])"
+];
+
+
+pub fn get_property(list: &Rc<RefCell<Vec<Object>>>, elemtype: &str, name: &str, ctx: &Ctx) -> Object {
+    let items = list.borrow();
+
+    match name {
+        "length" => Object::Int(items.len() as i64),
+        "isEmpty" => Object::Bool(items.is_empty()),
+        "isNotEmpty" => Object::Bool(!items.is_empty()),
+        "first" => match items.first() {
+            Some(obj) => obj.clone(),
+            // As dart.
+            None => exception::error("StateError", "No element", ctx)
+        },
+        "last" => match items.last() {
+            Some(obj) => obj.clone(),
+            None => exception::error("StateError", "No element", ctx)
+        },
+        // A view that sees the list change, as in dart.
+        "reversed" => Object::Iterable(Rc::new(Lazy::Reversed(Object::List(list.clone(), String::from(elemtype)))), String::from(elemtype)),
+        // Over the elements the list has now.
+        "iterator" => iterable::new_iterator(items.clone(), elemtype),
+        _ => dart_evalerror(format!("The getter '{}' isn't defined for the class 'List'.", name), ctx)
+    }
+}
+
+
+pub fn get_index(list: &Rc<RefCell<Vec<Object>>>, index: &Object, ctx: &Ctx) -> Object {
+    let items = list.borrow();
+    match checked_index(index, items.len(), ctx) {
+        Ok(i) => items[i].clone(),
+        Err(thrown) => *thrown
+    }
+}
+
+
+// Gives null, or the error thrown.
+pub fn set_index(list: &Rc<RefCell<Vec<Object>>>, index: &Object, value: Object, ctx: &Ctx) -> Object {
+    let mut items = list.borrow_mut();
+    match checked_index(index, items.len(), ctx) {
+        Ok(i) => {
+            items[i] = value;
+            Object::Null
+        }
+        Err(thrown) => *thrown
+    }
+}
+
+
+pub fn call_method(list: &Rc<RefCell<Vec<Object>>>, elemtype: &str, name: &str, args: Vec<Object>, ctx: &Ctx) -> Object {
+    match name {
+        "add" => {
+            expect_args(name, &args, 1, ctx);
+            list.borrow_mut().push(args[0].clone());
+            Object::Null
+        }
+        "addAll" => {
+            expect_args(name, &args, 1, ctx);
+            match &args[0] {
+                Object::List(other, _) => {
+                    // Copy first, the argument may be the list itself.
+                    let extra: Vec<Object> = other.borrow().clone();
+                    list.borrow_mut().extend(extra);
+                }
+                x => dart_evalerror(format!("The argument type '{}' can't be assigned to the parameter type 'Iterable'.", x), ctx)
+            }
+            Object::Null
+        }
+        "removeAt" => {
+            expect_args(name, &args, 1, ctx);
+            let len = list.borrow().len();
+            match checked_index(&args[0], len, ctx) {
+                Ok(i) => list.borrow_mut().remove(i),
+                Err(thrown) => *thrown
+            }
+        }
+        "insert" => {
+            expect_args(name, &args, 2, ctx);
+            let len = list.borrow().len();
+            // Inserting at the very end is allowed.
+            match checked_index(&args[0], len + 1, ctx) {
+                Ok(i) => {
+                    list.borrow_mut().insert(i, args[1].clone());
+                    Object::Null
+                }
+                Err(thrown) => *thrown
+            }
+        }
+        "indexOf" => {
+            expect_args(name, &args, 1, ctx);
+            match list.borrow().iter().position(|obj| obj.equals(&args[0])) {
+                Some(i) => Object::Int(i as i64),
+                None => Object::Int(-1)
+            }
+        }
+        "contains" => {
+            expect_args(name, &args, 1, ctx);
+            Object::Bool(list.borrow().iter().any(|obj| obj.equals(&args[0])))
+        }
+        "sublist" => {
+            if args.is_empty() || args.len() > 2 {
+                expect_args(name, &args, 1, ctx);
+            }
+            let items = list.borrow();
+            let start = match checked_index(&args[0], items.len() + 1, ctx) {
+                Ok(i) => i,
+                Err(thrown) => return *thrown
+            };
+            let end = match args.get(1) {
+                Some(Object::Null) | None => items.len(),
+                Some(obj) => match checked_index(obj, items.len() + 1, ctx) {
+                    Ok(i) => i,
+                    Err(thrown) => return *thrown
+                }
+            };
+            if end < start {
+                return exception::error(
+                    "RangeError",
+                    format!("(end): Invalid value: Not in inclusive range {}..{}: {}", start, items.len(), end),
+                    ctx
+                );
+            }
+            Object::new_list(items[start..end].to_vec(), elemtype)
+        }
+        "join" => {
+            let sep = match args.first() {
+                Some(Object::String(s)) => s.clone(),
+                None => String::new(),
+                Some(x) => dart_evalerror(format!("The argument type '{}' can't be assigned to the parameter type 'String'.", x), ctx)
+            };
+            let parts: Vec<String> = list.borrow().iter().map(|obj| obj.to_string()).collect();
+            Object::String(parts.join(&sep))
+        }
+        // As dart.
+        _ => exception::no_method(&format!("List<{}>", elemtype), &Object::List(list.clone(), String::from(elemtype)), name, &args, ctx)
+    }
+}
+
+
+// Valid indexes are 0..len. Others give a RangeError to throw.
+fn checked_index(index: &Object, len: usize, ctx: &Ctx) -> Result<usize, Box<Object>> {
+    match index {
+        Object::Int(i) => {
+            if *i < 0 || *i as usize >= len {
+                // As dart.
+                return Err(Box::new(exception::error(
+                    "RangeError",
+                    format!("(index): Index out of range: index should be less than {}: {}", len, i),
+                    ctx
+                )));
+            }
+            Ok(*i as usize)
+        }
+        x => dart_evalerror(format!("A value of type '{}' can't be used as an index.", x), ctx)
+    }
+}
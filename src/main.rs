@@ -16,6 +16,19 @@ mod object;
 mod testlist;
 mod context;
 mod reader;
+mod list;
+mod map;
+mod set;
+mod string;
+mod iterable;
+mod exception;
+mod typecheck;
+mod types;
+mod repl;
+mod future;
+mod duration;
+mod coroutine;
+mod stream;
 
 use std::{ fs::read_dir, io::prelude::* };
 use std::env;
@@ -26,8 +39,21 @@ use minimo::{divider, showln};
 use stack::Stack;
 use objsys::ObjSys;
 use node::{ Node, NodeType };
+use object::Object;
+
+// The evaluator recurses for each call in the dart program, and for each
+// level of nesting in an expression, so it gets more stack than the main thread has.
+const STACK_SIZE: usize = 1 << 30;
 
 fn main() {
+    let interpreter = std::thread::Builder::new().name(String::from("main")).stack_size(STACK_SIZE).spawn(run).unwrap();
+    if interpreter.join().is_err() {
+        // The panic is already reported. Exit as a panicking main thread does.
+        std::process::exit(101);
+    }
+}
+
+fn run() {
     let args: Vec<String> = env::args().collect();
 
     if args.len() < 2 {
@@ -37,6 +63,9 @@ fn main() {
     let mut ctx = Ctx {
         filepath: String::from(""),
         debug: true,
+        calls: Vec::new(),
+        events: future::EventLoop::default(),
+        stringifying: Vec::new(),
     };
 
     let a1 = &args[1];
@@ -102,6 +131,10 @@ fn main() {
             ctx.filepath = filepath.clone();
             do_task(task, filepath, &mut ctx);
         }
+        "repl" => {
+            ctx.filepath = String::from(repl::REPLPATH);
+            repl::run(&mut ctx);
+        }
         "testfail" => {
             if args.len() < 3 {
                 println!("Running all fail tests:");
@@ -203,24 +236,11 @@ fn filecurse(
 
     let mut looktable: HashMap<String, usize> = HashMap::new();
 
-    for i in oldlen..globals.len() {
-        let f = &globals[i];
-
-        match &f.nodetype {
-            NodeType::FunDef(funcname, _) => {
-                looktable.insert(funcname.clone(), i);
-            }
-            NodeType::Constructor(name, _) => {
-                looktable.insert(name.clone(), i);
-            }
-            _ => {
-                panic!("Unexpected node type in globals");
-            }
-        }
-    }
+    index_globals(globals, oldlen, globals.len(), &mut looktable);
 
     for s in imports {
-        if memo.contains_key(&s) {
+        // The sdk libraries, like dart:async, are built in.
+        if memo.contains_key(&s) || s.starts_with("dart:") {
             continue;
         }
 
@@ -230,25 +250,30 @@ fn filecurse(
 
         let (childstart, childend) = memo[&s];
 
-        for i in childstart..childend {
-            let f = &globals[i];
-            match &f.nodetype {
-                NodeType::FunDef(funcname, _) => {
-                    looktable.insert(funcname.clone(), i);
-                }
-                NodeType::Constructor(name, _) => {
-                    looktable.insert(name.clone(), i);
-                }
-                _ => {
-                    panic!("Unexpected node type in globals");
-                }
-            }
-        }
+        index_globals(globals, childstart, childend, &mut looktable);
     }
 
     looktables.insert(filepath.clone(), looktable);
 }
 
+// Enter the functions, constructors and extensions in globals[start..end] into a looktable.
+fn index_globals(globals: &[Node], start: usize, end: usize, looktable: &mut HashMap<String, usize>) {
+    for (i, f) in globals.iter().enumerate().take(end).skip(start) {
+        match &f.nodetype {
+            NodeType::FunDef(funcname, _, _, _) => {
+                looktable.insert(funcname.clone(), i);
+            }
+            NodeType::Constructor(name, _) |
+            NodeType::Extension(name) => {
+                looktable.insert(name.clone(), i);
+            }
+            _ => {
+                panic!("Unexpected node type in globals");
+            }
+        }
+    }
+}
+
 fn evaluate(filepath:  impl AsRef<std::path::Path>, ctx: &mut Ctx) {
     let mut globals: Vec<Node> = Vec::new();
     let mut memo: HashMap<String, (usize, usize)> = HashMap::new();
@@ -298,6 +323,9 @@ fn evaluate(filepath:  impl AsRef<std::path::Path>, ctx: &mut Ctx) {
         ctx
     );
 
+    objsys.check_classes(ctx);
+    typecheck::check(&globals, &looktables, &objsys, ctx);
+
     let toptable = &looktables[filename];
 
     if !toptable.contains_key("main") {
@@ -310,7 +338,7 @@ fn evaluate(filepath:  impl AsRef<std::path::Path>, ctx: &mut Ctx) {
     ctx.filepath = filename.to_string();
 
     match &mainfunc.nodetype {
-        NodeType::FunDef(_, _) => {
+        NodeType::FunDef(..) => {
             utils::dprint(" ");
             utils::dprint("EVALUATE");
             utils::dprint(" ");
@@ -318,8 +346,16 @@ fn evaluate(filepath:  impl AsRef<std::path::Path>, ctx: &mut Ctx) {
             let mainbody = &mainfunc.children[1];
 
             store.push_call();
-            evaluator::eval(mainbody, &looktables, &globals, &mut store, &mut objsys, ctx);
+            ctx.calls.push((String::from("main"), filename.to_string()));
+            let result = evaluator::eval(mainbody, &looktables, &globals, &mut store, &mut objsys, ctx);
+            if let Object::Throw(_, _) = result {
+                evaluator::unhandled(&result, &objsys, ctx);
+            }
+            ctx.calls.pop();
             store.pop_call();
+
+            // What main left for later, like timers and the rest of async functions.
+            future::run_event_loop(&looktables, &globals, &mut store, &mut objsys, ctx);
         }
         x => { panic!("Unexpected type of 'main': {:?}", x) }
     }
@@ -0,0 +1,187 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::context::*;
+use crate::iterable::Lazy;
+use crate::object::*;
+use crate::utils::dart_evalerror;
+
+
+// The members maps have, which extensions on Map can't replace.
+pub const MEMBERS: [&str; 10] = ["length", "isEmpty", "isNotEmpty", "keys", "values", "entries", "containsKey", "containsValue", "remove", "forEach"];
+
+
+// The members of Map<K, V> as declared in dart, for the checker.
+pub const SIGNATURES: [&str; 11] = [
+    "int get length", "bool get isEmpty", "bool get isNotEmpty",
+    "Iterable<K> get keys", "Iterable<V> get values", "Iterable<MapEntry<K, V>> get entries",
+    "V? operator [](Object? key)", "void operator []=(K key, V value)",
+    "bool containsKey(Object? key)", "bool containsValue(Object? value)", "V? remove(Object? key)"
+];
+
+
+// Hashable stand-in for an Object used as a map key.
+//
+// For builtin values two keys are equal exactly when the objects are '==', so
+// integral doubles hash as ints (1 == 1.0 in Dart), records by their fields,
+// durations and types by value, and lists, maps, instances and the rest by identity.
+// An instance of a class with a user defined '==' hashes by its hashCode, or,
+// without one, with all instances of the class that defines '=='. Those are
+// compared by that '==' with the keys of the same hash, see evaluator::find_entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    Int(i64),
+    Double(u64),
+    String(String),
+    Bool(bool),
+    Null,
+    Reference(String),
+    Pointer(usize),
+    Duration(i64),
+    Type(String),
+    // The positional fields, and the named ones sorted by name.
+    Record(Vec<MapKey>, Vec<(String, MapKey)>),
+    Hash(i64),
+    Equality(String),
+}
+
+
+impl MapKey {
+
+    pub fn from(obj: &Object, ctx: &Ctx) -> MapKey {
+        match obj {
+            Object::Int(n) => MapKey::Int(*n),
+            Object::Double(x) => {
+                if x.fract() == 0.0 && x.abs() < i64::MAX as f64 {
+                    MapKey::Int(*x as i64)
+                }
+                else {
+                    MapKey::Double(x.to_bits())
+                }
+            }
+            Object::String(s) => MapKey::String(s.clone()),
+            Object::Bool(b) => MapKey::Bool(*b),
+            Object::Null => MapKey::Null,
+            Object::Reference(id) => MapKey::Reference(id.clone()),
+            Object::List(items, _) => MapKey::Pointer(Rc::as_ptr(items) as usize),
+            Object::Map(map, _, _) => MapKey::Pointer(Rc::as_ptr(map) as usize),
+            Object::Set(set, _) => MapKey::Pointer(Rc::as_ptr(set) as usize),
+            Object::Iterable(lazy, _) => MapKey::Pointer(Rc::as_ptr(lazy) as usize),
+            Object::Iterator(iteration, _) => MapKey::Pointer(Rc::as_ptr(iteration) as usize),
+            Object::Exception(e) => MapKey::Pointer(Rc::as_ptr(e) as usize),
+            Object::Future(future, _) => MapKey::Pointer(Rc::as_ptr(future) as usize),
+            Object::Stream(stream, _) |
+            Object::StreamController(stream, _) => MapKey::Pointer(Rc::as_ptr(stream) as usize),
+            Object::StreamSubscription(subscription) => MapKey::Pointer(Rc::as_ptr(subscription) as usize),
+            Object::Duration(micros) => MapKey::Duration(*micros),
+            Object::Type(t) => MapKey::Type(t.clone()),
+            Object::Record(positional, named) => MapKey::Record(
+                positional.iter().map(|v| MapKey::from(v, ctx)).collect(),
+                named.iter().map(|(name, v)| (name.clone(), MapKey::from(v, ctx))).collect()
+            ),
+            x => dart_evalerror(format!("A value of type '{}' can't be used as a map key.", x), ctx)
+        }
+    }
+
+
+    // Whether all keys of this hash key are equal, so that they need no comparing by '=='.
+    pub fn is_exact(&self) -> bool {
+        match self {
+            MapKey::Hash(_) | MapKey::Equality(_) => false,
+            MapKey::Record(positional, named) => positional.iter().all(MapKey::is_exact) && named.iter().all(|(_, k)| k.is_exact()),
+            _ => true
+        }
+    }
+}
+
+
+// Insertion ordered, like Dart's default LinkedHashMap.
+// The index has the positions of the entries by the hash keys of their keys.
+#[derive(Debug, Default)]
+pub struct DartMap {
+    entries: Vec<(Object, Object)>,
+    index: HashMap<MapKey, Vec<usize>>,
+}
+
+
+impl DartMap {
+
+    pub fn new() -> DartMap {
+        DartMap::default()
+    }
+
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+
+    pub fn entries(&self) -> &Vec<(Object, Object)> {
+        &self.entries
+    }
+
+
+    // The positions of the entries whose keys have the hash key.
+    pub fn bucket(&self, hash: &MapKey) -> &[usize] {
+        self.index.get(hash).map(|bucket| bucket.as_slice()).unwrap_or(&[])
+    }
+
+
+    // Overwriting keeps the original position and key, as in Dart.
+    pub fn set_value(&mut self, i: usize, value: Object) {
+        self.entries[i].1 = value;
+    }
+
+
+    pub fn push(&mut self, hash: MapKey, key: Object, value: Object) {
+        self.index.entry(hash).or_default().push(self.entries.len());
+        self.entries.push((key, value));
+    }
+
+
+    pub fn remove_at(&mut self, hash: &MapKey, i: usize) -> Object {
+        if let Some(bucket) = self.index.get_mut(hash) {
+            bucket.retain(|pos| *pos != i);
+            if bucket.is_empty() {
+                self.index.remove(hash);
+            }
+        }
+        let (_, value) = self.entries.remove(i);
+
+        // Everything after the removed entry moved one step down.
+        for pos in self.index.values_mut().flatten() {
+            if *pos > i {
+                *pos -= 1;
+            }
+        }
+        value
+    }
+}
+
+
+pub fn get_property(map: &Rc<RefCell<DartMap>>, keytype: &str, valuetype: &str, name: &str, ctx: &Ctx) -> Object {
+    let whole = Object::Map(map.clone(), String::from(keytype), String::from(valuetype));
+    let map = map.borrow();
+
+    match name {
+        "length" => Object::Int(map.len() as i64),
+        "isEmpty" => Object::Bool(map.len() == 0),
+        "isNotEmpty" => Object::Bool(map.len() > 0),
+        // Views that see the map change, as in dart.
+        "keys" => Object::Iterable(Rc::new(Lazy::Keys(whole)), String::from(keytype)),
+        "values" => Object::Iterable(Rc::new(Lazy::Values(whole)), String::from(valuetype)),
+        "entries" => Object::Iterable(Rc::new(Lazy::Entries(whole)), format!("MapEntry<{}, {}>", keytype, valuetype)),
+        // As dart.
+        _ => dart_evalerror(format!("The getter '{}' isn't defined for the class 'Map'.", name), ctx)
+    }
+}
+
+
+pub fn get_entry_property(key: &Object, value: &Object, name: &str, ctx: &Ctx) -> Object {
+    match name {
+        "key" => key.clone(),
+        "value" => value.clone(),
+        _ => dart_evalerror(format!("The getter '{}' isn't defined for the class 'MapEntry'.", name), ctx)
+    }
+}
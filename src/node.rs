@@ -8,6 +8,7 @@ pub enum NodeType {
     Sub,
     Mul,
     Div,
+    IntDiv,
     PreIncrement,
     PostIncrement,
     PreDecrement,
@@ -23,6 +24,7 @@ pub enum NodeType {
     LessOrEq,
     GreaterOrEq,
     Equal,
+    Is(String),
     Assign,
     Int(i64),
     Double(f64),
@@ -30,6 +32,8 @@ pub enum NodeType {
     Bool(bool),
     Name(String),
     TypedVar(String, String),
+    Final(String), // 'final' or 'const', of the declaration it holds
+    Const, // with the const expression as child, canonicalized when evaluated
     Conditional,
     If,
     ElseIf,
@@ -37,16 +41,62 @@ pub enum NodeType {
     While,
     DoWhile,
     For,
+    ForIn(bool), // final; the loop variable or pattern, the iterable and the body
     Block,
-    List,
-    FunDef(String, String), // funcname, filename
+    Scope,
+    List(Vec<String>), // type arguments, if written
+    Map(Vec<String>), // type arguments, if written
+    Set(Vec<String>), // type arguments, if written
+    MapEntry,
+    Spread(bool), // '...?'; with the spread collection as child
+    IfElement, // the condition, the element, and the else element if there is one
+    Index,
+    FunDef(String, String, String, Vec<String>), // funcname, filename, return type, type parameters
+    FunLit(String), // filename
     FunCall(String),
-    MethodCall(String, Box<Node>, String),  // methodname, owner, filename
+    MethodCall(String, Box<Node>),  // methodname, owner
     ParamList,
+    OptionalParam, // the parameter, and its default value if it has one
+    NamedParam(bool), // required; with the same children
     ArgList,
+    NamedArg(String), // name, with the value as child
     ThisFieldInit(String),
+    EnumValue(usize, String), // index, name; with the constructor call as child
+    This,
+    Super,
+    SuperCall,
     Return,
+    Break(Option<String>),
+    Continue(Option<String>),
+    Label(String),
+    IfNull,
+    IfNullAssign,
+    NullCheck,
+    NullAware,
+    Cascade(bool), // '?..'; with the receiver, then the sections on the variable '..', as children
+    Switch,
+    SwitchExpr,
+    Case,
+    ConstPattern,
+    VarPattern(String, String), // type, name
+    RelPattern(String), // operator
+    OrPattern,
+    AndPattern,
+    Record, // fields, the named ones as NamedArg
+    RecordPattern, // the same, with patterns
+    Throw,
+    Rethrow,
+    Try,
+    Catch(Option<String>, Option<String>, Option<String>), // on type, exception name, stack trace name
+    Finally,
     Constructor(String, String), // consname, filename
+    Async(String), // the type of the value of the Future it gives; with the body as child
+    Await,
+    AsyncStar(String), // the type of the elements of the Stream it gives; with the body as child
+    AwaitFor(bool), // as ForIn, over the events of a Stream
+    Yield(bool), // 'yield*'; with the value, or the stream or iterable, as child
+    SyncStar(String), // the type of the elements of the Iterable it gives; with the body as child
+    Extension(String), // name of the class of its members
     Null,
 }
 
@@ -60,6 +110,7 @@ impl fmt::Display for NodeType {
             NodeType::Sub => write!(f, "-"),
             NodeType::Mul => write!(f, "*"),
             NodeType::Div => write!(f, "/"),
+            NodeType::IntDiv => write!(f, "~/"),
             NodeType::PreIncrement |
             NodeType::PostIncrement => write!(f, "++"),
             NodeType::PreDecrement |
@@ -75,18 +126,32 @@ impl fmt::Display for NodeType {
             NodeType::LessOrEq => write!(f, "<="),
             NodeType::GreaterOrEq => write!(f, ">="),
             NodeType::Equal => write!(f, "=="),
+            NodeType::Is(t) => write!(f, "is {}", t),
             NodeType::Int(s)                        => write!(f, "{}", s),
             NodeType::Double(s)                     => write!(f, "{}", s),
             NodeType::Str(s)                        => write!(f, "\"{}\"", s),
             NodeType::Bool(v)                        => write!(f, "{}", v),
             NodeType::Name(s)                       => write!(f, "{}", s),
             NodeType::TypedVar(tp, name)  => write!(f, "{}:{}", name, tp),
-            NodeType::FunDef(s, _filename)                     => write!(f, "{}() {{}}", s),
+            NodeType::Final(keyword) => write!(f, "{}", keyword),
+            NodeType::Const => write!(f, "const"),
+            NodeType::FunDef(s, _filename, _, _)                  => write!(f, "{}() {{}}", s),
+            NodeType::FunLit(_filename)                     => write!(f, "() {{}}"),
             NodeType::FunCall(s)                    => write!(f, "{}()", s),
-            NodeType::MethodCall(name, owner, _) => write!(f, "{}.{}()", name, owner),
-            NodeType::List => write!(f, "[]"),
+            NodeType::MethodCall(name, owner) => write!(f, "{}.{}()", name, owner),
+            NodeType::List(_) => write!(f, "[]"),
+            NodeType::Map(_) => write!(f, "{{}}"),
+            NodeType::Set(_) => write!(f, "Set"),
+            NodeType::MapEntry => write!(f, "MapEntry"),
+            NodeType::Spread(nullaware) => write!(f, "{}", if *nullaware { "...?" } else { "..." }),
+            NodeType::IfElement => write!(f, "IfElement"),
+            NodeType::Index => write!(f, "Index"),
             NodeType::ParamList => write!(f, "ParamList"),
+            NodeType::OptionalParam => write!(f, "[]"),
+            NodeType::NamedParam(required) => write!(f, "{{{}}}", if *required { "required" } else { "" }),
             NodeType::ArgList => write!(f, "ArgList"),
+            NodeType::EnumValue(index, name) => write!(f, "EnumValue({}, {})", index, name),
+            NodeType::NamedArg(name) => write!(f, "{}:", name),
             NodeType::Conditional => write!(f, "Conditional"),
             NodeType::If => write!(f, "If"),
             NodeType::ElseIf => write!(f, "ElseIf"),
@@ -94,10 +159,45 @@ impl fmt::Display for NodeType {
             NodeType::While => write!(f, "While"),
             NodeType::DoWhile => write!(f, "DoWhile"),
             NodeType::For => write!(f, "For"),
+            NodeType::ForIn(_) => write!(f, "ForIn"),
             NodeType::Block => write!(f, "Block"),
+            NodeType::Scope => write!(f, "Scope"),
             NodeType::ThisFieldInit(s) => write!(f, "this.{}", s),
+            NodeType::This => write!(f, "this"),
+            NodeType::Super => write!(f, "super"),
+            NodeType::SuperCall => write!(f, "super()"),
             NodeType::Return => write!(f, "Return"),
+            NodeType::Break(label) => write!(f, "Break({:?})", label),
+            NodeType::Continue(label) => write!(f, "Continue({:?})", label),
+            NodeType::Label(label) => write!(f, "{}:", label),
+            NodeType::IfNull => write!(f, "??"),
+            NodeType::IfNullAssign => write!(f, "??="),
+            NodeType::NullCheck => write!(f, "!"),
+            NodeType::NullAware => write!(f, "?."),
+            NodeType::Cascade(nullaware) => write!(f, "{}", if *nullaware { "?.." } else { ".." }),
+            NodeType::Switch => write!(f, "Switch"),
+            NodeType::SwitchExpr => write!(f, "SwitchExpr"),
+            NodeType::Case => write!(f, "Case"),
+            NodeType::ConstPattern => write!(f, "ConstPattern"),
+            NodeType::VarPattern(tp, name) => write!(f, "{} {}", tp, name),
+            NodeType::RelPattern(op) => write!(f, "{} _", op),
+            NodeType::OrPattern => write!(f, "OrPattern"),
+            NodeType::AndPattern => write!(f, "AndPattern"),
+            NodeType::Record => write!(f, "Record"),
+            NodeType::RecordPattern => write!(f, "RecordPattern"),
+            NodeType::Throw => write!(f, "Throw"),
+            NodeType::Rethrow => write!(f, "Rethrow"),
+            NodeType::Try => write!(f, "Try"),
+            NodeType::Catch(t, e, st) => write!(f, "Catch({:?}, {:?}, {:?})", t, e, st),
+            NodeType::Finally => write!(f, "Finally"),
             NodeType::Constructor(name, _filename) => write!(f, "Constructor({})", name),
+            NodeType::Async(_) => write!(f, "async"),
+            NodeType::Await => write!(f, "await"),
+            NodeType::AsyncStar(_) => write!(f, "async*"),
+            NodeType::AwaitFor(_) => write!(f, "AwaitFor"),
+            NodeType::Yield(star) => write!(f, "{}", if *star { "yield*" } else { "yield" }),
+            NodeType::SyncStar(_) => write!(f, "sync*"),
+            NodeType::Extension(name) => write!(f, "Extension({})", name),
             NodeType::Null => write!(f, "null"),
         }
     }
@@ -108,7 +208,9 @@ impl fmt::Display for NodeType {
 #[derive(Clone)]
 pub struct Node {
     pub nodetype: NodeType,
-    pub children: Vec<Node>
+    pub children: Vec<Node>,
+    // Line and column of the source, where the parser records it. For static errors.
+    pub pos: (usize, usize)
 }
 
 
@@ -118,6 +220,7 @@ impl Node {
         Node {
             nodetype,
             children: Vec::new(),
+            pos: (0, 0),
         }
     }
 
@@ -1,5 +1,13 @@
 use super::*;
+use crate::exception::DartException;
+use crate::future::Future;
+use crate::iterable::{Iteration, Lazy};
+use crate::map::DartMap;
+use crate::stack::Env;
+use crate::stream::{Stream, Subscription};
+use std::cell::RefCell;
 use std::fmt;
+use std::rc::Rc;
 
 
 #[derive(Debug)]
@@ -8,6 +16,18 @@ pub struct ParamObj {
     pub typ: String,
     pub name: String,
     pub fieldinit: bool,
+    pub kind: ParamKind,
+    pub default: Option<Node>
+}
+
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum ParamKind {
+    Positional,
+    Optional,
+    Named(bool) // required
 }
 
 
@@ -20,6 +40,32 @@ impl fmt::Display for ParamObj {
 }
 
 
+impl ParamObj {
+
+    // From a node of a parameter list.
+    pub fn from_node(node: &Node) -> ParamObj {
+        let (typ, name, fieldinit) = match &node.nodetype {
+            NodeType::Name(s) => (String::from("var"), s.clone(), false),
+            NodeType::TypedVar(t, s) => (t.clone(), s.clone(), false),
+            // Typed by the field it sets.
+            NodeType::ThisFieldInit(s) => (String::new(), s.clone(), true),
+            NodeType::OptionalParam |
+            NodeType::NamedParam(_) => {
+                let mut param = ParamObj::from_node(&node.children[0]);
+                param.kind = match node.nodetype {
+                    NodeType::NamedParam(required) => ParamKind::Named(required),
+                    _ => ParamKind::Optional
+                };
+                param.default = node.children.get(1).cloned();
+                return param;
+            }
+            x => panic!("Invalid parameter: {}", x)
+        };
+        ParamObj { typ, name, fieldinit, kind: ParamKind::Positional, default: None }
+    }
+}
+
+
 #[derive(Debug)]
 #[derive(Clone)]
 pub enum Object {
@@ -27,12 +73,43 @@ pub enum Object {
     Double(f64),
     Bool(bool),
     String(String),
-    // funcname, filename, body, params
-    Function(String, String, Node, Vec<ParamObj>),    // funcname, filename, body, params
+    // funcname, filename, body, params, captured environment
+    Function(String, String, Node, Vec<ParamObj>, Env),
     Constructor(String, String, Node, Vec<ParamObj>), // consname, filename, body, params
     Reference(String),
+    // Shared and mutable, like any Dart object. With their reified type arguments.
+    List(Rc<RefCell<Vec<Object>>>, String),
+    Map(Rc<RefCell<DartMap>>, String, String),
+    MapEntry(Box<Object>, Box<Object>),
+    // The elements are the keys of the map, in insertion order.
+    Set(Rc<RefCell<DartMap>>, String),
+    // What the lazy Iterable methods give, with its element type. See iterable.rs.
+    Iterable(Rc<Lazy>, String),
+    // What the iterator getter of a list, set or Iterable gives, with its element type.
+    Iterator(Rc<RefCell<Iteration>>, String),
+    // The positional fields, and the named ones sorted by name. Shared, as identical tells records apart.
+    Record(Rc<Vec<Object>>, Rc<Vec<(String, Object)>>),
+    // The exceptions and errors of dart:core.
+    Exception(Rc<DartException>),
+    // A type used as a value, like 'int' or what runtimeType gives.
+    Type(String),
+    // Shared, so that whoever has it sees it complete. With the type of its value.
+    Future(Future, String),
+    // In microseconds.
+    Duration(i64),
+    // A stream and the controller of one share it. With the type of its elements.
+    Stream(Stream, String),
+    StreamController(Stream, String),
+    StreamSubscription(Subscription),
     Null,
-    Return(Box<Object>)
+    Return(Box<Object>),
+    // Loop control on its way to the loop or labeled statement it targets.
+    Break(Option<String>),
+    Continue(Option<String>),
+    // The thrown object and the stack trace from where it was thrown.
+    Throw(Box<Object>, String),
+    // An argument given by name, on its way to the parameter it binds.
+    NamedArg(String, Box<Object>)
 }
 
 
@@ -45,7 +122,7 @@ impl fmt::Display for Object {
             Object::Double(x) => write!(f, "{}", x),
             Object::Bool(b) => write!(f, "{}", b),
             Object::String(s) => write!(f, "{}", s),
-            Object::Function(_, _, _, _) => {
+            Object::Function(_, _, _, _, _) => {
                 // Dart prints a function signature, like: (int) => String.
                 // But since the function will turn into a closure, it really prints
                 // Closure: (int) => String
@@ -57,11 +134,129 @@ impl fmt::Display for Object {
                 write!(f, "() => ?")
             },
             Object::Reference(_) => {
-                // TODO, need lookup, dont have access.
+                // Its toString needs the object system, see evaluator::stringify.
                 write!(f, "Reference")
             },
+            Object::List(items, _) => {
+                write!(f, "[")?;
+                for (i, item) in items.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Object::Map(map, _, _) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in map.borrow().entries().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", k, v)?;
+                }
+                write!(f, "}}")
+            },
+            Object::MapEntry(k, v) => write!(f, "MapEntry({}: {})", k, v),
+            Object::Set(set, _) => {
+                let items: Vec<String> = set.borrow().entries().iter().map(|(k, _)| k.to_string()).collect();
+                write!(f, "{{{}}}", items.join(", "))
+            },
+            // Its elements are only known when evaluated, see evaluator::stringify.
+            Object::Iterable(_, elemtype) => write!(f, "Instance of 'Iterable<{}>'", elemtype),
+            Object::Iterator(_, elemtype) => write!(f, "Instance of 'Iterator<{}>'", elemtype),
+            Object::Record(positional, named) => {
+                let fields: Vec<String> = positional.iter().map(|v| v.to_string())
+                    .chain(named.iter().map(|(name, v)| format!("{}: {}", name, v)))
+                    .collect();
+                // As dart, a record of one positional field: '(1,)'.
+                match (positional.len(), named.len()) {
+                    (1, 0) => write!(f, "({},)", fields[0]),
+                    _ => write!(f, "({})", fields.join(", "))
+                }
+            },
+            Object::Exception(e) => write!(f, "{}", e),
+            Object::Type(t) => write!(f, "{}", t),
+            Object::Future(_, valuetype) => write!(f, "Instance of 'Future<{}>'", valuetype),
+            Object::Duration(micros) => write!(f, "{}", crate::duration::to_string(*micros)),
+            Object::Stream(_, elemtype) => write!(f, "Instance of 'Stream<{}>'", elemtype),
+            Object::StreamController(_, elemtype) => write!(f, "Instance of 'StreamController<{}>'", elemtype),
+            Object::StreamSubscription(_) => write!(f, "Instance of 'StreamSubscription<dynamic>'"),
             Object::Null => write!(f, "null"),
-            Object::Return(_) => panic!("Tried to display Return Object")
+            Object::Return(_) => panic!("Tried to display Return Object"),
+            Object::Break(_) |
+            Object::Continue(_) => panic!("Tried to display loop control Object"),
+            Object::Throw(_, _) => panic!("Tried to display Throw Object"),
+            Object::NamedArg(_, _) => panic!("Tried to display NamedArg Object")
+        }
+    }
+}
+
+
+impl Object {
+
+    pub fn new_list(items: Vec<Object>, elemtype: &str) -> Object {
+        Object::List(Rc::new(RefCell::new(items)), String::from(elemtype))
+    }
+
+
+    pub fn new_map(map: DartMap, keytype: &str, valuetype: &str) -> Object {
+        Object::Map(Rc::new(RefCell::new(map)), String::from(keytype), String::from(valuetype))
+    }
+
+
+    pub fn new_set(set: DartMap, elemtype: &str) -> Object {
+        Object::Set(Rc::new(RefCell::new(set)), String::from(elemtype))
+    }
+
+
+    // Dart identical: numbers by their type and value, records by identity, as for instances,
+    // lists and the rest that equals already compares so.
+    pub fn identical(&self, other: &Object) -> bool {
+        match (self, other) {
+            (Object::Int(_), Object::Double(_)) |
+            (Object::Double(_), Object::Int(_)) => false,
+            // As dart, so that NaN is identical to itself, and 0.0 is not to -0.0.
+            (Object::Double(x1), Object::Double(x2)) => x1.to_bits() == x2.to_bits(),
+            // There is only one empty record.
+            (Object::Record(p1, n1), Object::Record(p2, n2)) => {
+                (Rc::ptr_eq(p1, p2) && Rc::ptr_eq(n1, n2)) || (p1.is_empty() && n1.is_empty() && p2.is_empty() && n2.is_empty())
+            }
+            _ => self.equals(other)
+        }
+    }
+
+
+    // Dart '==' for objects without a user defined operator.
+    pub fn equals(&self, other: &Object) -> bool {
+        match (self, other) {
+            (Object::Int(n1), Object::Int(n2)) => n1 == n2,
+            (Object::Int(n1), Object::Double(x2)) => (*n1 as f64) == *x2,
+            (Object::Double(x1), Object::Int(n2)) => *x1 == (*n2 as f64),
+            (Object::Double(x1), Object::Double(x2)) => x1 == x2,
+            (Object::Bool(b1), Object::Bool(b2)) => b1 == b2,
+            (Object::String(s1), Object::String(s2)) => s1 == s2,
+            (Object::Reference(r1), Object::Reference(r2)) => r1 == r2,
+            (Object::List(l1, _), Object::List(l2, _)) => Rc::ptr_eq(l1, l2),
+            (Object::Map(m1, _, _), Object::Map(m2, _, _)) => Rc::ptr_eq(m1, m2),
+            (Object::Set(s1, _), Object::Set(s2, _)) => Rc::ptr_eq(s1, s2),
+            (Object::Iterable(i1, _), Object::Iterable(i2, _)) => Rc::ptr_eq(i1, i2),
+            (Object::Iterator(i1, _), Object::Iterator(i2, _)) => Rc::ptr_eq(i1, i2),
+            (Object::MapEntry(k1, v1), Object::MapEntry(k2, v2)) => k1.equals(k2) && v1.equals(v2),
+            (Object::Record(p1, n1), Object::Record(p2, n2)) => {
+                p1.len() == p2.len() && n1.len() == n2.len()
+                    && p1.iter().zip(p2.iter()).all(|(v1, v2)| v1.equals(v2))
+                    && n1.iter().zip(n2.iter()).all(|((k1, v1), (k2, v2))| k1 == k2 && v1.equals(v2))
+            }
+            (Object::Exception(e1), Object::Exception(e2)) => Rc::ptr_eq(e1, e2),
+            (Object::Type(t1), Object::Type(t2)) => t1 == t2,
+            (Object::Future(f1, _), Object::Future(f2, _)) => Rc::ptr_eq(f1, f2),
+            (Object::Duration(d1), Object::Duration(d2)) => d1 == d2,
+            (Object::Stream(s1, _), Object::Stream(s2, _)) |
+            (Object::StreamController(s1, _), Object::StreamController(s2, _)) => Rc::ptr_eq(s1, s2),
+            (Object::StreamSubscription(s1), Object::StreamSubscription(s2)) => Rc::ptr_eq(s1, s2),
+            (Object::Null, Object::Null) => true,
+            _ => false
         }
     }
 }
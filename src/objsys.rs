@@ -1,5 +1,6 @@
-use std::collections::HashMap;
-use utils::dprint;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use utils::{dart_error_at, dprint};
 
 use crate::object::*;
 use super::*;
@@ -7,6 +8,8 @@ use super::*;
 pub struct Instance {
     pub id: String,
     pub classname: String,
+    // Reified, one for each type parameter of the class.
+    pub typeargs: Vec<String>,
     pub fields: HashMap<String, Object>
 }
 
@@ -17,6 +20,7 @@ impl Instance {
         Instance {
             id,
             classname,
+            typeargs: Vec::new(),
             fields: HashMap::new()
         }
     }
@@ -33,14 +37,50 @@ impl Instance {
     pub fn has_field(&self, name: String) -> bool {
         self.fields.contains_key(name.as_str())
     }
+
+    // As runtimeType gives it, like 'Box<int>'.
+    pub fn runtime_type(&self) -> String {
+        types::join(&self.classname, &self.typeargs)
+    }
 }
 
 
 pub struct Class {
     pub name: String,
     pub classid: String,
-    pub fields: Vec<(String, String, Node)>,
-    pub methods: HashMap<String, Object>
+    pub parent: Option<String>,
+    // 'T' for 'class Box<T>'.
+    pub typeparams: Vec<String>,
+    // 'int' for 'extends Base<int>', in terms of typeparams.
+    pub parent_typeargs: Vec<String>,
+    // In the order of the with clause.
+    pub mixins: Vec<String>,
+    pub interfaces: Vec<String>,
+    pub is_abstract: bool,
+    pub is_mixin: bool,
+    pub is_enum: bool,
+    // The type an extension adds its members to. Extensions are kept as classes of those members.
+    pub on: Option<String>,
+    pub fields: Vec<(String, String, Option<Node>)>,
+    pub methods: HashMap<String, Object>,
+    // The methods that are getters.
+    pub getters: Vec<String>,
+    // Declared return types of the methods, abstract ones included.
+    pub returntypes: HashMap<String, String>,
+    // Declared without a body.
+    pub abstract_methods: Vec<String>,
+    // Members of the class itself rather than its instances. A static field
+    // gets its value in statics when it is first used.
+    pub static_fields: Vec<(String, String, Option<Node>)>,
+    pub static_methods: HashMap<String, Object>,
+    pub statics: HashMap<String, Object>,
+    // Fields declared final or const, static or not.
+    pub finals: Vec<String>,
+    // Fields declared late, which are not set when an instance is constructed.
+    pub lates: Vec<String>,
+    // Where the class name was declared, for errors found after parsing.
+    pub filepath: String,
+    pub pos: (usize, usize)
 }
 
 
@@ -51,13 +91,33 @@ impl Class {
         Class {
             name,
             classid:  nuid::next().to_string(),
+            parent: None,
+            typeparams: Vec::new(),
+            parent_typeargs: Vec::new(),
+            mixins: Vec::new(),
+            interfaces: Vec::new(),
+            is_abstract: false,
+            is_mixin: false,
+            is_enum: false,
+            on: None,
             fields: Vec::new(),
-            methods: HashMap::new()
+            returntypes: HashMap::new(),
+            methods: HashMap::new(),
+            getters: Vec::new(),
+            abstract_methods: Vec::new(),
+            static_fields: Vec::new(),
+            static_methods: HashMap::new(),
+            statics: HashMap::new(),
+            finals: Vec::new(),
+            lates: Vec::new(),
+            filepath: String::new(),
+            pos: (0, 0)
         }
     }
 
 
-    pub fn add_field(&mut self, ftype: String, fname: String, initexpr: Node) {
+    // A field declared without initializer starts out null.
+    pub fn add_field(&mut self, ftype: String, fname: String, initexpr: Option<Node>) {
         self.fields.push((ftype.clone(), fname.clone(), initexpr));
         dprint(format!("Inserted to fieldtable: {}", fname));
     }
@@ -69,12 +129,9 @@ impl Class {
     }
 
 
-    pub fn get_method(&self, methname: &str) -> Object {
-
-        if let Object::Function(_, _, _, _) = &self.methods[methname] {
-            return self.methods[methname].clone();
-        }
-        panic!("No such method")
+    // Only methods declared in this class, see ObjSys::find_method for inherited ones.
+    pub fn get_method(&self, methname: &str) -> Option<Object> {
+        self.methods.get(methname).cloned()
     }
 
 
@@ -84,21 +141,44 @@ impl Class {
 }
 
 
+fn address(value: &Object) -> Option<usize> {
+    match value {
+        Object::List(items, _) => Some(Rc::as_ptr(items) as usize),
+        Object::Map(entries, _, _) |
+        Object::Set(entries, _) => Some(Rc::as_ptr(entries) as usize),
+        _ => None
+    }
+}
+
+
 pub struct ObjSys {
     classmap: HashMap<String, Class>,
     instancemap: HashMap<String, Instance>,
-    this: String
+    this: String,
+    // The class declaring the running method or constructor, which 'super' is relative to.
+    this_class: String,
+    // The values of the const expressions evaluated so far, by their canonical form.
+    consts: HashMap<String, Object>,
+    // The lists, maps and sets in those, by address. They can't be modified.
+    unmodifiable: HashSet<usize>
 }
 
 
 impl ObjSys {
 
     pub fn new() -> ObjSys {
-        ObjSys {
+        let mut objsys = ObjSys {
             classmap: HashMap::new(),
             instancemap: HashMap::new(),
-            this: String::from("")
+            this: String::from(""),
+            this_class: String::from(""),
+            consts: HashMap::new(),
+            unmodifiable: HashSet::new()
+        };
+        for class in core_classes() {
+            objsys.register_class(class);
         }
+        objsys
     }
 
 
@@ -117,6 +197,318 @@ impl ObjSys {
     }
 
 
+    pub fn has_class(&self, name: &str) -> bool {
+        self.classmap.contains_key(name)
+    }
+
+
+    // The order methods are looked up in, as dart.
+    // For 'class C extends S with M1, M2' that is C, M2, M1, then the same for S.
+    pub fn linearize(&self, classname: &str) -> Vec<String> {
+        let mut order = Vec::new();
+        let mut next = Some(String::from(classname));
+
+        while let Some(name) = next {
+            // Guard against cycles in erroneous programs.
+            if order.contains(&name) {
+                break;
+            }
+            let class = match self.classmap.get(&name) {
+                Some(class) => class,
+                None => break
+            };
+            order.push(name);
+            for m in class.mixins.iter().rev() {
+                if !order.contains(m) {
+                    order.push(m.clone());
+                }
+            }
+            next = class.parent.clone();
+        }
+        order
+    }
+
+
+    pub fn is_getter(&self, classname: &str, name: &str) -> bool {
+        self.find_method(classname, name).is_some_and(|(declaring, _)| self.classmap[&declaring].getters.iter().any(|g| g == name))
+    }
+
+
+    // A field declared final or const in classname or a class it inherits from.
+    pub fn is_final(&self, classname: &str, name: &str) -> bool {
+        self.linearize(classname).iter().any(|c| self.classmap[c].finals.iter().any(|f| f == name))
+    }
+
+
+    // A late field: the class declaring it and its initializer.
+    pub fn find_late(&self, classname: &str, name: &str) -> Option<(String, Option<Node>)> {
+        self.linearize(classname).into_iter().find_map(|c| {
+            let class = &self.classmap[&c];
+            if !class.lates.iter().any(|f| f == name) {
+                return None;
+            }
+            let initexpr = class.fields.iter().find(|(_, f, _)| f == name).and_then(|(_, _, init)| init.clone());
+            Some((c, initexpr))
+        })
+    }
+
+
+    // Static methods are not inherited.
+    pub fn find_static_method(&self, classname: &str, methname: &str) -> Option<Object> {
+        self.classmap.get(classname).and_then(|class| class.static_methods.get(methname).cloned())
+    }
+
+
+    pub fn set_static(&mut self, classname: &str, name: &str, value: Object) {
+        self.classmap.get_mut(classname).unwrap().statics.insert(String::from(name), value);
+    }
+
+
+    pub fn get_const(&self, key: &str) -> Option<Object> {
+        self.consts.get(key).cloned()
+    }
+
+
+    pub fn add_const(&mut self, key: &str, value: Object) {
+        self.freeze(&value);
+        self.consts.insert(String::from(key), value);
+    }
+
+
+    // Makes the collections in a const value unmodifiable. The const table keeps them, and their addresses, alive.
+    fn freeze(&mut self, value: &Object) {
+        if let Some(address) = address(value) {
+            self.unmodifiable.insert(address);
+        }
+        match value {
+            Object::List(items, _) => items.borrow().iter().for_each(|item| self.freeze(item)),
+            Object::Map(entries, _, _) |
+            Object::Set(entries, _) => entries.borrow().entries().iter().for_each(|(k, v)| {
+                self.freeze(k);
+                self.freeze(v);
+            }),
+            Object::Record(positional, named) => {
+                positional.iter().for_each(|v| self.freeze(v));
+                named.iter().for_each(|(_, v)| self.freeze(v));
+            }
+            _ => {}
+        }
+    }
+
+
+    pub fn is_unmodifiable(&self, value: &Object) -> bool {
+        address(value).is_some_and(|address| self.unmodifiable.contains(&address))
+    }
+
+
+    // Gives the method and the class declaring it.
+    pub fn find_method(&self, classname: &str, methname: &str) -> Option<(String, Object)> {
+        self.linearize(classname).into_iter().find_map(|name| {
+            self.classmap[&name].get_method(methname).map(|m| (name, m))
+        })
+    }
+
+
+    // Like find_method, but starting after the class 'from' in the lookup order of classname.
+    // This is what 'super.m()' means, also inside mixins.
+    pub fn find_super_method(&self, classname: &str, from: &str, methname: &str) -> Option<(String, Object)> {
+        self.linearize(classname).into_iter().skip_while(|name| name != from).skip(1).find_map(|name| {
+            self.classmap[&name].get_method(methname).map(|m| (name, m))
+        })
+    }
+
+
+    // Every class, mixin and interface classname is a subtype of, including itself.
+    pub fn supertypes(&self, classname: &str) -> Vec<String> {
+        let mut types: Vec<String> = Vec::new();
+        let mut todo = vec![String::from(classname)];
+
+        while let Some(name) = todo.pop() {
+            if types.contains(&name) || !self.classmap.contains_key(&name) {
+                continue;
+            }
+            let class = &self.classmap[&name];
+            todo.extend(class.interfaces.iter().rev().cloned());
+            todo.extend(class.mixins.iter().cloned());
+            todo.extend(class.parent.iter().cloned());
+            types.push(name);
+        }
+        types
+    }
+
+
+    pub fn is_subtype(&self, classname: &str, typename: &str) -> bool {
+        self.supertypes(classname).iter().any(|t| t == typename)
+    }
+
+
+    // The type arguments supername has in classname<typeargs>, following the extends clauses.
+    // Classes along the way that are mixed in or implemented get dynamic ones.
+    pub fn supertype_args(&self, classname: &str, typeargs: &[String], supername: &str) -> Vec<String> {
+        let mut name = String::from(classname);
+        let mut args = typeargs.to_vec();
+
+        while name != supername {
+            let class = match self.classmap.get(&name) {
+                Some(class) => class,
+                None => break
+            };
+            match &class.parent {
+                Some(parent) if self.is_subtype(parent, supername) => {
+                    args = class.parent_typeargs.iter().map(|t| types::substitute(t, &class.typeparams, &args)).collect();
+                    name = parent.clone();
+                }
+                _ => {
+                    args = Vec::new();
+                    break;
+                }
+            }
+        }
+
+        let count = self.classmap.get(supername).map(|c| c.typeparams.len()).unwrap_or(0);
+        args.resize(count, String::from("dynamic"));
+        args
+    }
+
+
+    // The field initializers classname runs itself: those of its mixins, then its own.
+    pub fn field_inits(&self, classname: &str) -> Vec<(String, String, Option<Node>)> {
+        let class = self.get_class(classname);
+        let mut fields = Vec::new();
+        for m in &class.mixins {
+            fields.extend(self.get_class(m).fields.iter().cloned());
+        }
+        fields.extend(class.fields.iter().cloned());
+        fields
+    }
+
+
+    // All classes, in the order they were declared.
+    pub fn classes(&self) -> Vec<&Class> {
+        let mut classes: Vec<&Class> = self.classmap.values().collect();
+        classes.sort_by(|a, b| (&a.filepath, a.pos).cmp(&(&b.filepath, b.pos)));
+        classes
+    }
+
+
+    pub fn check_classes(&self, ctx: &Ctx) {
+        for class in self.classes() {
+            self.check_class(&class.name, ctx);
+        }
+    }
+
+
+    // Whether classname extends, implements or mixes in itself, directly or through others.
+    fn is_cyclic(&self, classname: &str) -> bool {
+        let mut seen: Vec<&String> = Vec::new();
+        let mut todo: Vec<&String> = Vec::new();
+        let class = self.get_class(classname);
+        todo.extend(class.parent.iter().chain(&class.mixins).chain(&class.interfaces));
+
+        while let Some(name) = todo.pop() {
+            if name == classname {
+                return true;
+            }
+            if seen.contains(&name) || !self.has_class(name) {
+                continue;
+            }
+            seen.push(name);
+            let class = self.get_class(name);
+            todo.extend(class.parent.iter().chain(&class.mixins).chain(&class.interfaces));
+        }
+        false
+    }
+
+
+    // Errors dart gives at compile time for a class declaration.
+    pub fn check_class(&self, classname: &str, ctx: &Ctx) {
+        let class = self.get_class(classname);
+
+        if self.is_cyclic(classname) {
+            // As dart.
+            dart_error_at(format!("'{}' is a supertype of itself.", classname), &class.filepath, class.pos, ctx);
+        }
+
+        let mut referenced: Vec<&String> = class.parent.iter().collect();
+        referenced.extend(&class.mixins);
+        referenced.extend(&class.interfaces);
+        for name in referenced {
+            if !self.has_class(name) {
+                // As dart.
+                dart_error_at(format!("Type '{}' not found.", name), &class.filepath, class.pos, ctx);
+            }
+        }
+        for name in &class.mixins {
+            if !self.get_class(name).is_mixin {
+                dart_error_at(
+                    format!("The class '{}' can't be used as a mixin because it isn't a mixin class nor a mixin.", name),
+                    &class.filepath,
+                    class.pos,
+                    ctx
+                );
+            }
+        }
+
+        if class.is_abstract || class.is_mixin {
+            return;
+        }
+
+        // Anything inherited or mixed in is implemented, except what is abstract.
+        let linearized = self.linearize(classname);
+        let implemented = |member: &String| linearized.iter().any(|name| {
+            let c = self.get_class(name);
+            // A field implements both the getter and the setter.
+            c.methods.contains_key(member) || c.fields.iter().any(|(_, f, _)| f == member || format!("{}=", f) == *member)
+        });
+
+        // Abstract members along the way, and every member of an interface, must be implemented.
+        let mut required: Vec<(String, String)> = Vec::new();
+        for name in &linearized {
+            for m in &self.get_class(name).abstract_methods {
+                required.push((name.clone(), m.clone()));
+            }
+        }
+        for iface in self.supertypes(classname) {
+            if linearized.contains(&iface) {
+                continue;
+            }
+            let c = self.get_class(&iface);
+            let mut members: Vec<&String> = c.fields.iter().map(|(_, f, _)| f).collect();
+            let mut methods: Vec<&String> = c.methods.keys().collect();
+            methods.sort();
+            members.extend(methods);
+            members.extend(&c.abstract_methods);
+            for m in members {
+                required.push((iface.clone(), m.clone()));
+            }
+        }
+
+        let mut missing: Vec<String> = Vec::new();
+        let mut seen: Vec<&String> = Vec::new();
+        for (owner, member) in &required {
+            if !seen.contains(&member) && !implemented(member) {
+                missing.push(format!(" - {}.{}", owner, member));
+            }
+            seen.push(member);
+        }
+
+        if !missing.is_empty() {
+            // As dart.
+            dart_error_at(
+                format!(
+                    "The non-abstract class '{}' is missing implementations for these members:\n{}\n{}",
+                    classname,
+                    missing.join("\n"),
+                    "Try to either\n - provide an implementation,\n - inherit an implementation from a superclass or mixin,\n - mark the class as abstract, or\n - provide a 'noSuchMethod' implementation."
+                ),
+                &class.filepath,
+                class.pos,
+                ctx
+            );
+        }
+    }
+
+
     pub fn register_instance(&mut self, instance: Instance) -> Object {
         let id = instance.id.clone();
         self.instancemap.insert(id.clone(), instance);
@@ -138,6 +530,11 @@ impl ObjSys {
     }
 
 
+    pub fn get_instance_mut(&mut self, id: &str) -> &mut Instance {
+        self.instancemap.get_mut(id).unwrap()
+    }
+
+
     pub fn has_instance(&self, id: &str) -> bool {
         self.instancemap.contains_key(id)
     }
@@ -163,9 +560,53 @@ impl ObjSys {
     }
 
 
+    pub fn get_this_class(&self) -> String {
+        self.this_class.clone()
+    }
+
+
+    // Make refid 'this' while running code declared in classname.
+    // Gives what to hand to leave afterwards.
+    pub fn enter(&mut self, refid: String, classname: String) -> (String, String) {
+        let this = std::mem::replace(&mut self.this, refid);
+        let this_class = std::mem::replace(&mut self.this_class, classname);
+        (this, this_class)
+    }
+
+
+    pub fn leave(&mut self, saved: (String, String)) {
+        self.this = saved.0;
+        self.this_class = saved.1;
+    }
+
+
     // pub fn clear_this(&mut self) {
     //     println!("REAL CLEAR");
     //     self.this = String::from("");
     // }
 
 }
+
+
+// The classes of dart:core that user classes can extend and implement. Being declared
+// nowhere, they have no filepath.
+fn core_classes() -> Vec<Class> {
+    // abstract class Iterable<E> { Iterator<E> get iterator; }
+    let mut iterable = Class::new(String::from("Iterable"));
+    iterable.is_abstract = true;
+    iterable.typeparams = vec![String::from("E")];
+    iterable.getters.push(String::from("iterator"));
+    iterable.abstract_methods.push(String::from("iterator"));
+    iterable.returntypes.insert(String::from("iterator"), String::from("Iterator<E>"));
+
+    // abstract class Iterator<E> { bool moveNext(); E get current; }
+    let mut iterator = Class::new(String::from("Iterator"));
+    iterator.is_abstract = true;
+    iterator.typeparams = vec![String::from("E")];
+    iterator.getters.push(String::from("current"));
+    iterator.abstract_methods.extend([String::from("moveNext"), String::from("current")]);
+    iterator.returntypes.insert(String::from("moveNext"), String::from("bool"));
+    iterator.returntypes.insert(String::from("current"), String::from("E"));
+
+    vec![iterable, iterator]
+}
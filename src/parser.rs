@@ -8,6 +8,8 @@ use crate::expression::*;
 use crate::utils::*;
 use crate::objsys::*;
 use crate::object::*;
+use crate::stack::Env;
+use crate::types;
 
 pub fn parse(reader: &mut Reader,
              globals: &mut Vec<Node>,
@@ -54,20 +56,24 @@ fn directives(reader: &mut Reader, ctx: &Ctx) -> Vec<String> {
 pub fn decl(reader: &mut Reader, objsys: &mut ObjSys, globals: &mut Vec<Node>, ctx: &Ctx) {
     dprint(format!("Parse: decl: {:?}", reader.sym()));
 
+    record_type(reader);
     match reader.sym() {
-        Some(Token::Name(_, _, _)) => {
+        Some(Token::Name(s, _, _)) if types::split(&s).0 == "extension" && is_extension(reader) => {
+            extension(reader, objsys, globals, ctx);
+        }
+        Some(Token::Name(rettype, _, _)) => {
             match reader.next() {
                 Some(Token::Name(fname, _, _)) => {
                     reader.next();
-                    let mut node = Node::new(NodeType::FunDef(fname.to_string(), ctx.filepath.clone()));
+                    // The type parameters of a generic function are not reified, only checked.
+                    let (fname, typeparams) = types::split(&fname);
+                    let typeparams = typeparams.iter().map(|p| types::param_name(p)).collect();
+                    let mut node = Node::new(NodeType::FunDef(fname, ctx.filepath.clone(), rettype.clone(), typeparams));
                     let params = paramlist(reader, ctx);
                     node.children.push(params);
-                    if let Err(e) = reader.skip("{", ctx) {
-                        showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
-                        return;
-                    }
-                    let body = block(reader, ctx);
+                    let body = funbody(reader, &rettype, ctx);
                     node.children.push(body);
+                    skip_arrow_end(reader);
                     globals.push(node);
                 }
                 _ => {
@@ -76,7 +82,22 @@ pub fn decl(reader: &mut Reader, objsys: &mut ObjSys, globals: &mut Vec<Node>, c
             }
         }
         Some(Token::Class(_, _)) => {
-            class(reader, objsys, globals, ctx);
+            class(reader, objsys, globals, false, ctx);
+        }
+        Some(Token::Abstract(_, _)) => {
+            reader.next();
+            if let Some(Token::Class(_, _)) = reader.sym() {
+                class(reader, objsys, globals, true, ctx);
+            }
+            else {
+                dart_parseerror("Expected 'class' after 'abstract'.", ctx, reader.tokens(), reader.pos());
+            }
+        }
+        Some(Token::Mixin(_, _)) => {
+            mixin(reader, objsys, globals, ctx);
+        }
+        Some(Token::Enum(_, _)) => {
+            enumeration(reader, objsys, globals, ctx);
         }
         Some(Token::Import(_, _)) => {
             dart_parseerror(
@@ -96,50 +117,15 @@ pub fn decl(reader: &mut Reader, objsys: &mut ObjSys, globals: &mut Vec<Node>, c
     }
 }
 
-fn paramlist(reader: &mut Reader, ctx: &Ctx) -> Node {
+pub fn paramlist(reader: &mut Reader, ctx: &Ctx) -> Node {
     dprint(format!("Parse: paramlist: {:?}", reader.sym()));
+    parameters(reader, false, ctx)
+}
 
-    if let Some(Token::Paren1(_, _)) = reader.sym() {
-        let mut node = Node::new(NodeType::ParamList);
-        let mut expect_comma = false;
-        reader.next();
-        while reader.more() {
-            match reader.sym() {
-                Some(Token::Paren2(_, _)) => {
-                    reader.next();
-                    return node;
-                }
-                Some(Token::Comma(_, _)) => {
-                    if !expect_comma {
-                        dart_parseerror(
-                            "Unexpected separator in parameter list: ','.",
-                            ctx,
-                            reader.tokens(),
-                            reader.pos()
-                        );
-                        break;
-                    }
-                    reader.next();
-                    expect_comma = false;
-                }
-                Some(Token::Name(s, _, _)) => {
-                    let paramnode = Node::new(NodeType::Name(s.to_string()));
-                    node.children.push(paramnode);
-                    expect_comma = true;
-                    reader.next();
-                }
-                _ => {
-                    dart_parseerror(
-                        "Unexpected token when reading parameters.",
-                        ctx,
-                        reader.tokens(),
-                        reader.pos()
-                    );
-                    break;
-                }
-            }
-        }
-    } else {
+// Positional parameters, then optional positional ones in '[]' or named ones in '{}'.
+// Those are wrapped in an OptionalParam or NamedParam node, with their default value if they have one.
+fn parameters(reader: &mut Reader, fieldinits: bool, ctx: &Ctx) -> Node {
+    if !matches!(reader.sym(), Some(Token::Paren1(_, _))) {
         dart_parseerror(
             "A function declaration needs an explicit list of parameters.",
             ctx,
@@ -147,14 +133,161 @@ fn paramlist(reader: &mut Reader, ctx: &Ctx) -> Node {
             reader.pos() - 1
         )
     }
+    reader.next();
+
+    let mut node = Node::new(NodeType::ParamList);
+    // The bracket of the optional or named parameters, once inside it.
+    let mut section: Option<Token> = None;
+
+    while reader.more() {
+        match reader.sym() {
+            Some(Token::Paren2(_, _)) => {
+                reader.next();
+                return node;
+            }
+            Some(Token::Brack1(_, _)) |
+            Some(Token::Block1(_, _)) if section.is_none() => {
+                section = reader.sym();
+                reader.next();
+                continue;
+            }
+            Some(Token::Brack2(_, _)) if matches!(section, Some(Token::Brack1(_, _))) => {
+                reader.next();
+                continue;
+            }
+            Some(Token::Block2(_, _)) if matches!(section, Some(Token::Block1(_, _))) => {
+                reader.next();
+                continue;
+            }
+            _ => {}
+        }
+
+        let start = reader.pos();
+        let required = match (&section, reader.sym(), reader.peek()) {
+            (Some(Token::Block1(_, _)), Some(Token::Name(r, _, _)), Some(Token::Name(_, _, _) | Token::This(_, _))) if r == "required" => {
+                reader.next();
+                true
+            }
+            _ => false
+        };
+        let param = parameter(reader, fieldinits, ctx);
+
+        let mut wrapped = match section {
+            Some(Token::Brack1(_, _)) => Node::new(NodeType::OptionalParam),
+            Some(Token::Block1(_, _)) => Node::new(NodeType::NamedParam(required)),
+            _ => param.clone()
+        };
+        if let Some(Token::Assign(_, _)) = reader.sym() {
+            if section.is_none() {
+                // As dart.
+                dart_parseerror("Non-optional parameters can't have a default value.", ctx, reader.tokens(), reader.pos());
+            }
+            reader.next();
+            let default = expression(reader, ctx);
+            wrapped.children.push(param);
+            wrapped.children.push(constant(default, reader, ctx));
+        }
+        else if section.is_some() {
+            // Only nullable types have null as implicit default.
+            if let NodeType::TypedVar(typ, name) = &param.nodetype {
+                if !required && !typ.ends_with('?') && !matches!(typ.as_str(), "dynamic" | "Null") {
+                    // As dart.
+                    dart_parseerror(
+                        format!("The parameter '{}' can't have a value of 'null' because of its type '{}', but the implicit default value is 'null'.", name, typ),
+                        ctx,
+                        reader.tokens(),
+                        start
+                    )
+                }
+            }
+            wrapped.children.push(param);
+        }
+        node.children.push(wrapped);
+
+        match reader.sym() {
+            Some(Token::Comma(_, _)) => {
+                reader.next();
+            }
+            Some(Token::Paren2(_, _)) |
+            Some(Token::Brack2(_, _)) |
+            Some(Token::Block2(_, _)) => {}
+            _ => {
+                dart_parseerror(
+                    "Unexpected token when reading parameters.",
+                    ctx,
+                    reader.tokens(),
+                    reader.pos()
+                );
+            }
+        }
+    }
     Node::new(NodeType::ParamList)
 }
 
-fn class(reader: &mut Reader, objsys: &mut ObjSys, globals: &mut Vec<Node>, ctx: &Ctx) {
+// A parameter: a name, a type and a name, or 'this.name' in a constructor.
+fn parameter(reader: &mut Reader, fieldinits: bool, ctx: &Ctx) -> Node {
+    record_type(reader);
+    match (reader.sym(), reader.peek()) {
+        (Some(Token::This(_, _)), _) if fieldinits => {
+            reader.next();
+            if let Err(e) = reader.skip(".", ctx) {
+                showln!(red_bold, "error", white_bold, "Error while skipping '.': ", yellow_bold, e);
+            }
+            match reader.sym() {
+                Some(Token::Name(s, _, _)) => {
+                    reader.next();
+                    Node::new(NodeType::ThisFieldInit(s))
+                }
+                Some(x) => dart_parseerror(format!("Expected identifier. Got {:?}", x), ctx, reader.tokens(), reader.pos()),
+                None => dart_parseerror("Unexpected end of tokens.", ctx, reader.tokens(), reader.pos() - 1)
+            }
+        }
+        (Some(Token::Name(typ, _, _)), Some(Token::Name(s, _, _))) => {
+            reader.next();
+            reader.next();
+            Node::new(NodeType::TypedVar(typ, s))
+        }
+        (Some(Token::Name(s, _, _)), _) => {
+            reader.next();
+            Node::new(NodeType::Name(s))
+        }
+        // As dart.
+        (Some(x), _) => dart_parseerror(format!("Expected an identifier, but got '{}'.", x), ctx, reader.tokens(), reader.pos()),
+        (None, _) => dart_parseerror("Unexpected end of tokens.", ctx, reader.tokens(), reader.pos() - 1)
+    }
+}
+
+fn class(reader: &mut Reader, objsys: &mut ObjSys, globals: &mut Vec<Node>, is_abstract: bool, ctx: &Ctx) {
     match reader.next() {
-        Some(Token::Name(classname, _, _)) => {
-            let mut class = objsys.new_class(classname.clone());
+        Some(Token::Name(classname, line, col)) => {
+            let (classname, typeparams) = types::split(&classname);
+            let mut class = objsys.new_class(classname);
+            class.typeparams = typeparams.iter().map(|p| types::param_name(p)).collect();
+            class.is_abstract = is_abstract;
+            class.filepath = ctx.filepath.clone();
+            class.pos = (line, col);
             reader.next();
+            if let Some(Token::Extends(_, _)) = reader.sym() {
+                match reader.next() {
+                    Some(Token::Name(parent, _, _)) => {
+                        let (parent, typeargs) = types::split(&parent);
+                        class.parent = Some(parent);
+                        class.parent_typeargs = typeargs;
+                        reader.next();
+                    }
+                    _ => {
+                        dart_parseerror("Expected a class name after 'extends'.", ctx, reader.tokens(), reader.pos());
+                    }
+                }
+            }
+            if let Some(Token::With(_, _)) = reader.sym() {
+                reader.next();
+                class.mixins = typenames(reader, ctx);
+            }
+            if let Some(Token::Implements(_, _)) = reader.sym() {
+                reader.next();
+                class.interfaces = typenames(reader, ctx);
+            }
             if let Err(e) = reader.skip("{", ctx) {
                 showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
                 return;
@@ -175,26 +308,298 @@ fn class(reader: &mut Reader, objsys: &mut ObjSys, globals: &mut Vec<Node>, ctx:
     }
 }
 
+// A mixin declaration is a class that can't be extended or constructed, only mixed in.
+fn mixin(reader: &mut Reader, objsys: &mut ObjSys, globals: &mut Vec<Node>, ctx: &Ctx) {
+    match reader.next() {
+        Some(Token::Name(name, line, col)) => {
+            let (name, typeparams) = types::split(&name);
+            let mut class = objsys.new_class(name);
+            class.typeparams = typeparams.iter().map(|p| types::param_name(p)).collect();
+            class.is_mixin = true;
+            class.filepath = ctx.filepath.clone();
+            class.pos = (line, col);
+            reader.next();
+            // The 'on' clause only restricts where the mixin may be used.
+            if let Some(Token::Name(on, _, _)) = reader.sym() {
+                if on == "on" {
+                    reader.next();
+                    typenames(reader, ctx);
+                }
+            }
+            if let Some(Token::Implements(_, _)) = reader.sym() {
+                reader.next();
+                class.interfaces = typenames(reader, ctx);
+            }
+            if let Err(e) = reader.skip("{", ctx) {
+                showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
+                return;
+            }
+            readmembers(&mut class, reader, globals, ctx);
+            if let Err(e) = reader.skip("}", ctx) {
+                showln!(red_bold, "error", white_bold, "Error while skipping '}': ", yellow_bold, e);
+                return;
+            }
+            objsys.register_class(class);
+        }
+        _ => {
+            dart_parseerror("Expected an identifier.", ctx, reader.tokens(), reader.pos());
+        }
+    }
+}
+
+// 'extension E on T' or 'extension on T', rather than a function returning a type named extension.
+pub fn is_extension(reader: &Reader) -> bool {
+    let is_on = |token: Option<&Token>| matches!(token, Some(Token::Name(s, _, _)) if s == "on");
+    is_on(reader.tokens().get(reader.pos() + 1)) || is_on(reader.tokens().get(reader.pos() + 2))
+}
+
+// An extension adds members to an existing type. They are kept as a class named by the extension,
+// or by where it is if it has no name, and found through the looktables of the files importing it.
+fn extension(reader: &mut Reader, objsys: &mut ObjSys, globals: &mut Vec<Node>, ctx: &Ctx) {
+    let (keyword, line, col) = match reader.sym() {
+        Some(Token::Name(keyword, line, col)) => (keyword, line, col),
+        _ => return
+    };
+    let name = match reader.next() {
+        Some(Token::Name(name, _, _)) if name != "on" => {
+            reader.next();
+            name
+        }
+        // The type parameters of an unnamed extension come with the keyword.
+        _ => format!("extension#{}:{}:{}{}", ctx.filepath, line, col, keyword.strip_prefix("extension").unwrap_or(""))
+    };
+    let on = match reader.next() {
+        Some(Token::Name(on, _, _)) => on,
+        // As dart.
+        _ => dart_parseerror("Expected a type, but got something else.", ctx, reader.tokens(), reader.pos())
+    };
+    reader.next();
+
+    let (name, typeparams) = types::split(&name);
+    let mut class = objsys.new_class(name);
+    class.typeparams = typeparams.iter().map(|p| types::param_name(p)).collect();
+    class.on = Some(on);
+    class.filepath = ctx.filepath.clone();
+    class.pos = (line, col);
+    if let Err(e) = reader.skip("{", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
+        return;
+    }
+    let before = globals.len();
+    readmembers(&mut class, reader, globals, ctx);
+    if globals.len() > before {
+        // As dart.
+        dart_parseerror("Extensions can't declare constructors.", ctx, reader.tokens(), reader.pos())
+    }
+    if !class.fields.is_empty() {
+        // As dart.
+        dart_parseerror("Extensions can't declare instance fields.", ctx, reader.tokens(), reader.pos())
+    }
+    if let Err(e) = reader.skip("}", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping '}': ", yellow_bold, e);
+        return;
+    }
+    globals.push(Node::new(NodeType::Extension(class.name.clone())));
+    objsys.register_class(class);
+}
+
+// A record type followed by a name, as in '(int, String) pair', becomes one type name token,
+// like the lexer makes of 'Map<String, int>'. Gives whether there was one.
+fn record_type(reader: &mut Reader) -> bool {
+    let (line, col) = match reader.sym() {
+        Some(t @ Token::Paren1(_, _)) => t.find_token_position(),
+        _ => return false
+    };
+    match scan_record_type(reader.tokens(), reader.pos()) {
+        Some((t, end)) if matches!(reader.tokens().get(end), Some(Token::Name(_, _, _))) => {
+            reader.replace(end, Token::Name(t, line, col));
+            true
+        }
+        _ => false
+    }
+}
+
+// The record type starting with the '(' at start, and where it ends.
+pub fn scan_record_type(tokens: &[Token], start: usize) -> Option<(String, usize)> {
+    let mut positional = Vec::new();
+    let mut named = Vec::new();
+    let mut in_named = false;
+    let mut i = start + 1;
+    loop {
+        // A field type, which may be a record type itself.
+        let t = match tokens.get(i) {
+            Some(Token::Paren2(_, _)) if !in_named => break,
+            Some(Token::Block1(_, _)) if !in_named => {
+                in_named = true;
+                i += 1;
+                continue;
+            }
+            Some(Token::Block2(_, _)) if in_named => {
+                in_named = false;
+                i += 1;
+                continue;
+            }
+            Some(Token::Comma(_, _)) => {
+                i += 1;
+                continue;
+            }
+            Some(Token::Name(t, _, _)) => {
+                i += 1;
+                t.clone()
+            }
+            Some(Token::Paren1(_, _)) => {
+                let (t, end) = scan_record_type(tokens, i)?;
+                i = end;
+                t
+            }
+            _ => return None
+        };
+        // Named fields must be named, positional ones may be.
+        match tokens.get(i) {
+            Some(Token::Name(name, _, _)) => {
+                i += 1;
+                if in_named {
+                    named.push((name.clone(), t));
+                    continue;
+                }
+            }
+            _ if in_named => return None,
+            _ => {}
+        }
+        positional.push(t);
+    }
+    if positional.is_empty() && named.is_empty() {
+        return None;
+    }
+    named.sort();
+    Some((types::record(&positional, &named), i + 1))
+}
+
+// An enum is a class with a fixed set of instances, its values. They are static final fields
+// of the class, constructed when first used, and listed in the static field 'values'.
+fn enumeration(reader: &mut Reader, objsys: &mut ObjSys, globals: &mut Vec<Node>, ctx: &Ctx) {
+    let (name, line, col) = match reader.next() {
+        Some(Token::Name(name, line, col)) => (name, line, col),
+        // As dart.
+        _ => dart_parseerror("Expected an identifier.", ctx, reader.tokens(), reader.pos())
+    };
+    let mut class = objsys.new_class(name.clone());
+    class.is_enum = true;
+    class.filepath = ctx.filepath.clone();
+    class.pos = (line, col);
+    reader.next();
+    if let Some(Token::With(_, _)) = reader.sym() {
+        reader.next();
+        class.mixins = typenames(reader, ctx);
+    }
+    if let Some(Token::Implements(_, _)) = reader.sym() {
+        reader.next();
+        class.interfaces = typenames(reader, ctx);
+    }
+    if let Err(e) = reader.skip("{", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
+        return;
+    }
+
+    let mut values = Node::new(NodeType::List(vec![name.clone()]));
+    while let Some(Token::Name(value, _, _)) = reader.sym() {
+        reader.next();
+        let args = match reader.sym() {
+            Some(Token::Paren1(_, _)) => arglist(reader, ctx),
+            _ => Node::new(NodeType::ArgList)
+        };
+        let mut construct = Node::new(NodeType::FunCall(name.clone()));
+        construct.children.push(args);
+        let mut enumvalue = Node::new(NodeType::EnumValue(values.children.len(), value.clone()));
+        enumvalue.children.push(construct);
+
+        values.children.push(Node::new(NodeType::Name(value.clone())));
+        class.static_fields.push((name.clone(), value.clone(), Some(enumvalue)));
+        class.finals.push(value);
+
+        match reader.sym() {
+            Some(Token::Comma(_, _)) => {
+                reader.next();
+            }
+            _ => break
+        }
+    }
+    if values.children.is_empty() {
+        // As dart.
+        dart_parseerror("An enum declaration can't be empty.", ctx, reader.tokens(), reader.pos());
+    }
+    // Members follow the values after a ';'.
+    if let Some(Token::EndSt(_, _)) = reader.sym() {
+        reader.next();
+    }
+    readmembers(&mut class, reader, globals, ctx);
+    if let Err(e) = reader.skip("}", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping '}': ", yellow_bold, e);
+        return;
+    }
+
+    class.static_fields.push((format!("List<{}>", name), String::from("values"), Some(values)));
+    class.finals.push(String::from("values"));
+    class.add_field(String::from("int"), String::from("index"), None);
+    class.add_field(String::from("String"), String::from("name"), None);
+    class.finals.push(String::from("index"));
+    class.finals.push(String::from("name"));
+    objsys.register_class(class);
+}
+
+// Comma separated type names, as in 'with A, B' or 'implements A, B'.
+// Their type arguments are dropped.
+fn typenames(reader: &mut Reader, ctx: &Ctx) -> Vec<String> {
+    let mut names = Vec::new();
+    loop {
+        match reader.sym() {
+            Some(Token::Name(name, _, _)) => {
+                names.push(types::split(&name).0);
+                reader.next();
+            }
+            _ => {
+                dart_parseerror("Expected a type name.", ctx, reader.tokens(), reader.pos());
+            }
+        }
+        match reader.sym() {
+            Some(Token::Comma(_, _)) => {
+                reader.next();
+            }
+            _ => return names
+        }
+    }
+}
+
 fn readmembers(class: &mut Class, reader: &mut Reader, globals: &mut Vec<Node>, ctx: &Ctx) {
     let mut got_constructor = false;
 
     while reader.more() {
+        // 'const' before a constructor only allows it in const expressions.
+        let modifiers = modifiers(reader);
+        let is_static = modifiers.iter().any(|m| m == "static");
+        record_type(reader);
+
         match reader.sym() {
             Some(Token::Name(mtype, _, _)) => {
-                if *mtype == class.name {
+                // The class name is also the return type of members like 'Vec operator +(Vec v)'.
+                if *mtype == class.name && matches!(reader.peek(), Some(Token::Paren1(_, _))) {
                     reader.next();
                     let mut constructor_node = Node::new(NodeType::Constructor(class.name.clone(), ctx.filepath.clone()));
                     let params = constructor_paramlist(reader, ctx);
                     constructor_node.children.push(params);
+                    let supercall = initializers(class, reader, ctx);
                     match reader.sym() {
                         Some(Token::Block1(_, _)) => {
                             reader.next();
-                            let body = block(reader, ctx);
+                            let mut body = block(reader, ctx);
+                            if let Some(sc) = supercall {
+                                body.children.insert(0, sc);
+                            }
                             constructor_node.children.push(body);
                         }
                         Some(Token::EndSt(_, _)) => {
                             reader.next();
-                            constructor_node.children.push(Node::new(NodeType::Null));
+                            constructor_node.children.push(supercall_body(supercall));
                         }
                         Some(x) => {
                             dart_parseerror(
@@ -212,44 +617,57 @@ fn readmembers(class: &mut Class, reader: &mut Reader, globals: &mut Vec<Node>,
                     globals.push(constructor_node);
                     continue;
                 }
+                // Getters, setters and operators. Without a declared type, the keyword comes first.
+                let untyped = matches!(mtype.as_str(), "get" | "set" | "operator");
+                let keyword = match untyped {
+                    true => Some(mtype.clone()),
+                    false => match reader.peek() {
+                        Some(Token::Name(kw, _, _)) if matches!(kw.as_str(), "get" | "set" | "operator") => Some(kw),
+                        _ => None
+                    }
+                };
+                // A field with modifiers needs no type: 'final x = 1;'.
+                if !modifiers.is_empty() && matches!(reader.peek(), Some(Token::Assign(_, _)) | Some(Token::EndSt(_, _))) {
+                    reader.next();
+                    field(class, &modifiers, String::from("var"), mtype, reader, ctx);
+                    continue;
+                }
+                if let Some(keyword) = keyword {
+                    if untyped || !is_member_end(reader.tokens().get(reader.pos() + 2)) {
+                        if !untyped {
+                            reader.next();
+                        }
+                        let rettype = if untyped { String::from("dynamic") } else { mtype };
+                        accessor(class, &keyword, rettype, reader, ctx);
+                        continue;
+                    }
+                }
                 match reader.next() {
                     Some(Token::Name(fieldname, _, _)) => {
+                        let fieldname = types::split(&fieldname).0;
                         match reader.next() {
                             Some(Token::Paren1(_, _)) => {
                                 let param_node = paramlist(reader, ctx);
-                                if let Err(e) = reader.skip("{", ctx) {
-                                    showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
-                                    return;
+                                class.returntypes.insert(fieldname.clone(), mtype.clone());
+                                if let Some(Token::EndSt(_, _)) = reader.sym() {
+                                    reader.next();
+                                    class.abstract_methods.push(fieldname);
+                                    continue;
                                 }
-                                let body = block(reader, ctx);
-                                let mut args: Vec<ParamObj> = Vec::new();
-                                for i in 0..param_node.children.len() {
-                                    let p = &param_node.children[i];
-                                    match &p.nodetype {
-                                        NodeType::Name(s) => {
-                                            args.push(ParamObj { typ: String::from("var"), name: s.clone(), fieldinit: false });
-                                        }
-                                        x => {
-                                            showln!(red_bold, "error", white_bold, "Invalid parameter: {:?}", x);
-                                            return;
-                                        }
+                                let body = funbody(reader, &mtype, ctx);
+                                skip_arrow_end(reader);
+                                let args = method_params(&param_node);
+                                let methodobj = Object::Function(format!("{}.{}", class.name, fieldname), ctx.filepath.clone(), body, args, Env::default());
+                                match is_static {
+                                    true => {
+                                        class.static_methods.insert(fieldname, methodobj);
                                     }
+                                    false => class.add_method(fieldname.clone(), methodobj)
                                 }
-                                let methodobj = Object::Function(fieldname.to_string(), ctx.filepath.clone(), body, args);
-                                class.add_method(fieldname.clone(), methodobj);
-                            }
-                            Some(Token::EndSt(_, _)) => {
-                                reader.next();
-                                class.add_field(mtype, fieldname, Node::new(NodeType::Null));
                             }
+                            Some(Token::EndSt(_, _)) |
                             Some(Token::Assign(_, _)) => {
-                                reader.next();
-                                let val = expression(reader, ctx);
-                                if let Err(e) = reader.skip(";", ctx) {
-                                    showln!(red_bold, "error", white_bold, "Error while skipping ';': ", yellow_bold, e);
-                                    return;
-                                }
-                                class.add_field(mtype, fieldname, val);
+                                field(class, &modifiers, mtype, fieldname, reader, ctx);
                             }
                             Some(Token::Block2(_, _)) => {
                                 break;
@@ -286,92 +704,159 @@ fn readmembers(class: &mut Class, reader: &mut Reader, globals: &mut Vec<Node>,
         }
     }
 
-    if !got_constructor {
+    if !got_constructor && !class.is_mixin && class.on.is_none() {
         let mut constructor_node = Node::new(NodeType::Constructor(class.name.clone(), ctx.filepath.clone()));
         constructor_node.children.push(Node::new(NodeType::ParamList));
-        constructor_node.children.push(Node::new(NodeType::Null));
+        constructor_node.children.push(supercall_body(implicit_supercall(class)));
         globals.push(constructor_node);
     }
 }
 
-fn constructor_paramlist(reader: &mut Reader, ctx: &Ctx) -> Node {
-    if let Some(Token::Paren1(_, _)) = reader.sym() {
-        let mut node = Node::new(NodeType::ParamList);
-        let mut expect_comma = false;
-        reader.next();
-        while reader.more() {
-            match reader.sym() {
-                Some(Token::Paren2(_, _)) => {
-                    reader.next();
-                    return node;
-                }
-                Some(Token::This(_, _)) => {
+// A field, from the ';' or '=' after its name. Static fields belong to the class, not its instances.
+fn field(class: &mut Class, modifiers: &[String], ftype: String, fname: String, reader: &mut Reader, ctx: &Ctx) {
+    let has = |modifier: &str| modifiers.iter().any(|m| m == modifier);
+    if has("const") && !has("static") {
+        // As dart.
+        dart_parseerror("Only static fields can be declared as const.", ctx, reader.tokens(), reader.pos() - 1);
+    }
+
+    let initexpr = match reader.sym() {
+        Some(Token::Assign(_, _)) => {
+            reader.next();
+            let val = expression(reader, ctx);
+            Some(if has("const") { constant(val, reader, ctx) } else { val })
+        }
+        // As dart.
+        _ if has("const") => dart_parseerror(format!("The const variable '{}' must be initialized.", fname), ctx, reader.tokens(), reader.pos() - 1),
+        _ => None
+    };
+    if let Err(e) = reader.skip(";", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping ';': ", yellow_bold, e);
+        return;
+    }
+
+    if has("final") || has("const") {
+        class.finals.push(fname.clone());
+    }
+    if has("late") {
+        class.lates.push(fname.clone());
+    }
+    match has("static") {
+        true => class.static_fields.push((ftype, fname, initexpr)),
+        false => class.add_field(ftype, fname, initexpr)
+    }
+}
+
+// Whether a member name is followed by what ends a field, or starts the parameters of a method.
+// Then a name like 'get' is the name of the member, not a keyword.
+fn is_member_end(token: Option<&Token>) -> bool {
+    matches!(token, Some(Token::EndSt(_, _)) | Some(Token::Assign(_, _)) | Some(Token::Paren1(_, _)))
+}
+
+// A getter, setter or operator, from its keyword. Setters are named 'x=', as in dart,
+// and operators by their symbol, with unary minus as 'unary-'.
+fn accessor(class: &mut Class, keyword: &str, rettype: String, reader: &mut Reader, ctx: &Ctx) {
+    let name = match (keyword, reader.next()) {
+        ("get", Some(Token::Name(name, _, _))) => name,
+        ("set", Some(Token::Name(name, _, _))) => format!("{}=", name),
+        ("operator", Some(Token::Brack1(_, _))) => {
+            reader.next();
+            match reader.peek() {
+                Some(Token::Assign(_, _)) => {
                     reader.next();
-                    if let Err(e) = reader.skip(".", ctx) {
-                        showln!(red_bold, "error", white_bold, "Error while skipping '.': ", yellow_bold, e);
-                        break;
-                    }
-                    match reader.sym() {
-                        Some(Token::Name(s, _, _)) => {
-                            let this_fieldinit = Node::new(NodeType::ThisFieldInit(s));
-                            node.children.push(this_fieldinit);
-                            expect_comma = true;
-                            reader.next();
-                        }
-                        Some(x) => {
-                            dart_parseerror(
-                                format!("Expected identifier. Got {:?}", x),
-                                ctx,
-                                reader.tokens(),
-                                reader.pos()
-                            );
-                            break;
-                        }
-                        None => {
-                            showln!(red_bold, "error", white_bold, "Unexpected end of tokens.");
-                            break;
-                        }
-                    }
-                }
-                Some(Token::Comma(_, _)) => {
-                    if !expect_comma {
-                        dart_parseerror(
-                            "Expected an identifier, but got ','.",
-                            ctx,
-                            reader.tokens(),
-                            reader.pos()
-                        );
-                        break;
-                    }
-                    reader.next();
-                    expect_comma = false;
-                }
-                Some(Token::Name(s, _, _)) => {
-                    let paramnode = Node::new(NodeType::Name(s.to_string()));
-                    node.children.push(paramnode);
-                    expect_comma = true;
-                    reader.next();
-                }
-                _ => {
-                    dart_parseerror(
-                        "Unexpected token when reading parameters.",
-                        ctx,
-                        reader.tokens(),
-                        reader.pos()
-                    );
-                    break;
+                    String::from("[]=")
                 }
+                _ => String::from("[]")
             }
         }
-    } else {
-        dart_parseerror(
-            "A function declaration needs an explicit list of parameters.",
-            ctx,
-            reader.tokens(),
-            reader.pos() - 1
-        )
+        ("operator", Some(op)) if matches!(op,
+            Token::Add(_, _) | Token::Sub(_, _) | Token::Mul(_, _) | Token::Div(_, _) | Token::IntDiv(_, _) |
+            Token::Equal(_, _) | Token::LessThan(_, _) | Token::GreaterThan(_, _) | Token::LessOrEq(_, _) | Token::GreaterOrEq(_, _)
+        ) => op.to_string(),
+        _ => {
+            // As dart.
+            dart_parseerror(format!("Expected an identifier, but got '{}'.", keyword), ctx, reader.tokens(), reader.pos())
+        }
+    };
+    reader.next();
+
+    let params = match keyword {
+        "get" => Vec::new(),
+        _ => method_params(&paramlist(reader, ctx))
+    };
+    let name = if name == "-" && params.is_empty() { String::from("unary-") } else { name };
+
+    if keyword == "get" {
+        class.getters.push(name.clone());
     }
-    Node::new(NodeType::ParamList)
+    if keyword != "set" {
+        class.returntypes.insert(name.clone(), rettype.clone());
+    }
+    if let Some(Token::EndSt(_, _)) = reader.sym() {
+        reader.next();
+        class.abstract_methods.push(name);
+        return;
+    }
+    let body = funbody(reader, &rettype, ctx);
+    skip_arrow_end(reader);
+    let methodobj = Object::Function(format!("{}.{}", class.name, name), ctx.filepath.clone(), body, params, Env::default());
+    class.add_method(name, methodobj);
+}
+
+// The parameters of a method.
+fn method_params(param_node: &Node) -> Vec<ParamObj> {
+    param_node.children.iter().map(ParamObj::from_node).collect()
+}
+
+// The initializer list of a constructor: ': super(args)'.
+// A subclass without one still calls the unnamed superclass constructor.
+fn initializers(class: &Class, reader: &mut Reader, ctx: &Ctx) -> Option<Node> {
+    if let Some(Token::Colon(_, _)) = reader.sym() {
+        reader.next();
+        if let Err(e) = reader.skip("super", ctx) {
+            showln!(red_bold, "error", white_bold, "Error while skipping 'super': ", yellow_bold, e);
+            return None;
+        }
+        if class.parent.is_none() {
+            // As dart.
+            dart_parseerror(
+                format!("The class '{}' cannot invoke 'super' as it has no superclass.", class.name),
+                ctx,
+                reader.tokens(),
+                reader.pos() - 1
+            );
+        }
+        let args = arglist(reader, ctx);
+        let mut node = Node::new(NodeType::SuperCall);
+        node.children.push(args);
+        return Some(node);
+    }
+    implicit_supercall(class)
+}
+
+fn implicit_supercall(class: &Class) -> Option<Node> {
+    class.parent.as_ref().map(|_| {
+        let mut node = Node::new(NodeType::SuperCall);
+        node.children.push(Node::new(NodeType::ArgList));
+        node
+    })
+}
+
+// Body for a constructor without one of its own.
+fn supercall_body(supercall: Option<Node>) -> Node {
+    match supercall {
+        Some(sc) => {
+            let mut body = Node::new(NodeType::Block);
+            body.children.push(sc);
+            body
+        }
+        None => Node::new(NodeType::Null)
+    }
+}
+
+// Like paramlist, but parameters can also be 'this.field'.
+fn constructor_paramlist(reader: &mut Reader, ctx: &Ctx) -> Node {
+    parameters(reader, true, ctx)
 }
 
 pub fn arglist(reader: &mut Reader, ctx: &Ctx) -> Node {
@@ -408,7 +893,17 @@ pub fn arglist(reader: &mut Reader, ctx: &Ctx) -> Node {
                         );
                         break;
                     }
-                    let arg = expression(reader, ctx);
+                    // A named argument: 'name: value'.
+                    let arg = match (reader.sym(), reader.peek()) {
+                        (Some(Token::Name(name, _, _)), Some(Token::Colon(_, _))) => {
+                            reader.next();
+                            reader.next();
+                            let mut named = Node::new(NodeType::NamedArg(name));
+                            named.children.push(expression(reader, ctx));
+                            named
+                        }
+                        _ => expression(reader, ctx)
+                    };
                     node.children.push(arg);
                     expect_comma = true;
                 }
@@ -434,6 +929,58 @@ pub fn arglist(reader: &mut Reader, ctx: &Ctx) -> Node {
     Node::new(NodeType::ArgList)
 }
 
+// Either a block, or '=> expr' which is short for a block returning expr.
+// The body of an async function is wrapped in an Async node, typed by the declared return type.
+pub fn funbody(reader: &mut Reader, rettype: &str, ctx: &Ctx) -> Node {
+    if let Some(Token::Name(s, _, _)) = reader.sym() {
+        // 'sync*' bodies generate an iterable.
+        if s == "sync" && matches!(reader.peek(), Some(Token::Mul(_, _))) {
+            reader.next();
+            reader.next();
+            let mut node = Node::new(NodeType::SyncStar(types::generated(rettype)));
+            node.children.push(funbody(reader, rettype, ctx));
+            return node;
+        }
+        if s == "async" {
+            // 'async*' bodies generate a stream.
+            let mut node = match reader.next() {
+                Some(Token::Mul(_, _)) => {
+                    reader.next();
+                    Node::new(NodeType::AsyncStar(types::generated(rettype)))
+                }
+                _ => Node::new(NodeType::Async(types::flatten(rettype)))
+            };
+            node.children.push(funbody(reader, rettype, ctx));
+            return node;
+        }
+    }
+    match reader.sym() {
+        Some(Token::Arrow(_, _)) => {
+            reader.next();
+            let val = expression(reader, ctx);
+            let mut ret = Node::new(NodeType::Return);
+            ret.children.push(val);
+            let mut body = Node::new(NodeType::Block);
+            body.children.push(ret);
+            body
+        }
+        _ => {
+            if let Err(e) = reader.skip("{", ctx) {
+                showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
+                return Node::new(NodeType::Null);
+            }
+            block(reader, ctx)
+        }
+    }
+}
+
+// Declarations with an arrow body end with ';'.
+fn skip_arrow_end(reader: &mut Reader) {
+    if let Some(Token::EndSt(_, _)) = reader.sym() {
+        reader.next();
+    }
+}
+
 fn block(reader: &mut Reader, ctx: &Ctx) -> Node {
     let mut node = Node::new(NodeType::Block);
 
@@ -454,16 +1001,9 @@ fn block(reader: &mut Reader, ctx: &Ctx) -> Node {
                 let snode = statement(reader, ctx);
                 node.children.push(snode);
 
-                match reader.sym() {
-                    Some(Token::Block2(_, _)) => {
-                        reader.next();
-                        continue;
-                    }
-                    Some(Token::EndSt(_, _)) => {
-                        reader.next();
-                        continue;
-                    }
-                    _ => continue,
+                // A '}' here closes this block, it is consumed above.
+                if let Some(Token::EndSt(_, _)) = reader.sym() {
+                    reader.next();
                 }
             }
             None => {
@@ -475,9 +1015,607 @@ fn block(reader: &mut Reader, ctx: &Ctx) -> Node {
     node
 }
 
-fn statement(reader: &mut Reader, ctx: &Ctx) -> Node {
+// Loops keep their label as an extra last child, for 'continue label'.
+// Any other statement is wrapped, so 'break label' can leave it.
+fn labeled(label: String, mut stmt: Node) -> Node {
+    match stmt.nodetype {
+        NodeType::While |
+        NodeType::DoWhile |
+        NodeType::For |
+        NodeType::ForIn(_) |
+        NodeType::AwaitFor(_) => {
+            stmt.children.push(Node::new(NodeType::Label(label)));
+            stmt
+        }
+        _ => {
+            let mut node = Node::new(NodeType::Label(label));
+            node.children.push(stmt);
+            node
+        }
+    }
+}
+
+// The optional label after 'break' or 'continue'.
+fn jumplabel(reader: &mut Reader) -> Option<String> {
+    match reader.next() {
+        Some(Token::Name(label, _, _)) => {
+            reader.next();
+            Some(label)
+        }
+        _ => None
+    }
+}
+
+// try { } on T catch (e, st) { } catch (e) { } finally { }
+fn trystatement(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let mut node = Node::new(NodeType::Try);
+    reader.next();
+    node.children.push(braced_block(reader, ctx));
+
+    loop {
+        match reader.sym() {
+            Some(Token::Name(on, _, _)) if on == "on" => {
+                let typename = match reader.next() {
+                    Some(Token::Name(t, _, _)) => t,
+                    _ => dart_parseerror("Expected a type after 'on'.", ctx, reader.tokens(), reader.pos())
+                };
+                reader.next();
+                let (e, st) = match reader.sym() {
+                    Some(Token::Catch(_, _)) => catchparams(reader, ctx),
+                    _ => (None, None)
+                };
+                let mut clause = Node::new(NodeType::Catch(Some(typename), e, st));
+                clause.children.push(braced_block(reader, ctx));
+                node.children.push(clause);
+            }
+            Some(Token::Catch(_, _)) => {
+                let (e, st) = catchparams(reader, ctx);
+                let mut clause = Node::new(NodeType::Catch(None, e, st));
+                clause.children.push(braced_block(reader, ctx));
+                node.children.push(clause);
+            }
+            Some(Token::Finally(_, _)) => {
+                reader.next();
+                let mut clause = Node::new(NodeType::Finally);
+                clause.children.push(braced_block(reader, ctx));
+                node.children.push(clause);
+                break;
+            }
+            _ => break
+        }
+    }
+
+    if node.children.len() == 1 {
+        // As dart.
+        dart_parseerror("A try block must be followed by an 'on', 'catch', or 'finally' clause.", ctx, reader.tokens(), reader.pos());
+    }
+    node
+}
+
+// 'catch (e)' or 'catch (e, st)'.
+fn catchparams(reader: &mut Reader, ctx: &Ctx) -> (Option<String>, Option<String>) {
+    reader.next();
+    let params = paramlist(reader, ctx);
+    let names: Vec<String> = params.children.iter().map(|p| match &p.nodetype {
+        NodeType::Name(s) => s.clone(),
+        _ => dart_parseerror("Expected an identifier in catch clause.", ctx, reader.tokens(), reader.pos())
+    }).collect();
+
+    match names.len() {
+        1 | 2 => (names.first().cloned(), names.get(1).cloned()),
+        _ => dart_parseerror("A catch clause must have 1 or 2 parameters.", ctx, reader.tokens(), reader.pos())
+    }
+}
+
+fn braced_block(reader: &mut Reader, ctx: &Ctx) -> Node {
+    if let Err(e) = reader.skip("{", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
+        return Node::new(NodeType::Null);
+    }
+    block(reader, ctx)
+}
+
+// switch (x) { case p when g: ... default: ... }
+// Cases without statements share the body of the next case.
+fn switchstatement(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let mut node = Node::new(NodeType::Switch);
+    node.children.push(switchsubject(reader, ctx));
+
+    let mut pending: Vec<Node> = Vec::new();
+    let mut default: Option<Node> = None;
+    loop {
+        let is_default = match reader.sym() {
+            Some(Token::Case(_, _)) => false,
+            Some(Token::Default(_, _)) => true,
+            Some(Token::Block2(_, _)) => {
+                reader.next();
+                break;
+            }
+            _ => dart_parseerror("Expected 'case' or 'default'.", ctx, reader.tokens(), reader.pos())
+        };
+        reader.next();
+
+        let mut case = Node::new(NodeType::Case);
+        if is_default {
+            case.children.push(Node::new(NodeType::VarPattern(String::from("var"), String::from("_"))));
+            case.children.push(Node::new(NodeType::Null));
+        }
+        else {
+            case.children.push(pattern(reader, ctx));
+            case.children.push(guard(reader, ctx));
+        }
+        if let Err(e) = reader.skip(":", ctx) {
+            showln!(red_bold, "error", white_bold, "Error while skipping ':': ", yellow_bold, e);
+            return Node::new(NodeType::Null);
+        }
+
+        let body = casebody(reader, ctx);
+        let is_empty = body.children.is_empty();
+        pending.push(case);
+        if is_empty && !is_default {
+            continue;
+        }
+        for mut c in pending.drain(..) {
+            c.children.push(body.clone());
+            if is_default {
+                default = Some(c);
+            }
+            else {
+                node.children.push(c);
+            }
+        }
+    }
+
+    // The default case only runs when no other case matches.
+    if let Some(c) = default {
+        node.children.push(c);
+    }
+    node
+}
+
+// switch (x) { p when g => e, _ => e }
+pub fn switchexpression(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let mut node = Node::new(NodeType::SwitchExpr);
+    node.children.push(switchsubject(reader, ctx));
+
+    loop {
+        if let Some(Token::Block2(_, _)) = reader.sym() {
+            reader.next();
+            break;
+        }
+        let mut case = Node::new(NodeType::Case);
+        case.children.push(pattern(reader, ctx));
+        case.children.push(guard(reader, ctx));
+        if let Err(e) = reader.skip("=>", ctx) {
+            showln!(red_bold, "error", white_bold, "Error while skipping '=>': ", yellow_bold, e);
+            return Node::new(NodeType::Null);
+        }
+        case.children.push(expression(reader, ctx));
+        node.children.push(case);
+
+        match reader.sym() {
+            Some(Token::Comma(_, _)) => {
+                reader.next();
+            }
+            Some(Token::Block2(_, _)) => {}
+            _ => dart_parseerror("Expected ',' or '}' after a switch expression case.", ctx, reader.tokens(), reader.pos())
+        }
+    }
+    node
+}
+
+// '(x) {' of a switch.
+fn switchsubject(reader: &mut Reader, ctx: &Ctx) -> Node {
+    reader.next();
+    if let Err(e) = reader.skip("(", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping '(': ", yellow_bold, e);
+        return Node::new(NodeType::Null);
+    }
+    let subject = expression(reader, ctx);
+    if let Err(e) = reader.skip(")", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping ')': ", yellow_bold, e);
+        return Node::new(NodeType::Null);
+    }
+    if let Err(e) = reader.skip("{", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
+        return Node::new(NodeType::Null);
+    }
+    subject
+}
+
+// The optional 'when' clause of a case. 'when' is not a reserved word.
+fn guard(reader: &mut Reader, ctx: &Ctx) -> Node {
     match reader.sym() {
-        Some(Token::Name(s, _, _)) => {
+        Some(Token::Name(w, _, _)) if w == "when" => {
+            reader.next();
+            expression(reader, ctx)
+        }
+        _ => Node::new(NodeType::Null)
+    }
+}
+
+// The statements of a case, up to the next case or the end of the switch.
+fn casebody(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let mut node = Node::new(NodeType::Block);
+
+    loop {
+        match reader.sym() {
+            Some(Token::Case(_, _)) |
+            Some(Token::Default(_, _)) |
+            Some(Token::Block2(_, _)) => break,
+            Some(Token::EndSt(_, _)) => {
+                reader.next();
+            }
+            Some(Token::End) | None => {
+                showln!(red_bold, "error", white_bold, "Unexpected end of tokens in switch.");
+                break;
+            }
+            Some(_) => {
+                node.children.push(statement(reader, ctx));
+                if let Some(Token::EndSt(_, _)) = reader.sym() {
+                    reader.next();
+                }
+            }
+        }
+    }
+    node
+}
+
+// pattern: p || p, p && p, < e, == e, _, var x, T x, (p), a record pattern (p, x: p, :var y), or a constant.
+pub fn pattern(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let left = and_pattern(reader, ctx);
+
+    match reader.sym() {
+        Some(Token::LogOr(_, _)) => {
+            reader.next();
+            let mut node = Node::new(NodeType::OrPattern);
+            node.children.push(left);
+            node.children.push(pattern(reader, ctx));
+            node
+        }
+        _ => left
+    }
+}
+
+fn and_pattern(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let left = primary_pattern(reader, ctx);
+
+    match reader.sym() {
+        Some(Token::LogAnd(_, _)) => {
+            reader.next();
+            let mut node = Node::new(NodeType::AndPattern);
+            node.children.push(left);
+            node.children.push(and_pattern(reader, ctx));
+            node
+        }
+        _ => left
+    }
+}
+
+fn primary_pattern(reader: &mut Reader, ctx: &Ctx) -> Node {
+    match reader.sym() {
+        Some(Token::LessThan(_, _)) |
+        Some(Token::GreaterThan(_, _)) |
+        Some(Token::LessOrEq(_, _)) |
+        Some(Token::GreaterOrEq(_, _)) |
+        Some(Token::Equal(_, _)) |
+        Some(Token::NotEqual(_, _)) => {
+            let op = format!("{}", reader.sym().unwrap());
+            reader.next();
+            let mut node = Node::new(NodeType::RelPattern(op));
+            node.children.push(operand(reader, ctx));
+            node
+        }
+        Some(Token::Paren1(_, _)) => {
+            record_pattern(reader, ctx)
+        }
+        Some(Token::Name(s, _, _)) if s == "_" => {
+            reader.next();
+            Node::new(NodeType::VarPattern(String::from("var"), s))
+        }
+        Some(Token::Name(typ, _, _)) => {
+            match reader.peek() {
+                Some(Token::Name(name, _, _)) if name != "when" => {
+                    reader.next();
+                    reader.next();
+                    Node::new(NodeType::VarPattern(typ, name))
+                }
+                _ => constant_pattern(reader, ctx)
+            }
+        }
+        _ => constant_pattern(reader, ctx)
+    }
+}
+
+// Like a record, a single positional field makes a record pattern only with a trailing comma.
+// The name of a named field can be left out when its pattern is a variable: '(:var x)'.
+fn record_pattern(reader: &mut Reader, ctx: &Ctx) -> Node {
+    reader.next();
+    let mut node = Node::new(NodeType::RecordPattern);
+    let mut is_record = false;
+
+    while !matches!(reader.sym(), Some(Token::Paren2(_, _))) {
+        let field = match (reader.sym(), reader.peek()) {
+            (Some(Token::Name(name, _, _)), Some(Token::Colon(_, _))) => {
+                reader.next();
+                reader.next();
+                let mut named = Node::new(NodeType::NamedArg(name));
+                named.children.push(pattern(reader, ctx));
+                is_record = true;
+                named
+            }
+            (Some(Token::Colon(_, _)), _) => {
+                reader.next();
+                let field = pattern(reader, ctx);
+                let name = match &field.nodetype {
+                    NodeType::VarPattern(_, name) => name.clone(),
+                    NodeType::ConstPattern => match &field.children[0].nodetype {
+                        NodeType::Name(name) if field.children[0].children.is_empty() => name.clone(),
+                        // As dart.
+                        _ => dart_parseerror("The getter name is not specified explicitly, and the pattern is not a variable.", ctx, reader.tokens(), reader.pos())
+                    },
+                    _ => dart_parseerror("The getter name is not specified explicitly, and the pattern is not a variable.", ctx, reader.tokens(), reader.pos())
+                };
+                let mut named = Node::new(NodeType::NamedArg(name));
+                named.children.push(field);
+                is_record = true;
+                named
+            }
+            _ => pattern(reader, ctx)
+        };
+        node.children.push(field);
+        match reader.sym() {
+            Some(Token::Comma(_, _)) => {
+                reader.next();
+                is_record = true;
+            }
+            Some(Token::Paren2(_, _)) => {}
+            // As dart.
+            _ => dart_parseerror("Expected ')' before this.", ctx, reader.tokens(), reader.pos())
+        }
+    }
+    reader.next();
+
+    if !is_record && node.children.len() == 1 {
+        return node.children.remove(0);
+    }
+    node
+}
+
+// In a declaration, 'var (a, b) = r', the names in a pattern declare variables.
+fn binders(mut pattern: Node) -> Node {
+    match &pattern.nodetype {
+        NodeType::ConstPattern => match &pattern.children[0].nodetype {
+            NodeType::Name(name) if pattern.children[0].children.is_empty() => Node::new(NodeType::VarPattern(String::from("var"), name.clone())),
+            _ => pattern
+        },
+        NodeType::RecordPattern |
+        NodeType::NamedArg(_) => {
+            pattern.children = pattern.children.into_iter().map(binders).collect();
+            pattern
+        }
+        _ => pattern
+    }
+}
+
+// In a pattern assignment, '(a, b) = (b, a)', the record on the left is a pattern of the variables to assign.
+fn assigned_pattern(record: Node) -> Node {
+    match record.nodetype {
+        NodeType::Record => {
+            let mut node = Node::new(NodeType::RecordPattern);
+            node.children = record.children.into_iter().map(assigned_pattern).collect();
+            node
+        }
+        NodeType::NamedArg(name) => {
+            let mut node = Node::new(NodeType::NamedArg(name));
+            node.children = record.children.into_iter().map(assigned_pattern).collect();
+            node
+        }
+        _ => record
+    }
+}
+
+// A for loop up to its body: 'for (init; cond; step)' or 'for (var x in xs)'. Also what
+// the for elements of collection literals start with.
+pub fn for_header(reader: &mut Reader, ctx: &Ctx) -> Node {
+    reader.next();
+    if let Err(e) = reader.skip("(", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping '(': ", yellow_bold, e);
+        return Node::new(NodeType::Null);
+    }
+    let is_final = matches!(reader.sym(), Some(Token::Final(_, _)));
+    if is_final {
+        reader.next();
+    }
+    if let Some(variable) = loop_variable(reader) {
+        // A final loop variable is always declared by the loop.
+        let variable = match variable.nodetype {
+            NodeType::Name(name) if is_final => Node::new(NodeType::VarPattern(String::from("var"), name)),
+            _ => variable
+        };
+        return for_in(variable, is_final, reader, ctx);
+    }
+    match reader.sym() {
+        Some(Token::Name(n1, _, _)) if n1 == "var" && matches!(reader.peek(), Some(Token::Paren1(_, _))) => {
+            reader.next();
+            let pattern = declared_pattern(reader, ctx);
+            if is_in(reader) {
+                return for_in(pattern, false, reader, ctx);
+            }
+            let assign = pattern_assign(pattern, reader, ctx);
+            for_loop(assign, reader, ctx)
+        }
+        Some(Token::Paren1(_, _)) if is_final => {
+            let pattern = declared_pattern(reader, ctx);
+            for_in(pattern, true, reader, ctx)
+        }
+        Some(Token::Name(n1, _, _)) => {
+            reader.next();
+            match reader.sym() {
+                Some(Token::Name(n2, _, _)) => {
+                    reader.next();
+                    let typvar = Node::new(NodeType::TypedVar(n1.clone(), n2.clone()));
+                    if let Err(e) = reader.skip("=", ctx) {
+                        showln!(red_bold, "error", white_bold, "Error while skipping '=': ", yellow_bold, e);
+                        return Node::new(NodeType::Null);
+                    }
+                    let initexpr = expression(reader, ctx);
+                    let mut assign = Node::new(NodeType::Assign);
+                    assign.children.push(typvar);
+                    assign.children.push(initexpr);
+                    for_loop(assign, reader, ctx)
+                }
+                Some(Token::Assign(_, _)) => {
+                    reader.next();
+                    let initexpr = expression(reader, ctx);
+                    let mut assign = Node::new(NodeType::Assign);
+                    let namenode = Node::new(NodeType::Name(n1.clone()));
+                    assign.children.push(namenode);
+                    assign.children.push(initexpr);
+                    for_loop(assign, reader, ctx)
+                }
+                Some(x) => {
+                    dart_parseerror(
+                        format!("Expected identifier or assignment. Got: {:?}", x),
+                        ctx,
+                        &reader.tokens(),
+                        reader.pos()
+                    );
+                    Node::new(NodeType::Null)
+                }
+                None => {
+                    showln!(red_bold, "error", white_bold, "Unexpected end of tokens.");
+                    Node::new(NodeType::Null)
+                }
+            }
+        }
+        _ => {
+            dart_parseerror(
+                "Expected identifier.",
+                ctx,
+                &reader.tokens(),
+                reader.pos()
+            );
+            Node::new(NodeType::Null)
+        }
+    }
+}
+
+// The rest of a for loop header after its initializer: '; cond; step)'.
+fn for_loop(init: Node, reader: &mut Reader, ctx: &Ctx) -> Node {
+    if let Err(e) = reader.skip(";", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping ';': ", yellow_bold, e);
+        return Node::new(NodeType::Null);
+    }
+    let condexpr = expression(reader, ctx);
+    if let Err(e) = reader.skip(";", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping ';': ", yellow_bold, e);
+        return Node::new(NodeType::Null);
+    }
+    let mutexpr = expression(reader, ctx);
+    if let Err(e) = reader.skip(")", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping ')': ", yellow_bold, e);
+        return Node::new(NodeType::Null);
+    }
+    let mut forloop = Node::new(NodeType::For);
+    forloop.children.extend([init, condexpr, mutexpr]);
+    forloop
+}
+
+// The rest of a for-in loop header after its loop variable: 'in xs)'.
+fn for_in(variable: Node, is_final: bool, reader: &mut Reader, ctx: &Ctx) -> Node {
+    if let Err(e) = reader.skip("in", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping 'in': ", yellow_bold, e);
+        return Node::new(NodeType::Null);
+    }
+    let iterable = expression(reader, ctx);
+    if let Err(e) = reader.skip(")", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping ')': ", yellow_bold, e);
+        return Node::new(NodeType::Null);
+    }
+    let mut forloop = Node::new(NodeType::ForIn(is_final));
+    forloop.children.extend([variable, iterable]);
+    forloop
+}
+
+// The loop variable of 'for (x in xs)' or 'for (int x in xs)', if the loop is a for-in loop.
+// A variable declared with 'var' or a type is a pattern, like those of 'var (a, b) in pairs'.
+fn loop_variable(reader: &mut Reader) -> Option<Node> {
+    let pos = reader.pos();
+    let tokens = reader.tokens();
+    let is_in = |i: usize| matches!(tokens.get(i), Some(Token::Name(n, _, _)) if n == "in");
+    let (variable, end) = match (tokens.get(pos), tokens.get(pos + 1)) {
+        (Some(Token::Name(name, _, _)), _) if is_in(pos + 1) => (NodeType::Name(name.clone()), pos + 1),
+        (Some(Token::Name(t, _, _)), Some(Token::Name(name, _, _))) if is_in(pos + 2) => {
+            (NodeType::VarPattern(t.clone(), name.clone()), pos + 2)
+        }
+        _ => return None
+    };
+    while reader.pos() < end {
+        reader.next();
+    }
+    Some(Node::new(variable))
+}
+
+fn is_in(reader: &Reader) -> bool {
+    matches!(reader.sym(), Some(Token::Name(n, _, _)) if n == "in")
+}
+
+// A pattern variable declaration, from the pattern: 'var (a, b) = r;'.
+fn pattern_declaration(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let pattern = declared_pattern(reader, ctx);
+    pattern_assign(pattern, reader, ctx)
+}
+
+// The pattern of a pattern variable declaration. Its names declare variables.
+fn declared_pattern(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let pattern = binders(record_pattern(reader, ctx));
+    if !matches!(pattern.nodetype, NodeType::RecordPattern) {
+        // As dart.
+        dart_parseerror("Expected an identifier.", ctx, reader.tokens(), reader.pos())
+    }
+    pattern
+}
+
+// The '= r' of a pattern variable declaration.
+fn pattern_assign(pattern: Node, reader: &mut Reader, ctx: &Ctx) -> Node {
+    if let Err(e) = reader.skip("=", ctx) {
+        showln!(red_bold, "error", white_bold, "Error while skipping '=': ", yellow_bold, e);
+    }
+    let mut assign = Node::new(NodeType::Assign);
+    assign.children.push(pattern);
+    assign.children.push(expression(reader, ctx));
+    assign
+}
+
+fn constant_pattern(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let mut node = Node::new(NodeType::ConstPattern);
+    node.children.push(operand(reader, ctx));
+    node
+}
+
+pub fn statement(reader: &mut Reader, ctx: &Ctx) -> Node {
+    record_type(reader);
+    match reader.sym() {
+        Some(Token::Name(s, line, col)) if s == "await" && matches!(reader.peek(), Some(Token::For(_, _))) => {
+            reader.next();
+            match statement(reader, ctx) {
+                Node { nodetype: NodeType::ForIn(is_final), children, .. } => Node { nodetype: NodeType::AwaitFor(is_final), children, pos: (line, col) },
+                // As dart.
+                _ => dart_parseerror("Expected 'in'.", ctx, reader.tokens(), reader.pos())
+            }
+        }
+        Some(Token::Name(s, _, _)) if s == "yield" => {
+            let star = matches!(reader.next(), Some(Token::Mul(_, _)));
+            if star {
+                reader.next();
+            }
+            let mut node = Node::new(NodeType::Yield(star));
+            node.children.push(expression(reader, ctx));
+            node
+        }
+        // 'await f();' is an expression, not a declaration.
+        Some(Token::Name(s, _, _)) if s != "await" => {
             let t2 = reader.peek();
             match t2 {
                 Some(Token::Name(name, _, _)) => {
@@ -495,16 +1633,18 @@ fn statement(reader: &mut Reader, ctx: &Ctx) -> Node {
                         }
                         Some(Token::Paren1(_, _)) => {
                             let params = paramlist(reader, ctx);
-                            if let Err(e) = reader.skip("{", ctx) {
-                                showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
-                                return Node::new(NodeType::Null);
-                            }
-                            let body = block(reader, ctx);
-                            let mut funcnode = Node::new(NodeType::FunDef(name.clone(), ctx.filepath.clone()));
+                            let body = funbody(reader, &s, ctx);
+                            let (name, typeparams) = types::split(&name);
+                            let typeparams = typeparams.iter().map(|p| types::param_name(p)).collect();
+                            let mut funcnode = Node::new(NodeType::FunDef(name, ctx.filepath.clone(), s.clone(), typeparams));
                             funcnode.children.push(params);
                             funcnode.children.push(body);
                             funcnode
                         }
+                        Some(Token::EndSt(_, _)) => {
+                            // A declaration without initializer.
+                            typed_var
+                        }
                         Some(x) => {
                             showln!(red_bold, "error", white_bold, "Unexpected token: {:?}", x);
                             Node::new(NodeType::Null)
@@ -515,6 +1655,15 @@ fn statement(reader: &mut Reader, ctx: &Ctx) -> Node {
                         }
                     }
                 }
+                Some(Token::Colon(_, _)) => {
+                    reader.next();
+                    reader.next();
+                    labeled(s, statement(reader, ctx))
+                }
+                Some(Token::Paren1(_, _)) if s == "var" => {
+                    reader.next();
+                    pattern_declaration(reader, ctx)
+                }
                 Some(Token::Assign(_, _)) => {
                     reader.next();
                     reader.next();
@@ -525,9 +1674,14 @@ fn statement(reader: &mut Reader, ctx: &Ctx) -> Node {
                     ass_node.children.push(right_node);
                     ass_node
                 }
-                _ => expression(reader, ctx),
+                _ => assignment(reader, ctx),
             }
         }
+        Some(Token::Final(_, _)) |
+        Some(Token::Const(_, _)) |
+        Some(Token::Late(_, _)) => {
+            declaration(reader, ctx)
+        }
         Some(Token::If(_, _)) => {
             let mut condnode = Node::new(NodeType::Conditional);
             let condpart = conditional(reader, ctx);
@@ -543,6 +1697,18 @@ fn statement(reader: &mut Reader, ctx: &Ctx) -> Node {
             }
             condnode
         }
+        Some(Token::Try(_, _)) => {
+            trystatement(reader, ctx)
+        }
+        Some(Token::Switch(_, _)) => {
+            switchstatement(reader, ctx)
+        }
+        Some(Token::Break(_, _)) => {
+            Node::new(NodeType::Break(jumplabel(reader)))
+        }
+        Some(Token::Continue(_, _)) => {
+            Node::new(NodeType::Continue(jumplabel(reader)))
+        }
         Some(Token::While(_, _)) => {
             reader.next();
             if let Err(e) = reader.skip("(", ctx) {
@@ -590,104 +1756,24 @@ fn statement(reader: &mut Reader, ctx: &Ctx) -> Node {
             node
         }
         Some(Token::For(_, _)) => {
-            reader.next();
-            if let Err(e) = reader.skip("(", ctx) {
-                showln!(red_bold, "error", white_bold, "Error while skipping '(': ", yellow_bold, e);
-                return Node::new(NodeType::Null);
+            let mut forloop = for_header(reader, ctx);
+            if let NodeType::Null = forloop.nodetype {
+                return forloop;
             }
-            match reader.sym() {
-                Some(Token::Name(n1, _, _)) => {
-                    reader.next();
-                    match reader.sym() {
-                        Some(Token::Name(n2, _, _)) => {
-                            reader.next();
-                            let typvar = Node::new(NodeType::TypedVar(n1.clone(), n2.clone()));
-                            if let Err(e) = reader.skip("=", ctx) {
-                                showln!(red_bold, "error", white_bold, "Error while skipping '=': ", yellow_bold, e);
-                                return Node::new(NodeType::Null);
-                            }
-                            let initexpr = expression(reader, ctx);
-                            let mut assign = Node::new(NodeType::Assign);
-                            assign.children.push(typvar);
-                            assign.children.push(initexpr);
-                            if let Err(e) = reader.skip(";", ctx) {
-                                showln!(red_bold, "error", white_bold, "Error while skipping ';': ", yellow_bold, e);
-                                return Node::new(NodeType::Null);
-                            }
-                            let condexpr = expression(reader, ctx);
-                            if let Err(e) = reader.skip(";", ctx) {
-                                showln!(red_bold, "error", white_bold, "Error while skipping ';': ", yellow_bold, e);
-                                return Node::new(NodeType::Null);
-                            }
-                            let mutexpr = expression(reader, ctx);
-                            if let Err(e) = reader.skip(")", ctx) {
-                                showln!(red_bold, "error", white_bold, "Error while skipping ')': ", yellow_bold, e);
-                                return Node::new(NodeType::Null);
-                            }
-                            if let Err(e) = reader.skip("{", ctx) {
-                                showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
-                                return Node::new(NodeType::Null);
-                            }
-                            let body = block(reader, ctx);
-                            let mut forloop = Node::new(NodeType::For);
-                            forloop.children.extend([assign, condexpr, mutexpr, body]);
-                            forloop
-                        }
-                        Some(Token::Assign(_, _)) => {
-                            reader.next();
-                            let initexpr = expression(reader, ctx);
-                            let mut assign = Node::new(NodeType::Assign);
-                            let namenode = Node::new(NodeType::Name(n1.clone()));
-                            assign.children.push(namenode);
-                            assign.children.push(initexpr);
-                            if let Err(e) = reader.skip(";", ctx) {
-                                showln!(red_bold, "error", white_bold, "Error while skipping ';': ", yellow_bold, e);
-                                return Node::new(NodeType::Null);
-                            }
-                            let condexpr = expression(reader, ctx);
-                            if let Err(e) = reader.skip(";", ctx) {
-                                showln!(red_bold, "error", white_bold, "Error while skipping ';': ", yellow_bold, e);
-                                return Node::new(NodeType::Null);
-                            }
-                            let mutexpr = expression(reader, ctx);
-                            if let Err(e) = reader.skip(")", ctx) {
-                                showln!(red_bold, "error", white_bold, "Error while skipping ')': ", yellow_bold, e);
-                                return Node::new(NodeType::Null);
-                            }
-                            if let Err(e) = reader.skip("{", ctx) {
-                                showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
-                                return Node::new(NodeType::Null);
-                            }
-                            let body = block(reader, ctx);
-                            let mut forloop = Node::new(NodeType::For);
-                            forloop.children.extend([assign, condexpr, mutexpr, body]);
-                            forloop
-                        }
-                        Some(x) => {
-                            dart_parseerror(
-                                format!("Expected identifier or assignment. Got: {:?}", x),
-                                ctx,
-                                &reader.tokens(),
-                                reader.pos()
-                            );
-                            Node::new(NodeType::Null)
-                        }
-                        None => {
-                            showln!(red_bold, "error", white_bold, "Unexpected end of tokens.");
-                            Node::new(NodeType::Null)
-                        }
-                    }
-                }
-                _ => {
-                    dart_parseerror(
-                        "Expected identifier.",
-                        ctx,
-                        &reader.tokens(),
-                        reader.pos()
-                    );
-                    Node::new(NodeType::Null)
-                }
+            if let Err(e) = reader.skip("{", ctx) {
+                showln!(red_bold, "error", white_bold, "Error while skipping '{': ", yellow_bold, e);
+                return Node::new(NodeType::Null);
             }
+            forloop.children.push(block(reader, ctx));
+            forloop
+        }
+        Some(Token::Block1(_, _)) => {
+            // At the start of a statement, '{' is a block and never a map literal.
+            reader.next();
+            let body = block(reader, ctx);
+            let mut scope = Node::new(NodeType::Scope);
+            scope.children.push(body);
+            scope
         }
         Some(Token::Return(_, _)) => {
             reader.next();
@@ -696,7 +1782,95 @@ fn statement(reader: &mut Reader, ctx: &Ctx) -> Node {
             ret.children.push(val);
             ret
         }
-        _ => expression(reader, ctx),
+        _ => assignment(reader, ctx),
+    }
+}
+
+// A local variable declared final, const or late, with or without a type.
+// Late locals are otherwise like any other.
+fn declaration(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let modifiers = modifiers(reader);
+    let is_const = modifiers.iter().any(|m| m == "const");
+
+    if !record_type(reader) && matches!(reader.sym(), Some(Token::Paren1(_, _))) {
+        return finalized(&modifiers, pattern_declaration(reader, ctx));
+    }
+
+    let (typ, name) = match (reader.sym(), reader.peek()) {
+        (Some(Token::Name(typ, _, _)), Some(Token::Name(name, _, _))) => {
+            reader.next();
+            (typ, name)
+        }
+        (Some(Token::Name(name, _, _)), _) => (String::from("var"), name),
+        // As dart.
+        _ => dart_parseerror("Expected an identifier.", ctx, reader.tokens(), reader.pos())
+    };
+    let var = Node::new(NodeType::TypedVar(typ, name.clone()));
+
+    let decl = match reader.next() {
+        Some(Token::Assign(_, _)) => {
+            reader.next();
+            let value = expression(reader, ctx);
+            let mut assign = Node::new(NodeType::Assign);
+            assign.children.push(var);
+            assign.children.push(if is_const { constant(value, reader, ctx) } else { value });
+            assign
+        }
+        // As dart.
+        _ if is_const => dart_parseerror(format!("The const variable '{}' must be initialized.", name), ctx, reader.tokens(), reader.pos()),
+        _ => var
+    };
+    finalized(&modifiers, decl)
+}
+
+// A declaration made final if its modifiers say so.
+fn finalized(modifiers: &[String], decl: Node) -> Node {
+    match modifiers.iter().find(|m| *m == "final" || *m == "const") {
+        Some(keyword) => {
+            let mut node = Node::new(NodeType::Final(keyword.clone()));
+            node.children.push(decl);
+            node
+        }
+        None => decl
+    }
+}
+
+// The modifiers a declaration starts with, like 'static final'.
+fn modifiers(reader: &mut Reader) -> Vec<String> {
+    let mut modifiers = Vec::new();
+    while let Some(token @ (Token::Static(_, _) | Token::Final(_, _) | Token::Const(_, _) | Token::Late(_, _))) = reader.sym() {
+        modifiers.push(token.to_string());
+        reader.next();
+    }
+    modifiers
+}
+
+// An expression statement, which may turn out to be the target of an assignment: xs[i] = v.
+fn assignment(reader: &mut Reader, ctx: &Ctx) -> Node {
+    let left = expression(reader, ctx);
+
+    match reader.sym() {
+        Some(Token::Assign(_, _)) => {
+            reader.next();
+            let right_node = expression(reader, ctx);
+            let mut ass_node = Node::new(NodeType::Assign);
+            ass_node.children.push(assigned_pattern(left));
+            ass_node.children.push(right_node);
+            ass_node
+        }
+        Some(Token::IfNullAssign(_, _)) => {
+            // 'a ??= b' assigns 'b' to 'a' only if 'a' is null.
+            reader.next();
+            let right_node = expression(reader, ctx);
+            let mut ass_node = Node::new(NodeType::Assign);
+            ass_node.children.push(left.clone());
+            ass_node.children.push(right_node);
+            let mut node = Node::new(NodeType::IfNullAssign);
+            node.children.push(left);
+            node.children.push(ass_node);
+            node
+        }
+        _ => left,
     }
 }
 
@@ -59,6 +59,11 @@ impl Reader {
         self.tokens.get(self.pos + 1).cloned()
     }
 
+    // Replaces the tokens from the current one up to end with a single token.
+    pub fn replace(&mut self, end: usize, token: Token) {
+        self.tokens.splice(self.pos..end, [token]);
+    }
+
     pub fn tokens(&self) -> &Vec<Token> {
         &self.tokens
     }
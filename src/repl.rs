@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use minimo::showln;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+use crate::context::*;
+use crate::evaluator;
+use crate::future::{self, EventLoop};
+use crate::lexer;
+use crate::node::{Node, NodeType};
+use crate::object::Object;
+use crate::objsys::ObjSys;
+use crate::parser;
+use crate::reader::Reader;
+use crate::stack::Stack;
+use crate::token::Token;
+use crate::types;
+
+// Name of the looktable holding declarations made at the prompt.
+pub static REPLPATH: &str = "repl";
+
+static HISTFILE: &str = ".redart_history";
+
+static HELP: &str = "\
+:load <file.dart>  Load the declarations of a file into the session.
+:reset             Forget all declarations and variables.
+:help              Show this text.
+:quit              Leave the repl.";
+
+
+// Everything that must survive between two inputs.
+struct Session {
+    globals: Vec<Node>,
+    memo: HashMap<String, (usize, usize)>,
+    looktables: HashMap<String, HashMap<String, usize>>,
+    store: Stack,
+    objsys: ObjSys,
+}
+
+
+impl Session {
+
+    fn new() -> Session {
+        let mut looktables = HashMap::new();
+        looktables.insert(String::from(REPLPATH), HashMap::new());
+
+        let mut store = Stack::new();
+        store.push_call();
+
+        Session {
+            globals: Vec::new(),
+            memo: HashMap::new(),
+            looktables,
+            store,
+            objsys: ObjSys::new(),
+        }
+    }
+
+
+    // Parse and run one complete input, which may hold several declarations and statements.
+    fn eval_input(&mut self, input: &str, ctx: &mut Ctx) {
+        let mut reader = lexer::lex(input);
+
+        while reader.more() {
+            let startpos = reader.pos();
+
+            match reader.sym() {
+                Some(Token::EndSt(_, _)) => {
+                    reader.next();
+                    continue;
+                }
+                Some(Token::Class(_, _)) |
+                Some(Token::Abstract(_, _)) |
+                Some(Token::Mixin(_, _)) |
+                Some(Token::Enum(_, _)) => {
+                    self.declare(&mut reader, ctx);
+                }
+                Some(Token::Name(s, _, _)) if types::split(&s).0 == "extension" && parser::is_extension(&reader) => {
+                    self.declare(&mut reader, ctx);
+                }
+                Some(Token::Name(_, _, _)) if is_fundecl(&reader) => {
+                    self.declare(&mut reader, ctx);
+                }
+                _ => {
+                    let node = parser::statement(&mut reader, ctx);
+                    let result = evaluator::eval(&node, &self.looktables, &self.globals, &mut self.store, &mut self.objsys, ctx);
+
+                    if let Object::Throw(_, _) = result {
+                        evaluator::unhandled(&result, &self.objsys, ctx);
+                    }
+                    if is_expression(&node) {
+                        self.echo(&result, ctx);
+                    }
+                    // What the input left for later runs before the next prompt.
+                    future::run_event_loop(&self.looktables, &self.globals, &mut self.store, &mut self.objsys, ctx);
+                }
+            }
+
+            if reader.pos() == startpos {
+                showln!(red_bold, "error", white_bold, "Could not parse input at: ", yellow_bold, format!("{}", reader.sym().unwrap()));
+                return;
+            }
+        }
+    }
+
+
+    // Top level functions and classes go to globals, just like when read from a file.
+    fn declare(&mut self, reader: &mut Reader, ctx: &mut Ctx) {
+        let start = self.globals.len();
+        let classname = declared_class(reader);
+        parser::decl(reader, &mut self.objsys, &mut self.globals, ctx);
+        let table = self.looktables.get_mut(REPLPATH).unwrap();
+        crate::index_globals(&self.globals, start, self.globals.len(), table);
+
+        if let Some(name) = classname {
+            self.objsys.check_class(&name, ctx);
+        }
+    }
+
+
+    fn load(&mut self, path: &str, ctx: &mut Ctx) {
+        let path = Path::new(path);
+
+        if !path.is_file() {
+            showln!(red_bold, "error ", gray_dim, "could not open file: ", yellow_bold, path.display());
+            return;
+        }
+
+        let basepath = match path.parent().and_then(|p| p.to_str()) {
+            Some("") | None => String::from("."),
+            Some(p) => String::from(p),
+        };
+        let filename = String::from(path.file_name().unwrap().to_str().unwrap());
+
+        crate::filecurse(
+            basepath,
+            filename.clone(),
+            &mut self.memo,
+            &mut self.looktables,
+            &mut self.globals,
+            &mut self.store,
+            &mut self.objsys,
+            ctx
+        );
+
+        // Make the loaded declarations (and what the file imports) visible from the prompt.
+        let loaded = self.looktables[&filename].clone();
+        self.looktables.get_mut(REPLPATH).unwrap().extend(loaded);
+    }
+
+
+    fn echo(&mut self, obj: &Object, ctx: &mut Ctx) {
+        if let Object::Null = obj {
+            return;
+        }
+        // As dart prints it, by its toString.
+        match evaluator::stringify(obj, &self.looktables, &self.globals, &mut self.store, &mut self.objsys, ctx) {
+            thrown @ Object::Throw(_, _) => evaluator::unhandled(&thrown, &self.objsys, ctx),
+            text => println!("{}", text)
+        }
+    }
+
+
+    // Drop whatever frames an aborted evaluation left behind.
+    fn recover(&mut self, ctx: &mut Ctx) {
+        while self.store.call_level > 1 {
+            self.store.pop_call();
+        }
+        while self.store.lex_level > 1 {
+            self.store.pop_lex();
+        }
+        self.objsys.set_this(String::new());
+        ctx.filepath = String::from(REPLPATH);
+        ctx.calls.clear();
+        ctx.stringifying.clear();
+        ctx.events = EventLoop::default();
+    }
+}
+
+
+pub fn run(ctx: &mut Ctx) {
+    let mut rl = match DefaultEditor::new() {
+        Ok(rl) => rl,
+        Err(e) => {
+            showln!(red_bold, "error ", gray_dim, "could not start line editor: ", yellow_bold, e);
+            return;
+        }
+    };
+    let histfile = history_path();
+    if let Some(path) = &histfile {
+        rl.load_history(path).ok();
+    }
+
+    // Errors are reported by panicking, so keep the message and drop the rest.
+    ctx.debug = true;
+    panic::set_hook(Box::new(|info| {
+        if let Some(s) = info.payload().downcast_ref::<String>() {
+            println!("{}", s);
+        }
+        else if let Some(s) = info.payload().downcast_ref::<&str>() {
+            println!("{}", s);
+        }
+    }));
+
+    let mut session = Session::new();
+    let mut buffer = String::new();
+
+    loop {
+        let prompt = if buffer.is_empty() { ">> " } else { ".. " };
+
+        match rl.readline(prompt) {
+            Ok(line) => {
+                buffer.push_str(&line);
+                buffer.push('\n');
+
+                // Keep reading until all braces, brackets and parens are closed.
+                if nesting(&buffer) > 0 {
+                    continue;
+                }
+
+                let input = std::mem::take(&mut buffer);
+                let input = input.trim();
+                if input.is_empty() {
+                    continue;
+                }
+                rl.add_history_entry(input).ok();
+
+                if let Some(cmd) = input.strip_prefix(':') {
+                    let mut parts = cmd.splitn(2, char::is_whitespace);
+                    match (parts.next().unwrap_or(""), parts.next().map(str::trim)) {
+                        ("load", Some(path)) => {
+                            let res = panic::catch_unwind(AssertUnwindSafe(|| session.load(path, ctx)));
+                            if res.is_err() {
+                                session.recover(ctx);
+                            }
+                            ctx.filepath = String::from(REPLPATH);
+                        }
+                        ("reset", None) => {
+                            session = Session::new();
+                        }
+                        ("help", None) => {
+                            println!("{}", HELP);
+                        }
+                        ("quit", None) | ("q", None) => {
+                            break;
+                        }
+                        _ => {
+                            showln!(red_bold, "error ", gray_dim, "unknown command: ", yellow_bold, input);
+                            println!("{}", HELP);
+                        }
+                    }
+                    continue;
+                }
+
+                let source = format!("{}\n", input);
+                let res = panic::catch_unwind(AssertUnwindSafe(|| session.eval_input(&source, ctx)));
+                if res.is_err() {
+                    session.recover(ctx);
+                }
+            }
+            Err(ReadlineError::Interrupted) => {
+                // Ctrl-C throws away a half-written input.
+                buffer.clear();
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                showln!(red_bold, "error ", gray_dim, "could not read line: ", yellow_bold, e);
+                break;
+            }
+        }
+    }
+
+    if let Some(path) = &histfile {
+        rl.save_history(path).ok();
+    }
+    drop(panic::take_hook());
+}
+
+
+// Type name function name '(' at top level starts a function declaration.
+fn is_fundecl(reader: &Reader) -> bool {
+    matches!(reader.peek(), Some(Token::Name(_, _, _)))
+        && matches!(reader.tokens().get(reader.pos() + 2), Some(Token::Paren1(_, _)))
+}
+
+
+// Where the history of the prompt is kept: in the XDG data directory if there is one, else in
+// the home directory. Without either, it is not kept.
+fn history_path() -> Option<PathBuf> {
+    if let Some(data) = env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        let dir = Path::new(&data).join("redart");
+        return fs::create_dir_all(&dir).ok().map(|_| dir.join("history"));
+    }
+    env::var_os("HOME").map(|home| Path::new(&home).join(HISTFILE))
+}
+
+
+// The name of the class, mixin or enum a declaration starts, if it does.
+fn declared_class(reader: &Reader) -> Option<String> {
+    let tokens = reader.tokens();
+    let mut i = reader.pos();
+
+    while let Some(Token::Abstract(_, _)) | Some(Token::Class(_, _)) | Some(Token::Mixin(_, _)) | Some(Token::Enum(_, _)) = tokens.get(i) {
+        i += 1;
+    }
+    match (i > reader.pos(), tokens.get(i)) {
+        (true, Some(Token::Name(name, _, _))) => Some(name.clone()),
+        _ => None
+    }
+}
+
+
+// Statements whose value is worth echoing.
+fn is_expression(node: &Node) -> bool {
+    !matches!(
+        node.nodetype,
+        NodeType::Assign |
+        NodeType::Conditional |
+        NodeType::While |
+        NodeType::DoWhile |
+        NodeType::For |
+        NodeType::FunDef(..) |
+        NodeType::Return
+    )
+}
+
+
+// How many brackets are left open in src, ignoring strings and comments.
+fn nesting(src: &str) -> i64 {
+    let chars: Vec<char> = src.chars().collect();
+    let mut depth: i64 = 0;
+    let mut quote: Option<char> = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+        }
+        else {
+            match c {
+                '\'' | '"' => quote = Some(c),
+                '/' if chars.get(i + 1) == Some(&'/') => {
+                    while i < chars.len() && chars[i] != '\n' {
+                        i += 1;
+                    }
+                }
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' => depth -= 1,
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+    depth
+}
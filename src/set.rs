@@ -0,0 +1,43 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::context::*;
+use crate::exception;
+use crate::iterable;
+use crate::map::DartMap;
+use crate::object::*;
+use crate::utils::dart_evalerror;
+
+
+// The members sets have besides the Iterable methods, which extensions on Set can't replace.
+pub const MEMBERS: [&str; 10] = ["length", "isEmpty", "isNotEmpty", "first", "last", "iterator", "add", "addAll", "remove", "contains"];
+
+
+// The members of Set<E> as declared in dart, for the checker.
+pub const SIGNATURES: [&str; 10] = [
+    "int get length", "bool get isEmpty", "bool get isNotEmpty", "E get first", "E get last", "Iterator<E> get iterator",
+    "bool add(E value)", "void addAll(Iterable<E> elements)", "bool remove(Object? value)", "bool contains(Object? element)"
+];
+
+
+// A set is kept as the keys of a map, whose values are all null.
+pub fn get_property(set: &Rc<RefCell<DartMap>>, elemtype: &str, name: &str, ctx: &Ctx) -> Object {
+    let set = set.borrow();
+
+    match name {
+        "length" => Object::Int(set.len() as i64),
+        "isEmpty" => Object::Bool(set.len() == 0),
+        "isNotEmpty" => Object::Bool(set.len() > 0),
+        "first" => match set.entries().first() {
+            Some((elem, _)) => elem.clone(),
+            // As dart.
+            None => exception::error("StateError", "No element", ctx)
+        },
+        "last" => match set.entries().last() {
+            Some((elem, _)) => elem.clone(),
+            None => exception::error("StateError", "No element", ctx)
+        },
+        "iterator" => iterable::new_iterator(set.entries().iter().map(|(elem, _)| elem.clone()).collect(), elemtype),
+        _ => dart_evalerror(format!("The getter '{}' isn't defined for the class 'Set'.", name), ctx)
+    }
+}
@@ -1,7 +1,59 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
 use crate::object::*;
 
 
+// A lexical frame. Shared, so that closures can keep it alive
+// and mutate it after the function that made it has returned.
+pub type Frame = Rc<RefCell<Scope>>;
+
+
+// The variables of a lexical scope.
+#[derive(Clone, Default)]
+pub struct Scope {
+    vars: HashMap<String, Object>,
+    // Final and const variables, with the keyword they were declared with.
+    // A final one declared without a value is final once assigned.
+    finals: HashMap<String, String>,
+    unassigned: HashSet<String>,
+    // Variables declared dynamic. Extension members don't apply to their values.
+    dynamics: HashSet<String>
+}
+
+
+// What a closure captured where it was defined: the lexical
+// frames of the enclosing function, and its 'this'.
+#[derive(Clone, Default)]
+pub struct Env {
+    pub frames: Vec<Frame>,
+    pub this: String
+}
+
+
+impl Env {
+    // Where an extension member runs. 'this' is a variable there, as the receiver may be of any type.
+    pub fn extension(receiver: Object) -> Env {
+        let this = match &receiver {
+            Object::Reference(refid) => refid.clone(),
+            _ => String::new()
+        };
+        let frame = Frame::default();
+        frame.borrow_mut().vars.insert(String::from("this"), receiver);
+        Env { frames: vec![frame], this }
+    }
+}
+
+
+impl fmt::Debug for Env {
+    // The frames may well contain the closure itself.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Env({} frames)", self.frames.len())
+    }
+}
+
+
 pub struct Stack {
     // Two-level storage of "stack" data.
     //
@@ -20,12 +72,15 @@ pub struct Stack {
     //  - loop-blocks.
     //  - nested function definitions.
     //
+    // A closure call starts its call-frame with the lex-frames
+    // captured where the closure was defined, see Env.
+    //
     // A 'frame' means current lex-frame in the current call-frame.
     //
     // This data structure must be initialised by calling "push_call"
     // to make storage available.
 
-    pub stack: Vec<Vec<HashMap<String, Object>>>,
+    pub stack: Vec<Vec<Frame>>,
     pub call_level: usize,
     pub lex_level: usize
 }
@@ -44,11 +99,32 @@ impl Stack {
 
     // Add a new frame to the call-stack - a new function scope (lex-stack).
     pub fn push_call(&mut self) {
-        let mut lexstack = Vec::new();
-        lexstack.push(HashMap::new());
+        self.push_closure_call(&Env::default());
+    }
+
+
+    // Add a new frame to the call-stack, on top of the captured lex-frames of a closure.
+    pub fn push_closure_call(&mut self, env: &Env) {
+        let mut lexstack = env.frames.clone();
+        lexstack.push(Frame::default());
+        self.lex_level = lexstack.len();
         self.stack.push(lexstack);
         self.call_level += 1;
-        self.lex_level = 1;
+    }
+
+
+    // Continue a call-frame that was left with its lex-frames, as an async function
+    // does when it resumes after an 'await'.
+    pub fn resume_call(&mut self, frames: Vec<Frame>) {
+        self.lex_level = frames.len();
+        self.stack.push(frames);
+        self.call_level += 1;
+    }
+
+
+    // The lex-frames a closure defined right now would capture.
+    pub fn capture(&self) -> Vec<Frame> {
+        self.stack.last().unwrap().clone()
     }
 
 
@@ -66,13 +142,21 @@ impl Stack {
 
     // Add a new frame to the lex-stack - a new lexical scope.
     pub fn push_lex(&mut self) {
-        let lexframe : HashMap<String, Object> = HashMap::new();
         let callframe = self.stack.last_mut().unwrap();
-        callframe.push(lexframe);
+        callframe.push(Frame::default());
         self.lex_level += 1;
     }
 
 
+    // Replace the top frame of the lexical stack by a copy of it. Closures that captured
+    // it keep the old one, as each iteration of a for loop has its own loop variables.
+    pub fn renew_lex(&mut self) {
+        let lexframe = self.stack.last_mut().unwrap().last_mut().unwrap();
+        let copy = lexframe.borrow().clone();
+        *lexframe = Rc::new(RefCell::new(copy));
+    }
+
+
     // Remove the top frame from lexical stack.
     pub fn pop_lex(&mut self) {
         if self.lex_level > 1 {
@@ -87,48 +171,81 @@ impl Stack {
 
     // Add a new key-value pair to the current frame.
     pub fn add(&mut self, s: &str, v: Object) {
-        let callframe = self.stack.last_mut().unwrap();
-        let lexframe = callframe.last_mut().unwrap();
-        lexframe.insert(String::from(s), v);
+        let callframe = self.stack.last().unwrap();
+        let lexframe = callframe.last().unwrap();
+        lexframe.borrow_mut().vars.insert(String::from(s), v);
     }
 
 
-    // Searches backwards through current lexical stack frames to find s.
-    pub fn has(&self, s: &str) -> bool {
+    // Make s, declared in the current frame, final or const. Without a value, it may still be assigned once.
+    pub fn make_final(&mut self, s: &str, keyword: &str, assigned: bool) {
         let callframe = self.stack.last().unwrap();
+        let mut lexframe = callframe.last().unwrap().borrow_mut();
+        if assigned {
+            lexframe.finals.insert(String::from(s), String::from(keyword));
+        }
+        else {
+            lexframe.unassigned.insert(String::from(s));
+        }
+    }
+
+
+    // Mark s, declared in the current frame, as declared dynamic.
+    pub fn make_dynamic(&mut self, s: &str) {
+        let callframe = self.stack.last().unwrap();
+        callframe.last().unwrap().borrow_mut().dynamics.insert(String::from(s));
+    }
+
+
+    pub fn is_dynamic(&self, s: &str) -> bool {
+        self.find(s).is_some_and(|lexframe| lexframe.borrow().dynamics.contains(s))
+    }
 
-        let mut ll = self.lex_level;
 
-        while ll > 0 {
-            let lexframe : &HashMap<String, Object> = callframe.get(ll - 1).unwrap();
-            if lexframe.contains_key(s) {
-                return true
+    // 'final' or 'const', if s can't be assigned.
+    pub fn final_keyword(&self, s: &str) -> Option<String> {
+        self.find(s).and_then(|lexframe| lexframe.borrow().finals.get(s).cloned())
+    }
+
+
+    // Update s in the innermost frame that has it.
+    pub fn set(&mut self, s: &str, v: Object) {
+        match self.find(s) {
+            Some(lexframe) => {
+                let mut lexframe = lexframe.borrow_mut();
+                if lexframe.unassigned.remove(s) {
+                    lexframe.finals.insert(String::from(s), String::from("final"));
+                }
+                lexframe.vars.insert(String::from(s), v);
             }
-            ll = ll - 1;
+            None => panic!("Undefined variable: {}", s)
         }
-        return false
+    }
+
+
+    // Searches backwards through current lexical stack frames to find s.
+    pub fn has(&self, s: &str) -> bool {
+        self.find(s).is_some()
     }
 
 
     pub fn has_in_lexscope(&self, s: &str) -> bool {
-        self.stack.last().unwrap().last().unwrap().contains_key(s)
+        self.stack.last().unwrap().last().unwrap().borrow().vars.contains_key(s)
     }
 
 
     // Searches backwards through current lexical stack frames to find and return s.
-    pub fn get(&self, s: &str) -> &Object {
-        let callframe = self.stack.last().unwrap();
-
-        let mut ll = self.lex_level;
-        while ll > 0 {
-            let lexframe : &HashMap<String, Object>  = callframe.get(ll - 1).unwrap();
-            if lexframe.contains_key(s) {
-                return lexframe.get(s).unwrap();
-            }
-            ll = ll - 1;
+    pub fn get(&self, s: &str) -> Object {
+        match self.find(s) {
+            Some(lexframe) => lexframe.borrow().vars.get(s).unwrap().clone(),
+            None => panic!("Undefined variable: {}", s)
         }
+    }
+
 
-        panic!("Undefined variable: {}", s)
+    fn find(&self, s: &str) -> Option<&Frame> {
+        let callframe = self.stack.last().unwrap();
+        callframe[..self.lex_level].iter().rev().find(|lexframe| lexframe.borrow().vars.contains_key(s))
     }
 
 
@@ -145,7 +262,7 @@ impl Stack {
                 let lexframe = callframe.get(ll - 1).unwrap();
 
                 println!("level {},{}:", cl, ll);
-                for (k, v) in lexframe {
+                for (k, v) in lexframe.borrow().vars.iter() {
                     println!("{} : {:?}", k, v);
                 }
                 ll -= 1;
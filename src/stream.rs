@@ -0,0 +1,644 @@
+// Streams of dart:async: those of StreamControllers, of async* functions and of Stream.fromIterable,
+// and those map, where and take make of others. Events go to each subscription in a microtask of
+// its own, in the order they were added; map, where and take pass them on in that same microtask.
+// A single-subscription stream keeps what is added before it is listened to, a broadcast stream drops it.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::rc::Rc;
+
+use crate::context::*;
+use crate::coroutine::Coroutine;
+use crate::evaluator::{runtime_type, unhandled};
+use crate::exception;
+use crate::future::{self, callback, error_args, expect_function, Job};
+use crate::iterable;
+use crate::node::Node;
+use crate::object::*;
+use crate::objsys::ObjSys;
+use crate::stack::Stack;
+use crate::types;
+use crate::utils::{dart_evalerror, expect_args};
+
+
+pub type Stream = Rc<RefCell<StreamState>>;
+pub type Subscription = Rc<RefCell<SubscriptionState>>;
+
+
+#[derive(Clone)]
+pub enum Event {
+    Data(Box<Object>),
+    // A Throw.
+    Error(Box<Object>),
+    Done
+}
+
+
+pub struct StreamState {
+    source: Source,
+    broadcast: bool,
+    subscriptions: Vec<Subscription>,
+    // What was added to a single-subscription stream before it was listened to.
+    buffer: Vec<Event>,
+    listened: bool,
+    closed: bool,
+    // What close gives, completed once Done has been delivered.
+    done: Option<future::Future>,
+    // Whether its last subscription was cancelled. An async* function generating it ends at its next yield.
+    cancelled: bool
+}
+
+
+impl fmt::Debug for StreamState {
+    // The source may well hold the stream itself.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Stream({} subscriptions)", self.subscriptions.len())
+    }
+}
+
+
+enum Source {
+    // With its onListen and onCancel callbacks.
+    Controller(Option<Box<Object>>, Option<Box<Object>>),
+    // An async* function, until it starts when listened to.
+    Generator(Option<Box<Coroutine>>),
+    // Stream.fromIterable, until it is listened to.
+    Iterable(Option<Box<Object>>),
+    // What map, where or take made of a stream, with the subscription to it while listened to.
+    Derived(Stream, Transform, Option<Subscription>)
+}
+
+
+enum Transform {
+    Map(Box<Object>),
+    Where(Box<Object>),
+    // The number of elements left to take.
+    Take(i64)
+}
+
+
+pub struct SubscriptionState {
+    stream: Stream,
+    sink: Sink,
+    cancelled: bool
+}
+
+
+impl fmt::Debug for SubscriptionState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StreamSubscription({})", if self.cancelled { "cancelled" } else { "active" })
+    }
+}
+
+
+pub enum Sink {
+    // listen(onData, onError: onError, onDone: onDone, cancelOnError: cancelOnError).
+    Callbacks(Option<Box<Object>>, Option<Box<Object>>, Option<Box<Object>>, bool),
+    // The events of a stream that map, where or take made of this one.
+    Derived(Stream),
+    // Kept until an 'await for' or a 'yield*' takes them, with the future it waits on for the next one.
+    Pull(VecDeque<Event>, Option<future::Future>),
+    // toList(), with the elements so far, their type and the future of the list.
+    Collect(Vec<Object>, String, future::Future)
+}
+
+
+impl Sink {
+    pub fn pull() -> Sink {
+        Sink::Pull(VecDeque::new(), None)
+    }
+}
+
+
+fn new_stream(source: Source, broadcast: bool) -> Stream {
+    Rc::new(RefCell::new(StreamState {
+        source,
+        broadcast,
+        subscriptions: Vec::new(),
+        buffer: Vec::new(),
+        listened: false,
+        closed: false,
+        done: None,
+        cancelled: false
+    }))
+}
+
+
+// The stream of an async* function, which runs when it is listened to.
+pub fn generator() -> Stream {
+    new_stream(Source::Generator(None), false)
+}
+
+
+pub fn set_generator(stream: &Stream, co: Coroutine) {
+    stream.borrow_mut().source = Source::Generator(Some(Box::new(co)));
+}
+
+
+pub fn is_cancelled(stream: &Stream) -> bool {
+    stream.borrow().cancelled
+}
+
+
+// Adds an event, for the subscriptions to get in microtasks.
+pub fn emit(stream: &Stream, event: Event, ctx: &mut Ctx) {
+    let mut state = stream.borrow_mut();
+    if let Event::Done = event {
+        state.closed = true;
+    }
+    if state.subscriptions.is_empty() {
+        if !state.broadcast {
+            state.buffer.push(event);
+        }
+        return;
+    }
+    for sub in &state.subscriptions {
+        ctx.events.microtask(Job::Deliver(sub.clone(), event.clone()));
+    }
+}
+
+
+pub fn listen(
+    stream: &Stream,
+    sink: Sink,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Result<Subscription, Box<Object>> {
+
+    let sub = Rc::new(RefCell::new(SubscriptionState { stream: stream.clone(), sink, cancelled: false }));
+    let first = {
+        let mut state = stream.borrow_mut();
+        if state.listened && !state.broadcast {
+            // As dart.
+            return Err(Box::new(exception::error("StateError", "Stream has already been listened to.", ctx)));
+        }
+        state.listened = true;
+        state.cancelled = false;
+        state.subscriptions.push(sub.clone());
+        for event in std::mem::take(&mut state.buffer) {
+            ctx.events.microtask(Job::Deliver(sub.clone(), event));
+        }
+        // A broadcast stream that is done tells those who listen late.
+        if state.broadcast && state.closed {
+            ctx.events.microtask(Job::Deliver(sub.clone(), Event::Done));
+        }
+        state.subscriptions.len() == 1
+    };
+    if !first {
+        return Ok(sub);
+    }
+
+    // The source starts giving events once the stream has a subscription.
+    let mut state = stream.borrow_mut();
+    match &mut state.source {
+        Source::Controller(Some(on_listen), _) => {
+            let on_listen = (**on_listen).clone();
+            drop(state);
+            callback(on_listen, Vec::new(), looktables, globals, stack, objsys, ctx)?;
+        }
+        Source::Generator(co) => {
+            if let Some(co) = co.take() {
+                ctx.events.microtask(Job::Start(co));
+            }
+        }
+        Source::Iterable(iterable) => {
+            if let Some(iterable) = iterable.take() {
+                drop(state);
+                for elem in iterable::elements(&iterable, looktables, globals, stack, objsys, ctx)? {
+                    emit(stream, Event::Data(Box::new(elem)), ctx);
+                }
+                emit(stream, Event::Done, ctx);
+            }
+        }
+        Source::Derived(_, Transform::Take(0), _) => {
+            drop(state);
+            emit(stream, Event::Done, ctx);
+        }
+        Source::Derived(parent, _, _) => {
+            let parent = parent.clone();
+            drop(state);
+            let sub = listen(&parent, Sink::Derived(stream.clone()), looktables, globals, stack, objsys, ctx)?;
+            if let Source::Derived(_, _, upstream) = &mut stream.borrow_mut().source {
+                *upstream = Some(sub);
+            }
+        }
+        Source::Controller(None, _) => {}
+    }
+    Ok(sub)
+}
+
+
+pub fn cancel(sub: &Subscription, ctx: &mut Ctx) {
+    let stream = {
+        let mut state = sub.borrow_mut();
+        if state.cancelled {
+            return;
+        }
+        state.cancelled = true;
+        state.stream.clone()
+    };
+    let mut state = stream.borrow_mut();
+    state.subscriptions.retain(|s| !Rc::ptr_eq(s, sub));
+    if !state.subscriptions.is_empty() {
+        return;
+    }
+    state.cancelled = true;
+    match &mut state.source {
+        Source::Controller(_, Some(on_cancel)) => ctx.events.microtask(Job::Call(on_cancel.clone(), None)),
+        Source::Derived(_, _, upstream) => {
+            if let Some(upstream) = upstream.take() {
+                drop(state);
+                cancel(&upstream, ctx);
+            }
+        }
+        _ => {}
+    }
+}
+
+
+// Gives an event of its stream to a subscription.
+pub fn deliver(
+    sub: &Subscription,
+    event: Event,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) {
+
+    // The future close gave completes after the sink had Done, and so after what it did with it.
+    if let Event::Done = event {
+        let stream = sub.borrow().stream.clone();
+        deliver_to_sink(sub, event, looktables, globals, stack, objsys, ctx);
+        if let Some(done) = stream.borrow_mut().done.take() {
+            future::complete(&done, Ok(Object::Null), ctx);
+        }
+        return;
+    }
+    deliver_to_sink(sub, event, looktables, globals, stack, objsys, ctx);
+}
+
+
+fn deliver_to_sink(
+    sub: &Subscription,
+    event: Event,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) {
+
+    let mut state = sub.borrow_mut();
+    if state.cancelled {
+        return;
+    }
+    match &mut state.sink {
+        Sink::Callbacks(on_data, on_error, on_done, cancel_on_error) => {
+            // The subscription ends when the stream is done, or at an error if it cancels on errors.
+            let (handler, args, ends) = match event {
+                Event::Data(value) => (on_data.clone(), vec![*value], false),
+                Event::Error(thrown) => match on_error.clone() {
+                    Some(on_error) => {
+                        let args = error_args(&on_error, *thrown);
+                        (Some(on_error), args, *cancel_on_error)
+                    }
+                    // Nothing handles an error of a stream listened to without onError.
+                    None => unhandled(&thrown, objsys, ctx)
+                },
+                Event::Done => (on_done.clone(), Vec::new(), true)
+            };
+            drop(state);
+            if ends {
+                cancel(sub, ctx);
+            }
+            if let Some(handler) = handler {
+                if let Err(thrown) = callback(*handler, args, looktables, globals, stack, objsys, ctx) {
+                    unhandled(&thrown, objsys, ctx);
+                }
+            }
+        }
+        Sink::Derived(stream) => {
+            let stream = stream.clone();
+            drop(state);
+            transform(&stream, event, looktables, globals, stack, objsys, ctx);
+        }
+        Sink::Pull(queue, waiter) => {
+            queue.push_back(event);
+            if let Some(waiter) = waiter.take() {
+                drop(state);
+                future::complete_now(&waiter, Ok(Object::Null), looktables, globals, stack, objsys, ctx);
+            }
+        }
+        Sink::Collect(items, elemtype, result) => match event {
+            Event::Data(value) => items.push(*value),
+            Event::Error(thrown) => {
+                let result = result.clone();
+                drop(state);
+                cancel(sub, ctx);
+                future::complete(&result, Err(thrown), ctx);
+            }
+            Event::Done => {
+                let list = Object::new_list(std::mem::take(items), elemtype);
+                future::complete(result, Ok(list), ctx);
+            }
+        }
+    }
+}
+
+
+// What map, where or take make of an event of the stream they were made of.
+fn transform(
+    stream: &Stream,
+    event: Event,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) {
+
+    let value = match event {
+        Event::Data(value) => value,
+        event => return forward(stream, event, looktables, globals, stack, objsys, ctx)
+    };
+    let mut state = stream.borrow_mut();
+    let (transform, upstream) = match &mut state.source {
+        Source::Derived(_, transform, upstream) => (transform, upstream),
+        _ => panic!("Not a derived stream: {:?}", state)
+    };
+    match transform {
+        Transform::Map(f) => {
+            let f = (**f).clone();
+            drop(state);
+            let event = match callback(f, vec![*value], looktables, globals, stack, objsys, ctx) {
+                Ok(mapped) => Event::Data(Box::new(mapped)),
+                Err(thrown) => Event::Error(thrown)
+            };
+            forward(stream, event, looktables, globals, stack, objsys, ctx);
+        }
+        Transform::Where(test) => {
+            let test = (**test).clone();
+            drop(state);
+            match callback(test, vec![(*value).clone()], looktables, globals, stack, objsys, ctx) {
+                Ok(Object::Bool(true)) => forward(stream, Event::Data(value), looktables, globals, stack, objsys, ctx),
+                Ok(Object::Bool(false)) => {}
+                // As dart.
+                Ok(x) => dart_evalerror(format!("type '{}' is not a subtype of type 'bool'", runtime_type(&x, objsys)), ctx),
+                Err(thrown) => forward(stream, Event::Error(thrown), looktables, globals, stack, objsys, ctx)
+            }
+        }
+        Transform::Take(left) => {
+            *left -= 1;
+            // Done once it has taken enough, without waiting for the stream it takes from.
+            let upstream = if *left == 0 { upstream.take() } else { None };
+            drop(state);
+            forward(stream, Event::Data(value), looktables, globals, stack, objsys, ctx);
+            if let Some(upstream) = upstream {
+                cancel(&upstream, ctx);
+                forward(stream, Event::Done, looktables, globals, stack, objsys, ctx);
+            }
+        }
+    }
+}
+
+
+// Gives an event of a stream that map, where or take made to its subscriptions right away,
+// as it came in the microtask that delivered it to the stream it was made of.
+fn forward(
+    stream: &Stream,
+    event: Event,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) {
+
+    let subscriptions = {
+        let mut state = stream.borrow_mut();
+        if let Event::Done = event {
+            state.closed = true;
+        }
+        if state.subscriptions.is_empty() {
+            if !state.broadcast {
+                state.buffer.push(event);
+            }
+            return;
+        }
+        state.subscriptions.clone()
+    };
+    for sub in subscriptions {
+        deliver(&sub, event.clone(), looktables, globals, stack, objsys, ctx);
+    }
+}
+
+
+// The next event an 'await for' or 'yield*' takes, or the future it waits on for one.
+pub fn pull(sub: &Subscription) -> Result<Event, future::Future> {
+    match &mut sub.borrow_mut().sink {
+        Sink::Pull(queue, waiter) => match queue.pop_front() {
+            Some(event) => Ok(event),
+            None => {
+                let next = future::pending();
+                *waiter = Some(next.clone());
+                Err(next)
+            }
+        },
+        _ => panic!("Not pulled from: {:?}", sub)
+    }
+}
+
+
+pub fn has_constructor(name: &str) -> bool {
+    name == "StreamController"
+}
+
+
+// StreamController<T>({onListen, onCancel}).
+pub fn construct(typeargs: &[String], args: Vec<Object>, ctx: &Ctx) -> Object {
+    controller(typeargs, args, false, ctx)
+}
+
+
+fn controller(typeargs: &[String], args: Vec<Object>, broadcast: bool, ctx: &Ctx) -> Object {
+    let mut on_listen = None;
+    let mut on_cancel = None;
+    for arg in args {
+        match arg {
+            Object::NamedArg(name, f) if name == "onListen" => on_listen = Some(Box::new(expect_function(&name, &f, ctx))),
+            Object::NamedArg(name, f) if name == "onCancel" => on_cancel = Some(Box::new(expect_function(&name, &f, ctx))),
+            // As dart.
+            Object::NamedArg(name, _) => dart_evalerror(format!("No named parameter with the name '{}'.", name), ctx),
+            _ => dart_evalerror("Too many positional arguments: 0 allowed, but 1 found.", ctx)
+        }
+    }
+    let elemtype = typeargs.first().cloned().unwrap_or_else(|| String::from("dynamic"));
+    Object::StreamController(new_stream(Source::Controller(on_listen, on_cancel), broadcast), elemtype)
+}
+
+
+// StreamController.broadcast, Stream.fromIterable, Stream.value, Stream.error and Stream.empty.
+// The owner is as written, with its type arguments.
+pub fn call_static(owner: &str, name: &str, args: Vec<Object>, objsys: &ObjSys, ctx: &mut Ctx) -> Object {
+    let (owner, typeargs) = types::split(owner);
+    let elemtype = |inferred: String| typeargs.first().cloned().unwrap_or(inferred);
+    // The events of a stream made of them are there once it is listened to.
+    let made = |events: Vec<Event>| {
+        let stream = new_stream(Source::Controller(None, None), false);
+        stream.borrow_mut().buffer = events;
+        stream.borrow_mut().closed = true;
+        stream
+    };
+    match (owner.as_str(), name) {
+        ("StreamController", "broadcast") => controller(&typeargs, args, true, ctx),
+        ("Stream", "fromIterable") => {
+            expect_args(name, &args, 1, ctx);
+            if !iterable::is_iterable(&args[0], objsys) {
+                // As dart.
+                dart_evalerror(format!("The argument type '{}' can't be assigned to the parameter type 'Iterable<dynamic>'.", runtime_type(&args[0], objsys)), ctx)
+            }
+            let elemtype = elemtype(iterable::element_type(&args[0], objsys));
+            let source = Source::Iterable(Some(Box::new(args[0].clone())));
+            Object::Stream(new_stream(source, false), elemtype)
+        }
+        ("Stream", "value") => {
+            expect_args(name, &args, 1, ctx);
+            let elemtype = elemtype(runtime_type(&args[0], objsys));
+            let value = args.into_iter().next().unwrap();
+            Object::Stream(made(vec![Event::Data(Box::new(value)), Event::Done]), elemtype)
+        }
+        ("Stream", "error") => {
+            expect_args(name, &args, 1, ctx);
+            let thrown = exception::throw(args[0].clone(), ctx);
+            Object::Stream(made(vec![Event::Error(Box::new(thrown)), Event::Done]), elemtype(String::from("dynamic")))
+        }
+        ("Stream", "empty") => Object::Stream(made(vec![Event::Done]), elemtype(String::from("dynamic"))),
+        // As dart.
+        _ => dart_evalerror(format!("Member not found: '{}'.", name), ctx)
+    }
+}
+
+
+pub fn get_property(owner: &Object, name: &str, ctx: &Ctx) -> Object {
+    match (owner, name) {
+        (Object::StreamController(stream, elemtype), "stream") => Object::Stream(stream.clone(), elemtype.clone()),
+        (Object::StreamController(stream, _), "isClosed") => Object::Bool(stream.borrow().closed),
+        (Object::StreamController(stream, _), "hasListener") => Object::Bool(!stream.borrow().subscriptions.is_empty()),
+        (Object::StreamController(stream, _) | Object::Stream(stream, _), "isBroadcast") => Object::Bool(stream.borrow().broadcast),
+        (Object::StreamSubscription(sub), "isCancelled") => Object::Bool(sub.borrow().cancelled),
+        // As dart.
+        _ => dart_evalerror(format!("The getter '{}' isn't defined for the class '{}'.", name, class_name(owner)), ctx)
+    }
+}
+
+
+// The methods of streams, controllers and subscriptions, with the receiver as first operand.
+pub fn call_method(
+    name: &str,
+    operands: Vec<Object>,
+    looktables: &HashMap<String, HashMap<String, usize>>,
+    globals: &Vec<Node>,
+    stack: &mut Stack,
+    objsys: &mut ObjSys,
+    ctx: &mut Ctx) -> Object {
+
+    let mut operands = operands.into_iter();
+    let receiver = operands.next().unwrap();
+    let args: Vec<Object> = operands.collect();
+    let done = |ctx: &mut Ctx| Object::Future(future::completed(Ok(Object::Null), ctx), String::from("void"));
+
+    match (&receiver, name) {
+        (Object::StreamController(stream, _), "add" | "addError") => {
+            expect_args(name, &args, 1, ctx);
+            if stream.borrow().closed {
+                // As dart.
+                return exception::error("StateError", "Cannot add new events after calling close", ctx);
+            }
+            let event = match name {
+                "add" => Event::Data(Box::new(args[0].clone())),
+                _ => Event::Error(Box::new(exception::throw(args[0].clone(), ctx)))
+            };
+            emit(stream, event, ctx);
+            Object::Null
+        }
+        (Object::StreamController(stream, _), "close") => {
+            expect_args(name, &args, 0, ctx);
+            if !stream.borrow().closed {
+                stream.borrow_mut().done = Some(future::pending());
+                emit(stream, Event::Done, ctx);
+                // With nothing to deliver it to, as a broadcast stream nobody listens to.
+                let state = stream.borrow();
+                if state.broadcast && state.subscriptions.is_empty() {
+                    future::complete(state.done.as_ref().unwrap(), Ok(Object::Null), ctx);
+                }
+            }
+            match &stream.borrow().done {
+                Some(done) => Object::Future(done.clone(), String::from("void")),
+                None => done(ctx)
+            }
+        }
+        (Object::Stream(stream, _), "listen") => {
+            let mut handlers = [None, None, None];
+            let mut cancel_on_error = false;
+            let mut positional = Vec::new();
+            for arg in args {
+                match arg {
+                    Object::NamedArg(n, value) if n == "onError" => handlers[1] = Some(Box::new(expect_function(&n, &value, ctx))),
+                    Object::NamedArg(n, value) if n == "onDone" => handlers[2] = Some(Box::new(expect_function(&n, &value, ctx))),
+                    Object::NamedArg(n, value) if n == "cancelOnError" => cancel_on_error = matches!(*value, Object::Bool(true)),
+                    // As dart.
+                    Object::NamedArg(n, _) => dart_evalerror(format!("No named parameter with the name '{}'.", n), ctx),
+                    arg => positional.push(arg)
+                }
+            }
+            expect_args(name, &positional, 1, ctx);
+            if !matches!(positional[0], Object::Null) {
+                handlers[0] = Some(Box::new(expect_function(name, &positional[0], ctx)));
+            }
+            let [on_data, on_error, on_done] = handlers;
+            let sink = Sink::Callbacks(on_data, on_error, on_done, cancel_on_error);
+            match listen(stream, sink, looktables, globals, stack, objsys, ctx) {
+                Ok(sub) => Object::StreamSubscription(sub),
+                Err(thrown) => *thrown
+            }
+        }
+        (Object::Stream(stream, elemtype), "map" | "where" | "take") => {
+            expect_args(name, &args, 1, ctx);
+            let (transform, elemtype) = match (name, &args[0]) {
+                ("take", Object::Int(n)) => (Transform::Take((*n).max(0)), elemtype.clone()),
+                // As dart.
+                ("take", x) => dart_evalerror(format!("The argument type '{}' can't be assigned to the parameter type 'int'.", runtime_type(x, objsys)), ctx),
+                ("map", f) => (Transform::Map(Box::new(expect_function(name, f, ctx))), String::from("dynamic")),
+                (_, f) => (Transform::Where(Box::new(expect_function(name, f, ctx))), elemtype.clone())
+            };
+            let broadcast = stream.borrow().broadcast;
+            Object::Stream(new_stream(Source::Derived(stream.clone(), transform, None), broadcast), elemtype)
+        }
+        (Object::Stream(stream, elemtype), "toList") => {
+            expect_args(name, &args, 0, ctx);
+            let result = future::pending();
+            let sink = Sink::Collect(Vec::new(), elemtype.clone(), result.clone());
+            match listen(stream, sink, looktables, globals, stack, objsys, ctx) {
+                Ok(_) => Object::Future(result, format!("List<{}>", elemtype)),
+                Err(thrown) => *thrown
+            }
+        }
+        (Object::StreamSubscription(sub), "cancel") => {
+            expect_args(name, &args, 0, ctx);
+            cancel(sub, ctx);
+            done(ctx)
+        }
+        // As dart.
+        _ => dart_evalerror(format!("The method '{}' isn't defined for the class '{}'.", name, class_name(&receiver)), ctx)
+    }
+}
+
+
+fn class_name(obj: &Object) -> &'static str {
+    match obj {
+        Object::Stream(_, _) => "Stream",
+        Object::StreamController(_, _) => "StreamController",
+        _ => "StreamSubscription"
+    }
+}
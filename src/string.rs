@@ -0,0 +1,59 @@
+use crate::context::*;
+use crate::exception;
+use crate::object::*;
+use crate::utils::{dart_evalerror, expect_args};
+
+
+// The members strings have, which extensions on String can't replace.
+pub const MEMBERS: [&str; 9] = ["length", "isEmpty", "isNotEmpty", "trim", "toUpperCase", "toLowerCase", "contains", "startsWith", "endsWith"];
+
+
+// The members of String as declared in dart, for the checker. Patterns are only strings here.
+pub const SIGNATURES: [&str; 9] = [
+    "int get length", "bool get isEmpty", "bool get isNotEmpty", "String trim()", "String toUpperCase()", "String toLowerCase()",
+    "bool contains(String other)", "bool startsWith(String pattern)", "bool endsWith(String other)"
+];
+
+
+pub fn get_property(s: &str, name: &str, ctx: &Ctx) -> Object {
+    match name {
+        // In UTF-16 code units, as dart.
+        "length" => Object::Int(s.encode_utf16().count() as i64),
+        "isEmpty" => Object::Bool(s.is_empty()),
+        "isNotEmpty" => Object::Bool(!s.is_empty()),
+        _ => dart_evalerror(format!("The getter '{}' isn't defined for the class 'String'.", name), ctx)
+    }
+}
+
+
+pub fn call_method(s: &str, name: &str, args: Vec<Object>, ctx: &Ctx) -> Object {
+    match name {
+        "trim" => {
+            expect_args(name, &args, 0, ctx);
+            Object::String(String::from(s.trim()))
+        }
+        "toUpperCase" => {
+            expect_args(name, &args, 0, ctx);
+            Object::String(s.to_uppercase())
+        }
+        "toLowerCase" => {
+            expect_args(name, &args, 0, ctx);
+            Object::String(s.to_lowercase())
+        }
+        "contains" | "startsWith" | "endsWith" => {
+            expect_args(name, &args, 1, ctx);
+            let other = match &args[0] {
+                Object::String(other) => other.as_str(),
+                // As dart.
+                x => dart_evalerror(format!("The argument type '{}' can't be assigned to the parameter type 'Pattern'.", x), ctx)
+            };
+            Object::Bool(match name {
+                "contains" => s.contains(other),
+                "startsWith" => s.starts_with(other),
+                _ => s.ends_with(other)
+            })
+        }
+        // As dart.
+        _ => exception::no_method("String", &Object::String(String::from(s)), name, &args, ctx)
+    }
+}
@@ -12,10 +12,12 @@ pub enum Token {
   Sub(usize, usize),
   Mul(usize, usize),
   Div(usize, usize),
+  IntDiv(usize, usize),
   Increment(usize, usize),
   Decrement(usize, usize),
   // Logic
   Not(usize, usize),
+  NotEqual(usize, usize),
   LogOr(usize, usize),
   LogAnd(usize, usize),
   BitOr(usize, usize),
@@ -27,19 +29,30 @@ pub enum Token {
   LessOrEq(usize, usize),
   GreaterOrEq(usize, usize),
   Equal(usize, usize),
+  Is(usize, usize),
   // Primitive
   Int(i64, usize, usize),
   Double(f64, usize, usize),
   Str(String, Vec<Vec<Token>>, usize, usize),
   Bool(bool, usize, usize),
+  Null(usize, usize),
   Name(String, usize, usize),
   // Structure
   Class(usize, usize),
+  Abstract(usize, usize),
+  Mixin(usize, usize),
+  Enum(usize, usize),
+  Extends(usize, usize),
+  Implements(usize, usize),
+  With(usize, usize),
   If(usize, usize),
   Else(usize, usize),
   While(usize, usize),
   Do(usize, usize),
   For(usize, usize),
+  Try(usize, usize),
+  Catch(usize, usize),
+  Finally(usize, usize),
   Paren1(usize, usize),
   Paren2(usize, usize),
   Block1(usize, usize),
@@ -47,11 +60,32 @@ pub enum Token {
   Brack1(usize, usize),
   Brack2(usize, usize),
   Comma(usize, usize),
+  Colon(usize, usize),
   // Other
   Assign(usize, usize),
+  Arrow(usize, usize),
   Access(usize, usize),
+  NullAccess(usize, usize),
+  Spread(usize, usize),
+  NullSpread(usize, usize),
+  Cascade(usize, usize),
+  NullCascade(usize, usize),
+  IfNull(usize, usize),
+  IfNullAssign(usize, usize),
   This(usize, usize),
+  Super(usize, usize),
   Return(usize, usize),
+  Break(usize, usize),
+  Continue(usize, usize),
+  Switch(usize, usize),
+  Case(usize, usize),
+  Default(usize, usize),
+  Throw(usize, usize),
+  Rethrow(usize, usize),
+  Static(usize, usize),
+  Final(usize, usize),
+  Const(usize, usize),
+  Late(usize, usize),
   Import(usize, usize),
   EndSt(usize, usize),
   End
@@ -67,10 +101,12 @@ impl fmt::Display for Token {
       Token::Sub(_, _) => write!(f, "-"),
       Token::Mul(_, _) => write!(f, "*"),
       Token::Div(_, _) => write!(f, "/"),
+      Token::IntDiv(_, _) => write!(f, "~/"),
       Token::Increment(_, _) => write!(f, "++"),
       Token::Decrement(_, _) => write!(f, "--"),
       // Logic
       Token::Not(_, _) => write!(f, "!"),
+      Token::NotEqual(_, _) => write!(f, "!="),
       Token::LogOr(_, _) => write!(f, "||"),
       Token::LogAnd(_, _) => write!(f, "&&"),
       Token::BitOr(_, _) => write!(f, "|"),
@@ -82,6 +118,7 @@ impl fmt::Display for Token {
       Token::LessOrEq(_, _)    => write!(f, "<="),
       Token::GreaterOrEq(_, _) => write!(f, ">="),
       Token::Equal(_, _) => write!(f, "=="),
+      Token::Is(_, _) => write!(f, "is"),
       // Primitive
       Token::Int(s, _, _)     => write!(f, "{}", s),
       Token::Double(s, _, _)     => write!(f, "{}", s),
@@ -101,14 +138,24 @@ impl fmt::Display for Token {
         }
       },
       Token::Bool(v, _, _)     => write!(f, "{}", v),
+      Token::Null(_, _)     => write!(f, "null"),
       Token::Name(s, _, _)    => write!(f, "{}", s),
       // Structure
       Token::Class(_, _) => write!(f, "class"),
+      Token::Abstract(_, _) => write!(f, "abstract"),
+      Token::Mixin(_, _) => write!(f, "mixin"),
+      Token::Enum(_, _) => write!(f, "enum"),
+      Token::Extends(_, _) => write!(f, "extends"),
+      Token::Implements(_, _) => write!(f, "implements"),
+      Token::With(_, _) => write!(f, "with"),
       Token::If(_, _) => write!(f, "if"),
       Token::Else(_, _) => write!(f, "else"),
       Token::While(_, _) => write!(f, "while"),
       Token::Do(_, _) => write!(f, "do"),
       Token::For(_, _) => write!(f, "for"),
+      Token::Try(_, _) => write!(f, "try"),
+      Token::Catch(_, _) => write!(f, "catch"),
+      Token::Finally(_, _) => write!(f, "finally"),
       Token::Paren1(_, _) => write!(f, "("),
       Token::Paren2(_, _) => write!(f, ")"),
       Token::Block1(_, _) => write!(f, "{{"),
@@ -116,11 +163,32 @@ impl fmt::Display for Token {
       Token::Brack1(_, _) => write!(f, "["),
       Token::Brack2(_, _) => write!(f, "]"),
       Token::Comma(_, _) => write!(f, ","),
+      Token::Colon(_, _) => write!(f, ":"),
       // Other
       Token::Assign(_, _) => write!(f, "="),
+      Token::Arrow(_, _) => write!(f, "=>"),
       Token::Access(_, _) => write!(f, "."),
+      Token::NullAccess(_, _) => write!(f, "?."),
+      Token::Spread(_, _) => write!(f, "..."),
+      Token::NullSpread(_, _) => write!(f, "...?"),
+      Token::Cascade(_, _) => write!(f, ".."),
+      Token::NullCascade(_, _) => write!(f, "?.."),
+      Token::IfNull(_, _) => write!(f, "??"),
+      Token::IfNullAssign(_, _) => write!(f, "??="),
       Token::This(_, _)   => write!(f, "this"),
+      Token::Super(_, _)  => write!(f, "super"),
       Token::Return(_, _) => write!(f, "return"),
+      Token::Break(_, _) => write!(f, "break"),
+      Token::Continue(_, _) => write!(f, "continue"),
+      Token::Switch(_, _) => write!(f, "switch"),
+      Token::Case(_, _) => write!(f, "case"),
+      Token::Default(_, _) => write!(f, "default"),
+      Token::Throw(_, _) => write!(f, "throw"),
+      Token::Rethrow(_, _) => write!(f, "rethrow"),
+      Token::Static(_, _) => write!(f, "static"),
+      Token::Final(_, _) => write!(f, "final"),
+      Token::Const(_, _) => write!(f, "const"),
+      Token::Late(_, _) => write!(f, "late"),
       Token::Import(_, _) => write!(f, "import"),
       Token::EndSt(_, _) => write!(f, ";"),
       Token::End => write!(f, "END"),
@@ -138,10 +206,12 @@ impl Token {
       Token::Sub(l, i) |
       Token::Mul(l, i) |
       Token::Div(l, i) |
+      Token::IntDiv(l, i) |
       Token::Increment(l, i) |
       Token::Decrement(l, i) |
       // Logic
       Token::Not(l, i) |
+      Token::NotEqual(l, i) |
       Token::LogOr(l, i) |
       Token::LogAnd(l, i) |
       Token::BitOr(l, i) |
@@ -153,19 +223,30 @@ impl Token {
       Token::LessOrEq(l, i) |
       Token::GreaterOrEq(l, i) |
       Token::Equal(l, i) |
+      Token::Is(l, i) |
       // Primitive
       Token::Int(_, l, i) |
       Token::Double(_, l, i) |
       Token::Str(_, _, l, i) |
       Token::Bool(_, l, i) |
+      Token::Null(l, i) |
       Token::Name(_, l, i) |
       // Structure
       Token::Class(l, i) |
+      Token::Abstract(l, i) |
+      Token::Mixin(l, i) |
+      Token::Enum(l, i) |
+      Token::Extends(l, i) |
+      Token::Implements(l, i) |
+      Token::With(l, i) |
       Token::If(l, i) |
       Token::Else(l, i) |
       Token::While(l, i) |
       Token::Do(l, i) |
       Token::For(l, i) |
+      Token::Try(l, i) |
+      Token::Catch(l, i) |
+      Token::Finally(l, i) |
       Token::Paren1(l, i) |
       Token::Paren2(l, i) |
       Token::Block1(l, i) |
@@ -173,11 +254,32 @@ impl Token {
       Token::Brack1(l, i) |
       Token::Brack2(l, i) |
       Token::Comma(l, i) |
+      Token::Colon(l, i) |
       // Other
       Token::Assign(l, i) |
+      Token::Arrow(l, i) |
       Token::Access(l, i) |
+      Token::NullAccess(l, i) |
+      Token::Spread(l, i) |
+      Token::NullSpread(l, i) |
+      Token::Cascade(l, i) |
+      Token::NullCascade(l, i) |
+      Token::IfNull(l, i) |
+      Token::IfNullAssign(l, i) |
       Token::This(l, i) |
+      Token::Super(l, i) |
       Token::Return(l, i) |
+      Token::Break(l, i) |
+      Token::Continue(l, i) |
+      Token::Switch(l, i) |
+      Token::Case(l, i) |
+      Token::Default(l, i) |
+      Token::Throw(l, i) |
+      Token::Rethrow(l, i) |
+      Token::Static(l, i) |
+      Token::Final(l, i) |
+      Token::Const(l, i) |
+      Token::Late(l, i) |
       Token::Import(l, i) |
       Token::EndSt(l, i) => {
         (l.clone(), i.clone())
@@ -0,0 +1,1397 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::builtin;
+use crate::context::*;
+use crate::list;
+use crate::map;
+use crate::node::*;
+use crate::object::{Object, ParamKind, ParamObj};
+use crate::objsys::ObjSys;
+use crate::set;
+use crate::string;
+use crate::types;
+use crate::utils::dart_error_at;
+
+
+// Types of the variables in scope, innermost scope last.
+// A promoted variable has a narrower type where it is promoted, but can still be assigned its declared type.
+// A promoted variable keeps its declared type too, and a local function its declaration, to check calls of it.
+type Scope = HashMap<String, (String, Option<String>, Option<Rc<Node>>)>;
+type Scopes = Vec<Scope>;
+
+
+// What a value is checked against, for the wording of the error.
+enum Target {
+    Variable,
+    Parameter,
+    Return,
+    // The return type of the async function, as declared.
+    AsyncReturn(String),
+    Yield
+}
+
+
+// Infers the static type of expressions and rejects values that can't be assigned
+// where they are stored, passed or returned. Runs after parsing, before any code runs.
+pub fn check(globals: &[Node], looktables: &HashMap<String, HashMap<String, usize>>, objsys: &ObjSys, ctx: &Ctx) {
+    let mut checker = Checker {
+        globals,
+        looktables,
+        objsys,
+        ctx,
+        filepath: String::new(),
+        classname: None,
+        rettype: None,
+        is_async: false,
+        generating: None,
+        scopes: Vec::new()
+    };
+
+    for class in objsys.classes() {
+        checker.filepath = class.filepath.clone();
+        checker.classname = Some(class.name.clone());
+        checker.scopes = vec![checker.class_scope(&class.name)];
+        for (ftype, _, init) in class.fields.iter().chain(&class.static_fields) {
+            if let Some(init) = init {
+                let valtype = checker.infer(init);
+                checker.check_assignable(&valtype, ftype, Target::Variable, init);
+            }
+        }
+        let mut names: Vec<&String> = class.methods.keys().chain(class.static_methods.keys()).collect();
+        names.sort();
+        for name in names {
+            if let Some(Object::Function(_, filepath, body, params, _)) = class.methods.get(name).or(class.static_methods.get(name)) {
+                checker.filepath = filepath.clone();
+                checker.rettype = class.returntypes.get(name).cloned();
+                checker.is_async = false;
+                checker.generating = None;
+                checker.scopes.push(params.iter().map(|p| (p.name.clone(), (p.typ.clone(), None, None))).collect());
+                checker.check_defaults(params);
+                checker.walk(body);
+                checker.scopes.pop();
+            }
+        }
+    }
+
+    for node in globals {
+        let (filepath, classname, rettype) = match &node.nodetype {
+            NodeType::FunDef(_, filepath, rettype, _) => (filepath, None, Some(rettype.clone())),
+            NodeType::Constructor(classname, filepath) => (filepath, Some(classname.clone()), None),
+            _ => continue
+        };
+        checker.filepath = filepath.clone();
+        checker.scopes = match &classname {
+            Some(classname) => vec![checker.class_scope(classname)],
+            None => Vec::new()
+        };
+        checker.classname = classname;
+        checker.rettype = rettype;
+        checker.is_async = false;
+        checker.generating = None;
+        checker.scopes.push(checker.params_scope(&node.children[0]));
+        checker.check_defaults(&param_objs(&node.children[0]));
+        checker.walk(&node.children[1]);
+    }
+}
+
+
+struct Checker<'a> {
+    globals: &'a [Node],
+    looktables: &'a HashMap<String, HashMap<String, usize>>,
+    objsys: &'a ObjSys,
+    ctx: &'a Ctx,
+    filepath: String,
+    // The class of the method or constructor being checked.
+    classname: Option<String>,
+    // The declared return type of the function being checked.
+    rettype: Option<String>,
+    // Whether that function is async, returning the future of what its body returns.
+    is_async: bool,
+    // The stream or iterable that function generates, when it is async* or sync*.
+    generating: Option<String>,
+    scopes: Scopes
+}
+
+
+impl<'a> Checker<'a> {
+
+    // The fields of a class and the classes it inherits from, and its own static fields.
+    fn class_scope(&self, classname: &str) -> Scope {
+        let mut scope = HashMap::new();
+        for name in self.objsys.linearize(classname).iter().rev() {
+            for (ftype, fname, _) in &self.objsys.get_class(name).fields {
+                scope.insert(fname.clone(), (ftype.clone(), None, None));
+            }
+        }
+        for (ftype, fname, _) in &self.objsys.get_class(classname).static_fields {
+            scope.insert(fname.clone(), (ftype.clone(), None, None));
+        }
+        scope
+    }
+
+
+    fn params_scope(&self, params: &Node) -> Scope {
+        let mut scope = HashMap::new();
+        for p in &params.children {
+            // Optional and named parameters wrap their declaration.
+            let p = match &p.nodetype {
+                NodeType::OptionalParam |
+                NodeType::NamedParam(_) => &p.children[0],
+                _ => p
+            };
+            match &p.nodetype {
+                NodeType::TypedVar(t, name) => {
+                    scope.insert(name.clone(), (t.clone(), None, None));
+                }
+                NodeType::Name(name) => {
+                    scope.insert(name.clone(), (String::from("dynamic"), None, None));
+                }
+                _ => {}
+            }
+        }
+        scope
+    }
+
+
+    // Default values must be assignable to their parameters.
+    fn check_defaults(&mut self, params: &[ParamObj]) {
+        for p in params {
+            if let Some(default) = &p.default {
+                let t = self.infer(default);
+                self.check_assignable(&t, &p.typ, Target::Variable, default);
+            }
+        }
+    }
+
+
+    fn declare(&mut self, name: &str, t: &str) {
+        self.scopes.last_mut().unwrap().insert(String::from(name), (String::from(t), None, None));
+    }
+
+
+    fn lookup(&self, name: &str) -> Option<String> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name)).map(|(t, _, _)| t.clone())
+    }
+
+
+    // The declaration of the local function a name is, unless something else hides it.
+    fn local_function(&self, name: &str) -> Option<Rc<Node>> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name)).and_then(|(_, _, decl)| decl.clone())
+    }
+
+
+    fn declared_type(&self, name: &str) -> Option<String> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name)).map(|(t, declared, _)| declared.clone().unwrap_or_else(|| t.clone()))
+    }
+
+
+    // Narrows the type of a variable in the innermost scope.
+    fn promote(&mut self, name: &str, t: String) {
+        if let Some(declared) = self.declared_type(name) {
+            self.scopes.last_mut().unwrap().insert(String::from(name), (t, Some(declared), None));
+        }
+    }
+
+
+    // Assigning a variable a value that isn't null promotes it to its non-nullable type,
+    // and assigning it one that may be null undoes that.
+    fn assigned(&mut self, name: &str, valtype: &str) {
+        if let Some(declared) = self.declared_type(name) {
+            let t = if is_nullable(valtype) { declared } else { non_nullable(&declared) };
+            self.promote(name, t);
+        }
+    }
+
+
+    fn walk(&mut self, node: &Node) {
+        match &node.nodetype {
+            NodeType::Assign => {
+                let target = &node.children[0];
+                let valtype = match &target.nodetype {
+                    NodeType::TypedVar(t, _) => self.infer_in_context(&node.children[1], t),
+                    _ => self.infer(&node.children[1])
+                };
+                match &target.nodetype {
+                    NodeType::TypedVar(t, name) => {
+                        self.check_assignable(&valtype, t, Target::Variable, &node.children[1]);
+                        // 'var x = e' gets the type of e.
+                        let declared = match (t.as_str(), valtype.as_str()) {
+                            ("var", "Null") => String::from("dynamic"),
+                            ("var", _) => valtype,
+                            _ => t.clone()
+                        };
+                        self.declare(name, &declared);
+                    }
+                    NodeType::Name(name) if target.children.is_empty() => {
+                        if let Some(t) = self.declared_type(name) {
+                            self.check_assignable(&valtype, &t, Target::Variable, &node.children[1]);
+                        }
+                        self.assigned(name, &valtype);
+                    }
+                    NodeType::Name(name) => {
+                        let owner = self.infer(&target.children[0]);
+                        if let Some(t) = self.field_type(&owner, name) {
+                            self.check_assignable(&valtype, &t, Target::Variable, &node.children[1]);
+                        }
+                    }
+                    NodeType::Index => {
+                        let owner = self.infer(&target.children[0]);
+                        match builtin_member(&owner, "[]=") {
+                            Some((_, Some(params))) => {
+                                let index = self.infer(&target.children[1]);
+                                self.check_assignable(&index, &params[0].typ, Target::Parameter, &target.children[1]);
+                                self.check_assignable(&valtype, &params[1].typ, Target::Parameter, &node.children[1]);
+                            }
+                            _ => {
+                                self.infer(target);
+                            }
+                        }
+                    }
+                    NodeType::RecordPattern => {
+                        let required = types::of_pattern(target);
+                        if !self.is_assignable(&valtype, &required) {
+                            // As dart.
+                            dart_error_at(
+                                format!("The matched value of type '{}' isn't assignable to the required type '{}'.", valtype, required),
+                                &self.filepath,
+                                node.children[1].pos,
+                                self.ctx
+                            )
+                        }
+                        self.pattern(target, &valtype);
+                    }
+                    _ => {
+                        self.infer(target);
+                    }
+                }
+            }
+            NodeType::TypedVar(t, name) => {
+                self.declare(name, t);
+            }
+            // After 'x ??= e' x is not null if e isn't.
+            NodeType::IfNullAssign => {
+                self.walk(&node.children[1]);
+            }
+            NodeType::Block => {
+                self.scopes.push(HashMap::new());
+                for c in &node.children {
+                    self.walk(c);
+                }
+                self.scopes.pop();
+            }
+            NodeType::Conditional => {
+                // Each branch knows that the variables the conditions before it test for null are not null.
+                // So do the statements after, of those that every branch leaves not null or doesn't complete.
+                let mut tested: Vec<String> = Vec::new();
+                let mut settled: Option<Vec<String>> = None;
+                for branch in &node.children {
+                    self.scopes.push(HashMap::new());
+                    for name in &tested {
+                        self.promote_non_null(name);
+                    }
+                    let body = match branch.nodetype {
+                        NodeType::Else => &branch.children[0],
+                        _ => {
+                            self.check_condition(&branch.children[0]);
+                            for (name, t) in self.promotions(&branch.children[0]) {
+                                self.promote(&name, t);
+                            }
+                            tested.extend(null_tests(&branch.children[0]));
+                            &branch.children[1]
+                        }
+                    };
+                    let left = self.walk_branch(body, &tested);
+                    self.scopes.pop();
+                    settled = Some(match settled {
+                        Some(names) => names.into_iter().filter(|name| left.contains(name)).collect(),
+                        None => left
+                    });
+                }
+                for name in settled.unwrap_or_default() {
+                    self.promote_non_null(&name);
+                }
+            }
+            NodeType::While => {
+                self.check_condition(&node.children[0]);
+                self.walk(&node.children[1]);
+                // Leaving 'while (x == null) { ... }' means x is not null.
+                for name in null_tests(&node.children[0]) {
+                    self.promote_non_null(&name);
+                }
+            }
+            NodeType::DoWhile => {
+                self.walk(&node.children[0]);
+                self.check_condition(&node.children[1]);
+            }
+            NodeType::For => {
+                self.scopes.push(HashMap::new());
+                self.walk(&node.children[0]);
+                self.check_condition(&node.children[1]);
+                self.walk(&node.children[2]);
+                self.walk(&node.children[3]);
+                self.scopes.pop();
+            }
+            NodeType::ForIn(_) => {
+                self.scopes.push(HashMap::new());
+                self.loop_variable(node);
+                self.walk(&node.children[2]);
+                self.scopes.pop();
+            }
+            NodeType::AwaitFor(_) => {
+                if !self.is_async {
+                    // As dart.
+                    dart_error_at("The asynchronous for-in can only be used in functions marked with 'async' or 'async*'.", &self.filepath, node.pos, self.ctx)
+                }
+                self.scopes.push(HashMap::new());
+                self.loop_variable(node);
+                self.walk(&node.children[2]);
+                self.scopes.pop();
+            }
+            NodeType::Yield(star) => {
+                let valtype = self.infer(&node.children[0]);
+                match (self.generating.clone(), star) {
+                    // As dart.
+                    (None, _) => dart_error_at(
+                        "Yield statements must be in a generator function (one marked with either 'async*' or 'sync*').",
+                        &self.filepath,
+                        node.pos,
+                        self.ctx
+                    ),
+                    (Some(generated), false) => self.check_assignable(&valtype, &types::generated(&generated), Target::Yield, &node.children[0]),
+                    (Some(generated), true) => self.check_assignable(&valtype, &generated, Target::Yield, &node.children[0])
+                }
+            }
+            NodeType::Return if self.generating.is_some() => {
+                if !matches!(node.children[0].nodetype, NodeType::Null) {
+                    // As dart.
+                    dart_error_at(
+                        "Can't return a value from a generator function that uses the 'async*' or 'sync*' modifier.",
+                        &self.filepath,
+                        node.pos,
+                        self.ctx
+                    )
+                }
+            }
+            NodeType::Return => {
+                let valtype = self.infer(&node.children[0]);
+                if let (Some(rettype), true) = (self.rettype.clone(), self.is_async) {
+                    // Returning a future from an async function returns its value.
+                    let flat = types::flatten(&rettype);
+                    if flat != "void" {
+                        self.check_assignable(&types::flatten(&valtype), &flat, Target::AsyncReturn(rettype), &node.children[0]);
+                    }
+                }
+                else if let Some(rettype) = self.rettype.clone() {
+                    if rettype != "void" {
+                        self.check_assignable(&valtype, &rettype, Target::Return, &node.children[0]);
+                    }
+                }
+            }
+            NodeType::FunDef(name, _, rettype, _) => {
+                self.scopes.last_mut().unwrap().insert(name.clone(), (String::from("Function"), None, Some(Rc::new(node.clone()))));
+                self.function(node, Some(rettype.clone()));
+            }
+            NodeType::Final(_) => {
+                self.walk(&node.children[0]);
+            }
+            NodeType::Async(_) => {
+                self.is_async = true;
+                self.walk(&node.children[0]);
+            }
+            NodeType::AsyncStar(elemtype) => {
+                self.is_async = true;
+                self.generating = Some(format!("Stream<{}>", elemtype));
+                self.walk(&node.children[0]);
+            }
+            NodeType::SyncStar(elemtype) => {
+                self.generating = Some(format!("Iterable<{}>", elemtype));
+                self.walk(&node.children[0]);
+            }
+            NodeType::Catch(t, e, st) => {
+                self.scopes.push(HashMap::new());
+                if let Some(e) = e {
+                    self.declare(e, t.as_deref().unwrap_or("dynamic"));
+                }
+                if let Some(st) = st {
+                    self.declare(st, "StackTrace");
+                }
+                self.walk(&node.children[0]);
+                self.scopes.pop();
+            }
+            NodeType::Switch => {
+                let subject = self.infer(&node.children[0]);
+                for case in &node.children[1..] {
+                    self.scopes.push(HashMap::new());
+                    self.pattern(&case.children[0], &subject);
+                    self.check_condition(&case.children[1]);
+                    self.walk(&case.children[2]);
+                    self.scopes.pop();
+                }
+            }
+            NodeType::Scope |
+            NodeType::Try |
+            NodeType::Finally |
+            NodeType::Label(_) => {
+                for c in &node.children {
+                    self.walk(c);
+                }
+            }
+            _ => {
+                self.infer(node);
+            }
+        }
+    }
+
+
+    // The block of a branch, in the scope of the branch. Gives those of names that are
+    // not null at its end, or all of them if it doesn't complete.
+    fn walk_branch(&mut self, body: &Node, names: &[String]) -> Vec<String> {
+        for stmt in &body.children {
+            self.walk(stmt);
+        }
+        let exits = body.children.last().is_some_and(|last| {
+            matches!(last.nodetype, NodeType::Return | NodeType::Throw | NodeType::Rethrow | NodeType::Break(_) | NodeType::Continue(_))
+        });
+        names.iter().filter(|name| exits || self.lookup(name).is_some_and(|t| !is_nullable(&t))).cloned().collect()
+    }
+
+
+    fn promote_non_null(&mut self, name: &str) {
+        if let Some(t) = self.lookup(name) {
+            self.promote(name, non_nullable(&t));
+        }
+    }
+
+
+    // The body of a function or function literal, in a scope of its own.
+    fn function(&mut self, node: &Node, rettype: Option<String>) {
+        let outer = std::mem::replace(&mut self.rettype, rettype);
+        let outer_async = std::mem::replace(&mut self.is_async, false);
+        let outer_generating = self.generating.take();
+        self.scopes.push(self.params_scope(&node.children[0]));
+        self.check_defaults(&param_objs(&node.children[0]));
+        self.walk(&node.children[1]);
+        self.scopes.pop();
+        self.generating = outer_generating;
+        self.is_async = outer_async;
+        self.rettype = outer;
+    }
+
+
+    fn pattern(&mut self, pattern: &Node, subject: &str) {
+        match &pattern.nodetype {
+            NodeType::VarPattern(t, name) if name != "_" => {
+                let t = if t == "var" { subject } else { t };
+                self.declare(name, t);
+            }
+            // Fields of records of other shapes don't match, so their types don't matter.
+            NodeType::RecordPattern => {
+                let (positional, named) = types::record_fields(subject).unwrap_or_default();
+                let mut positional = positional.into_iter();
+                for field in &pattern.children {
+                    match &field.nodetype {
+                        NodeType::NamedArg(name) => {
+                            let t = named.iter().find(|(n, _)| n == name).map(|(_, t)| t.clone());
+                            self.pattern(&field.children[0], &t.unwrap_or_else(|| String::from("dynamic")));
+                        }
+                        _ => self.pattern(field, &positional.next().unwrap_or_else(|| String::from("dynamic")))
+                    }
+                }
+            }
+            // A variable assigned by a pattern assignment.
+            NodeType::Name(name) if pattern.children.is_empty() => {
+                if let Some(t) = self.declared_type(name) {
+                    self.check_assignable(subject, &t, Target::Variable, pattern);
+                }
+            }
+            _ => {
+                for c in &pattern.children {
+                    self.pattern(c, subject);
+                    self.infer(c);
+                }
+            }
+        }
+    }
+
+
+    // Declares the variable of a for-in loop, for-in element or 'await for' loop in the current scope,
+    // checking it against the elements of the iterable or stream.
+    fn loop_variable(&mut self, node: &Node) {
+        let iterable = self.infer(&node.children[1]);
+        let (over, elemtype) = match node.nodetype {
+            NodeType::AwaitFor(_) => ("Stream", stream_element_type(&iterable)),
+            _ => ("Iterable", self.element_type(&iterable))
+        };
+        let elemtype = match elemtype {
+            Some(t) => t,
+            // As dart.
+            None => dart_error_at(
+                format!("The type '{}' used in the 'for' loop must implement '{}<dynamic>'.", iterable, over),
+                &self.filepath,
+                node.children[1].pos,
+                self.ctx
+            )
+        };
+        let variable = &node.children[0];
+        if let NodeType::VarPattern(t, _) = &variable.nodetype {
+            if t != "var" && !self.is_assignable(&elemtype, t) {
+                // As dart.
+                dart_error_at(
+                    format!("The type '{}' used in the 'for' loop must implement '{}<{}>'.", iterable, over, t),
+                    &self.filepath,
+                    node.children[1].pos,
+                    self.ctx
+                )
+            }
+        }
+        self.pattern(variable, &elemtype);
+    }
+
+
+    // A missing guard or condition is a null node.
+    fn check_condition(&mut self, cond: &Node) {
+        if matches!(cond.nodetype, NodeType::Null) {
+            return;
+        }
+        let condtype = self.infer(cond);
+        self.check_assignable(&condtype, "bool", Target::Variable, cond);
+    }
+
+
+    // The static type of an expression, checking the calls in it.
+    fn infer(&mut self, node: &Node) -> String {
+        match &node.nodetype {
+            NodeType::Int(_) => String::from("int"),
+            NodeType::Double(_) => String::from("double"),
+            NodeType::Bool(_) => String::from("bool"),
+            NodeType::Null => String::from("Null"),
+            NodeType::NamedArg(_) => self.infer(&node.children[0]),
+            NodeType::Await => {
+                if !self.is_async {
+                    // As dart.
+                    dart_error_at("'await' can only be used in 'async' or 'async*' methods.", &self.filepath, node.pos, self.ctx)
+                }
+                types::flatten(&self.infer(&node.children[0]))
+            }
+            NodeType::Record => {
+                let mut positional = Vec::new();
+                let mut named = Vec::new();
+                for field in &node.children {
+                    match &field.nodetype {
+                        NodeType::NamedArg(name) => named.push((name.clone(), self.infer(&field.children[0]))),
+                        _ => positional.push(self.infer(field))
+                    }
+                }
+                named.sort();
+                types::record(&positional, &named)
+            }
+            NodeType::Str(_) => {
+                self.infer_all(&node.children);
+                String::from("String")
+            }
+            NodeType::List(typeargs) => {
+                let elemtypes: Vec<String> = self.elements(&node.children).into_iter().map(|(_, t, _)| t).collect();
+                match typeargs.first() {
+                    Some(t) => format!("List<{}>", t),
+                    None => format!("List<{}>", same_or_dynamic(elemtypes))
+                }
+            }
+            NodeType::Map(typeargs) => {
+                let entries = self.elements(&node.children);
+                map_type(typeargs, entries)
+            }
+            NodeType::Set(typeargs) => {
+                let elems = self.elements(&node.children);
+                // Spreads of maps make a map.
+                if elems.iter().any(|(_, _, v)| v.is_some()) {
+                    return map_type(typeargs, elems);
+                }
+                let elemtypes: Vec<String> = elems.into_iter().map(|(_, t, _)| t).collect();
+                match typeargs.first() {
+                    Some(t) => format!("Set<{}>", t),
+                    None => format!("Set<{}>", same_or_dynamic(elemtypes))
+                }
+            }
+            NodeType::Index => {
+                let owner = self.infer(&node.children[0]);
+                self.method_call(&owner, "[]", &node.children[1..], node.pos)
+            }
+            NodeType::Add |
+            NodeType::Sub |
+            NodeType::Mul |
+            NodeType::Div |
+            NodeType::IntDiv if node.children.len() == 2 => {
+                let left = self.infer(&node.children[0]);
+                // A user defined operator.
+                if self.objsys.has_class(&types::split(&left).0) {
+                    return self.method_call(&left, &node.nodetype.to_string(), &node.children[1..], node.pos);
+                }
+                let right = self.infer(&node.children[1]);
+                arithmetic(&node.nodetype, &left, &right)
+            }
+            NodeType::Sub => {
+                let operand = self.infer(&node.children[0]);
+                match self.objsys.has_class(&types::split(&operand).0) {
+                    true => self.method_call(&operand, "unary-", &[], node.pos),
+                    false => operand
+                }
+            }
+            NodeType::BitAnd |
+            NodeType::BitOr |
+            NodeType::BitXor => {
+                self.infer_all(&node.children);
+                String::from("int")
+            }
+            NodeType::PreIncrement |
+            NodeType::PostIncrement |
+            NodeType::PreDecrement |
+            NodeType::PostDecrement => self.infer(&node.children[0]),
+            NodeType::Not |
+            NodeType::LogAnd |
+            NodeType::LogOr |
+            NodeType::LessThan |
+            NodeType::GreaterThan |
+            NodeType::LessOrEq |
+            NodeType::GreaterOrEq |
+            NodeType::Equal |
+            NodeType::Is(_) => {
+                self.infer_all(&node.children);
+                String::from("bool")
+            }
+            NodeType::Name(name) if node.children.is_empty() => {
+                match self.lookup(name) {
+                    Some(t) => dynamic_if_untyped(t),
+                    None if self.toplevel(name).is_some_and(|n| !matches!(n.nodetype, NodeType::Extension(_))) => String::from("Function"),
+                    None => String::from("dynamic")
+                }
+            }
+            NodeType::Name(name) if self.static_owner(&node.children[0]).is_some() => {
+                let classname = self.static_owner(&node.children[0]).unwrap();
+                let class = self.objsys.get_class(&classname);
+                match class.static_fields.iter().find(|(_, f, _)| f == name) {
+                    Some((t, _, _)) => dynamic_if_untyped(t.clone()),
+                    None if class.static_methods.contains_key(name) => String::from("Function"),
+                    None => String::from("dynamic")
+                }
+            }
+            NodeType::Name(name) => {
+                let owner = self.infer(&node.children[0]);
+                let nullaware = matches!(node.children[0].nodetype, NodeType::NullAware);
+                let t = self.field_type(&non_nullable(&owner), name);
+                // What every object has.
+                let t = if t.is_none() && name == "runtimeType" { Some(String::from("Type")) } else { t };
+                if t.is_none() && types::record_fields(&owner).is_some() {
+                    // As dart.
+                    dart_error_at(format!("The getter '{}' isn't defined for the type '{}'.", name, owner), &self.filepath, node.pos, self.ctx)
+                }
+                let t = t.unwrap_or_else(|| String::from("dynamic"));
+                if nullaware { nullable(&t) } else { t }
+            }
+            NodeType::MethodCall(name, owner) if self.static_owner(owner).is_some() => {
+                let classname = self.static_owner(owner).unwrap();
+                self.static_call(&classname, name, &node.children[0].children, node.pos)
+            }
+            NodeType::MethodCall(name, owner) => {
+                let ownertype = self.infer(owner);
+                let nullaware = matches!(owner.nodetype, NodeType::NullAware);
+                let t = self.method_call(&non_nullable(&ownertype), name, &node.children[0].children, node.pos);
+                if nullaware { nullable(&t) } else { t }
+            }
+            NodeType::FunCall(name) if self.objsys.has_class(name) && self.objsys.get_class(name).is_enum => {
+                // As dart.
+                dart_error_at("Enums can't be instantiated.", &self.filepath, node.pos, self.ctx)
+            }
+            NodeType::FunCall(name) => self.funcall(name, &node.children[0].children, node.pos),
+            // The values of an enum are constructed by its declaration.
+            NodeType::EnumValue(_, _) => match &node.children[0].nodetype {
+                NodeType::FunCall(name) => self.funcall(name, &node.children[0].children[0].children, node.children[0].pos),
+                _ => String::from("dynamic")
+            },
+            NodeType::Const => self.infer(&node.children[0]),
+            NodeType::NullAware => self.infer(&node.children[0]),
+            NodeType::Cascade(nullaware) => {
+                let receiver = self.infer(&node.children[0]);
+                self.scopes.push(HashMap::new());
+                self.declare("..", &if *nullaware { non_nullable(&receiver) } else { receiver.clone() });
+                for section in &node.children[1..] {
+                    self.walk(section);
+                }
+                self.scopes.pop();
+                receiver
+            }
+            NodeType::NullCheck => non_nullable(&self.infer(&node.children[0])),
+            NodeType::IfNull => {
+                let left = non_nullable(&self.infer(&node.children[0]));
+                let right = self.infer(&node.children[1]);
+                if left == right || right == "Null" {
+                    left
+                }
+                else if right == nullable(&left) {
+                    right
+                }
+                else {
+                    String::from("dynamic")
+                }
+            }
+            // In an extension, 'this' is of the type it extends.
+            NodeType::This => match &self.classname {
+                Some(classname) => self.objsys.get_class(classname).on.clone().unwrap_or_else(|| classname.clone()),
+                None => String::from("dynamic")
+            },
+            NodeType::Super => {
+                let parent = self.classname.as_ref().and_then(|c| self.objsys.get_class(c).parent.clone());
+                parent.unwrap_or_else(|| String::from("dynamic"))
+            }
+            NodeType::FunLit(_) => {
+                self.function(node, None);
+                String::from("Function")
+            }
+            NodeType::SwitchExpr => {
+                let subject = self.infer(&node.children[0]);
+                let mut types = Vec::new();
+                for case in &node.children[1..] {
+                    self.scopes.push(HashMap::new());
+                    self.pattern(&case.children[0], &subject);
+                    self.check_condition(&case.children[1]);
+                    types.push(self.infer(&case.children[2]));
+                    self.scopes.pop();
+                }
+                same_or_dynamic(types)
+            }
+            _ => {
+                self.infer_all(&node.children);
+                String::from("dynamic")
+            }
+        }
+    }
+
+
+    // Like infer, but an untyped list, map or set literal gets the type arguments of the type
+    // it is assigned to, and its elements are checked against them.
+    fn infer_in_context(&mut self, node: &Node, context: &str) -> String {
+        let (base, args) = types::split(&non_nullable(context));
+        match (&node.nodetype, args.as_slice()) {
+            (NodeType::List(written), [elemtype]) if written.is_empty() && base == "List" => {
+                for (elem, t, _) in self.elements(&node.children) {
+                    self.check_assignable(&t, elemtype, Target::Variable, elem);
+                }
+                types::join(&base, &args)
+            }
+            (NodeType::Map(written) | NodeType::Set(written), [keytype, valuetype]) if written.is_empty() && base == "Map" => {
+                for (entry, k, v) in self.elements(&node.children) {
+                    // Errors of entries are at their key and value, those of spreads at the spread.
+                    let (knode, vnode) = match entry.nodetype {
+                        NodeType::MapEntry => (&entry.children[0], &entry.children[1]),
+                        _ => (entry, entry)
+                    };
+                    self.check_assignable(&k, keytype, Target::Variable, knode);
+                    if let Some(v) = v {
+                        self.check_assignable(&v, valuetype, Target::Variable, vnode);
+                    }
+                }
+                types::join(&base, &args)
+            }
+            (NodeType::Set(written), [elemtype]) if written.is_empty() && base == "Set" => {
+                for (elem, t, _) in self.elements(&node.children) {
+                    self.check_assignable(&t, elemtype, Target::Variable, elem);
+                }
+                types::join(&base, &args)
+            }
+            // '{}' is an empty set where a set is expected.
+            (NodeType::Map(written), [_]) if written.is_empty() && node.children.is_empty() && base == "Set" => types::join(&base, &args),
+            _ => self.infer(node)
+        }
+    }
+
+
+    // The elements of a list, set or map literal with their types, spreads, if elements and for
+    // elements expanded. Map entries and the entries of spread maps have a value type too.
+    fn elements<'n>(&mut self, nodes: &'n [Node]) -> Vec<(&'n Node, String, Option<String>)> {
+        let mut elems = Vec::new();
+        for node in nodes {
+            self.element(node, &mut elems);
+        }
+        elems
+    }
+
+
+    fn element<'n>(&mut self, node: &'n Node, elems: &mut Vec<(&'n Node, String, Option<String>)>) {
+        match &node.nodetype {
+            NodeType::MapEntry => {
+                let k = self.infer(&node.children[0]);
+                let v = self.infer(&node.children[1]);
+                elems.push((node, k, Some(v)));
+            }
+            NodeType::Spread(nullaware) => {
+                let spread = self.infer(&node.children[0]);
+                let spread = if *nullaware { non_nullable(&spread) } else { spread };
+                let (base, args) = types::split(&spread);
+                match (base.as_str(), args.as_slice()) {
+                    ("Null", _) if *nullaware => {}
+                    ("Map", [k, v]) => elems.push((node, k.clone(), Some(v.clone()))),
+                    _ => match self.element_type(&spread) {
+                        Some(t) => elems.push((node, t, None)),
+                        // As dart.
+                        None => dart_error_at(
+                            "Spread elements in list or set literals must implement 'Iterable'.",
+                            &self.filepath,
+                            node.children[0].pos,
+                            self.ctx
+                        )
+                    }
+                }
+            }
+            NodeType::IfElement => {
+                self.check_condition(&node.children[0]);
+                for elem in &node.children[1..] {
+                    self.element(elem, elems);
+                }
+            }
+            NodeType::For => {
+                self.scopes.push(HashMap::new());
+                self.walk(&node.children[0]);
+                self.check_condition(&node.children[1]);
+                self.walk(&node.children[2]);
+                self.element(&node.children[3], elems);
+                self.scopes.pop();
+            }
+            NodeType::ForIn(_) => {
+                self.scopes.push(HashMap::new());
+                self.loop_variable(node);
+                self.element(&node.children[2], elems);
+                self.scopes.pop();
+            }
+            _ => {
+                let t = self.infer(node);
+                elems.push((node, t, None));
+            }
+        }
+    }
+
+
+    fn infer_all(&mut self, nodes: &[Node]) -> Vec<String> {
+        nodes.iter().map(|n| self.infer(n)).collect()
+    }
+
+
+    fn toplevel(&self, name: &str) -> Option<&'a Node> {
+        self.looktables.get(&self.filepath)
+            .and_then(|table| table.get(name))
+            .map(|index| &self.globals[*index])
+    }
+
+
+    // Resolved like the evaluator resolves calls: locals, methods of this, builtins, then top level.
+    fn funcall(&mut self, name: &str, args: &[Node], pos: (usize, usize)) -> String {
+        let (name, typeargs) = types::split(name);
+        let name = name.as_str();
+        if let Some(decl) = self.local_function(name) {
+            return self.function_call(&decl, &typeargs, args, pos);
+        }
+        if self.lookup(name).is_some() {
+            self.infer_all(args);
+            return String::from("dynamic");
+        }
+        if let Some(classname) = self.classname.clone() {
+            if self.objsys.find_method(&classname, name).is_some() {
+                return self.method_call(&classname, name, args, pos);
+            }
+            if self.objsys.find_static_method(&classname, name).is_some() {
+                return self.static_call(&classname, name, args, pos);
+            }
+        }
+        if builtin::has_function(name) {
+            let argtypes = self.infer_all(args);
+            return match name {
+                "print" |
+                "scheduleMicrotask" => String::from("void"),
+                "identical" => String::from("bool"),
+                "assert" => {
+                    if let Some(t) = argtypes.first() {
+                        self.check_assignable(t, "bool", Target::Variable, &args[0]);
+                    }
+                    String::from("void")
+                }
+                _ => String::from(name)
+            };
+        }
+        match self.toplevel(name) {
+            Some(node) if matches!(node.nodetype, NodeType::Extension(_)) => {
+                dart_error_at(format!("Explicitly applying the extension '{}' is not supported.", name), &self.filepath, pos, self.ctx)
+            }
+            Some(funcnode) if matches!(funcnode.nodetype, NodeType::FunDef(..)) => self.function_call(funcnode, &typeargs, args, pos),
+            Some(funcnode) => {
+                let typeparams = &self.objsys.get_class(name).typeparams;
+                let params: Vec<(Option<String>, String)> = param_types(&funcnode.children[0], |field| self.raw_field_type(name, field))
+                    .into_iter()
+                    .map(|(p, t)| (p, if typeargs.is_empty() { t } else { types::substitute(&t, typeparams, &typeargs) }))
+                    .collect();
+                self.check_arity(args, &param_objs(&funcnode.children[0]), pos);
+                let bindings = self.check_args(args, &params);
+                match typeargs.is_empty() {
+                    // Without type arguments, they are inferred from the arguments.
+                    true => {
+                        let typeparams = &self.objsys.get_class(name).typeparams;
+                        let mut bound = vec![Vec::new(); typeparams.len()];
+                        for (param, arg) in &bindings {
+                            types::bind(param, arg, typeparams, &mut bound);
+                        }
+                        let inferred: Vec<String> = bound.into_iter().map(same_or_dynamic).collect();
+                        types::join(name, &inferred)
+                    }
+                    false => types::join(name, &typeargs)
+                }
+            }
+            None => {
+                self.infer_all(args);
+                String::from("dynamic")
+            }
+        }
+    }
+
+
+    // A call of a top level or local function. Type arguments given replace its type parameters,
+    // else those are types the checker does not know.
+    fn function_call(&mut self, decl: &Node, typeargs: &[String], args: &[Node], pos: (usize, usize)) -> String {
+        let NodeType::FunDef(_, _, rettype, typeparams) = &decl.nodetype else {
+            unreachable!()
+        };
+        let typed = |t: &str| if typeargs.is_empty() { String::from(t) } else { types::substitute(t, typeparams, typeargs) };
+        let params: Vec<(Option<String>, String)> = param_types(&decl.children[0], |_| None).into_iter().map(|(p, t)| (p, typed(&t))).collect();
+        self.check_arity(args, &param_objs(&decl.children[0]), pos);
+        self.check_args(args, &params);
+        dynamic_if_untyped(typed(rettype))
+    }
+
+
+    fn method_call(&mut self, ownertype: &str, name: &str, args: &[Node], pos: (usize, usize)) -> String {
+        if name == "toString" {
+            self.infer_all(args);
+            return String::from("String");
+        }
+        let (classname, typeargs) = types::split(ownertype);
+        if !self.objsys.has_class(&classname) {
+            if let Some((rettype, Some(params))) = builtin_member(ownertype, name) {
+                let types: Vec<(Option<String>, String)> = params.iter().map(|p| (named(p), p.typ.clone())).collect();
+                self.check_arity(args, &params, pos);
+                self.check_args(args, &types);
+                return rettype;
+            }
+            self.infer_all(args);
+            return String::from("dynamic");
+        }
+        if let Some((declaring, Object::Function(_, _, _, params, _))) = self.objsys.find_method(&classname, name) {
+            let types: Vec<(Option<String>, String)> = params.iter()
+                .map(|p: &ParamObj| (named(p), self.member_type(&classname, &typeargs, &declaring, &p.typ)))
+                .collect();
+            self.check_arity(args, &params, pos);
+            self.check_args(args, &types);
+        }
+        else {
+            self.infer_all(args);
+        }
+        self.objsys.linearize(&classname).iter()
+            .chain(self.objsys.supertypes(&classname).iter())
+            .find_map(|c| self.objsys.get_class(c).returntypes.get(name).map(|t| self.member_type(&classname, &typeargs, c, t)))
+            .map(dynamic_if_untyped)
+            .unwrap_or_else(|| String::from("dynamic"))
+    }
+
+
+    // The class named by the owner of 'C.x', when C is not a variable.
+    fn static_owner(&self, owner: &Node) -> Option<String> {
+        match &owner.nodetype {
+            NodeType::Name(name) if owner.children.is_empty() && self.lookup(name).is_none() && self.objsys.has_class(name) => Some(name.clone()),
+            _ => None
+        }
+    }
+
+
+    fn static_call(&mut self, classname: &str, name: &str, args: &[Node], pos: (usize, usize)) -> String {
+        match self.objsys.find_static_method(classname, name) {
+            Some(Object::Function(_, _, _, params, _)) => {
+                let types: Vec<(Option<String>, String)> = params.iter().map(|p| (named(p), p.typ.clone())).collect();
+                self.check_arity(args, &params, pos);
+                self.check_args(args, &types);
+                let rettype = self.objsys.get_class(classname).returntypes.get(name).cloned();
+                dynamic_if_untyped(rettype.unwrap_or_default())
+            }
+            _ => {
+                self.infer_all(args);
+                String::from("dynamic")
+            }
+        }
+    }
+
+
+    // The params are the types of the parameters, with the names of the named ones.
+    // Gives the type of each parameter given an argument, with the type of the argument.
+    fn check_args(&mut self, args: &[Node], params: &[(Option<String>, String)]) -> Vec<(String, String)> {
+        let mut positional = params.iter().filter(|(name, _)| name.is_none());
+        let mut bindings = Vec::new();
+        for arg in args {
+            let (param, arg) = match &arg.nodetype {
+                NodeType::NamedArg(name) => (params.iter().find(|(n, _)| n.as_ref() == Some(name)), &arg.children[0]),
+                _ => (positional.next(), arg)
+            };
+            let argtype = self.infer(arg);
+            if let Some((_, paramtype)) = param {
+                self.check_assignable(&argtype, paramtype, Target::Parameter, arg);
+                bindings.push((paramtype.clone(), argtype));
+            }
+        }
+        bindings
+    }
+
+
+    // Too many or too few positional arguments, and missing or unknown named ones, are errors before anything runs.
+    fn check_arity(&self, args: &[Node], params: &[ParamObj], pos: (usize, usize)) {
+        let positional: Vec<&Node> = args.iter().filter(|arg| !matches!(arg.nodetype, NodeType::NamedArg(_))).collect();
+        let required = params.iter().filter(|p| matches!(p.kind, ParamKind::Positional)).count();
+        let allowed = params.iter().filter(|p| matches!(p.kind, ParamKind::Positional | ParamKind::Optional)).count();
+        // As dart.
+        if positional.len() > allowed {
+            let extra = positional[allowed];
+            dart_error_at(format!("Too many positional arguments: {} expected, but {} found.", allowed, positional.len()), &self.filepath, extra.pos, self.ctx)
+        }
+        if positional.len() < required {
+            dart_error_at(format!("Too few positional arguments: {} required, {} given.", required, positional.len()), &self.filepath, pos, self.ctx)
+        }
+        for arg in args {
+            if let NodeType::NamedArg(name) = &arg.nodetype {
+                if !params.iter().any(|p| named(p).as_ref() == Some(name)) {
+                    dart_error_at(format!("The named parameter '{}' isn't defined.", name), &self.filepath, pos, self.ctx)
+                }
+            }
+        }
+        for p in params.iter().filter(|p| matches!(p.kind, ParamKind::Named(true))) {
+            if !args.iter().any(|arg| matches!(&arg.nodetype, NodeType::NamedArg(name) if *name == p.name)) {
+                dart_error_at(format!("The named parameter '{}' is required, but there's no corresponding argument.", p.name), &self.filepath, pos, self.ctx)
+            }
+        }
+    }
+
+
+    // The type of a field of a class, as seen on a value of type ownertype, like 'Box<int>'.
+    fn field_type(&self, ownertype: &str, field: &str) -> Option<String> {
+        if let Some((positional, named)) = types::record_fields(ownertype) {
+            return match field.strip_prefix('$').and_then(|i| i.parse::<usize>().ok()) {
+                Some(i) if i >= 1 => positional.get(i - 1).cloned(),
+                _ => named.into_iter().find(|(n, _)| n == field).map(|(_, t)| t)
+            };
+        }
+        let (classname, typeargs) = types::split(ownertype);
+        if !self.objsys.has_class(&classname) {
+            return match builtin_member(ownertype, field) {
+                Some((t, None)) => Some(t),
+                _ => None
+            };
+        }
+        // Getters are typed like fields.
+        self.objsys.linearize(&classname).iter().find_map(|c| {
+            let class = self.objsys.get_class(c);
+            let t = match class.getters.iter().any(|g| g == field) {
+                true => class.returntypes.get(field),
+                false => class.fields.iter().find(|(_, f, _)| f == field).map(|(t, _, _)| t)
+            };
+            t.map(|t| dynamic_if_untyped(self.member_type(&classname, &typeargs, c, t)))
+        })
+    }
+
+
+    // The type of a field as declared, in terms of the type parameters of its class.
+    fn raw_field_type(&self, classname: &str, field: &str) -> Option<String> {
+        let class = self.objsys.get_class(classname);
+        self.field_type(&types::join(classname, &class.typeparams), field)
+    }
+
+
+    // A type declared in the class 'declaring', with its type parameters replaced by
+    // what they are in classname<typeargs>. Type arguments not given are dynamic.
+    fn member_type(&self, classname: &str, typeargs: &[String], declaring: &str, t: &str) -> String {
+        let typeparams = &self.objsys.get_class(declaring).typeparams;
+        if typeparams.is_empty() {
+            return String::from(t);
+        }
+        types::substitute(t, typeparams, &self.objsys.supertype_args(classname, typeargs, declaring))
+    }
+
+
+    // The variables a condition narrows when it is true: 'x != null', 'x is T', and conjunctions of those.
+    fn promotions(&self, cond: &Node) -> Vec<(String, String)> {
+        match &cond.nodetype {
+            NodeType::Not => match null_test(&cond.children[0]) {
+                Some(name) => match self.lookup(&name) {
+                    Some(t) => vec![(name, non_nullable(&t))],
+                    None => Vec::new()
+                },
+                None => Vec::new()
+            },
+            NodeType::Is(t) => match &cond.children[0].nodetype {
+                NodeType::Name(name) if cond.children[0].children.is_empty() => vec![(name.clone(), t.clone())],
+                _ => Vec::new()
+            },
+            NodeType::LogAnd => {
+                let mut names = self.promotions(&cond.children[0]);
+                names.extend(self.promotions(&cond.children[1]));
+                names
+            }
+            _ => Vec::new()
+        }
+    }
+
+
+    fn check_assignable(&self, from: &str, to: &str, target: Target, value: &Node) {
+        if self.is_assignable(from, to) {
+            return;
+        }
+        // An int literal is a double where a double is expected, as dart.
+        if from == "int" && non_nullable(to) == "double" && types::is_int_literal(value) {
+            return;
+        }
+        // As dart.
+        let msg = match (target, from) {
+            (Target::Variable, "Null") => format!("The value 'null' can't be assigned to a variable of type '{}' because '{}' is not nullable.", to, to),
+            (Target::Parameter, "Null") => format!("The value 'null' can't be assigned to the parameter type '{}' because '{}' is not nullable.", to, to),
+            (Target::Return, "Null") => format!("The value 'null' can't be returned from a function with return type '{}' because '{}' is not nullable.", to, to),
+            (Target::Variable, _) if self.is_assignable(&non_nullable(from), to) => {
+                format!("A value of type '{}' can't be assigned to a variable of type '{}' because '{}' is nullable and '{}' isn't.", from, to, from, to)
+            }
+            (Target::Parameter, _) if self.is_assignable(&non_nullable(from), to) => {
+                format!("The argument type '{}' can't be assigned to the parameter type '{}' because '{}' is nullable and '{}' isn't.", from, to, from, to)
+            }
+            (Target::Return, _) if self.is_assignable(&non_nullable(from), to) => {
+                format!("A value of type '{}' can't be returned from a function with return type '{}' because '{}' is nullable and '{}' isn't.", from, to, from, to)
+            }
+            (Target::Variable, _) => format!("A value of type '{}' can't be assigned to a variable of type '{}'.", from, to),
+            (Target::Parameter, _) => format!("The argument type '{}' can't be assigned to the parameter type '{}'.", from, to),
+            (Target::Return, _) => format!("A value of type '{}' can't be returned from a function with return type '{}'.", from, to),
+            (Target::AsyncReturn(declared), _) => format!("A value of type '{}' can't be returned from an async function with return type '{}'.", from, declared),
+            (Target::Yield, _) => format!("A yielded value of type '{}' must be assignable to '{}'.", from, to)
+        };
+        dart_error_at(msg, &self.filepath, value.pos, self.ctx)
+    }
+
+
+    fn is_assignable(&self, from: &str, to: &str) -> bool {
+        if matches!(to, "" | "var" | "dynamic" | "Object?" | "void") || matches!(from, "dynamic" | "void") {
+            return true;
+        }
+        if from == "Null" {
+            return to.ends_with('?') || to == "Null";
+        }
+        if from.ends_with('?') && !to.ends_with('?') {
+            return false;
+        }
+        // Records of the same shape are assignable field by field.
+        if let Some((fpositional, fnamed)) = types::record_fields(&non_nullable(from)) {
+            return match types::record_fields(&non_nullable(to)) {
+                Some((tpositional, tnamed)) => {
+                    fpositional.len() == tpositional.len()
+                        && fnamed.len() == tnamed.len()
+                        && fpositional.iter().zip(&tpositional).all(|(f, t)| self.is_assignable(f, t))
+                        && fnamed.iter().zip(&tnamed).all(|((fname, f), (tname, t))| fname == tname && self.is_assignable(f, t))
+                }
+                None => matches!(non_nullable(to).as_str(), "Record" | "Object")
+            };
+        }
+        if types::record_fields(&non_nullable(to)).is_some() {
+            return !self.is_known(&types::split(&non_nullable(from)).0);
+        }
+        let (frombase, fromargs) = types::split(&non_nullable(from));
+        let (tobase, toargs) = types::split(&non_nullable(to));
+        if frombase != tobase && !self.is_subtype(&frombase, &tobase) {
+            return false;
+        }
+
+        // Type arguments are covariant. Missing ones, as in a raw 'List', are dynamic.
+        let fromargs = match self.objsys.has_class(&frombase) && self.objsys.has_class(&tobase) {
+            true => self.objsys.supertype_args(&frombase, &fromargs, &tobase),
+            false => fromargs
+        };
+        toargs.iter().zip(fromargs.iter()).all(|(t, f)| self.is_assignable(f, t))
+    }
+
+
+    // Types this checker does not know, like those of the exceptions or type parameters, are subtypes of anything.
+    fn is_subtype(&self, from: &str, to: &str) -> bool {
+        match (from, to) {
+            (_, "Object") => true,
+            ("int", "num") | ("double", "num") => true,
+            ("List", "Iterable") | ("Set", "Iterable") => true,
+            _ if self.objsys.has_class(from) => !self.is_known(to) || self.objsys.is_subtype(from, to),
+            _ => !self.is_known(from) || !self.is_known(to)
+        }
+    }
+
+
+    // The type of the elements a for-in loop gives for an iterable of type t.
+    fn element_type(&self, t: &str) -> Option<String> {
+        let (base, args) = types::split(t);
+        match base.as_str() {
+            "String" => Some(String::from("String")),
+            "List" | "Set" => Some(args.first().cloned().unwrap_or_else(|| String::from("dynamic"))),
+            c if self.objsys.has_class(c) && self.objsys.is_subtype(c, "Iterable") => {
+                self.objsys.supertype_args(c, &args, "Iterable").first().cloned()
+            }
+            c if !self.is_known(c) || c == "dynamic" => Some(String::from("dynamic")),
+            _ => None
+        }
+    }
+
+
+    fn is_known(&self, t: &str) -> bool {
+        types::BUILTINS.contains(&t) || self.objsys.has_class(t)
+    }
+}
+
+
+// The type of the events of a stream, or None if t isn't a stream.
+fn stream_element_type(t: &str) -> Option<String> {
+    let (base, args) = types::split(t);
+    match base.as_str() {
+        "Stream" => Some(args.first().cloned().unwrap_or_else(|| String::from("dynamic"))),
+        "dynamic" => Some(String::from("dynamic")),
+        _ => None
+    }
+}
+
+
+// The type of a map literal with the given type arguments and entries.
+fn map_type(typeargs: &[String], entries: Vec<(&Node, String, Option<String>)>) -> String {
+    if let [k, v] = typeargs {
+        return format!("Map<{}, {}>", k, v);
+    }
+    let (keytypes, valuetypes): (Vec<String>, Vec<String>) = entries.into_iter()
+        .map(|(_, k, v)| (k, v.unwrap_or_else(|| String::from("dynamic"))))
+        .unzip();
+    format!("Map<{}, {}>", same_or_dynamic(keytypes), same_or_dynamic(valuetypes))
+}
+
+
+// The types of the parameters of a function or constructor, with the names of the named ones.
+// Field initializing parameters have the type of their field.
+fn param_types<F: Fn(&str) -> Option<String>>(params: &Node, field_type: F) -> Vec<(Option<String>, String)> {
+    param_objs(params).iter().map(|p| {
+        let t = match p.fieldinit {
+            true => field_type(&p.name).unwrap_or_else(|| String::from("dynamic")),
+            false if p.typ == "var" => String::from("dynamic"),
+            false => p.typ.clone()
+        };
+        (named(p), t)
+    }).collect()
+}
+
+
+fn param_objs(params: &Node) -> Vec<ParamObj> {
+    params.children.iter().map(ParamObj::from_node).collect()
+}
+
+
+// The name a parameter is given an argument by, if it is a named parameter.
+// The member name of a List, Map, Set or String of type t, with the type arguments of t for
+// the type parameters of the class. Gives its type, and its parameters if it is a method.
+fn builtin_member(t: &str, name: &str) -> Option<(String, Option<Vec<ParamObj>>)> {
+    let (classname, typeargs) = types::split(&non_nullable(t));
+    let (typeparams, signatures): (&[&str], &[&str]) = match classname.as_str() {
+        "List" => (&["E"], &list::SIGNATURES),
+        "Map" => (&["K", "V"], &map::SIGNATURES),
+        "Set" => (&["E"], &set::SIGNATURES),
+        "String" => (&[], &string::SIGNATURES),
+        _ => return None
+    };
+    let typeparams: Vec<String> = typeparams.iter().map(|p| String::from(*p)).collect();
+    let (_, t, params) = signatures.iter().map(|sig| types::signature(sig)).find(|(n, _, _)| n == name)?;
+    let params = params.map(|params| params.into_iter().map(|p| ParamObj { typ: types::substitute(&p.typ, &typeparams, &typeargs), ..p }).collect());
+    Some((types::substitute(&t, &typeparams, &typeargs), params))
+}
+
+
+fn named(p: &ParamObj) -> Option<String> {
+    match p.kind {
+        ParamKind::Named(_) => Some(p.name.clone()),
+        _ => None
+    }
+}
+
+
+fn arithmetic(op: &NodeType, left: &str, right: &str) -> String {
+    let numeric = |t: &str| matches!(t, "int" | "double" | "num");
+    let t = match (op, left, right) {
+        (NodeType::Add, "String", "String") => "String",
+        (NodeType::Div, l, r) if numeric(l) && numeric(r) => "double",
+        (NodeType::IntDiv, l, r) if numeric(l) && numeric(r) => "int",
+        (_, "int", "int") => "int",
+        (_, "double", r) if numeric(r) => "double",
+        (_, l, "double") if numeric(l) => "double",
+        (_, l, r) if numeric(l) && numeric(r) => "num",
+        _ => "dynamic"
+    };
+    String::from(t)
+}
+
+
+fn nullable(t: &str) -> String {
+    if t.ends_with('?') || matches!(t, "dynamic" | "Null") {
+        String::from(t)
+    }
+    else {
+        format!("{}?", t)
+    }
+}
+
+
+// Whether a value of type t may be null.
+fn is_nullable(t: &str) -> bool {
+    t.ends_with('?') || matches!(t, "Null" | "dynamic")
+}
+
+
+fn non_nullable(t: &str) -> String {
+    String::from(t.strip_suffix('?').unwrap_or(t))
+}
+
+
+// The type all of types are, if they are the same.
+fn same_or_dynamic(mut types: Vec<String>) -> String {
+    types.dedup();
+    match types.len() {
+        1 => types.remove(0),
+        _ => String::from("dynamic")
+    }
+}
+
+
+// Variables declared with 'var' or without type are dynamic, until they get inferred.
+fn dynamic_if_untyped(t: String) -> String {
+    match t.as_str() {
+        "" | "var" => String::from("dynamic"),
+        _ => t
+    }
+}
+
+
+// The variable of 'x == null'.
+fn null_test(cond: &Node) -> Option<String> {
+    if !matches!(cond.nodetype, NodeType::Equal) {
+        return None;
+    }
+    match (&cond.children[0].nodetype, &cond.children[1].nodetype) {
+        (NodeType::Name(name), NodeType::Null) if cond.children[0].children.is_empty() => Some(name.clone()),
+        _ => None
+    }
+}
+
+
+// The variables a condition is false only when they are not null: those of 'x == null', also in disjunctions.
+fn null_tests(cond: &Node) -> Vec<String> {
+    match cond.nodetype {
+        NodeType::LogOr => {
+            let mut names = null_tests(&cond.children[0]);
+            names.extend(null_tests(&cond.children[1]));
+            names
+        }
+        _ => null_test(cond).into_iter().collect()
+    }
+}
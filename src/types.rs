@@ -0,0 +1,335 @@
+// Types are kept as the strings they are written as in dart, like 'Map<String, List<int>>?'.
+
+use crate::exception;
+use crate::node::{Node, NodeType};
+use crate::object::{ParamKind, ParamObj};
+use crate::objsys::ObjSys;
+
+
+pub const BUILTINS: [&str; 20] = [
+    "int", "double", "num", "String", "bool", "List", "Map", "Set", "MapEntry", "Null", "Object", "Function", "Type", "Record", "dynamic",
+    "Future", "Duration", "Stream", "StreamController", "StreamSubscription"
+];
+
+
+// The name and the type arguments of a type: 'Map<String, int>' gives 'Map' and ['String', 'int'].
+// A nullable type keeps its '?' on the name.
+pub fn split(t: &str) -> (String, Vec<String>) {
+    let nullable = t.ends_with(">?");
+    let t = if nullable { &t[..t.len() - 1] } else { t };
+
+    let open = match t.find('<') {
+        Some(i) if t.ends_with('>') => i,
+        _ => return (String::from(t), Vec::new())
+    };
+
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut arg = String::new();
+    for c in t[open + 1 .. t.len() - 1].chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(String::from(arg.trim()));
+                arg.clear();
+                continue;
+            }
+            _ => {}
+        }
+        arg.push(c);
+    }
+    args.push(String::from(arg.trim()));
+
+    let name = if nullable { format!("{}?", &t[..open]) } else { String::from(&t[..open]) };
+    (name, args)
+}
+
+
+// The inverse of split.
+pub fn join(name: &str, args: &[String]) -> String {
+    if args.is_empty() {
+        return String::from(name);
+    }
+    match name.strip_suffix('?') {
+        Some(base) => format!("{}<{}>?", base, args.join(", ")),
+        None => format!("{}<{}>", name, args.join(", "))
+    }
+}
+
+
+// The type of what awaiting a value of type t gives: 'int' for 'Future<int>'.
+pub fn flatten(t: &str) -> String {
+    let (base, args) = split(t);
+    match (base.as_str(), args.as_slice()) {
+        ("Future", [arg]) => arg.clone(),
+        ("Future?", [arg]) if arg.ends_with('?') || arg == "dynamic" => arg.clone(),
+        ("Future?", [arg]) => format!("{}?", arg),
+        ("Future", []) | ("Future?", []) => String::from("dynamic"),
+        _ => String::from(t)
+    }
+}
+
+
+// The type of the elements a generator of type t gives: 'int' for 'Stream<int>' or 'Iterable<int>'.
+pub fn generated(t: &str) -> String {
+    let (base, args) = split(t);
+    match (base.as_str(), args.as_slice()) {
+        ("Stream" | "Iterable", [arg]) => arg.clone(),
+        _ => String::from("dynamic")
+    }
+}
+
+
+// A record type, as dart writes it: '(int, String)', '({int x, int y})' or '(int, {int y})'.
+// The named fields must be sorted by name.
+pub fn record(positional: &[String], named: &[(String, String)]) -> String {
+    let mut fields = positional.join(", ");
+    if positional.len() == 1 && named.is_empty() {
+        fields.push(',');
+    }
+    if !named.is_empty() {
+        if !positional.is_empty() {
+            fields.push_str(", ");
+        }
+        let named: Vec<String> = named.iter().map(|(name, t)| format!("{} {}", t, name)).collect();
+        fields.push_str(&format!("{{{}}}", named.join(", ")));
+    }
+    format!("({})", fields)
+}
+
+
+// The types of the positional fields of a record, and the named fields with their types.
+pub type RecordFields = (Vec<String>, Vec<(String, String)>);
+
+
+// The inverse of record.
+pub fn record_fields(t: &str) -> Option<RecordFields> {
+    let inner = t.strip_prefix('(')?.strip_suffix(')')?;
+
+    let mut positional = Vec::new();
+    let mut named = Vec::new();
+    for part in fields(inner) {
+        match part.strip_prefix('{').and_then(|p| p.strip_suffix('}')) {
+            Some(inner) => {
+                for field in fields(inner) {
+                    let (t, name) = field.rsplit_once(' ')?;
+                    named.push((String::from(name), String::from(t)));
+                }
+            }
+            None => positional.push(part)
+        }
+    }
+    Some((positional, named))
+}
+
+
+// Splits at the commas that are not nested in brackets.
+fn fields(s: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut part = String::new();
+    for c in s.chars() {
+        match c {
+            '<' | '(' | '{' => depth += 1,
+            '>' | ')' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(String::from(part.trim()));
+                part.clear();
+                continue;
+            }
+            _ => {}
+        }
+        part.push(c);
+    }
+    parts.push(String::from(part.trim()));
+    parts.into_iter().filter(|p| !p.is_empty()).collect()
+}
+
+
+// A member as declared in dart, like 'E removeAt(int index)', 'int get length' or 'String join([String separator])'.
+// Gives its name, its type, and the parameters if it is a method or operator.
+pub fn signature(sig: &str) -> (String, String, Option<Vec<ParamObj>>) {
+    let (head, params) = match sig.split_once('(') {
+        Some((head, params)) => (head, Some(params.trim_end_matches(')'))),
+        None => (sig, None)
+    };
+    let (rettype, name) = head.rsplit_once(' ').unwrap();
+    let rettype = rettype.trim_end_matches(" get").trim_end_matches(" operator");
+    let params = params.map(|params| fields(params).into_iter().map(|p| {
+        let optional = p.starts_with('[');
+        let (typ, name) = p.trim_matches(|c| c == '[' || c == ']').rsplit_once(' ').unwrap();
+        let kind = if optional { ParamKind::Optional } else { ParamKind::Positional };
+        ParamObj { typ: String::from(typ), name: String::from(name), fieldinit: false, kind, default: None }
+    }).collect());
+    (String::from(name), String::from(rettype), params)
+}
+
+
+// The type a value must have to match a pattern that can't fail otherwise, like '(Object?, Object?)' for '(a, b)'.
+pub fn of_pattern(pattern: &Node) -> String {
+    match &pattern.nodetype {
+        NodeType::RecordPattern => {
+            let mut positional = Vec::new();
+            let mut named = Vec::new();
+            for field in &pattern.children {
+                match &field.nodetype {
+                    NodeType::NamedArg(name) => named.push((name.clone(), of_pattern(&field.children[0]))),
+                    _ => positional.push(of_pattern(field))
+                }
+            }
+            named.sort();
+            record(&positional, &named)
+        }
+        NodeType::VarPattern(t, _) if t != "var" => t.clone(),
+        _ => String::from("Object?")
+    }
+}
+
+
+// '1', or '-1'. Where a double is expected, it is one.
+pub fn is_int_literal(node: &Node) -> bool {
+    match &node.nodetype {
+        NodeType::Int(_) => true,
+        NodeType::Sub => node.children.len() == 1 && matches!(node.children[0].nodetype, NodeType::Int(_)),
+        _ => false
+    }
+}
+
+
+// The name a type parameter declares: 'T' for 'T extends num'.
+pub fn param_name(param: &str) -> String {
+    String::from(param.split_whitespace().next().unwrap_or(param))
+}
+
+
+// Replaces the type parameters in t with the type arguments given for them.
+pub fn substitute(t: &str, params: &[String], args: &[String]) -> String {
+    let nullable = t.ends_with('?');
+    let base = t.strip_suffix('?').unwrap_or(t);
+
+    if let Some(i) = params.iter().position(|p| p == base) {
+        let arg = args.get(i).map(|a| a.as_str()).unwrap_or("dynamic");
+        return if nullable && !arg.ends_with('?') && arg != "dynamic" { format!("{}?", arg) } else { String::from(arg) };
+    }
+
+    let (name, targs) = split(t);
+    if targs.is_empty() {
+        return String::from(t);
+    }
+    let targs: Vec<String> = targs.iter().map(|a| substitute(a, params, args)).collect();
+    join(&name, &targs)
+}
+
+
+// Whether a value of runtime type 'actual' is a 'wanted'. Type arguments are covariant.
+pub fn is_subtype(actual: &str, wanted: &str, objsys: &ObjSys) -> bool {
+    if matches!(wanted, "dynamic" | "Object?") || actual == wanted {
+        return true;
+    }
+    if let Some(base) = wanted.strip_suffix('?') {
+        return actual == "Null" || is_subtype(actual.strip_suffix('?').unwrap_or(actual), base, objsys);
+    }
+    if actual == "Null" || actual == "dynamic" || actual.ends_with('?') {
+        return false;
+    }
+    if wanted == "Object" {
+        return true;
+    }
+    // Records are subtypes of records of the same shape with supertypes for field types.
+    if let Some((apos, anamed)) = record_fields(actual) {
+        return match record_fields(wanted) {
+            Some((wpos, wnamed)) => {
+                apos.len() == wpos.len()
+                    && anamed.len() == wnamed.len()
+                    && apos.iter().zip(&wpos).all(|(a, w)| is_subtype(a, w, objsys))
+                    && anamed.iter().zip(&wnamed).all(|((an, a), (wn, w))| an == wn && is_subtype(a, w, objsys))
+            }
+            None => wanted == "Record"
+        };
+    }
+
+    let (abase, aargs) = split(actual);
+    let (wbase, wargs) = split(wanted);
+
+    let args = match (abase.as_str(), wbase.as_str()) {
+        ("int", "num") | ("double", "num") => aargs,
+        ("List", "Iterable") | ("Set", "Iterable") => aargs,
+        (a, w) if objsys.has_class(a) => {
+            if !objsys.is_subtype(a, w) {
+                return false;
+            }
+            objsys.supertype_args(a, &aargs, w)
+        }
+        (a, w) if a == w => aargs,
+        (a, w) => return exception::is_subtype(a, w)
+    };
+
+    // A raw type, like 'List', takes any type arguments.
+    wargs.is_empty() || wargs.iter().enumerate().all(|(i, w)| {
+        let a = args.get(i).map(|a| a.as_str()).unwrap_or("dynamic");
+        match a {
+            "dynamic" => matches!(w.as_str(), "dynamic" | "Object?"),
+            _ => is_subtype(a, w, objsys)
+        }
+    })
+}
+
+
+// The least upper bound of types, as for the elements of a list literal.
+pub fn lub(types: &[String], objsys: &ObjSys) -> String {
+    let mut distinct: Vec<&String> = Vec::new();
+    for t in types {
+        if !distinct.contains(&t) {
+            distinct.push(t);
+        }
+    }
+
+    if distinct.is_empty() {
+        return String::from("dynamic");
+    }
+    if distinct.len() == 1 {
+        return distinct[0].clone();
+    }
+    if distinct.iter().any(|t| *t == "Null") {
+        let rest: Vec<String> = distinct.iter().filter(|t| **t != "Null").map(|t| (*t).clone()).collect();
+        let t = lub(&rest, objsys);
+        return if t.ends_with('?') || t == "dynamic" { t } else { format!("{}?", t) };
+    }
+    if distinct.iter().all(|t| matches!(t.as_str(), "int" | "double" | "num")) {
+        return String::from("num");
+    }
+
+    // The first supertype of one that all are subtypes of.
+    let (first, args) = split(distinct[0]);
+    if objsys.has_class(&first) {
+        for candidate in objsys.supertypes(&first) {
+            let t = join(&candidate, &objsys.supertype_args(&first, &args, &candidate));
+            if distinct.iter().all(|d| is_subtype(d, &t, objsys)) {
+                return t;
+            }
+        }
+    }
+    String::from("Object")
+}
+
+
+// Binds the type parameters in paramtype to the matching parts of the actual type of an argument.
+// A parameter bound twice gets the least upper bound.
+pub fn bind(paramtype: &str, actual: &str, params: &[String], bound: &mut [Vec<String>]) {
+    let base = paramtype.strip_suffix('?').unwrap_or(paramtype);
+    if let Some(i) = params.iter().position(|p| p == base) {
+        let actual = if paramtype.ends_with('?') { actual.strip_suffix('?').unwrap_or(actual) } else { actual };
+        if actual != "Null" || !paramtype.ends_with('?') {
+            bound[i].push(String::from(actual));
+        }
+        return;
+    }
+    let (pbase, pargs) = split(base);
+    let (abase, aargs) = split(actual);
+    if pbase == abase && pargs.len() == aargs.len() {
+        for (p, a) in pargs.iter().zip(aargs.iter()) {
+            bind(p, a, params, bound);
+        }
+    }
+}
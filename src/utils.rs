@@ -1,5 +1,5 @@
 use std::process;
-use crate::{token::*, Ctx};
+use crate::{object::Object, token::*, Ctx};
 
 
 
@@ -13,14 +13,20 @@ pub fn dprint<S: Into<String>>(s: S) {
 
 
 pub fn dart_parseerror<S: Into<String>>(msg: S, ctx: &Ctx, tokens: &Vec<Token>, index: usize) -> ! {
+    dart_error_at(msg, &ctx.filepath, tokens[index].find_token_position(), ctx)
+}
+
 
-    let (linenum, symnum) = &tokens[index].find_token_position();
+// For errors found after parsing, at a position remembered from it.
+pub fn dart_error_at<S: Into<String>>(msg: S, filepath: &str, pos: (usize, usize), ctx: &Ctx) -> ! {
+
+    let (linenum, symnum) = pos;
 
     if ctx.debug {
-        panic!("{}:{}:{}: Error: {}", ctx.filepath, linenum, symnum, msg.into());
+        panic!("{}:{}:{}: Error: {}", filepath, linenum, symnum, msg.into());
     }
     else {
-        println!("{}:{}:{}: Error: {}", ctx.filepath, linenum, symnum, msg.into());
+        println!("{}:{}:{}: Error: {}", filepath, linenum, symnum, msg.into());
         process::exit(1);
     }
 }
@@ -36,3 +42,25 @@ pub fn dart_evalerror<S: Into<String>>(msg: S, ctx: &Ctx) -> ! {
         process::exit(1);
     }
 }
+
+
+// An exception that got all the way out of main.
+pub fn dart_unhandled(exception: String, trace: &str, ctx: &Ctx) -> ! {
+
+    if ctx.debug {
+        panic!("Unhandled exception:\n{}\n{}", exception, trace);
+    }
+    else {
+        println!("Unhandled exception:\n{}\n{}", exception, trace);
+        // As the dart vm.
+        process::exit(255);
+    }
+}
+
+
+// Arity check for methods on the built-in types.
+pub fn expect_args(name: &str, args: &[Object], count: usize, ctx: &Ctx) {
+    if args.len() != count {
+        dart_evalerror(format!("Too few or too many arguments to '{}': expected {}, got {}.", name, count, args.len()), ctx)
+    }
+}
@@ -0,0 +1,100 @@
+abstract class Shape {
+  String name = "shape";
+
+  double area();
+
+  bool isBig() {
+    return area() > 5.0;
+  }
+}
+
+class Square extends Shape {
+  double side;
+
+  Square(this.side) {
+    name = "square";
+  }
+
+  double area() {
+    return side * side;
+  }
+}
+
+class Printable {
+  String show() {
+    return "printable";
+  }
+}
+
+class Doc implements Printable {
+  String show() {
+    return "doc";
+  }
+}
+
+mixin Walker {
+  int steps = 0;
+
+  String move() {
+    steps++;
+    return "walk";
+  }
+}
+
+mixin Swimmer {
+  String move() {
+    return "swim and " + super.move();
+  }
+}
+
+class Creature {
+  String move() {
+    return "crawl";
+  }
+}
+
+class Duck extends Creature with Walker, Swimmer {
+  String quack() {
+    return "quack";
+  }
+}
+
+class Frog extends Creature with Swimmer {
+}
+
+void main() {
+  var s = Square(3.0);
+  assert(s.area() == 9.0);
+  assert(s.isBig());
+  assert(s.name == "square");
+  assert(s is Shape);
+  assert(s is Square);
+  assert(s is Object);
+  assert(s is! Printable);
+
+  var d = Doc();
+  assert(d.show() == "doc");
+  assert(d is Printable);
+  assert(d is! Shape);
+
+  // Later mixins take precedence, super goes down the linearization.
+  var duck = Duck();
+  assert(duck.move() == "swim and walk");
+  assert(duck.steps == 1);
+  assert(duck is Walker);
+  assert(duck is Swimmer);
+  assert(duck is Creature);
+
+  var frog = Frog();
+  assert(frog.move() == "swim and crawl");
+  assert(frog is! Walker);
+
+  assert(1 is int);
+  assert(1 is num);
+  assert(1.5 is! int);
+  assert("a" is String);
+  assert([1] is List);
+  assert(null is! Object);
+
+  print(duck.move());
+}
@@ -0,0 +1,151 @@
+import 'dart:async';
+
+Future<int> double(List log, int n) async {
+  log.add("double ${n}");
+  return n * 2;
+}
+
+Future<int> sum(List log, List<int> xs) async {
+  var total = 0;
+  for (var x in xs) {
+    total = total + await double(log, x);
+  }
+  return total;
+}
+
+Future<String> fail() async {
+  await null;
+  throw FormatException("bad");
+}
+
+Future<String> recover(List log) async {
+  try {
+    return await fail();
+  } on FormatException catch (e) {
+    return "caught ${e.message}";
+  } finally {
+    log.add("finally");
+  }
+}
+
+Future<void> count(List log, String name, int n) async {
+  var i = 0;
+  while (i < n) {
+    log.add("${name}${i}");
+    await null;
+    i++;
+  }
+}
+
+Future<bool> check(List log, String name) async {
+  log.add(name);
+  return true;
+}
+
+class Box {
+  int? value;
+  List<int> items = [];
+}
+
+class Counter {
+  int value = 0;
+
+  Future<int> add(int n) async {
+    value = value + await Future.value(n);
+    return value;
+  }
+}
+
+Future<void> main() async {
+  // The synchronous part of an async function runs before it returns.
+  var log = [];
+  var f = double(log, 1);
+  assert(log.join(",") == "double 1");
+  assert(f is Future<int>);
+  assert(await f == 2);
+
+  // Microtasks run before timers, and timers in the order they are due.
+  var order = [];
+  Future.delayed(Duration(milliseconds: 20), () => order.add("late"));
+  Future(() => order.add("timer"));
+  scheduleMicrotask(() => order.add("microtask"));
+  Future.delayed(Duration(milliseconds: 10), () => order.add("soon"));
+  order.add("sync");
+  await Future.delayed(Duration(milliseconds: 30));
+  assert(order.join(",") == "sync,microtask,timer,soon,late");
+
+  log = [];
+  assert(await sum(log, [1, 2, 3]) == 12);
+  assert(log.join(",") == "double 1,double 2,double 3");
+
+  // An await in try continues in the catch clause when the future fails.
+  log = [];
+  assert(await recover(log) == "caught bad");
+  assert(log.join(",") == "finally");
+
+  // Two async functions take turns at their awaits.
+  log = [];
+  var a = count(log, "a", 2);
+  var b = count(log, "b", 2);
+  await a;
+  await b;
+  assert(log.join(",") == "a0,b0,a1,b1");
+
+  var counter = Counter();
+  await counter.add(3);
+  assert(await counter.add(4) == 7);
+
+  var results = [];
+  await double(log, 5).then((v) => results.add(v));
+  await fail().catchError((e) => results.add(e.message));
+  await Future.error("oops").then((v) => results.add(v), onError: (e) => results.add("error ${e}"));
+  assert(results.join(",") == "10,bad,error oops");
+
+  // Awaits in collection if elements, for-in iterables and after elements that loop or branch.
+  var c = true;
+  assert([if (c) 1, for (var i in [2, 3]) i, await Future.value(4)].join(",") == "1,2,3,4");
+  assert([0, if (await Future.value(c)) await Future.value(1) else 2, if (!c || await Future.value(c)) 3].join(",") == "0,1,3");
+  assert([for (var i in await Future.value([1, 2])) i * 10].join(",") == "10,20");
+
+  // Awaits in cascade sections, which run in order on the receiver.
+  var box = Box()..value = 1..items.add(await Future.value(2))..items.add(3);
+  assert(box.value == 1 && box.items.join(",") == "2,3");
+  var inner = Box()..items.add(await Future.value(4))..value = (Box()..value = await Future.value(5)).value;
+  assert(inner.value == 5 && inner.items.join(",") == "4");
+  Box? none;
+  none?..value = await Future.value(6);
+  assert(none == null);
+
+  // '??=' with an await in its target.
+  var boxes = [Box()];
+  boxes[await Future.value(0)].value ??= await Future.value(7);
+  boxes[await Future.value(0)].value ??= 8;
+  assert(boxes[0].value == 7);
+
+  // Awaits in the guards of switch cases, in the scope of their patterns.
+  var guards = [];
+  switch (await Future.value(2)) {
+    case int n when await check(guards, "one") && n == 1:
+      guards.add("matched one");
+    case int n when await check(guards, "two") && n == 2:
+      guards.add("matched ${n}");
+    case _:
+      guards.add("matched other");
+  }
+  assert(guards.join(",") == "one,two,matched 2");
+  var guarded = "";
+  try {
+    switch (1) {
+      case 1 when await Future.error("bad guard"):
+        guarded = "body";
+    }
+  } catch (e) {
+    guarded = e;
+  }
+  assert(guarded == "bad guard");
+
+  var d = Duration(hours: 1, minutes: 2, seconds: 3, microseconds: 4);
+  assert(d.inMinutes == 62);
+  assert(d.toString() == "1:02:03.000004");
+  print("async done");
+}
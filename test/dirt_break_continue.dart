@@ -0,0 +1,86 @@
+int firstOver(List xs, int limit) {
+  var found = -1;
+  for (var i = 0; i < xs.length; i++) {
+    if (xs[i] > limit) {
+      found = xs[i];
+      break;
+    }
+  }
+  return found;
+}
+
+int indexOf(List xs, int x) {
+  var i = 0;
+  while (i < xs.length) {
+    if (xs[i] == x) {
+      return i;
+    }
+    i++;
+  }
+  return -1;
+}
+
+void main() {
+  assert(firstOver([1, 5, 9, 12], 6) == 9);
+  assert(firstOver([1, 2], 6) == -1);
+
+  // continue skips the rest of the body, but not the update.
+  var odds = [];
+  for (var i = 0; i < 10; i++) {
+    if (i / 2 == i ~/ 2) {
+      continue;
+    }
+    odds.add(i);
+  }
+  assert(odds.join(",") == "1,3,5,7,9");
+
+  var n = 0;
+  while (true) {
+    n++;
+    if (n < 5) {
+      continue;
+    }
+    break;
+  }
+  assert(n == 5);
+
+  var m = 0;
+  do {
+    m++;
+    if (m == 3) {
+      break;
+    }
+  } while (m < 10);
+  assert(m == 3);
+
+  // Labels reach out of nested loops.
+  var pairs = [];
+  outer:
+  for (var i = 0; i < 3; i++) {
+    for (var j = 0; j < 3; j++) {
+      if (j == 2) {
+        continue outer;
+      }
+      if (i == 2) {
+        break outer;
+      }
+      pairs.add(i * 10 + j);
+    }
+  }
+  assert(pairs.join(",") == "0,1,10,11");
+
+  // A labeled block can be left with break.
+  var reached = false;
+  block: {
+    if (pairs.length == 4) {
+      break block;
+    }
+    reached = true;
+  }
+  assert(!reached);
+
+  // Returning from inside a loop.
+  assert(indexOf([4, 8, 15], 15) == 2);
+
+  print(pairs);
+}
@@ -0,0 +1,97 @@
+class Paint {
+  String color = "black";
+  int strokeWidth = 1;
+  List<String> log = [];
+  Paint? next;
+
+  void stroke(String what) {
+    log.add(color + " " + what);
+  }
+
+  Paint self() {
+    return this;
+  }
+}
+
+Paint? nothing() {
+  return null;
+}
+
+Future<Paint> later() async {
+  return Paint();
+}
+
+Future<void> main() async {
+  var paint = Paint()
+    ..color = "red"
+    ..strokeWidth = 2
+    ..stroke("line");
+  assert(paint.color == "red");
+  assert(paint.strokeWidth == 2);
+  assert(paint.log.join(",") == "red line");
+
+  // The cascade gives its receiver, not what its sections give.
+  var same = paint..stroke("circle");
+  assert(same == paint);
+  assert(paint.log.join(",") == "red line,red circle");
+
+  // Index assignments, and methods that give other values.
+  var xs = [1, 2, 3]
+    ..[0] = 10
+    ..add(4)
+    ..removeAt(1);
+  assert(xs.join(",") == "10,3,4");
+  var m = {"a": 1}..["b"] = 2;
+  assert(m["b"] == 2);
+  assert(m.length == 2);
+
+  // Sections can access further, and assign further in.
+  paint
+    ..next = Paint()
+    ..next!.color = "blue"
+    ..self().strokeWidth = 5
+    ..log.add("x");
+  assert(paint.next!.color == "blue");
+  assert(paint.strokeWidth == 5);
+  assert(paint.log.length == 3);
+
+  // A section assigns the whole expression after '=', but no cascade of its own.
+  var p = Paint()..strokeWidth = 1 + 2 * 3..color = "green";
+  assert(p.strokeWidth == 7);
+  assert(p.color == "green");
+
+  // Cascades nest inside parentheses and arguments.
+  var outer = Paint()..next = (Paint()..color = "white");
+  assert(outer.next!.color == "white");
+  var names = [];
+  names.add(Paint()..color = "grey");
+  assert(names[0].color == "grey");
+
+  // '?..' gives null, and runs nothing, on null.
+  var ran = [];
+  var none = nothing()?..stroke("never")..log.add("never");
+  assert(none == null);
+  var some = Paint()?..color = "pink";
+  assert(some!.color == "pink");
+
+  // '??=' in a section.
+  var q = Paint()
+    ..next ??= Paint()
+    ..next!.color = "first"
+    ..next ??= Paint();
+  assert(q.next!.color == "first");
+
+  // The receiver can be awaited.
+  var awaited = (await later())..color = "gold";
+  assert(awaited.color == "gold");
+
+  // Errors in a section are thrown by the cascade.
+  try {
+    var broken = [1]..removeAt(5);
+    assert(false);
+  } on RangeError catch (e) {
+    ran.add("caught");
+  }
+  assert(ran.join(",") == "caught");
+  print("cascades done");
+}
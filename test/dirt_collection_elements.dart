@@ -0,0 +1,60 @@
+// Spread, if and for elements in list, set and map literals.
+
+List<int> evens(int n) {
+  return [for (int i = 0; i < n; i++) if (i > 0) i * 2];
+}
+
+void main() {
+  // Spreads.
+  var xs = [1, 2];
+  var ys = [0, ...xs, 3];
+  print(ys);
+  assert(ys.length == 4);
+  List? none;
+  var zs = [...?none, 4];
+  print(zs);
+  assert(zs.length == 1);
+  var s = {...xs, ...ys};
+  print(s);
+  assert(s.length == 4);
+  var m = {'a': 1};
+  var m2 = {...m, 'b': 2};
+  print(m2);
+  assert(m2['b'] == 2);
+  var copy = {...m};
+  print(copy);
+  assert(copy['a'] == 1);
+  Map<String, int> typed = {...m};
+  print(typed.runtimeType);
+
+  // If elements.
+  bool promo = true;
+  var nav = ['Home', 'Furniture', if (promo) 'Outlet'];
+  print(nav);
+  assert(nav.length == 3);
+  var parity = [if (xs.length == 3) 'odd' else 'even'];
+  print(parity);
+  assert(parity[0] == 'even');
+  var flags = {'a': 1, if (!promo) 'b': 2};
+  print(flags);
+  assert(flags.length == 1);
+
+  // For elements.
+  var strs = ['#0', for (var i in xs) '#${i}'];
+  print(strs);
+  assert(strs[2] == '#2');
+  print(evens(4));
+  assert(evens(4).length == 3);
+  var squares = {for (var i in [1, 2, 3]) i: i * i};
+  print(squares);
+  assert(squares[3] == 9);
+  var pairs = [for (var (a, b) in [(1, 2), (3, 4)]) a + b];
+  print(pairs);
+  assert(pairs[1] == 7);
+  var nested = [for (var i in xs) for (var j in xs) if (i != j) [i, j]];
+  print(nested);
+  assert(nested.length == 2);
+  var spreads = [for (var i in xs) ...[i, i]];
+  print(spreads);
+  assert(spreads.length == 4);
+}
@@ -0,0 +1,43 @@
+int depth(int n) {
+  if (n == 0) {
+    return 0;
+  }
+  return depth(n - 1) + 1;
+}
+
+void countdown(int n, List<int> seen) {
+  if (n > 0) {
+    seen.add(n);
+    countdown(n - 1, seen);
+  }
+}
+
+int forever(int n) => forever(n + 1) + 1;
+
+int fib(int n) {
+  if (n < 2) {
+    return n;
+  }
+  return fib(n - 1) + fib(n - 2);
+}
+
+void main() {
+  // As deep as the dart vm goes, more than the main thread has stack for.
+  assert(depth(5000) == 5000);
+  var seen = <int>[];
+  countdown(5000, seen);
+  assert(seen.length == 5000);
+
+  // Running out of stack is an error that can be caught.
+  var overflowed = false;
+  try {
+    forever(0);
+  } on StackOverflowError catch (e) {
+    overflowed = true;
+    assert(e.toString() == "Stack Overflow");
+  }
+  assert(overflowed);
+  assert(depth(100) == 100);
+  assert(fib(15) == 610);
+  print("deep recursion done");
+}
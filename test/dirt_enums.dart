@@ -0,0 +1,95 @@
+enum Color { red, green, blue }
+
+abstract class Describable {
+  String describe();
+}
+
+enum Planet implements Describable {
+  mercury(3.7, "Mercury"),
+  earth(9.8, "Earth"),
+  jupiter(24.8, "Jupiter");
+
+  final double gravity;
+  final String title;
+
+  const Planet(this.gravity, this.title);
+
+  double weight(double mass) => mass * gravity;
+
+  bool get isHeavy => gravity > 10.0;
+
+  String describe() => title + " is planet number " + index.toString();
+
+  static Planet heaviest() {
+    var heaviest = mercury;
+    for (var i = 0; i < values.length; i++) {
+      if (values[i].gravity > heaviest.gravity) {
+        heaviest = values[i];
+      }
+    }
+    return heaviest;
+  }
+}
+
+enum Size {
+  small, large;
+
+  String toString() => "size " + name;
+}
+
+String label(Color c) {
+  switch (c) {
+    case Color.red:
+      return "warm";
+    case Color.green:
+    case Color.blue:
+      return "cool";
+  }
+  return "none";
+}
+
+void main() {
+  var c = Color.green;
+  assert(c.index == 1);
+  assert(c.name == "green");
+  assert(c == Color.green);
+  assert(c != Color.red);
+  assert(identical(c, Color.green));
+  assert(c.toString() == "Color.green");
+
+  assert(Color.values.length == 3);
+  assert(Color.values[0] == Color.red);
+  assert(Color.values[2].name == "blue");
+
+  assert(label(Color.red) == "warm");
+  assert(label(Color.blue) == "cool");
+
+  var kind = switch (c) {
+    Color.red => 0,
+    Color.green => 1,
+    Color.blue => 2
+  };
+  assert(kind == 1);
+
+  assert(Planet.earth.weight(10.0) == 98.0);
+  assert(Planet.jupiter.isHeavy);
+  assert(!Planet.mercury.isHeavy);
+  assert(Planet.heaviest() == Planet.jupiter);
+  assert(Planet.earth.describe() == "Earth is planet number 1");
+
+  Describable d = Planet.mercury;
+  assert(d.describe() == "Mercury is planet number 0");
+  assert(d is Planet);
+
+  // Printing, interpolation and collections go through toString.
+  assert("${c}" == "Color.green");
+  assert("color: ${Color.red}!" == "color: Color.red!");
+  assert(Color.values.toString() == "[Color.red, Color.green, Color.blue]");
+  assert({Color.blue: 1}.toString() == "{Color.blue: 1}");
+  assert(Color.values.join(",") == "Color.red,Color.green,Color.blue");
+  assert("${Size.small}" == "size small");
+  assert([Size.large].toString() == "[size large]");
+  print(Color.values);
+
+  print(Planet.values[1].name);
+}
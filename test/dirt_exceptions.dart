@@ -0,0 +1,176 @@
+class BadInput {
+  String why;
+
+  BadInput(this.why) {}
+}
+
+int parse(String s) {
+  if (s == "") {
+    throw FormatException("empty input");
+  }
+  return 1;
+}
+
+int safeDivide(int a, int b) {
+  try {
+    return a ~/ b;
+  } on IntegerDivisionByZeroException {
+    return -1;
+  }
+}
+
+String check(int n) {
+  if (n < 0) {
+    throw BadInput("negative");
+  }
+  return "ok";
+}
+
+void main() {
+  var log = [];
+
+  try {
+    parse("");
+    log.add("not reached");
+  } on FormatException catch (e) {
+    log.add(e.message);
+  } finally {
+    log.add("finally");
+  }
+  assert(log.join(",") == "empty input,finally");
+
+  assert(safeDivide(7, 2) == 3);
+  assert(safeDivide(7, 0) == -1);
+
+  // Built-in failures are catchable.
+  var xs = [1, 2, 3];
+  var caught = "";
+  try {
+    xs[5];
+  } on RangeError catch (e) {
+    caught = e.toString();
+  }
+  assert(caught == "RangeError (index): Index out of range: index should be less than 3: 5");
+
+  try {
+    var empty = [];
+    empty.first;
+  } on StateError catch (e) {
+    caught = e.toString();
+  }
+  assert(caught == "Bad state: No element");
+
+  var nothing = null;
+  try {
+    nothing.length;
+  } catch (e) {
+    caught = "null access";
+  }
+  assert(caught == "null access");
+
+  // User objects can be thrown, and 'on' clauses are tried in order.
+  try {
+    check(-1);
+  } on FormatException {
+    caught = "wrong clause";
+  } on BadInput catch (e, st) {
+    caught = e.why;
+  }
+  assert(caught == "negative");
+
+  // Uncaught in the inner try, caught by the outer one.
+  try {
+    try {
+      throw "inner";
+    } on FormatException {
+      caught = "wrong";
+    } finally {
+      log.add("inner finally");
+    }
+  } catch (e) {
+    caught = e;
+  }
+  assert(caught == "inner");
+  assert(log.last == "inner finally");
+
+  try {
+    try {
+      throw Exception("again");
+    } catch (e) {
+      rethrow;
+    }
+  } catch (e) {
+    caught = e.toString();
+  }
+  assert(caught == "Exception: again");
+
+  // A throw inside a loop stops it.
+  var count = 0;
+  try {
+    while (true) {
+      count++;
+      if (count == 3) {
+        throw count;
+      }
+    }
+  } catch (e) {
+    caught = "stopped";
+  }
+  assert(count == 3);
+
+  // A failed assert throws, and can be caught.
+  try {
+    assert(count == 4, "count is off");
+    caught = "not reached";
+  } on AssertionError catch (e) {
+    caught = "asserted";
+  }
+  assert(caught == "asserted");
+
+  // A getter the value doesn't have, as on a dynamic receiver.
+  dynamic n = 3;
+  try {
+    print(n.isEven);
+  } on NoSuchMethodError {
+    caught = "no getter";
+  }
+  assert(caught == "no getter");
+
+  // So are methods it doesn't have, and calling what isn't a function.
+  dynamic input = BadInput("none");
+  try {
+    input.fix(1);
+  } on NoSuchMethodError catch (e) {
+    caught = e.toString();
+  }
+  assert(caught.startsWith("NoSuchMethodError: Class 'BadInput' has no instance method 'fix'."));
+  assert(caught.endsWith("Tried calling: fix(1)"));
+  try {
+    input.reason;
+  } on NoSuchMethodError {
+    caught = "no field";
+  }
+  assert(caught == "no field");
+  dynamic text = "x";
+  try {
+    text.shout();
+  } on NoSuchMethodError {
+    caught = "no string method";
+  }
+  assert(caught == "no string method");
+  dynamic items = [1];
+  try {
+    items.push(2);
+  } on NoSuchMethodError {
+    caught = "no list method";
+  }
+  assert(caught == "no list method");
+  try {
+    n();
+  } on NoSuchMethodError {
+    caught = "not callable";
+  }
+  assert(caught == "not callable");
+
+  print(caught);
+}
@@ -0,0 +1,135 @@
+import 'extensions/shout.dart';
+
+extension StringX on String {
+  bool get isBlank => trim().isEmpty;
+
+  String repeat(int n) {
+    var out = "";
+    for (var i = 0; i < n; i++) {
+      out = out + this;
+    }
+    return out;
+  }
+
+  // Strings have a length of their own, which wins.
+  int get length => 0;
+}
+
+extension IntX on int {
+  int get doubled => this * 2;
+
+  bool isBetween(int lo, int hi) => this >= lo && this <= hi;
+
+  List<int> addTo(List<int> xs) => xs.map((x) => x + this).toList();
+
+  static int zero() => 0;
+}
+
+extension ListX<T> on List<T> {
+  T get second => this[1];
+
+  List<T> twice() => [...this, ...this];
+}
+
+extension NumX on num {
+  String kind() => "num";
+
+  String get sign {
+    if (this < 0) {
+      return "-";
+    }
+    return "+";
+  }
+}
+
+// More specific than NumX, so it wins for ints.
+extension IntKind on int {
+  String kind() => "int";
+
+  String get sign {
+    if (this < 0) {
+      return "negative";
+    }
+    return "positive";
+  }
+}
+
+class Point {
+  int x;
+  int y;
+
+  Point(this.x, this.y);
+
+  int get sum => x + y;
+}
+
+extension PointX on Point {
+  Point scaled(int k) => Point(x * k, y * k);
+
+  int get area => x * y;
+
+  int total() => sum + area;
+}
+
+extension on bool {
+  String get yesNo {
+    if (this) {
+      return "yes";
+    }
+    return "no";
+  }
+}
+
+void main() {
+  assert("  ".isBlank);
+  assert(!" a ".isBlank);
+  assert("ab".repeat(3) == "ababab");
+  assert("abc".length == 3);
+
+  assert(21.doubled == 42);
+  assert(5.isBetween(1, 10));
+  assert(!11.isBetween(1, 10));
+  assert(10.addTo([1, 2]).join(",") == "11,12");
+  assert(IntX.zero() == 0);
+
+  var xs = [1, 2, 3];
+  assert(xs.second == 2);
+  assert(xs.twice().join(",") == "1,2,3,1,2,3");
+  assert(["a", "b"].second == "b");
+
+  assert(3.kind() == "int");
+  assert(3.5.kind() == "num");
+  assert(3.sign == "positive");
+  assert((-1.5).sign == "-");
+  num n = 3;
+  assert(n.kind() == "int");
+
+  // Extensions don't apply to dynamic receivers.
+  dynamic d = 3;
+  var found = true;
+  try {
+    d.sign;
+  } on NoSuchMethodError {
+    found = false;
+  }
+  assert(!found);
+  found = true;
+  try {
+    d.kind();
+  } on NoSuchMethodError {
+    found = false;
+  }
+  assert(!found);
+
+  var p = Point(2, 3).scaled(2);
+  assert(p.x == 4 && p.y == 6);
+  assert(p.area == 24);
+  assert(p.total() == 34);
+
+  assert(true.yesNo == "yes");
+
+  // Only the extensions of the files imported are visible.
+  assert("hi".shout() == "HI!");
+  assert(viaLoud("hi") == "loud");
+  print("extensions done");
+}
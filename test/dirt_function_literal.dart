@@ -0,0 +1,71 @@
+
+
+int double(int x) => x * 2;
+
+int apply(f, int x) {
+  return f(x);
+}
+
+class Multiplier {
+  int factor = 3;
+
+  Multiplier() {}
+
+  int times(int x) => x * factor;
+
+  Function scaler() {
+    return (x) => x * factor;
+  }
+}
+
+class Button {
+  Function onTap;
+  var twice = (x) => x * 2;
+
+  Button(this.onTap);
+
+  void press() {
+    onTap();
+  }
+}
+
+void main() {
+  var add = (a, b) {
+    return a + b;
+  };
+  assert(add(2, 3) == 5);
+
+  var square = (int x) => x * x;
+  assert(square(4) == 16);
+
+  // Functions are values, literal or declared.
+  assert(apply(square, 5) == 25);
+  assert(apply(double, 5) == 10);
+  assert(apply((x) => x - 1, 5) == 4);
+
+  int triple(int x) => x * 3;
+  assert(apply(triple, 5) == 15);
+
+  var m = Multiplier();
+  assert(m.times(2) == 6);
+  var s = m.scaler();
+  assert(s(5) == 15);
+
+  // A function in a field is called like a method.
+  var taps = 0;
+  var b = Button(() {
+    taps = taps + 1;
+  });
+  b.press();
+  b.onTap();
+  assert(taps == 2);
+  assert(b.twice(3) == 6);
+
+  var names = [];
+  var letters = {"a": 1, "b": 2};
+  letters.forEach((k, v) {
+    names.add(k);
+  });
+  print(names.join(" "));
+  assert(names.length == 2);
+}
@@ -0,0 +1,108 @@
+Iterable<int> range(int n) sync* {
+  for (var i = 0; i < n; i++) {
+    yield i;
+  }
+}
+
+Iterable<int> naturals(List log) sync* {
+  var i = 0;
+  while (true) {
+    log.add(i);
+    yield i;
+    i++;
+  }
+}
+
+Iterable<String> words() sync* {
+  yield "a";
+  yield* ["b", "c"];
+  yield* range(2).map((i) => "n${i}");
+  yield "d";
+}
+
+Iterable<int> failing() sync* {
+  yield 1;
+  throw StateError("no more");
+}
+
+Iterable<int> cleaned(List log) sync* {
+  try {
+    yield 1;
+    yield 2;
+  } finally {
+    log.add("finally");
+  }
+}
+
+class Countdown {
+  int from;
+
+  Countdown(this.from);
+
+  Iterable<int> steps() sync* {
+    var i = from;
+    while (i > 0) {
+      yield i;
+      i--;
+    }
+  }
+}
+
+void main() {
+  var seen = [];
+  for (var i in range(4)) {
+    seen.add(i);
+  }
+  assert(seen.join(",") == "0,1,2,3");
+
+  // Nothing runs until the elements are asked for, and only as far as they are.
+  var log = [];
+  var all = naturals(log);
+  assert(log.isEmpty);
+  assert(all.take(3).toList().join(",") == "0,1,2");
+  assert(log.join(",") == "0,1,2");
+
+  // first, isEmpty and isNotEmpty only run it to its first element.
+  log = [];
+  assert(naturals(log).first == 0);
+  assert(naturals(log).isNotEmpty);
+  assert(!naturals(log).isEmpty);
+  assert(log.join(",") == "0,0,0");
+  assert(naturals([]).map((i) => i * 10).skip(2).first == 20);
+  assert(range(0).isEmpty);
+
+  // Each iteration runs the body from the start.
+  assert(range(3).toList().join(",") == "0,1,2");
+  var r = range(2);
+  assert(r.toList().join(",") == "0,1");
+  assert(r.toList().join(",") == "0,1");
+
+  assert(words().toList().join(",") == "a,b,c,n0,n1,d");
+  assert(range(10).where((i) => i > 6).toList().join(",") == "7,8,9");
+  assert(Countdown(3).steps().toList().join(",") == "3,2,1");
+
+  // Breaking out of the loop leaves the rest of the body unrun.
+  log = [];
+  for (var n in naturals(log)) {
+    if (n == 2) {
+      break;
+    }
+  }
+  assert(log.join(",") == "0,1,2");
+
+  var got = [];
+  try {
+    for (var x in failing()) {
+      got.add(x);
+    }
+    assert(false);
+  } on StateError catch (e) {
+    assert(e.message == "no more");
+  }
+  assert(got.join(",") == "1");
+
+  log = [];
+  assert(cleaned(log).toList().join(",") == "1,2");
+  assert(log.join(",") == "finally");
+  print("generators done");
+}
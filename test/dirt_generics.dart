@@ -0,0 +1,160 @@
+class Box<T> {
+  T value;
+
+  Box(this.value);
+
+  T get() {
+    return value;
+  }
+
+  bool holds(Object o) {
+    return o is T;
+  }
+}
+
+class Pair<A, B> {
+  A first;
+  B second;
+
+  Pair(this.first, this.second);
+}
+
+class IntBox extends Box<int> {
+  IntBox(int v) : super(v);
+}
+
+class Animal {}
+
+class Dog extends Animal {}
+
+class Cat extends Animal {}
+
+T first<T>(List<T> xs) {
+  return xs[0];
+}
+
+Map<String, int>? lookup(bool found) {
+  if (found) {
+    return {"a": 1};
+  }
+  return null;
+}
+
+void main() {
+  List<int> xs = [1, 2, 3];
+  assert(xs is List<int>);
+  assert(xs is List<num>);
+  assert(xs is List);
+  assert(!(xs is List<String>));
+  assert(xs.runtimeType == List<int>);
+
+  var empty = [];
+  assert(empty is List<dynamic>);
+  assert(!(empty is List<int>));
+
+  List<String> names = [];
+  assert(names is List<String>);
+  names.add("a");
+
+  var mixed = [1, 2.5];
+  assert(mixed is List<num>);
+  assert(!(mixed is List<int>));
+
+  var maybe = [1, null];
+  assert(maybe is List<int?>);
+  assert(!(maybe is List<int>));
+
+  var explicit = <Object>[1, 2];
+  assert(!(explicit is List<int>));
+  assert(explicit is List<Object>);
+
+  Map<String, List<int>> scores = {"a": [1, 2]};
+  assert(scores is Map<String, List<int>>);
+  assert(!(scores is Map<String, int>));
+  assert(scores.keys is Iterable<String>);
+  var ages = <String, int>{};
+  assert(ages is Map<String, int>);
+
+  var b = Box<int>(3);
+  assert(b is Box<int>);
+  assert(b is Box<num>);
+  assert(!(b is Box<String>));
+  assert(b.get() == 3);
+  assert(b.holds(4));
+  assert(!b.holds("four"));
+  assert(b.runtimeType == Box<int>);
+
+  // Type arguments not given are inferred from the constructor arguments.
+  var s = Box("s");
+  assert(s is Box<String>);
+  var p = Pair(1, "one");
+  assert(p is Pair<int, String>);
+  assert(p.second == "one");
+
+  var animals = [Dog(), Cat()];
+  assert(animals is List<Animal>);
+  assert(!(animals is List<Dog>));
+
+  var ib = IntBox(7);
+  assert(ib is Box<int>);
+  assert(!(ib is Box<String>));
+  assert(ib.holds(1));
+
+  assert(first<int>(xs) == 1);
+  assert(first(["x", "y"]) == "x");
+
+  // Explicit type arguments type the parameters and the result.
+  int one = first<int>([1]);
+  Box<num> boxed = Box<num>(one);
+  T same<T>(T x) => x;
+  String text = same<String>("t");
+  assert(boxed.value == 1 && text == "t");
+
+  // Generic types can be nullable too.
+  List<int>? nullable;
+  assert(nullable == null);
+  nullable = [1, 2];
+  assert(nullable!.length == 2);
+  assert(lookup(false) == null);
+  assert(lookup(true)!["a"] == 1);
+
+  assert(int == int);
+  assert(String != int);
+
+  // The members of lists, maps and sets are typed by their type arguments.
+  List<int> ints = [3, 4];
+  int removed = ints.removeAt(0);
+  String joined = ints.join(",");
+  Map<String, int> counts = {"a": 1};
+  int? count = counts["a"];
+  assert(removed == 3 && joined == "4" && count == 1);
+
+  // Through dynamic, storing what isn't of the type argument throws.
+  dynamic loose = ints;
+  var errors = [];
+  try {
+    loose.add("s");
+  } on TypeError catch (e) {
+    errors.add(e.toString());
+  }
+  try {
+    loose[0] = "s";
+  } on TypeError catch (e) {
+    errors.add(e.toString());
+  }
+  dynamic looseCounts = counts;
+  try {
+    looseCounts[1] = "x";
+  } on TypeError catch (e) {
+    errors.add(e.toString());
+  }
+  assert(errors.length == 3);
+  assert(errors[0] == "type 'String' is not a subtype of type 'int' of 'value'");
+  assert(errors[2] == "type 'int' is not a subtype of type 'String' of 'key'");
+  assert(ints.length == 1 && counts.length == 1);
+
+  print(xs.runtimeType);
+  print(scores.runtimeType);
+  print(b.runtimeType);
+  print(p.runtimeType);
+}
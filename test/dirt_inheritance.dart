@@ -0,0 +1,77 @@
+class Animal {
+  String name;
+  int legs = 4;
+
+  Animal(this.name) {}
+
+  String sound() {
+    return "...";
+  }
+
+  String describe() {
+    return name + " says " + sound();
+  }
+}
+
+class Dog extends Animal {
+  Dog(String name) : super(name) {}
+
+  String sound() {
+    return "woof";
+  }
+}
+
+class Puppy extends Dog {
+  Puppy(String name) : super(name + " jr") {}
+
+  String sound() {
+    return super.sound() + " (quietly)";
+  }
+
+  String describe() {
+    return "puppy: " + super.describe();
+  }
+}
+
+class Bird extends Animal {
+  Bird() : super("bird") {
+    legs = 2;
+  }
+}
+
+class Base {
+  int count = 1;
+}
+
+class Derived extends Base {
+  int step() {
+    count++;
+    return count;
+  }
+}
+
+void main() {
+  var a = Animal("cat");
+  assert(a.describe() == "cat says ...");
+
+  var d = Dog("rex");
+  assert(d.sound() == "woof");
+  // Dispatch from an inherited method goes to the override.
+  assert(d.describe() == "rex says woof");
+  assert(d.legs == 4);
+
+  var p = Puppy("rex");
+  assert(p.name == "rex jr");
+  assert(p.describe() == "puppy: rex jr says woof (quietly)");
+
+  var b = Bird();
+  assert(b.legs == 2);
+  assert(b.describe() == "bird says ...");
+
+  // Implicit constructors call the superclass one.
+  var x = Derived();
+  assert(x.step() == 2);
+  assert(x.count == 2);
+
+  print(p.describe());
+}
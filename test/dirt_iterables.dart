@@ -0,0 +1,172 @@
+class Countdown extends Iterable<int> {
+  final int start;
+
+  Countdown(this.start);
+
+  Iterator<int> get iterator => CountdownIterator(start);
+}
+
+class CountdownIterator implements Iterator<int> {
+  int _next;
+  int _current = 0;
+
+  CountdownIterator(this._next);
+
+  bool moveNext() {
+    if (_next <= 0) {
+      return false;
+    }
+    _current = _next;
+    _next--;
+    return true;
+  }
+
+  int get current => _current;
+}
+
+class Pair extends Iterable<String> {
+  List<String> items = ["left", "right"];
+
+  Iterator<String> get iterator => items.iterator;
+}
+
+class Naturals extends Iterable<int> {
+  Iterator<int> get iterator => NaturalsIterator();
+}
+
+class NaturalsIterator implements Iterator<int> {
+  int current = 0;
+
+  bool moveNext() {
+    current++;
+    return true;
+  }
+}
+
+void main() {
+  var sum = 0;
+  for (var x in [1, 2, 3]) {
+    sum = sum + x;
+  }
+  assert(sum == 6);
+
+  var seen = <int>{};
+  for (final n in {3, 1, 3, 2}) {
+    seen.add(n);
+  }
+  assert(seen.length == 3);
+  assert(seen.contains(1) && !seen.contains(4));
+  assert(seen.first == 3);
+
+  var ages = {"ann": 30, "bob": 25};
+  var names = "";
+  for (String name in ages.keys) {
+    names = names + name;
+  }
+  assert(names == "annbob");
+
+  var letters = [];
+  for (var c in "abc") {
+    letters.add(c);
+  }
+  assert(letters.join("-") == "a-b-c");
+
+  var down = [];
+  for (var i in Countdown(3)) {
+    down.add(i);
+  }
+  assert(down.join(",") == "3,2,1");
+
+  var last = 0;
+  for (last in [7, 8, 9]) {
+    if (last == 8) {
+      break;
+    }
+  }
+  assert(last == 8);
+
+  var total = 0;
+  for (var (name, age) in [("ann", 30), ("bob", 25)]) {
+    if (name == "bob") {
+      continue;
+    }
+    total = total + age;
+  }
+  assert(total == 30);
+
+  var doubled = [1, 2, 3].map((x) => x * 2);
+  assert(doubled.toList().join(",") == "2,4,6");
+  assert(doubled.toString() == "(2, 4, 6)");
+  assert(Countdown(3).toString() == "(3, 2, 1)");
+  assert("${Pair()}" == "(left, right)");
+  assert([Countdown(2)].toString() == "[(2, 1)]");
+
+  var calls = 0;
+  var lazy = [1, 2, 3, 4].map((x) {
+    calls++;
+    return x * 10;
+  });
+  assert(calls == 0);
+  assert(lazy.first == 10);
+  assert(lazy.take(2).toList().length == 2);
+
+  var evens = [1, 2, 3, 4, 5, 6].where((x) => x ~/ 2 * 2 == x);
+  assert(evens.toList().join(",") == "2,4,6");
+  assert([1, 2, 3, 4, 5].skip(2).take(2).toList().join(",") == "3,4");
+  assert([1, 2].expand((x) => [x, x]).toList().join(",") == "1,1,2,2");
+  assert([1, 2, 3].fold(0, (acc, x) => acc + x) == 6);
+  assert([1, 2, 3].reduce((a, b) => a * b) == 6);
+  assert([1, 2, 3].any((x) => x > 2));
+  assert(![1, 2, 3].every((x) => x > 2));
+  assert({1, 2, 3}.map((x) => x + 1).toList().join(",") == "2,3,4");
+  assert("abc".map((c) => c + c).toList().join() == "aabbcc");
+
+  assert(Countdown(4).where((x) => x > 2).toList().join(",") == "4,3");
+  assert(Countdown(3).fold(0, (acc, x) => acc + x) == 6);
+  assert(Countdown(3).every((x) => x > 0));
+  assert(Countdown(3).length == 3);
+  assert(Naturals().map((x) => x * x).skip(1).take(3).toList().join(",") == "4,9,16");
+  assert(Naturals().any((x) => x > 100));
+
+  Iterable<int> xs = [5, 6];
+  assert(xs is Iterable<int>);
+  assert(Countdown(1) is Iterable<int>);
+  Set<String> empty = {};
+  assert(empty.isEmpty && empty is Set<String>);
+
+  var caught = false;
+  try {
+    [].reduce((a, b) => a);
+  } on StateError catch (e) {
+    caught = true;
+  }
+  assert(caught);
+
+  // Lists, sets and Iterables have an iterator of their own.
+  var it = [1, 2].iterator;
+  var walked = [];
+  while (it.moveNext()) {
+    walked.add(it.current);
+  }
+  assert(walked.join(",") == "1,2");
+  assert(!it.moveNext());
+  assert(it is Iterator<int>);
+  var chars = {"a", "b"}.iterator;
+  assert(chars.moveNext() && chars.current == "a");
+  var squares = Countdown(3).map((x) => x * x).iterator;
+  assert(squares.moveNext() && squares.current == 9);
+  assert(squares.moveNext() && squares.current == 4);
+  assert(Pair().toList().join(",") == "left,right");
+  assert(Pair().first == "left");
+
+  // Only the first element is mapped for first.
+  var mapped = [];
+  var first = [1, 2, 3, 4].map((x) {
+    mapped.add(x);
+    return x * 10;
+  }).first;
+  assert(first == 10);
+  assert(mapped.length == 1);
+
+  print(Countdown(5).map((x) => x * 2));
+}
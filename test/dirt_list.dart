@@ -0,0 +1,64 @@
+
+
+void main() {
+  var xs = [1, 2, 3];
+  print(xs);
+  assert(xs.length == 3);
+  assert(xs[0] == 1);
+
+  xs[1] = 20;
+  assert(xs[1] == 20);
+
+  // Lists are shared, not copied.
+  var ys = xs;
+  ys.add(4);
+  assert(xs.length == 4);
+  assert(xs.last == 4);
+  assert(xs.first == 1);
+
+  xs.addAll([5, 6]);
+  assert(xs.removeAt(0) == 1);
+  xs.insert(0, 0);
+  assert(xs.indexOf(20) == 1);
+  assert(xs.indexOf(99) == -1);
+  assert(xs.contains(6));
+  assert(!xs.isEmpty);
+  assert([].isEmpty);
+
+  var part = xs.sublist(1, 3);
+  assert(part.join(", ") == "20, 3");
+  assert(xs.sublist(4).join("-") == "5-6");
+
+  // Any Iterable can be added, sets and lazy ones too.
+  var more = [1];
+  more.addAll({2, 3});
+  more.addAll([4, 5].map((x) => x * 10));
+  more.addAll(more.where((x) => x < 3));
+  assert(more.join(",") == "1,2,3,40,50,1,2");
+  var unique = {1};
+  unique.addAll([2, 3].map((x) => x + 1));
+  assert(unique.toList().join(",") == "1,3,4");
+  List<int> ints = [];
+  try {
+    ints.addAll(["a"].map((x) => x));
+    assert(false);
+  } on TypeError {
+    assert(ints.isEmpty);
+  }
+
+  // A list within itself shows as '[...]'.
+  var cyclic = [];
+  cyclic.add(cyclic);
+  assert(cyclic.toString() == "[[...]]");
+  var nested = [1, cyclic];
+  assert("${nested}" == "[1, [[...]]]");
+  // Reversed is a view of the list.
+  var abc = [1, 2, 3];
+  var backwards = abc.reversed;
+  assert(backwards.toString() == "(3, 2, 1)");
+  abc.add(4);
+  assert(backwards.toList().join(",") == "4,3,2,1");
+  assert(backwards is Iterable<int>);
+  print(xs.reversed);
+  print(['a', 'b', 'c'].join());
+}
@@ -0,0 +1,54 @@
+
+
+void main() {
+  var ages = {"alice": 31, "bob": 42};
+  print(ages);
+  assert(ages["alice"] == 31);
+  assert(ages.length == 2);
+
+  ages["carol"] = 27;
+  ages["alice"] = 32;
+  assert(ages["alice"] == 32);
+  assert(ages["nobody"] == null);
+  assert(ages.containsKey("carol"));
+  assert(!ages.containsKey("dave"));
+
+  // Insertion order is kept, also when overwriting.
+  assert(ages.keys.join(",") == "alice,bob,carol");
+  assert(ages.values.join(",") == "32,42,27");
+
+  // The keys are a view that sees later changes.
+  var names = ages.keys;
+  ages["dave"] = 50;
+  assert(names.length == 4);
+  assert(names.toString() == "(alice, bob, carol, dave)");
+  ages.remove("dave");
+
+  assert(ages.remove("bob") == 42);
+  assert(ages.remove("bob") == null);
+  assert(ages.keys.join(",") == "alice,carol");
+
+  // 1 and 1.0 are the same key, as they are '=='.
+  var mixed = {1: "int", true: "bool", null: "null"};
+  mixed[1.0] = "double";
+  assert(mixed.length == 3);
+  assert(mixed[1] == "double");
+  assert(mixed[null] == "null");
+
+  void add(k, v) {
+    print(k + ": " + "${v}");
+  }
+  ages.forEach(add);
+
+  var entry = ages.entries.first;
+  assert(entry.key == "alice");
+  // So are the entries.
+  var entries = ages.entries;
+  ages["erin"] = 33;
+  assert(entries.length == 3);
+  assert(entries.toString() == "(MapEntry(alice: 32), MapEntry(carol: 27), MapEntry(erin: 33))");
+  assert(entries is Iterable<MapEntry<String, int>>);
+  ages.remove("erin");
+  print(entry);
+  print({});
+}
@@ -0,0 +1,51 @@
+int area({required int width, int height = 1}) {
+  return width * height;
+}
+
+String greet(String name, [String greeting = "Hello", String? suffix]) {
+  if (suffix == null) {
+    return greeting + " " + name;
+  }
+  return greeting + " " + name + suffix;
+}
+
+class Box {
+  int width;
+  int height;
+  String label;
+
+  Box(this.width, {this.height = 2, required this.label});
+
+  int volume(int depth, {int scale = 1}) => width * height * depth * scale;
+
+  static Box square(int side, [String label = "square"]) {
+    return Box(side, height: side, label: label);
+  }
+}
+
+void main() {
+  assert(area(width: 3) == 3);
+  assert(area(width: 3, height: 4) == 12);
+  assert(area(height: 5, width: 2) == 10);
+
+  assert(greet("Ann") == "Hello Ann");
+  assert(greet("Ann", "Hi") == "Hi Ann");
+  assert(greet("Ann", "Hi", "!") == "Hi Ann!");
+
+  var b = Box(3, label: "b");
+  assert(b.height == 2);
+  assert(b.label == "b");
+  assert(b.volume(2) == 12);
+  assert(b.volume(2, scale: 10) == 120);
+
+  var s = Box.square(4);
+  assert(s.width == 4 && s.height == 4);
+  assert(s.label == "square");
+  assert(Box.square(1, "one").label == "one");
+
+  var join = (String a, {String sep = ","}) => a + sep + a;
+  assert(join("x") == "x,x");
+  assert(join("x", sep: "-") == "x-x");
+
+  print(area(width: 6, height: 7));
+}
@@ -1,5 +1,28 @@
 
 
+Function makeCounter() {
+  int count = 0;
+
+  int next() {
+    count++;
+    return count;
+  }
+
+  return next;
+}
+
+
+Function adder(int a) {
+  return (int b) => a + b;
+}
+
+
+void twice(f) {
+  f();
+  f();
+}
+
+
 void main() {
 
   void foo() {
@@ -7,5 +30,39 @@ void main() {
   }
 
   foo();
-}
 
+  // Closures keep the frame they were defined in alive.
+  var counter = makeCounter();
+  counter();
+  counter();
+  assert(counter() == 3);
+
+  // Every call of makeCounter gets a fresh frame.
+  var other = makeCounter();
+  assert(other() == 1);
+  assert(counter() == 4);
+
+  // And the captured variables are shared, not copied.
+  var total = 0;
+  void bump() {
+    total = total + 10;
+  }
+  twice(bump);
+  assert(total == 20);
+  print(total);
+
+  // What a call gives can be called right away.
+  assert(adder(2)(5) == 7);
+  var adders = [adder(1), adder(10)];
+  assert(adders[1](1) == 11);
+
+  // Each iteration of a for loop has its own loop variable.
+  var fs = [];
+  for (int i = 0; i < 3; i++) {
+    fs.add(() => i);
+  }
+  assert(fs[0]() == 0);
+  assert(fs[2]() == 2);
+  var gs = [for (var j = 0; j < 2; j++) () => j];
+  assert(gs[1]() == 1);
+}
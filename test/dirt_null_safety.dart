@@ -0,0 +1,152 @@
+class Node {
+  int value = 0;
+  Node? next;
+
+  Node(this.value);
+
+  int sum() {
+    var rest = next?.sum() ?? 0;
+    return value + rest;
+  }
+}
+
+String shout(String? s) {
+  if (s == null) {
+    return "";
+  }
+  String t = s;
+  return t + "!";
+}
+
+// A variable is promoted by what is assigned to it, and by a test for null the code after can't have passed.
+int assigned() {
+  int? c;
+  c = 4;
+  int d = c;
+  return d;
+}
+
+int assignedIfNull(int? x) {
+  if (x == null) {
+    x = 0;
+  }
+  return x;
+}
+
+int defaulted(int? x) {
+  x ??= 5;
+  return x;
+}
+
+int positive(int? x) {
+  if (x == null || x < 0) {
+    return 0;
+  }
+  return x;
+}
+
+int looped(int? x) {
+  while (x == null) {
+    x = 1;
+  }
+  return x;
+}
+
+int branched(int? x) {
+  if (x == null) {
+    x = 0;
+  } else {
+    x = x + 1;
+  }
+  return x;
+}
+
+void main() {
+  int? a;
+  assert(a == null);
+  assert((a ?? 3) == 3);
+  a ??= 5;
+  assert(a == 5);
+  a ??= 7;
+  assert(a == 5);
+  assert(a != null);
+
+  int b = a!;
+  assert(b == 5);
+
+  String? name = "dart";
+  if (name != null) {
+    String s = name;
+    assert(s == "dart");
+  }
+  assert(shout(null) == "");
+  assert(shout("abc") == "abc!");
+
+  var first = Node(1);
+  first.next = Node(2);
+  assert(first.sum() == 3);
+  assert(first.next?.value == 2);
+  assert(first.next?.next?.value == null);
+  assert(first.next?.next == null);
+
+  // A '?.' that finds null skips the rest of the selectors.
+  Node? none;
+  assert(none?.next.value == null);
+  assert(none?.next!.sum() == null);
+  none?.next.value = 4;
+
+  // Null dereferences are errors that can be caught.
+  Node? missing;
+  var caught = "";
+  try {
+    print(missing!.value);
+  } on TypeError catch (e) {
+    caught = e.toString();
+  }
+  assert(caught == "Null check operator used on a null value");
+
+  var list;
+  try {
+    list[0] = 1;
+  } on NoSuchMethodError {
+    caught = "list";
+  }
+  assert(caught == "list");
+
+  try {
+    missing.value = 3;
+  } catch (e) {
+    caught = "setter";
+  }
+  assert(caught == "setter");
+
+  // So is an operator or a call on a dynamic null.
+  dynamic d;
+  try {
+    d + 1;
+  } on NoSuchMethodError {
+    caught = "plus";
+  }
+  assert(caught == "plus");
+  try {
+    d < 1;
+  } on NoSuchMethodError {
+    caught = "less";
+  }
+  assert(caught == "less");
+  try {
+    d();
+  } on NoSuchMethodError {
+    caught = "call";
+  }
+  assert(caught == "call");
+  assert(null is int? && 3 is int? && !("a" is int?));
+
+  assert(assigned() == 4);
+  assert(assignedIfNull(null) == 0 && assignedIfNull(2) == 2);
+  assert(defaulted(null) == 5 && defaulted(1) == 1);
+  assert(positive(null) == 0 && positive(-1) == 0 && positive(3) == 3);
+  assert(looped(null) == 1);
+  assert(branched(null) == 0 && branched(1) == 2);
+  print(first.sum());
+}
@@ -0,0 +1,169 @@
+class Vec {
+  int x;
+  int y;
+
+  Vec(this.x, this.y);
+
+  Vec operator +(Vec other) => Vec(x + other.x, y + other.y);
+
+  Vec operator -(Vec other) => Vec(x - other.x, y - other.y);
+
+  Vec operator -() => Vec(-x, -y);
+
+  Vec operator *(int k) => Vec(x * k, y * k);
+
+  bool operator ==(Object other) {
+    if (other is Vec) {
+      return x == other.x && y == other.y;
+    }
+    return false;
+  }
+
+  bool operator <(Vec other) => length < other.length;
+
+  int get length => x * x + y * y;
+
+  String toString() => "Vec";
+}
+
+class Grid {
+  List<int> cells = [0, 0, 0, 0];
+
+  int operator [](int i) => cells[i];
+
+  void operator []=(int i, int v) {
+    cells[i] = v;
+  }
+}
+
+abstract class Shape {
+  double get area;
+}
+
+class Rect extends Shape {
+  double w;
+  double h;
+
+  Rect(this.w, this.h);
+
+  double get area => w * h;
+
+  double get width => w;
+
+  set width(double value) {
+    if (value < 0) {
+      throw ArgumentError("negative width");
+    }
+    w = value;
+  }
+
+  bool get isSquare {
+    return w == h;
+  }
+
+  void grow() {
+    width = width + 1.0;
+  }
+}
+
+class Temperature {
+  double celsius = 0.0;
+
+  double get fahrenheit => celsius * 9 / 5 + 32;
+
+  set fahrenheit(double f) {
+    celsius = (f - 32) * 5 / 9;
+  }
+}
+
+class P {
+  int n;
+
+  P(this.n);
+
+  bool operator ==(Object other) => other is P && n == other.n;
+
+  int get hashCode => n ~/ 4;
+}
+
+void main() {
+  var a = Vec(1, 2);
+  var b = Vec(3, 4);
+
+  var c = a + b;
+  assert(c.x == 4 && c.y == 6);
+  assert(c == Vec(4, 6));
+  assert(c != Vec(4, 7));
+  assert(!(c == null));
+  assert((b - a) == Vec(2, 2));
+  assert(-a == Vec(-1, -2));
+  assert(a * 3 == Vec(3, 6));
+  assert(a < b);
+  assert(!(b < a));
+  assert(b.length == 25);
+
+  var sum = Vec(0, 0);
+  sum = sum + a;
+  sum = sum + a;
+  assert(sum == Vec(2, 4));
+
+  var g = Grid();
+  g[1] = 5;
+  g[2] = g[1] + 1;
+  assert(g[1] == 5);
+  assert(g[2] == 6);
+  assert(g.cells.join(",") == "0,5,6,0");
+
+  var r = Rect(2.0, 3.0);
+  assert(r.area == 6.0);
+  assert(!r.isSquare);
+  r.width = 3.0;
+  assert(r.isSquare);
+  r.grow();
+  assert(r.w == 4.0);
+
+  Shape s = r;
+  assert(s.area == 12.0);
+
+  var caught = false;
+  try {
+    r.width = -1.0;
+  } on ArgumentError catch (e) {
+    caught = true;
+  }
+  assert(caught);
+  assert(r.width == 4.0);
+
+  var t = Temperature();
+  t.fahrenheit = 212.0;
+  assert(t.celsius == 100.0);
+  assert(t.fahrenheit == 212.0);
+
+  // Collections compare elements and keys by a user defined '=='.
+  var vs = [Vec(1, 1), Vec(2, 3)];
+  assert(vs.contains(Vec(2, 3)));
+  assert(!vs.contains(Vec(3, 2)));
+  assert(vs.indexOf(Vec(2, 3)) == 1);
+  var named = {Vec(0, 0): "origin"};
+  assert(named[Vec(0, 0)] == "origin");
+  assert(named.containsKey(Vec(0, 0)));
+  named[Vec(0, 0)] = "zero";
+  assert(named.length == 1);
+  assert(named.remove(Vec(0, 0)) == "zero");
+  var unique = {Vec(1, 2)};
+  assert(!unique.add(Vec(1, 2)));
+  assert(unique.contains(Vec(1, 2)));
+  assert({Vec(1, 2), Vec(1, 2)}.length == 1);
+
+  // And by the hashCode, when the class defines one.
+  assert({P(2), P(2)}.length == 1);
+  var ps = {P(3): 1, P(3): 2, P(5): 3};
+  assert(ps.length == 2);
+  assert(ps[P(3)] == 2);
+  assert(ps[P(1)] == null);
+  assert(ps.remove(P(3)) == 2);
+  assert(ps.keys.first == P(5));
+  assert(ps[P(5)] == 3);
+
+  print(r.isSquare);
+}
@@ -0,0 +1,108 @@
+(int, int) divmod(int a, int b) {
+  return (a ~/ b, a - (a ~/ b) * b);
+}
+
+String describe(Object shape) {
+  switch (shape) {
+    case (0, 0):
+      return "origin";
+    case (int x, 0):
+      return "on x axis at " + x.toString();
+    case (x: var x, y: var y):
+      return "point";
+    default:
+      return "other";
+  }
+}
+
+class Cell {
+  int n;
+
+  Cell(this.n);
+
+  bool operator ==(Object other) => other is Cell && n == other.n;
+
+  int get hashCode => n;
+}
+
+void main() {
+  var pair = (1, "a");
+  assert(pair.$1 == 1);
+  assert(pair.$2 == "a");
+
+  var point = (x: 3, y: 4);
+  assert(point.x == 3 && point.y == 4);
+
+  var mixed = (1, 2, label: "m");
+  assert(mixed.$2 == 2);
+  assert(mixed.label == "m");
+
+  assert((1, 2) == (1, 2));
+  assert((1, 2) != (2, 1));
+  assert((x: 1, y: 2) == (y: 2, x: 1));
+  assert((1, x: 2) != (1, y: 2));
+  assert(pair.toString() == "(1, a)");
+  assert(point.toString() == "(x: 3, y: 4)");
+  var one = (1,);
+  assert(one.toString() == "(1,)");
+  assert("${one}" == "(1,)");
+  assert((x: 1).toString() == "(x: 1)");
+  assert([(1,)].toString() == "[(1,)]");
+  assert(pair is (int, String));
+  assert(point is Record);
+
+  var (q, r) = divmod(17, 5);
+  assert(q == 3 && r == 2);
+
+  final (String name, int age) = ("Ann", 30);
+  assert(name == "Ann" && age == 30);
+
+  var (x: px, :y) = point;
+  assert(px == 3 && y == 4);
+
+  var a = 1;
+  var b = 2;
+  (a, b) = (b, a);
+  assert(a == 2 && b == 1);
+
+  var sum = 0;
+  for (var (i, j) = (0, 10); i < j; i++) {
+    sum = sum + 1;
+  }
+  assert(sum == 10);
+
+  var nested = ((1, 2), (3, 4));
+  var ((n1, n2), (n3, n4)) = nested;
+  assert(n1 + n2 + n3 + n4 == 10);
+
+  assert(describe((0, 0)) == "origin");
+  assert(describe((5, 0)) == "on x axis at 5");
+  assert(describe((x: 1, y: 1)) == "point");
+  assert(describe((1, 2, 3)) == "other");
+
+  var single = (7,);
+  assert(single.$1 == 7);
+  var grouped = (7);
+  assert(grouped == 7);
+  assert((1, "a").runtimeType.toString() == "(int, String)");
+
+  // Records are equal map keys and set elements when their fields are.
+  var names = {(1, 2): "x", (x: 1, y: 2): "named"};
+  assert(names[(1, 2)] == "x");
+  assert(names[(x: 1, y: 2)] == "named");
+  assert(names[(2, 1)] == null);
+  names[(1, 2)] = "y";
+  assert(names.length == 2);
+  assert(names[(1, 2)] == "y");
+  assert({(1, "a"), (1, "a"), (1, "b")}.length == 2);
+  assert((Cell(1), 2) == (Cell(1), 2));
+  assert({(Cell(1), 2), (Cell(1), 2)}.length == 1);
+  assert({(Cell(1), 2): 0}.containsKey((Cell(1), 2)));
+
+  // So are durations and types when they are equal.
+  var delays = {Duration(seconds: 1): "short"};
+  assert(delays[Duration(milliseconds: 1000)] == "short");
+  assert({int, String, int}.length == 2);
+
+  print(divmod(7, 2));
+}
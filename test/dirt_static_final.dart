@@ -0,0 +1,183 @@
+class Counter {
+  static int count = 0;
+  static const int limit = 3;
+  static final List<String> log = [];
+
+  final int id;
+
+  Counter(this.id);
+
+  static Counter create() {
+    count++;
+    log.add("created");
+    return Counter(count);
+  }
+
+  static bool full() {
+    return count >= limit;
+  }
+
+  int remaining() {
+    return limit - count;
+  }
+}
+
+class Point {
+  final int x;
+  final int y;
+
+  const Point(this.x, this.y);
+
+  static const origin = Point(0, 0);
+}
+
+List<int> first() {
+  const a = 1;
+  return const [a];
+}
+
+List<int> second() {
+  const a = 2;
+  return const [a];
+}
+
+class Lazy {
+  late String name;
+  late int length = compute();
+  int computed = 0;
+
+  int compute() {
+    computed++;
+    return 42;
+  }
+}
+
+class Once {
+  late final int value;
+}
+
+void main() {
+  assert(Counter.count == 0);
+  var a = Counter.create();
+  var b = Counter.create();
+  assert(a.id == 1 && b.id == 2);
+  assert(Counter.count == 2);
+  assert(Counter.log.length == 2);
+  assert(!Counter.full());
+  assert(a.remaining() == 1);
+  Counter.count = 3;
+  assert(Counter.full());
+
+  final int f = 7;
+  final g = f * 2;
+  assert(g == 14);
+  final String later;
+  later = "set once";
+  assert(later == "set once");
+
+  const c = 2 * 3 + 1;
+  assert(c == 7);
+  const s = "con" + "cat";
+  assert(s == "concat");
+
+  var p1 = const Point(1, 2);
+  var p2 = const Point(1, 2);
+  var p3 = Point(1, 2);
+  assert(identical(p1, p2));
+  assert(p1 == p2);
+  assert(!identical(p1, p3));
+  assert(identical(Point.origin, const Point(0, 0)));
+
+  const xs = [1, 2];
+  const ys = [1, 2];
+  assert(identical(xs, ys));
+  assert(!identical([1, 2], [1, 2]));
+
+  // Const values are canonicalized by the values of the constants in them.
+  assert(first()[0] == 1);
+  assert(second()[0] == 2);
+  assert(identical(first(), first()));
+  assert(!identical(first(), second()));
+  const one = 1;
+  assert(identical(const [one + 1], const [2]));
+  assert(identical(const Point(one, one), const Point(1, 1)));
+
+  // Numbers are identical by type and value, records as instances are.
+  assert(identical(1, 1));
+  assert(!identical(1, 1.0));
+  assert(1 == 1.0);
+  assert(!identical((1, 2), (1, 2)));
+  var pair = (1, 2);
+  assert(identical(pair, pair));
+  assert(identical(const (1, 2), const (1, 2)));
+
+  var lazy = Lazy();
+  var caught = false;
+  try {
+    print(lazy.name);
+  } on LateInitializationError catch (e) {
+    caught = true;
+    assert(e.toString() == "LateInitializationError: Field 'name' has not been initialized.");
+  }
+  assert(caught);
+  lazy.name = "lazy";
+  assert(lazy.name == "lazy");
+  assert(lazy.computed == 0);
+  assert(lazy.length == 42);
+  assert(lazy.length == 42);
+  assert(lazy.computed == 1);
+
+  var once = Once();
+  once.value = 1;
+  caught = false;
+  try {
+    once.value = 2;
+  } catch (e) {
+    caught = true;
+  }
+  assert(caught);
+  assert(once.value == 1);
+
+  // Const collections can't be modified, nor what is in them.
+  const letters = ["a", "b"];
+  const table = {1: [2]};
+  var errors = [];
+  try {
+    letters.add("c");
+  } on UnsupportedError catch (e) {
+    errors.add(e.message);
+  }
+  try {
+    letters[0] = "z";
+  } on UnsupportedError catch (e) {
+    errors.add(e.message);
+  }
+  try {
+    table[5] = [6];
+  } on UnsupportedError catch (e) {
+    errors.add(e.message);
+  }
+  try {
+    table[1]!.add(3);
+  } on UnsupportedError catch (e) {
+    errors.add(e.message);
+  }
+  var digits = const {1, 2};
+  try {
+    digits.remove(1);
+  } on UnsupportedError catch (e) {
+    errors.add(e.message);
+  }
+  assert(errors.length == 5);
+  assert(errors[0] == "Cannot add to an unmodifiable list");
+  assert(errors[2] == "Cannot modify unmodifiable map");
+  assert(const ["a", "b"].length == 2);
+  assert(letters.join(",") == "a,b");
+
+  // A copy is modifiable.
+  var copy = letters.toList();
+  copy.add("c");
+  assert(copy.length == 3);
+
+  print(Counter.count);
+}
@@ -0,0 +1,159 @@
+import 'dart:async';
+
+Stream<int> countTo(int n) async* {
+  var i = 1;
+  while (i <= n) {
+    yield i;
+    i++;
+  }
+}
+
+Stream<int> both(int n) async* {
+  yield 0;
+  yield* countTo(n);
+  yield* Stream.fromIterable([10, 20]);
+}
+
+Stream<int> failing() async* {
+  yield 1;
+  throw FormatException("broken");
+}
+
+Stream<int> endless(List log) async* {
+  var i = 0;
+  try {
+    while (true) {
+      yield i;
+      i++;
+    }
+  } finally {
+    log.add("cleaned up");
+  }
+}
+
+Stream<int> logged(List log) async* {
+  var i = 1;
+  while (i <= 3) {
+    log.add("gen ${i}");
+    yield i;
+    i++;
+  }
+}
+
+Future<int> sum(Stream<int> numbers) async {
+  var total = 0;
+  await for (final n in numbers) {
+    total = total + n;
+  }
+  return total;
+}
+
+Future<void> main() async {
+  // Events added before anyone listens are buffered.
+  var log = [];
+  var controller = StreamController<int>();
+  controller.add(1);
+  controller.add(2);
+  assert(!controller.hasListener);
+  controller.stream.listen((v) => log.add("data ${v}"), onDone: () => log.add("done"));
+  controller.add(3);
+  controller.close();
+  await Future.delayed(Duration(milliseconds: 1));
+  assert(log.join(",") == "data 1,data 2,data 3,done");
+
+  // What close gives completes once the listeners, also of streams made of it, have had their events.
+  log = [];
+  var derived = StreamController<int>();
+  derived.stream.map((v) => v * 10).listen((v) => log.add(v), onDone: () => log.add("done"));
+  derived.add(1);
+  await derived.close();
+  log.add("closed");
+  assert(log.join(",") == "10,done,closed");
+  var unheard = StreamController<int>.broadcast();
+  await unheard.close();
+
+  // A single-subscription stream can only be listened to once.
+  var again = StreamController<int>();
+  again.stream.listen((v) => null);
+  try {
+    again.stream.listen((v) => null);
+    assert(false);
+  } on StateError catch (e) {
+    assert(e.message == "Stream has already been listened to.");
+  }
+
+  // A broadcast stream gives each event to all its listeners, and drops events nobody listens to.
+  log = [];
+  var broadcast = StreamController<String>.broadcast();
+  broadcast.add("lost");
+  broadcast.stream.listen((v) => log.add("a ${v}"));
+  broadcast.stream.listen((v) => log.add("b ${v}"));
+  broadcast.add("x");
+  await Future.delayed(Duration(milliseconds: 1));
+  assert(log.join(",") == "a x,b x");
+
+  // Cancelling a subscription stops its events.
+  log = [];
+  var source = StreamController<int>();
+  var sub = source.stream.listen((v) => log.add(v));
+  source.add(1);
+  await Future.delayed(Duration(milliseconds: 1));
+  sub.cancel();
+  source.add(2);
+  await Future.delayed(Duration(milliseconds: 1));
+  assert(log.join(",") == "1");
+
+  // Streams made of others get their events in the microtask that delivered them to those.
+  log = [];
+  var first = StreamController<int>();
+  var second = StreamController<int>();
+  first.stream.map((v) => v).listen((v) => log.add("a ${v}"));
+  second.stream.listen((v) => log.add("b ${v}"));
+  first.add(1);
+  second.add(2);
+  first.add(3);
+  await Future.delayed(Duration(milliseconds: 1));
+  assert(log.join(", ") == "a 1, b 2, a 3");
+
+  // An async* function waits at each yield for the listener to have its value.
+  log = [];
+  await for (var n in logged(log).take(2)) {
+    log.add("n ${n}");
+  }
+  assert(log.join(", ") == "gen 1, n 1, gen 2, n 2");
+
+  var numbers = Stream.fromIterable([1, 2, 3, 4, 5, 6]);
+  var evens = await numbers.where((n) => n ~/ 2 * 2 == n).map((n) => n * 10).take(2).toList();
+  assert(evens.join(",") == "20,40");
+
+  assert(await sum(countTo(4)) == 10);
+  assert(await sum(Stream.value(7)) == 7);
+  assert(await sum(Stream.empty()) == 0);
+  assert((await both(2).toList()).join(",") == "0,1,2,10,20");
+
+  // Errors reach the onError handler, or are thrown by 'await for'.
+  log = [];
+  failing().listen((v) => log.add(v), onError: (e) => log.add(e.message), onDone: () => log.add("done"));
+  await Future.delayed(Duration(milliseconds: 1));
+  assert(log.join(",") == "1,broken,done");
+  try {
+    await sum(failing());
+    assert(false);
+  } on FormatException catch (e) {
+    assert(e.message == "broken");
+  }
+
+  // Breaking out of 'await for' cancels the subscription, which runs the generator's finally clause.
+  log = [];
+  var taken = [];
+  await for (var n in endless(log)) {
+    if (n == 3) {
+      break;
+    }
+    taken.add(n);
+  }
+  await Future.delayed(Duration(milliseconds: 1));
+  assert(taken.join(",") == "0,1,2");
+  assert(log.join(",") == "cleaned up");
+  print("streams done");
+}
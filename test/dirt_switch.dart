@@ -0,0 +1,68 @@
+String describe(int x) {
+  var s = "";
+  switch (x) {
+    case 0:
+      s = "zero";
+    case 1:
+    case 2:
+      s = "small";
+    case > 100:
+      s = "huge";
+    case int n when n < 0:
+      s = "negative";
+    default:
+      s = "other";
+  }
+  return s;
+}
+
+String classify(Object o) => switch (o) {
+  int n when n > 5 => "big int",
+  int _ => "int",
+  String s => "string " + s,
+  1.5 || 2.5 => "half",
+  _ => "something"
+};
+
+void main() {
+  assert(describe(0) == "zero");
+  assert(describe(1) == "small");
+  assert(describe(2) == "small");
+  assert(describe(500) == "huge");
+  assert(describe(-3) == "negative");
+  assert(describe(7) == "other");
+
+  assert(classify(9) == "big int");
+  assert(classify(3) == "int");
+  assert(classify("a") == "string a");
+  assert(classify(2.5) == "half");
+  assert(classify(true) == "something");
+
+  var x = 7;
+  var size = switch (x) { < 5 => "small", >= 5 && <= 10 => "medium", _ => "large" };
+  assert(size == "medium");
+  assert(x != 8);
+
+  // break leaves the switch, continue goes on with the loop around it.
+  var seen = [];
+  for (var i = 0; i < 6; i++) {
+    switch (i) {
+      case 1:
+        continue;
+      case 3:
+        break;
+      default:
+        seen.add(i);
+    }
+    seen.add(-i);
+  }
+  assert(seen.join(",") == "0,0,2,-2,-3,4,-4,5,-5");
+
+  // Pattern variables live in the scope of their case only.
+  var n = 10;
+  switch (n) {
+    case int n when n > 5:
+      assert(n == 10);
+  }
+  print(describe(1));
+}
@@ -0,0 +1,71 @@
+abstract class Shape {
+  double area();
+}
+
+class Square extends Shape {
+  double side = 0;
+
+  Square(this.side);
+
+  double area() => side * side;
+}
+
+num total(List shapes) {
+  num sum = 0;
+  for (var i = 0; i < shapes.length; i++) {
+    Shape s = shapes[i];
+    sum = sum + s.area();
+  }
+  return sum;
+}
+
+double half(double x) {
+  return x / 2;
+}
+
+double one() {
+  return 1;
+}
+
+String describe(Object o) {
+  if (o is Square) {
+    double side = o.side;
+    return "square " + side.toString();
+  }
+  return "shape";
+}
+
+void main() {
+  Shape s = Square(2.0);
+  double a = s.area();
+  assert(a == 4.0);
+
+  int n = 7 ~/ 2;
+  double d = 7 / 2;
+  num m = n;
+  m = d;
+  assert(m == 3.5);
+
+  var dyn;
+  dyn = "anything";
+  String str = dyn;
+  assert(str == "anything");
+
+  assert(total([Square(1.0), Square(3.0)]) == 10.0);
+  assert(describe(Square(1.5)) == "square 1.5");
+  assert(describe(3) == "shape");
+
+  // Int literals are doubles where doubles are expected.
+  double i = 1;
+  double? j = -2;
+  assert(i.runtimeType == double);
+  assert(j == -2.0);
+  assert(half(3) == 1.5);
+  assert(one() == 1.0);
+
+  // Local functions are checked like top level ones.
+  double scale(double x, int k) => x * k;
+  double scaled = scale(1, 3);
+  assert(scaled == 3.0);
+  print(a);
+}
@@ -0,0 +1,5 @@
+extension Loud on String {
+  String shout() => "loud";
+}
+
+String loudly(String s) => s.shout();
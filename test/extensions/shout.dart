@@ -0,0 +1,8 @@
+import 'extensions/loud.dart';
+
+extension Shout on String {
+  String shout() => toUpperCase() + "!";
+}
+
+// Calls the shout of the extension visible here: this file sees both.
+String viaLoud(String s) => loudly(s);